@@ -1,19 +1,24 @@
 // src/adapters/discord.rs
 //
-// Discord Gateway (WebSocket) adapter. Receives MESSAGE_CREATE events,
-// hands them to `run_agent_for_message`, and sends replies back via the
-// REST API.
+// Discord Gateway (WebSocket) adapter. Receives MESSAGE_CREATE and
+// INTERACTION_CREATE events, hands them to `run_agent_for_message`, and
+// sends replies back via the REST API.
 //
-// Scope (v1):
+// Scope:
 //   - Gateway opcodes: Hello(10), Identify(2), Heartbeat(1)/Ack(11),
 //     Resume(6), Dispatch(0), Reconnect(7), InvalidSession(9)
-//   - Dispatch events: READY, RESUMED, MESSAGE_CREATE (others ignored)
+//   - Dispatch events: READY, RESUMED, MESSAGE_CREATE, INTERACTION_CREATE
+//     (others ignored)
 //   - REST: send message (POST /channels/{id}/messages), typing indicator
 //   - Session persistence at .juglans/discord/gateway.json for resume
+//   - Slash commands (bulk-registered on READY from `commands` config or
+//     `@command` functions), buttons and select menus. Interactions are
+//     acked with a deferred response; replies edit the deferred message
+//     (streamed) and overflow into follow-ups.
 //
 // Out of scope (deferred):
-//   - Slash / interactions API
-//   - Message edit / delete / reactions
+//   - Autocomplete, modals, context-menu commands
+//   - Message delete / reactions on the receive side
 //   - `dm_policy` / `group_policy` / guild allowlist enforcement
 //   - Sharding (only matters beyond 2500 guilds)
 //   - `discord_send` builtin (planned separately as part of a unified
@@ -34,6 +39,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -41,7 +47,10 @@ use tokio_tungstenite::tungstenite::protocol::Message as WsMessage;
 use tracing::{debug, error, info, warn};
 
 use super::{Channel, MessageDispatcher, PlatformMessage};
-use crate::services::config::JuglansConfig;
+use crate::core::graph::WorkflowGraph;
+use crate::services::config::{
+    ChannelLimitsConfig, DiscordCommandConfig, DiscordCommandOption, JuglansConfig,
+};
 
 // ─── Constants ──────────────────────────────────────────────────────────────
 
@@ -50,6 +59,14 @@ const GATEWAY_ENCODING: &str = "json";
 pub(crate) const DISCORD_API: &str = "https://discord.com/api/v10";
 pub(crate) const MAX_MESSAGE_LEN: usize = 2000;
 
+/// Streaming edits of an interaction reply are throttled to this interval.
+const STREAM_EDIT_INTERVAL_MS: u128 = 1000;
+
+/// Component limits: rows per message, buttons per row, `custom_id` bytes.
+const MAX_ACTION_ROWS: usize = 5;
+const MAX_ROW_BUTTONS: usize = 5;
+const CUSTOM_ID_MAX_LEN: usize = 100;
+
 /// Intent name → bitmask. Keep in sync with
 /// <https://discord.com/developers/docs/topics/gateway#gateway-intents>.
 const INTENT_TABLE: &[(&str, u64)] = &[
@@ -180,6 +197,12 @@ struct GatewayRuntime {
     resume_url: Mutex<Option<String>>,
     bot_user_id: Mutex<Option<String>>,
     processed_message_ids: DashSet<String>,
+    /// Slash commands to register on READY (empty: leave registrations alone).
+    commands: Vec<DiscordCommandConfig>,
+    /// Guilds to register into; empty registers globally.
+    command_guilds: Vec<String>,
+    /// Registration runs once per process, not on every reconnect.
+    commands_registered: AtomicBool,
 }
 
 impl GatewayRuntime {
@@ -192,6 +215,9 @@ impl GatewayRuntime {
             resume_url: Mutex::new(None),
             bot_user_id: Mutex::new(None),
             processed_message_ids: DashSet::new(),
+            commands: Vec::new(),
+            command_guilds: Vec::new(),
+            commands_registered: AtomicBool::new(false),
         }
     }
 
@@ -251,53 +277,71 @@ pub(crate) async fn send_typing(http: &reqwest::Client, token: &str, channel_id:
 }
 
 /// Send a message to a channel, chunking at MAX_MESSAGE_LEN characters.
-/// Retries once on HTTP 429 using `retry_after` from the body.
+/// Retries once on HTTP 429 using `retry_after` from the body. `components`
+/// (see [`action_rows`]) attach to the last chunk. A failure after the first
+/// chunk carries the unsent rest for the outbox.
 pub(crate) async fn send_channel_message(
     http: &reqwest::Client,
     token: &str,
     channel_id: &str,
     text: &str,
+    components: Option<&Value>,
 ) -> Result<()> {
-    for chunk in split_message(text, MAX_MESSAGE_LEN) {
+    let chunks = split_message(text, MAX_MESSAGE_LEN);
+    let last = chunks.len().saturating_sub(1);
+    for (i, chunk) in chunks.iter().enumerate() {
         if chunk.is_empty() {
             continue;
         }
-        let url = format!("{}/channels/{}/messages", DISCORD_API, channel_id);
-        let body = json!({ "content": chunk });
-
-        let mut attempt = 0;
-        loop {
-            let resp = http
-                .post(&url)
-                .header("Authorization", format!("Bot {}", token))
-                .json(&body)
-                .timeout(Duration::from_secs(15))
-                .send()
-                .await?;
-
-            if resp.status().is_success() {
-                break;
-            }
-            if resp.status().as_u16() == 429 && attempt < 1 {
-                let j: Value = resp.json().await.unwrap_or(json!({}));
-                let wait = j["retry_after"].as_f64().unwrap_or(1.0);
-                tokio::time::sleep(Duration::from_millis((wait * 1000.0) as u64)).await;
-                attempt += 1;
-                continue;
-            }
-            let status = resp.status();
-            let err_body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!(
-                "POST /channels/{}/messages failed: {} {}",
-                channel_id,
-                status,
-                err_body
-            ));
+        let mut body = json!({ "content": chunk });
+        if let (Some(c), true) = (components, i == last) {
+            body["components"] = c.clone();
+        }
+        if let Err(e) = post_channel_message(http, token, channel_id, &body).await {
+            return Err(crate::services::outbox::partial_failure(e, &chunks, i));
         }
     }
     Ok(())
 }
 
+async fn post_channel_message(
+    http: &reqwest::Client,
+    token: &str,
+    channel_id: &str,
+    body: &Value,
+) -> Result<()> {
+    let url = format!("{}/channels/{}/messages", DISCORD_API, channel_id);
+    let mut attempt = 0;
+    loop {
+        let resp = http
+            .post(&url)
+            .header("Authorization", format!("Bot {}", token))
+            .json(body)
+            .timeout(Duration::from_secs(15))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            return Ok(());
+        }
+        if resp.status().as_u16() == 429 && attempt < 1 {
+            let j: Value = resp.json().await.unwrap_or(json!({}));
+            let wait = j["retry_after"].as_f64().unwrap_or(1.0);
+            tokio::time::sleep(Duration::from_millis((wait * 1000.0) as u64)).await;
+            attempt += 1;
+            continue;
+        }
+        let status = resp.status();
+        let err_body = resp.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "POST /channels/{}/messages failed: {} {}",
+            channel_id,
+            status,
+            err_body
+        ));
+    }
+}
+
 /// UTF-8 / char-boundary safe chunker. Prefers splitting at a newline within
 /// the last 10% of the window; otherwise splits at the last char boundary.
 pub(crate) fn split_message(text: &str, max_chars: usize) -> Vec<String> {
@@ -338,6 +382,242 @@ pub(crate) fn split_message(text: &str, max_chars: usize) -> Vec<String> {
     out
 }
 
+// ─── Slash commands & components ────────────────────────────────────────────
+
+/// Build message `components` from the `buttons` builtin parameter: rows of
+/// buttons, each either a string (label = custom_id) or an object with
+/// `text` plus `data` (custom_id) or `url` (link button), and an optional
+/// `style` ("primary" | "secondary" | "success" | "danger").
+///
+/// `[["Yes", "No"], [{"text": "Docs", "url": "https://..."}]]`
+pub(crate) fn action_rows(buttons: &Value) -> Result<Value> {
+    let rows = buttons
+        .as_array()
+        .ok_or_else(|| anyhow!("buttons must be a list of rows"))?;
+    if rows.len() > MAX_ACTION_ROWS {
+        return Err(anyhow!(
+            "Discord allows at most {} rows of buttons",
+            MAX_ACTION_ROWS
+        ));
+    }
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        // A flat list of buttons is one row.
+        let cells = match row.as_array() {
+            Some(cells) => cells.clone(),
+            None => vec![row.clone()],
+        };
+        if cells.len() > MAX_ROW_BUTTONS {
+            return Err(anyhow!(
+                "Discord allows at most {} buttons per row",
+                MAX_ROW_BUTTONS
+            ));
+        }
+        let mut out_row = Vec::with_capacity(cells.len());
+        for cell in cells {
+            let button = match cell {
+                Value::String(label) => {
+                    json!({ "type": 2, "style": 1, "label": label, "custom_id": label })
+                }
+                Value::Object(ref obj) => {
+                    let text = obj
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| anyhow!("button without `text`: {}", cell))?;
+                    if let Some(url) = obj.get("url").and_then(|v| v.as_str()) {
+                        json!({ "type": 2, "style": 5, "label": text, "url": url })
+                    } else {
+                        let data = obj.get("data").and_then(|v| v.as_str()).unwrap_or(text);
+                        let style = match obj.get("style").and_then(|v| v.as_str()) {
+                            None | Some("primary") => 1,
+                            Some("secondary") => 2,
+                            Some("success") => 3,
+                            Some("danger") => 4,
+                            Some(other) => return Err(anyhow!("unknown button style {:?}", other)),
+                        };
+                        json!({ "type": 2, "style": style, "label": text, "custom_id": data })
+                    }
+                }
+                other => return Err(anyhow!("invalid button: {}", other)),
+            };
+            if let Some(id) = button["custom_id"].as_str() {
+                if id.len() > CUSTOM_ID_MAX_LEN {
+                    return Err(anyhow!(
+                        "button data {:?} exceeds Discord's {}-byte custom_id limit",
+                        id,
+                        CUSTOM_ID_MAX_LEN
+                    ));
+                }
+            }
+            out_row.push(button);
+        }
+        out.push(json!({ "type": 1, "components": out_row }));
+    }
+    Ok(Value::Array(out))
+}
+
+/// Discord's rule for command and option names.
+fn valid_command_name(name: &str) -> bool {
+    (1..=32).contains(&name.chars().count())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+fn option_type(kind: &str) -> Option<u8> {
+    Some(match kind {
+        "string" => 3,
+        "integer" => 4,
+        "boolean" => 5,
+        "user" => 6,
+        "channel" => 7,
+        "role" => 8,
+        "number" => 10,
+        _ => return None,
+    })
+}
+
+/// One configured command → application-command JSON (CHAT_INPUT). Discord
+/// requires a description and wants required options before optional ones.
+pub(crate) fn command_payload(cmd: &DiscordCommandConfig) -> Result<Value> {
+    if !valid_command_name(&cmd.name) {
+        return Err(anyhow!(
+            "invalid command name {:?} (1-32 chars of a-z, 0-9, _ or -)",
+            cmd.name
+        ));
+    }
+    let describe = |d: &str, name: &str| -> String {
+        let d = if d.is_empty() { name } else { d };
+        d.chars().take(100).collect()
+    };
+    let mut options: Vec<&DiscordCommandOption> = cmd.options.iter().collect();
+    options.sort_by_key(|o| !o.required);
+    let options = options
+        .into_iter()
+        .map(|o| {
+            if !valid_command_name(&o.name) {
+                return Err(anyhow!("/{}: invalid option name {:?}", cmd.name, o.name));
+            }
+            let kind = option_type(&o.kind).ok_or_else(|| {
+                anyhow!(
+                    "/{}: unknown option type {:?} for {}",
+                    cmd.name,
+                    o.kind,
+                    o.name
+                )
+            })?;
+            let mut opt = json!({
+                "type": kind,
+                "name": o.name,
+                "description": describe(&o.description, &o.name),
+                "required": o.required,
+            });
+            if !o.choices.is_empty() {
+                opt["choices"] = o
+                    .choices
+                    .iter()
+                    .map(|c| json!({ "name": c, "value": c }))
+                    .collect();
+            }
+            Ok(opt)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(json!({
+        "type": 1,
+        "name": cmd.name,
+        "description": describe(&cmd.description, &cmd.name),
+        "options": options,
+    }))
+}
+
+/// Commands declared with `@command("name", "description")` on functions of
+/// a workflow. Each function parameter becomes a required string option; a
+/// missing name falls back to the function name.
+pub fn commands_from_graph(wf: &WorkflowGraph) -> Vec<DiscordCommandConfig> {
+    let mut out: Vec<DiscordCommandConfig> = wf
+        .functions
+        .iter()
+        .filter_map(|(fn_name, fn_def)| {
+            let ann = fn_def.annotations.get("command")?;
+            let name = match ann["name"].as_str() {
+                Some(n) if !n.is_empty() => n.to_string(),
+                _ => fn_name.to_lowercase(),
+            };
+            Some(DiscordCommandConfig {
+                name,
+                description: ann["description"].as_str().unwrap_or("").to_string(),
+                options: fn_def
+                    .params
+                    .iter()
+                    .map(|p| DiscordCommandOption {
+                        name: p.to_lowercase(),
+                        description: String::new(),
+                        kind: "string".into(),
+                        required: true,
+                        choices: Vec::new(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+/// Parse + macro-expand a workflow file and collect its `@command` functions.
+pub fn commands_from_workflow(path: &Path) -> Result<Vec<DiscordCommandConfig>> {
+    let content = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut wf = crate::core::parser::GraphParser::parse(&content)?;
+    crate::core::macro_expand::expand_decorators(&mut wf)?;
+    Ok(commands_from_graph(&wf))
+}
+
+/// Bulk-overwrite the application's commands (PUT replaces the whole set,
+/// so removed entries disappear from the client too).
+async fn register_commands(
+    http: &reqwest::Client,
+    token: &str,
+    application_id: &str,
+    commands: &[DiscordCommandConfig],
+    guilds: &[String],
+) -> Result<()> {
+    let payload = commands
+        .iter()
+        .map(command_payload)
+        .collect::<Result<Vec<_>>>()?;
+    let urls: Vec<String> = if guilds.is_empty() {
+        vec![format!(
+            "{}/applications/{}/commands",
+            DISCORD_API, application_id
+        )]
+    } else {
+        guilds
+            .iter()
+            .map(|g| {
+                format!(
+                    "{}/applications/{}/guilds/{}/commands",
+                    DISCORD_API, application_id, g
+                )
+            })
+            .collect()
+    };
+    for url in urls {
+        let resp = http
+            .put(&url)
+            .header("Authorization", format!("Bot {}", token))
+            .json(&payload)
+            .timeout(Duration::from_secs(15))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("PUT {} failed: {} {}", url, status, body));
+        }
+    }
+    Ok(())
+}
+
 // ─── Gateway session ────────────────────────────────────────────────────────
 
 type WsStream =
@@ -552,6 +832,34 @@ async fn handle_dispatch(
                 username, id_short
             );
             rt.persist_session(&project_root);
+
+            let application_id = d["application"]["id"].as_str().unwrap_or("").to_string();
+            if !rt.commands.is_empty()
+                && !application_id.is_empty()
+                && !rt.commands_registered.swap(true, Ordering::SeqCst)
+            {
+                let rt = rt.clone();
+                tokio::spawn(async move {
+                    match register_commands(
+                        &http,
+                        &rt.token,
+                        &application_id,
+                        &rt.commands,
+                        &rt.command_guilds,
+                    )
+                    .await
+                    {
+                        Ok(()) => info!(
+                            "[discord] Registered {} slash command(s)",
+                            rt.commands.len()
+                        ),
+                        Err(e) => {
+                            error!("[discord] slash command registration failed: {:#}", e);
+                            rt.commands_registered.store(false, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
         }
         "RESUMED" => {
             info!("[discord] Gateway session resumed");
@@ -559,6 +867,9 @@ async fn handle_dispatch(
         "MESSAGE_CREATE" => {
             handle_message_create(&v["d"], dispatcher, project_root, rt, http).await?;
         }
+        "INTERACTION_CREATE" => {
+            handle_interaction_create(&v["d"], dispatcher, rt, http).await?;
+        }
         _ => {
             debug!("[discord] ignored dispatch: {}", t);
         }
@@ -566,6 +877,25 @@ async fn handle_dispatch(
     Ok(())
 }
 
+/// Map a `MESSAGE_CREATE` payload to a PlatformMessage. None for media-only
+/// messages (v1 has no image handling) or a missing channel id.
+pub(crate) fn parse_message_create(d: &Value) -> Option<PlatformMessage> {
+    let content = d["content"].as_str().unwrap_or("");
+    let channel_id = d["channel_id"].as_str().unwrap_or("");
+    if content.is_empty() || channel_id.is_empty() {
+        return None;
+    }
+    Some(PlatformMessage {
+        event_type: "message".into(),
+        event_data: json!({ "text": content }),
+        platform_user_id: d["author"]["id"].as_str().unwrap_or("").to_string(),
+        platform_chat_id: channel_id.to_string(),
+        text: content.to_string(),
+        username: d["author"]["username"].as_str().map(String::from),
+        platform: "discord".into(),
+    })
+}
+
 async fn handle_message_create(
     d: &Value,
     dispatcher: Arc<dyn MessageDispatcher>,
@@ -599,21 +929,16 @@ async fn handle_message_create(
         rt.processed_message_ids.clear();
     }
 
-    let content = d["content"].as_str().unwrap_or("").to_string();
-    if content.is_empty() {
-        // Media-only message; v1 has no image handling
-        return Ok(());
-    }
-    let channel_id = d["channel_id"].as_str().unwrap_or("").to_string();
-    if channel_id.is_empty() {
-        return Ok(());
-    }
-    let username = d["author"]["username"].as_str().map(String::from);
+    let platform_msg = match parse_message_create(d) {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+    let channel_id = platform_msg.platform_chat_id.clone();
 
-    let preview: String = content.chars().take(50).collect();
+    let preview: String = platform_msg.text.chars().take(50).collect();
     info!(
         "[discord] {} (id={}…): {}",
-        username.as_deref().unwrap_or("?"),
+        platform_msg.username.as_deref().unwrap_or("?"),
         &author_id[..author_id.len().min(6)],
         preview
     );
@@ -624,17 +949,7 @@ async fn handle_message_create(
     // Run the agent + send reply in a detached task so the receive loop
     // keeps reading gateway events in parallel.
     let token = rt.token.clone();
-    tokio::spawn(async move {
-        let platform_msg = PlatformMessage {
-            event_type: "message".into(),
-            event_data: json!({ "text": &content }),
-            platform_user_id: author_id,
-            platform_chat_id: channel_id.clone(),
-            text: content,
-            username,
-            platform: "discord".into(),
-        };
-
+    tokio::spawn(super::while_delivering(async move {
         send_typing(&http, &token, &channel_id).await;
 
         match dispatcher.dispatch(&platform_msg).await {
@@ -642,18 +957,448 @@ async fn handle_message_create(
                 if reply.text.is_empty() || reply.text == "(No response)" {
                     return;
                 }
-                if let Err(e) = send_channel_message(&http, &token, &channel_id, &reply.text).await
+                if let Err(e) =
+                    send_channel_message(&http, &token, &channel_id, &reply.text, None).await
                 {
                     error!("[discord] send failed: {}", e);
                 }
             }
             Err(e) => {
                 error!("[discord] agent error: {}", e);
-                let _ = send_channel_message(&http, &token, &channel_id, &format!("Error: {}", e))
-                    .await;
+                let _ = send_channel_message(
+                    &http,
+                    &token,
+                    &channel_id,
+                    &format!("Error: {}", e),
+                    None,
+                )
+                .await;
+            }
+        }
+    }));
+
+    Ok(())
+}
+
+// ─── Interactions ───────────────────────────────────────────────────────────
+//
+// Every interaction must be acknowledged within 3 seconds, so we ack with a
+// deferred response first and run the workflow afterwards:
+//   - slash command → type 5 (the client shows "thinking…"; the first reply
+//     fills in that message)
+//   - button / select → type 6 (no visible change; replies are follow-ups)
+// Replies go through the interaction webhook (valid for 15 minutes).
+
+/// Flatten command options into `{name: value}`, collecting the
+/// subcommand / subcommand-group path on the way down.
+fn flatten_options(options: &Value, values: &mut Vec<(String, Value)>, path: &mut Vec<String>) {
+    for opt in options.as_array().into_iter().flatten() {
+        let name = opt["name"].as_str().unwrap_or("").to_string();
+        match opt["type"].as_u64() {
+            // SUB_COMMAND / SUB_COMMAND_GROUP
+            Some(1) | Some(2) => {
+                path.push(name);
+                flatten_options(&opt["options"], values, path);
+            }
+            _ => {
+                values.push((name, opt["value"].clone()));
+            }
+        }
+    }
+}
+
+/// Approve / deny buttons for a tool-approval card. Presses come back as
+/// `card_action` interactions carrying the `custom_id`.
+fn approval_components(allow_action: &str, deny_action: &str) -> Value {
+    json!([{
+        "type": 1,
+        "components": [
+            { "type": 2, "style": 3, "label": "Approve", "custom_id": allow_action },
+            { "type": 2, "style": 4, "label": "Deny", "custom_id": deny_action },
+        ],
+    }])
+}
+
+/// Map an `INTERACTION_CREATE` payload to a PlatformMessage.
+///
+/// - Slash command → `event_type = "command"`, `event_data.command` /
+///   `subcommand` / `options`; `text` is the command line as typed.
+/// - Button / select menu → `event_type = "card_action"`,
+///   `event_data.action` = the component's `custom_id` (plus `values` for
+///   selects).
+///
+/// Anything else (ping, autocomplete, modal submit) yields None.
+pub(crate) fn parse_interaction(d: &Value) -> Option<PlatformMessage> {
+    let channel_id = d["channel_id"].as_str()?.to_string();
+    // Guild interactions carry `member.user`; DMs carry `user`.
+    let user = if d["member"]["user"].is_object() {
+        &d["member"]["user"]
+    } else {
+        &d["user"]
+    };
+    let data = &d["data"];
+    let (event_type, event_data, text) = match d["type"].as_u64()? {
+        2 => {
+            let command = data["name"].as_str()?.to_string();
+            // Ordered as the user typed them; `options` below is keyed by name.
+            let mut values = Vec::new();
+            let mut path = Vec::new();
+            flatten_options(&data["options"], &mut values, &mut path);
+            let mut line = std::iter::once(command.clone())
+                .chain(path.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" ");
+            for (_, v) in &values {
+                line.push(' ');
+                match v.as_str() {
+                    Some(s) => line.push_str(s),
+                    None => line.push_str(&v.to_string()),
+                }
+            }
+            let subcommand = (!path.is_empty()).then(|| path.join(" "));
+            let options: serde_json::Map<String, Value> = values.into_iter().collect();
+            (
+                "command",
+                json!({
+                    "command": command,
+                    "subcommand": subcommand,
+                    "options": options,
+                    "interaction_id": d["id"],
+                    "guild_id": d["guild_id"],
+                }),
+                format!("/{}", line),
+            )
+        }
+        3 => {
+            let component = match data["component_type"].as_u64() {
+                Some(2) => "button",
+                _ => "select",
+            };
+            (
+                "card_action",
+                json!({
+                    "action": data["custom_id"],
+                    "component": component,
+                    "values": data.get("values").cloned().unwrap_or(json!([])),
+                    "message_id": d["message"]["id"],
+                    "message_text": d["message"]["content"],
+                    "interaction_id": d["id"],
+                }),
+                String::new(),
+            )
+        }
+        _ => return None,
+    };
+    Some(PlatformMessage {
+        event_type: event_type.into(),
+        event_data,
+        platform_user_id: user["id"].as_str().unwrap_or("").to_string(),
+        platform_chat_id: channel_id,
+        text,
+        username: user["username"].as_str().map(String::from),
+        platform: "discord".into(),
+    })
+}
+
+/// Webhook call with the same single 429 retry as `send_channel_message`.
+async fn webhook_call(
+    http: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    body: Option<&Value>,
+) -> Result<Value> {
+    let mut attempt = 0;
+    loop {
+        let mut req = http
+            .request(method.clone(), url)
+            .timeout(Duration::from_secs(15));
+        if let Some(b) = body {
+            req = req.json(b);
+        }
+        let resp = req.send().await?;
+        if resp.status().is_success() {
+            return Ok(resp.json().await.unwrap_or(Value::Null));
+        }
+        if resp.status().as_u16() == 429 && attempt < 1 {
+            let j: Value = resp.json().await.unwrap_or(json!({}));
+            let wait = j["retry_after"].as_f64().unwrap_or(1.0);
+            tokio::time::sleep(Duration::from_millis((wait * 1000.0) as u64)).await;
+            attempt += 1;
+            continue;
+        }
+        let status = resp.status();
+        let err_body = resp.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "{} interaction webhook failed: {} {}",
+            method,
+            status,
+            err_body
+        ));
+    }
+}
+
+/// Egress for one interaction. Handed to the workflow as its channel origin,
+/// so `reply()` / `chat()` answer the interaction instead of posting a
+/// fresh channel message.
+#[derive(Clone)]
+struct InteractionReply {
+    http: reqwest::Client,
+    api_base: String,
+    application_id: String,
+    token: String,
+    /// Deferred "thinking…" message (slash commands) not yet filled in.
+    original_pending: Arc<AtomicBool>,
+}
+
+impl InteractionReply {
+    fn webhook_url(&self, suffix: &str) -> String {
+        format!(
+            "{}/webhooks/{}/{}{}",
+            self.api_base, self.application_id, self.token, suffix
+        )
+    }
+
+    /// Acknowledge the interaction with a deferred response.
+    async fn ack(&self, interaction_id: &str, deferred_message: bool) -> Result<()> {
+        let url = format!(
+            "{}/interactions/{}/{}/callback",
+            self.api_base, interaction_id, self.token
+        );
+        let kind = if deferred_message { 5 } else { 6 };
+        webhook_call(
+            &self.http,
+            reqwest::Method::POST,
+            &url,
+            Some(&json!({ "type": kind })),
+        )
+        .await?;
+        self.original_pending
+            .store(deferred_message, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Post one chunk — into the deferred original while it's still empty,
+    /// as a follow-up otherwise. Returns the id to edit it by.
+    async fn post(&self, text: &str) -> Result<String> {
+        self.post_body(json!({ "content": text })).await
+    }
+
+    async fn post_body(&self, body: Value) -> Result<String> {
+        if self.original_pending.swap(false, Ordering::SeqCst) {
+            webhook_call(
+                &self.http,
+                reqwest::Method::PATCH,
+                &self.webhook_url("/messages/@original"),
+                Some(&body),
+            )
+            .await?;
+            return Ok("@original".into());
+        }
+        let msg = webhook_call(
+            &self.http,
+            reqwest::Method::POST,
+            &self.webhook_url("?wait=true"),
+            Some(&body),
+        )
+        .await?;
+        Ok(msg["id"].as_str().unwrap_or("").to_string())
+    }
+
+    async fn edit(&self, message_id: &str, text: &str) -> Result<()> {
+        webhook_call(
+            &self.http,
+            reqwest::Method::PATCH,
+            &self.webhook_url(&format!("/messages/{}", message_id)),
+            Some(&json!({ "content": text })),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Drop the "thinking…" placeholder if the workflow never replied.
+    async fn close(&self) {
+        if self.original_pending.swap(false, Ordering::SeqCst) {
+            let _ = webhook_call(
+                &self.http,
+                reqwest::Method::DELETE,
+                &self.webhook_url("/messages/@original"),
+                None,
+            )
+            .await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for InteractionReply {
+    async fn send(&self, _conversation: &str, text: &str) -> Result<()> {
+        for chunk in split_message(text, MAX_MESSAGE_LEN) {
+            if !chunk.is_empty() {
+                self.post(&chunk).await?;
             }
         }
+        Ok(())
+    }
+
+    async fn start_stream(
+        &self,
+        _conversation: &str,
+    ) -> Result<Box<dyn crate::core::context::StreamHandle>> {
+        Ok(Box::new(InteractionStreamHandle {
+            reply: self.clone(),
+            buffer: String::new(),
+            message_id: None,
+            last_edit: std::time::Instant::now(),
+            pending: false,
+        }))
+    }
+
+    async fn send_approval(
+        &self,
+        _conversation: &str,
+        text: &str,
+        allow_action: &str,
+        deny_action: &str,
+    ) -> Result<()> {
+        self.post_body(json!({
+            "content": text,
+            "components": approval_components(allow_action, deny_action),
+        }))
+        .await
+        .map(|_| ())
+    }
+}
+
+/// First token fills the deferred original (or posts a follow-up); later
+/// tokens edit that message at most once per second. Past MAX_MESSAGE_LEN
+/// the live message shows the first chunk and finalize posts the rest as
+/// follow-ups.
+struct InteractionStreamHandle {
+    reply: InteractionReply,
+    buffer: String,
+    message_id: Option<String>,
+    last_edit: std::time::Instant,
+    pending: bool,
+}
+
+impl InteractionStreamHandle {
+    async fn flush(&mut self) -> Result<()> {
+        let head = split_message(&self.buffer, MAX_MESSAGE_LEN)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        match self.message_id.clone() {
+            None => self.message_id = Some(self.reply.post(&head).await?),
+            Some(id) => self.reply.edit(&id, &head).await?,
+        }
+        self.last_edit = std::time::Instant::now();
+        self.pending = false;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::StreamHandle for InteractionStreamHandle {
+    async fn push_token(&mut self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.buffer.push_str(text);
+        self.pending = true;
+        if self.message_id.is_none()
+            || self.last_edit.elapsed().as_millis() >= STREAM_EDIT_INTERVAL_MS
+        {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn finalize(mut self: Box<Self>) -> Result<()> {
+        if self.message_id.is_none() && self.buffer.is_empty() {
+            return Ok(());
+        }
+        if self.pending || self.message_id.is_none() {
+            self.flush().await?;
+        }
+        for chunk in split_message(&self.buffer, MAX_MESSAGE_LEN).iter().skip(1) {
+            self.reply.post(chunk).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn handle_interaction_create(
+    d: &Value,
+    dispatcher: Arc<dyn MessageDispatcher>,
+    rt: Arc<GatewayRuntime>,
+    http: reqwest::Client,
+) -> Result<()> {
+    let interaction_id = d["id"].as_str().unwrap_or("").to_string();
+    let token = d["token"].as_str().unwrap_or("").to_string();
+    if interaction_id.is_empty() || token.is_empty() {
+        return Ok(());
+    }
+    let msg = match parse_interaction(d) {
+        Some(m) => m,
+        None => {
+            debug!(
+                "[discord] ignored interaction type {}",
+                d["type"].as_u64().unwrap_or(0)
+            );
+            return Ok(());
+        }
+    };
+    if !rt.processed_message_ids.insert(interaction_id.clone()) {
+        return Ok(());
+    }
+
+    let reply = Arc::new(InteractionReply {
+        http,
+        api_base: DISCORD_API.to_string(),
+        application_id: d["application_id"].as_str().unwrap_or("").to_string(),
+        token,
+        original_pending: Arc::new(AtomicBool::new(false)),
     });
+    reply
+        .ack(&interaction_id, msg.event_type == "command")
+        .await
+        .context("interaction ack")?;
+
+    info!(
+        "[discord] {} (id={}…): {} {}",
+        msg.username.as_deref().unwrap_or("?"),
+        &msg.platform_user_id[..msg.platform_user_id.len().min(6)],
+        msg.event_type,
+        if msg.text.is_empty() {
+            msg.event_data["action"].as_str().unwrap_or("")
+        } else {
+            msg.text.as_str()
+        }
+    );
+
+    tokio::spawn(super::while_delivering(async move {
+        use crate::core::context::ChannelEgress;
+        let origin = crate::core::context::ChannelOrigin {
+            channel: reply.clone(),
+            conversation: msg.platform_chat_id.clone(),
+        };
+        match dispatcher.dispatch_with_origin(&msg, Some(origin)).await {
+            Ok(r) => {
+                if !r.text.is_empty() && r.text != "(No response)" {
+                    if let Err(e) = reply.send(&msg.platform_chat_id, &r.text).await {
+                        error!("[discord] interaction reply failed: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("[discord] agent error: {}", e);
+                let _ = reply
+                    .send(&msg.platform_chat_id, &format!("Error: {}", e))
+                    .await;
+            }
+        }
+        reply.close().await;
+    }));
 
     Ok(())
 }
@@ -750,6 +1495,7 @@ pub struct DiscordChannel {
     project_root: Arc<PathBuf>,
     intents: u64,
     intents_label: String,
+    limits: Option<ChannelLimitsConfig>,
 }
 
 impl DiscordChannel {
@@ -762,15 +1508,61 @@ impl DiscordChannel {
             project_root: Arc::new(project_root),
             intents,
             intents_label,
+            limits: None,
         }
     }
+
+    /// Inbound limits from `[channels.discord.<id>.limits]`.
+    pub fn with_limits(mut self, limits: Option<ChannelLimitsConfig>) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Slash commands to bulk-register on READY (per guild when `guilds` is
+    /// non-empty, globally otherwise).
+    pub fn with_commands(
+        mut self,
+        commands: Vec<DiscordCommandConfig>,
+        guilds: Vec<String>,
+    ) -> Self {
+        self.rt = Arc::new(GatewayRuntime {
+            commands,
+            command_guilds: guilds,
+            ..GatewayRuntime::new(self.rt.token.clone(), self.intents)
+        });
+        self
+    }
 }
 
 #[async_trait::async_trait]
 impl crate::core::context::ChannelEgress for DiscordChannel {
     async fn send(&self, conversation: &str, text: &str) -> Result<()> {
         // `conversation` is a Discord channel id (snowflake string).
-        send_channel_message(&reqwest::Client::new(), &self.rt.token, conversation, text).await
+        send_channel_message(
+            &reqwest::Client::new(),
+            &self.rt.token,
+            conversation,
+            text,
+            None,
+        )
+        .await
+    }
+
+    async fn send_approval(
+        &self,
+        conversation: &str,
+        text: &str,
+        allow_action: &str,
+        deny_action: &str,
+    ) -> Result<()> {
+        send_channel_message(
+            &reqwest::Client::new(),
+            &self.rt.token,
+            conversation,
+            text,
+            Some(&approval_components(allow_action, deny_action)),
+        )
+        .await
     }
 }
 
@@ -784,6 +1576,17 @@ impl Channel for DiscordChannel {
         "discord"
     }
 
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.limits.as_ref()
+    }
+
+    /// The gateway connection is dropped without a close frame, so the
+    /// saved session stays resumable for the next process.
+    async fn persist_resume_state(&self) -> Result<()> {
+        self.rt.persist_session(&self.project_root);
+        Ok(())
+    }
+
     async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
         info!(
             "🤖 Discord channel starting — intents 0x{:X} ({})",
@@ -808,12 +1611,26 @@ impl Channel for DiscordChannel {
 /// Reads `[channels.discord.<id>]` entries; tokens are deduplicated so the
 /// same bot can't be listed twice. Each pair is `(channel, agent_slug)` —
 /// per-channel agent lets different bots route to different workflows.
+/// Entries without `commands` take the `@command` functions of
+/// `entry_file`, if any.
 pub fn discover_channels(
     config: &JuglansConfig,
     project_root: &Path,
+    entry_file: &Path,
 ) -> Result<Vec<(Arc<DiscordChannel>, String)>> {
     let mut tokens_seen: std::collections::HashSet<String> = std::collections::HashSet::new();
     let mut out: Vec<(Arc<DiscordChannel>, String)> = Vec::new();
+    let entry_commands = match commands_from_workflow(entry_file) {
+        Ok(c) => c,
+        Err(e) => {
+            debug!(
+                "[discord] no @command functions from {}: {:#}",
+                entry_file.display(),
+                e
+            );
+            Vec::new()
+        }
+    };
 
     let mut emit = |bot_config: &crate::services::config::DiscordChannelConfig| -> Result<()> {
         if bot_config.token.is_empty() {
@@ -836,13 +1653,26 @@ pub fn discover_channels(
             .intents_bitmask
             .unwrap_or_else(|| intents_to_bitmask(&bot_config.intents));
         let intents_label = bot_config.intents.join(", ");
+        let commands = if bot_config.commands.is_empty() {
+            entry_commands.clone()
+        } else {
+            bot_config.commands.clone()
+        };
+        // Fail at startup rather than on READY for a malformed command.
+        for cmd in &commands {
+            command_payload(cmd)?;
+        }
         out.push((
-            Arc::new(DiscordChannel::new(
-                bot_config.token.clone(),
-                intents,
-                intents_label,
-                project_root.to_path_buf(),
-            )),
+            Arc::new(
+                DiscordChannel::new(
+                    bot_config.token.clone(),
+                    intents,
+                    intents_label,
+                    project_root.to_path_buf(),
+                )
+                .with_commands(commands, bot_config.command_guilds.clone())
+                .with_limits(bot_config.limits.clone()),
+            ),
             bot_config.agent.clone(),
         ));
         Ok(())
@@ -967,4 +1797,224 @@ mod tests {
         let cleared = SessionFile::load(&tmp);
         assert!(!cleared.is_resumable());
     }
+
+    #[test]
+    fn slash_command_becomes_command_event() {
+        let d = json!({
+            "id": "900", "type": 2, "token": "tok", "application_id": "app",
+            "channel_id": "C1", "guild_id": "G1",
+            "member": { "user": { "id": "U123456789", "username": "ann" } },
+            "data": {
+                "name": "todo",
+                "options": [{
+                    "type": 1, "name": "add",
+                    "options": [
+                        { "type": 3, "name": "title", "value": "buy milk" },
+                        { "type": 4, "name": "priority", "value": 2 }
+                    ]
+                }]
+            }
+        });
+        let msg = parse_interaction(&d).unwrap();
+        assert_eq!(msg.event_type, "command");
+        assert_eq!(msg.platform_chat_id, "C1");
+        assert_eq!(msg.platform_user_id, "U123456789");
+        assert_eq!(msg.username.as_deref(), Some("ann"));
+        assert_eq!(msg.text, "/todo add buy milk 2");
+        assert_eq!(msg.event_data["command"], "todo");
+        assert_eq!(msg.event_data["subcommand"], "add");
+        assert_eq!(msg.event_data["options"]["title"], "buy milk");
+        assert_eq!(msg.event_data["options"]["priority"], 2);
+    }
+
+    #[test]
+    fn components_become_card_actions() {
+        let button = json!({
+            "id": "901", "type": 3, "token": "tok", "channel_id": "D1",
+            "user": { "id": "U1", "username": "bob" },
+            "message": { "id": "M1", "content": "Approve?" },
+            "data": { "component_type": 2, "custom_id": "approve" }
+        });
+        let msg = parse_interaction(&button).unwrap();
+        assert_eq!(msg.event_type, "card_action");
+        assert_eq!(msg.event_data["action"], "approve");
+        assert_eq!(msg.event_data["component"], "button");
+        assert_eq!(msg.event_data["message_id"], "M1");
+        assert_eq!(msg.username.as_deref(), Some("bob"));
+
+        let select = json!({
+            "id": "902", "type": 3, "token": "tok", "channel_id": "D1",
+            "user": { "id": "U1" },
+            "data": { "component_type": 3, "custom_id": "size", "values": ["l"] }
+        });
+        let msg = parse_interaction(&select).unwrap();
+        assert_eq!(msg.event_data["component"], "select");
+        assert_eq!(msg.event_data["values"], json!(["l"]));
+
+        let ping = json!({ "id": "903", "type": 1, "channel_id": "D1" });
+        assert!(parse_interaction(&ping).is_none());
+    }
+
+    #[test]
+    fn command_payload_orders_required_options_first() {
+        let cmd = DiscordCommandConfig {
+            name: "ask".into(),
+            description: String::new(),
+            options: vec![
+                DiscordCommandOption {
+                    name: "tone".into(),
+                    description: "How to answer".into(),
+                    kind: "string".into(),
+                    required: false,
+                    choices: vec!["brief".into(), "detailed".into()],
+                },
+                DiscordCommandOption {
+                    name: "question".into(),
+                    description: String::new(),
+                    kind: "string".into(),
+                    required: true,
+                    choices: vec![],
+                },
+            ],
+        };
+        let p = command_payload(&cmd).unwrap();
+        assert_eq!(p["description"], "ask");
+        assert_eq!(p["options"][0]["name"], "question");
+        assert_eq!(p["options"][0]["type"], 3);
+        assert_eq!(p["options"][1]["choices"][1]["value"], "detailed");
+
+        let bad = DiscordCommandConfig {
+            name: "Ask Me".into(),
+            ..cmd.clone()
+        };
+        assert!(command_payload(&bad).is_err());
+        let mut bad_type = cmd;
+        bad_type.options[0].kind = "float".into();
+        assert!(command_payload(&bad_type).is_err());
+    }
+
+    #[test]
+    fn commands_derived_from_decorated_functions() {
+        let src = r#"
+@command("ask", "Ask the bot a question")
+[ask(question)]: chat(message=question)
+
+[start]: notify(status="ready")
+"#;
+        let mut wf = crate::core::parser::GraphParser::parse(src).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut wf).unwrap();
+        let cmds = commands_from_graph(&wf);
+        assert_eq!(cmds.len(), 1);
+        assert_eq!(cmds[0].name, "ask");
+        assert_eq!(cmds[0].description, "Ask the bot a question");
+        assert_eq!(cmds[0].options[0].name, "question");
+        assert!(cmds[0].options[0].required);
+    }
+
+    #[test]
+    fn action_rows_from_buttons() {
+        let rows = action_rows(&json!([
+            ["Yes", { "text": "No", "data": "no", "style": "danger" }],
+            { "text": "Docs", "url": "https://example.com" }
+        ]))
+        .unwrap();
+        assert_eq!(rows[0]["type"], 1);
+        assert_eq!(rows[0]["components"][0]["custom_id"], "Yes");
+        assert_eq!(rows[0]["components"][1]["style"], 4);
+        assert_eq!(rows[1]["components"][0]["style"], 5);
+        assert!(action_rows(&json!([vec!["x"; 6]])).is_err());
+        assert!(action_rows(&json!([["x".repeat(101)]])).is_err());
+    }
+
+    /// Records `(method, path, body)` for every call to the interaction
+    /// webhook endpoints; follow-ups answer with an incrementing message id.
+    async fn mock_discord_api() -> (String, Arc<Mutex<Vec<(String, String, Value)>>>) {
+        let calls: Arc<Mutex<Vec<(String, String, Value)>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let app = axum::Router::new().fallback(
+            move |method: axum::http::Method, uri: axum::http::Uri, body: axum::body::Bytes| {
+                let recorded = recorded.clone();
+                async move {
+                    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                    let n = {
+                        let mut c = recorded.lock().unwrap();
+                        c.push((method.to_string(), uri.to_string(), body));
+                        c.len()
+                    };
+                    axum::Json(json!({ "id": format!("F{}", n) }))
+                }
+            },
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}", addr), calls)
+    }
+
+    fn interaction_reply(api_base: &str) -> InteractionReply {
+        InteractionReply {
+            http: reqwest::Client::new(),
+            api_base: api_base.to_string(),
+            application_id: "app".into(),
+            token: "tok".into(),
+            original_pending: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[tokio::test]
+    async fn command_stream_fills_deferred_original_then_edits_it() {
+        use crate::core::context::ChannelEgress;
+        let (api, calls) = mock_discord_api().await;
+        let reply = interaction_reply(&api);
+        reply.ack("900", true).await.unwrap();
+        let mut handle = reply.start_stream("C1").await.unwrap();
+        handle.push_token("Hel").await.unwrap();
+        handle.push_token("lo").await.unwrap();
+        handle.finalize().await.unwrap();
+        reply.close().await;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls[0].0, "POST");
+        assert_eq!(calls[0].1, "/interactions/900/tok/callback");
+        assert_eq!(calls[0].2["type"], 5);
+        assert_eq!(calls[1].0, "PATCH");
+        assert_eq!(calls[1].1, "/webhooks/app/tok/messages/@original");
+        assert_eq!(calls[1].2["content"], "Hel");
+        let last = calls.last().unwrap();
+        assert_eq!(last.0, "PATCH");
+        assert_eq!(last.1, "/webhooks/app/tok/messages/@original");
+        assert_eq!(last.2["content"], "Hello");
+    }
+
+    #[tokio::test]
+    async fn component_replies_are_follow_ups() {
+        use crate::core::context::ChannelEgress;
+        let (api, calls) = mock_discord_api().await;
+        let reply = interaction_reply(&api);
+        reply.ack("901", false).await.unwrap();
+        reply.send("C1", "done").await.unwrap();
+        reply.close().await;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].2["type"], 6);
+        assert_eq!(calls[1].0, "POST");
+        assert_eq!(calls[1].1, "/webhooks/app/tok?wait=true");
+        assert_eq!(calls[1].2["content"], "done");
+    }
+
+    #[tokio::test]
+    async fn silent_command_deletes_placeholder() {
+        let (api, calls) = mock_discord_api().await;
+        let reply = interaction_reply(&api);
+        reply.ack("902", true).await.unwrap();
+        reply.close().await;
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].0, "DELETE");
+        assert_eq!(calls[1].1, "/webhooks/app/tok/messages/@original");
+    }
 }
@@ -0,0 +1,1062 @@
+// src/adapters/email.rs
+//
+// Email adapter. One `[channels.email.<id>]` entry = one mailbox.
+//
+// Ingress (active `run()`, only when `imap_host` is set): log in over IMAP,
+// select the mailbox, fetch every UNSEEN message, then wait for more with
+// IDLE (or poll every `poll_interval_secs` when IDLE is off/unsupported).
+// Each message is parsed into a PlatformMessage and dispatched in a detached
+// task; handled messages are flagged `\Seen` unless `mark_seen = false`.
+//
+// Conversations are threads. The conversation id is the thread root's
+// Message-ID in angle brackets (`<root@host>`): the first entry of
+// `References`, else `In-Reply-To`, else the message's own Message-ID. So a
+// whole back-and-forth shares one history, however many replies deep.
+//
+// Which messages are handled: everything except mail from our own address,
+// auto-replies (`Auto-Submitted` other than `no`) and bulk/list mail
+// (`Precedence: bulk | list | junk`) — answering those is how mail loops start.
+//
+// Egress: SMTP via lettre. `send(conversation, text)` replies to the last
+// message of a known thread (`In-Reply-To` + `References`, `Re:` subject);
+// a conversation that is a plain address starts a new message instead.
+// Known threads are kept at `.juglans/email/<instance_id>.threads.json`
+// (the MAX_THREADS most recently used), so replies after a restart still
+// land in-thread.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use dashmap::DashSet;
+use futures_util::TryStreamExt;
+use lettre::message::header::ContentType;
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use mail_parser::{HeaderValue, MessageParser, MimeHeaders};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use super::{Channel, MessageDispatcher, PlatformMessage};
+use crate::services::config::{ChannelLimitsConfig, EmailChannelConfig, JuglansConfig};
+
+// ─── Constants ──────────────────────────────────────────────────────────────
+
+/// RFC 2177: servers may drop an IDLE after 30 minutes, so re-issue it well
+/// before that.
+const IDLE_REFRESH_SECS: u64 = 25 * 60;
+
+const SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// Threads remembered per mailbox; the least recently used go first.
+const MAX_THREADS: usize = 1000;
+
+// ─── Threads ────────────────────────────────────────────────────────────────
+
+/// What a reply into a thread needs. Ids are stored without angle brackets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct EmailThread {
+    pub reply_to: String,
+    pub subject: String,
+    pub last_message_id: String,
+    pub references: Vec<String>,
+}
+
+impl EmailThread {
+    /// `References` for a reply: the chain so far plus the message replied to.
+    fn reply_references(&self) -> Vec<String> {
+        let mut refs = self.references.clone();
+        if !refs.contains(&self.last_message_id) {
+            refs.push(self.last_message_id.clone());
+        }
+        refs
+    }
+}
+
+/// Thread state by conversation id, bounded to MAX_THREADS and mirrored to
+/// disk on every change.
+struct ThreadStore {
+    path: Option<PathBuf>,
+    max: usize,
+    inner: Mutex<ThreadMap>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ThreadMap {
+    /// Bumped on every insert/update; orders threads by recency.
+    tick: u64,
+    threads: HashMap<String, (EmailThread, u64)>,
+}
+
+impl ThreadStore {
+    fn path(project_root: &Path, instance_id: &str) -> PathBuf {
+        project_root
+            .join(".juglans")
+            .join("email")
+            .join(format!("{}.threads.json", instance_id))
+    }
+
+    /// Load the saved threads, or start empty. `None` keeps them in memory.
+    fn load(path: Option<PathBuf>, max: usize) -> Self {
+        let map = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str::<ThreadMap>(&s).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            max,
+            inner: Mutex::new(map),
+        }
+    }
+
+    fn get(&self, conversation: &str) -> Option<EmailThread> {
+        let map = self.inner.lock().unwrap();
+        map.threads.get(conversation).map(|(t, _)| t.clone())
+    }
+
+    fn insert(&self, conversation: &str, thread: EmailThread) {
+        let max = self.max;
+        self.change(|map| {
+            map.tick += 1;
+            let tick = map.tick;
+            map.threads.insert(conversation.to_string(), (thread, tick));
+            if map.threads.len() > max {
+                let oldest = map
+                    .threads
+                    .iter()
+                    .min_by_key(|(_, (_, used))| *used)
+                    .map(|(k, _)| k.clone());
+                if let Some(k) = oldest {
+                    map.threads.remove(&k);
+                }
+            }
+        });
+    }
+
+    fn update(&self, conversation: &str, f: impl FnOnce(&mut EmailThread)) {
+        self.change(|map| {
+            map.tick += 1;
+            let tick = map.tick;
+            if let Some((t, used)) = map.threads.get_mut(conversation) {
+                f(t);
+                *used = tick;
+            }
+        });
+    }
+
+    fn change(&self, f: impl FnOnce(&mut ThreadMap)) {
+        let mut map = self.inner.lock().unwrap();
+        f(&mut map);
+        let Some(path) = &self.path else {
+            return;
+        };
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, serde_json::to_vec(&*map).unwrap_or_default()));
+        if let Err(e) = saved {
+            warn!(
+                "[email] failed to save threads to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// `Re: <subject>`, without stacking prefixes.
+pub(crate) fn reply_subject(subject: &str) -> String {
+    let trimmed = subject.trim();
+    if trimmed
+        .get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("re:"))
+    {
+        trimmed.to_string()
+    } else if trimmed.is_empty() {
+        "Re: (no subject)".to_string()
+    } else {
+        format!("Re: {}", trimmed)
+    }
+}
+
+fn bracketed(id: &str) -> String {
+    format!("<{}>", id.trim_matches(|c| c == '<' || c == '>'))
+}
+
+/// A thread conversation id (`<root@host>`) as opposed to a bare address.
+fn is_thread_id(conversation: &str) -> bool {
+    conversation.starts_with('<') && conversation.ends_with('>')
+}
+
+// ─── Parsing ────────────────────────────────────────────────────────────────
+
+fn header_ids(value: &HeaderValue) -> Vec<String> {
+    match value {
+        HeaderValue::Text(t) => t
+            .split_whitespace()
+            .map(|s| s.trim_matches(|c| c == '<' || c == '>').to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        HeaderValue::TextList(list) => list
+            .iter()
+            .map(|s| s.trim_matches(|c| c == '<' || c == '>').to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Cut the quoted history off a reply: everything from an `On ... wrote:`
+/// or `-----Original Message-----` line down, and `>` lines anywhere.
+pub(crate) fn strip_quoted_reply(body: &str) -> String {
+    let mut kept: Vec<&str> = Vec::new();
+    for line in body.lines() {
+        let t = line.trim();
+        if (t.starts_with("On ") && t.ends_with("wrote:"))
+            || t.starts_with("-----Original Message-----")
+        {
+            break;
+        }
+        if t.starts_with('>') {
+            continue;
+        }
+        kept.push(line);
+    }
+    kept.join("\n").trim().to_string()
+}
+
+/// Parse a raw RFC 5322 message into a PlatformMessage plus the thread state
+/// a reply needs. None for mail that must not be dispatched (see module docs)
+/// or that carries neither text nor attachments.
+pub(crate) fn parse_email(
+    raw: &[u8],
+    own_address: &str,
+    max_attachment_bytes: usize,
+) -> Option<(PlatformMessage, EmailThread)> {
+    let msg = MessageParser::default().parse(raw)?;
+
+    let from = msg.from().and_then(|a| a.first())?;
+    let from_address = from.address()?.to_lowercase();
+    if from_address == own_address.to_lowercase() {
+        return None;
+    }
+    if let Some(auto) = msg.header_raw("Auto-Submitted") {
+        if !auto.trim().eq_ignore_ascii_case("no") {
+            return None;
+        }
+    }
+    if let Some(precedence) = msg.header_raw("Precedence") {
+        if matches!(
+            precedence.trim().to_lowercase().as_str(),
+            "bulk" | "list" | "junk"
+        ) {
+            return None;
+        }
+    }
+
+    let message_id = msg
+        .message_id()
+        .map(String::from)
+        .unwrap_or_else(|| format!("{}@juglans.local", uuid::Uuid::new_v4().simple()));
+    let references = header_ids(msg.references());
+    let in_reply_to = header_ids(msg.in_reply_to());
+    let root = references
+        .first()
+        .or(in_reply_to.first())
+        .cloned()
+        .unwrap_or_else(|| message_id.clone());
+
+    let reply_to = msg
+        .reply_to()
+        .and_then(|a| a.first())
+        .and_then(|a| a.address())
+        .map(|a| a.to_lowercase())
+        .unwrap_or_else(|| from_address.clone());
+    let subject = msg.subject().unwrap_or("").to_string();
+    let body = msg.body_text(0).map(|b| b.to_string()).unwrap_or_default();
+    let text = strip_quoted_reply(&body);
+
+    let attachments: Vec<Value> = msg
+        .attachments()
+        .map(|part| {
+            let content_type = part
+                .content_type()
+                .map(|ct| match ct.subtype() {
+                    Some(sub) => format!("{}/{}", ct.ctype(), sub),
+                    None => ct.ctype().to_string(),
+                })
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            let size = part.contents().len();
+            let mut att = json!({
+                "filename": part.attachment_name().unwrap_or("attachment"),
+                "content_type": content_type,
+                "size": size,
+            });
+            if size <= max_attachment_bytes {
+                att["content_base64"] =
+                    json!(base64::engine::general_purpose::STANDARD.encode(part.contents()));
+            }
+            att
+        })
+        .collect();
+
+    if text.is_empty() && attachments.is_empty() {
+        return None;
+    }
+
+    let addresses = |a: Option<&mail_parser::Address>| -> Vec<String> {
+        a.map(|a| {
+            a.iter()
+                .filter_map(|addr| addr.address())
+                .map(|s| s.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+    };
+    let from_name = from.name().map(String::from);
+
+    let thread = EmailThread {
+        reply_to: reply_to.clone(),
+        subject: subject.clone(),
+        last_message_id: message_id.clone(),
+        references: references.clone(),
+    };
+    let message = PlatformMessage {
+        event_type: "message".into(),
+        event_data: json!({
+            "text": &text,
+            "body": body,
+            "from": &from_address,
+            "from_name": &from_name,
+            "reply_to": reply_to,
+            "to": addresses(msg.to()),
+            "cc": addresses(msg.cc()),
+            "subject": subject,
+            "message_id": bracketed(&message_id),
+            "in_reply_to": in_reply_to.iter().map(|id| bracketed(id)).collect::<Vec<_>>(),
+            "references": references.iter().map(|id| bracketed(id)).collect::<Vec<_>>(),
+            "date": msg.date().map(|d| d.to_rfc3339()),
+            "attachments": attachments,
+        }),
+        platform_user_id: from_address,
+        platform_chat_id: bracketed(&root),
+        text,
+        username: from_name,
+        platform: "email".into(),
+    };
+    Some((message, thread))
+}
+
+// ─── SMTP ───────────────────────────────────────────────────────────────────
+
+/// One outbound message. Ids are without angle brackets.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutgoingMail {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+}
+
+impl OutgoingMail {
+    /// A reply to the last message of `thread`.
+    pub(crate) fn reply(thread: &EmailThread, text: &str) -> Self {
+        Self {
+            to: vec![thread.reply_to.clone()],
+            subject: reply_subject(&thread.subject),
+            text: text.to_string(),
+            in_reply_to: Some(thread.last_message_id.clone()),
+            references: thread.reply_references(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A fresh Message-ID (no brackets) on the mailbox's own domain.
+fn new_message_id(own_address: &str) -> String {
+    let domain = own_address
+        .rsplit_once('@')
+        .map(|(_, d)| d)
+        .filter(|d| !d.is_empty())
+        .unwrap_or("juglans.local");
+    format!("{}@{}", uuid::Uuid::new_v4().simple(), domain)
+}
+
+pub(crate) fn build_message(
+    cfg: &EmailChannelConfig,
+    mail: &OutgoingMail,
+    message_id: &str,
+) -> Result<lettre::Message> {
+    let from = Mailbox::new(
+        cfg.display_name.clone(),
+        cfg.address
+            .parse()
+            .map_err(|e| anyhow!("invalid address {:?}: {}", cfg.address, e))?,
+    );
+    let mut builder = lettre::Message::builder()
+        .from(from)
+        .subject(mail.subject.clone())
+        .message_id(Some(bracketed(message_id)))
+        .date_now();
+    if mail.to.is_empty() {
+        return Err(anyhow!("email: no recipients"));
+    }
+    for to in &mail.to {
+        builder = builder.to(to
+            .parse::<Mailbox>()
+            .map_err(|e| anyhow!("invalid recipient {:?}: {}", to, e))?);
+    }
+    for cc in &mail.cc {
+        builder = builder.cc(cc
+            .parse::<Mailbox>()
+            .map_err(|e| anyhow!("invalid cc {:?}: {}", cc, e))?);
+    }
+    if let Some(ref id) = mail.in_reply_to {
+        builder = builder.in_reply_to(bracketed(id));
+    }
+    if !mail.references.is_empty() {
+        let refs: Vec<String> = mail.references.iter().map(|id| bracketed(id)).collect();
+        builder = builder.references(refs.join(" "));
+    }
+    let message = match mail.html {
+        Some(ref html) => builder.multipart(MultiPart::alternative_plain_html(
+            mail.text.clone(),
+            html.clone(),
+        ))?,
+        None => builder.singlepart(
+            SinglePart::builder()
+                .header(ContentType::TEXT_PLAIN)
+                .body(mail.text.clone()),
+        )?,
+    };
+    Ok(message)
+}
+
+fn smtp_transport(cfg: &EmailChannelConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let host = cfg.smtp_host.as_str();
+    let builder = match cfg.smtp_security.as_str() {
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        other => {
+            return Err(anyhow!(
+                "unknown smtp_security \"{}\" (expected \"starttls\", \"tls\" or \"none\")",
+                other
+            ))
+        }
+    };
+    let mut builder = builder
+        .port(cfg.smtp_port)
+        .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECS)));
+    if !cfg.password.is_empty() {
+        let user = cfg.username.clone().unwrap_or_else(|| cfg.address.clone());
+        builder = builder.credentials(Credentials::new(user, cfg.password.clone()));
+    }
+    Ok(builder.build())
+}
+
+/// Send one message over SMTP. Returns its Message-ID (no brackets).
+pub(crate) async fn send_mail(cfg: &EmailChannelConfig, mail: &OutgoingMail) -> Result<String> {
+    let message_id = new_message_id(&cfg.address);
+    let message = build_message(cfg, mail, &message_id)?;
+    smtp_transport(cfg)?
+        .send(message)
+        .await
+        .with_context(|| format!("SMTP send via {}:{}", cfg.smtp_host, cfg.smtp_port))?;
+    Ok(message_id)
+}
+
+// ─── IMAP ───────────────────────────────────────────────────────────────────
+
+/// TLS and plaintext IMAP connections behind one type.
+trait ImapIo: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + std::fmt::Debug {}
+impl<T> ImapIo for T where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + std::fmt::Debug
+{
+}
+
+type ImapSession = async_imap::Session<Box<dyn ImapIo>>;
+
+async fn imap_connect(cfg: &EmailChannelConfig, host: &str) -> Result<ImapSession> {
+    let tcp = tokio::net::TcpStream::connect((host, cfg.imap_port))
+        .await
+        .with_context(|| format!("IMAP connect {}:{}", host, cfg.imap_port))?;
+    let stream: Box<dyn ImapIo> = if cfg.imap_tls {
+        use tokio_rustls::rustls;
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        let name = rustls::pki_types::ServerName::try_from(host.to_string())
+            .map_err(|e| anyhow!("invalid IMAP host {:?}: {}", host, e))?;
+        Box::new(
+            tokio_rustls::TlsConnector::from(Arc::new(tls))
+                .connect(name, tcp)
+                .await
+                .context("IMAP TLS handshake")?,
+        )
+    } else {
+        Box::new(tcp)
+    };
+    let user = cfg.username.clone().unwrap_or_else(|| cfg.address.clone());
+    async_imap::Client::new(stream)
+        .login(&user, &cfg.password)
+        .await
+        .map_err(|(e, _)| anyhow!("IMAP login as {} failed: {}", user, e))
+}
+
+// ─── Channel ────────────────────────────────────────────────────────────────
+
+/// One mailbox = one [`EmailChannel`]. `id()` is `"email:<instance_id>"`.
+pub struct EmailChannel {
+    id: String,
+    instance_id: String,
+    cfg: EmailChannelConfig,
+    /// Thread state by conversation id, filled on ingress so `send` can
+    /// reply in-thread.
+    threads: ThreadStore,
+    /// Message-IDs already dispatched (guards `mark_seen = false` and
+    /// duplicate deliveries).
+    seen: DashSet<String>,
+    /// Highest UID fetched in the selected mailbox.
+    last_uid: AtomicU32,
+}
+
+impl EmailChannel {
+    /// `project_root` is where threads are persisted; `None` keeps them in
+    /// memory only.
+    fn new(instance_id: String, cfg: EmailChannelConfig, project_root: Option<&Path>) -> Self {
+        let threads = ThreadStore::load(
+            project_root.map(|r| ThreadStore::path(r, &instance_id)),
+            MAX_THREADS,
+        );
+        Self {
+            id: format!("email:{}", instance_id),
+            instance_id,
+            cfg,
+            threads,
+            seen: DashSet::new(),
+            last_uid: AtomicU32::new(0),
+        }
+    }
+
+    /// Parse one fetched message and dispatch it in the background.
+    fn handle_raw(self: &Arc<Self>, raw: &[u8], dispatcher: Arc<dyn MessageDispatcher>) {
+        let (msg, thread) = match parse_email(raw, &self.cfg.address, self.cfg.max_attachment_bytes)
+        {
+            Some(parsed) => parsed,
+            None => return,
+        };
+        if !self.seen.insert(thread.last_message_id.clone()) {
+            return;
+        }
+        if self.seen.len() > 10_000 {
+            self.seen.clear();
+        }
+        self.threads.insert(&msg.platform_chat_id, thread);
+
+        let preview: String = msg.text.chars().take(50).collect();
+        info!(
+            "[email:{}] {} in {}: {}",
+            self.instance_id, msg.platform_user_id, msg.platform_chat_id, preview
+        );
+
+        let channel = self.clone();
+        tokio::spawn(super::while_delivering(async move {
+            use crate::core::context::ChannelEgress;
+            let conversation = msg.platform_chat_id.clone();
+            match dispatcher.dispatch(&msg).await {
+                Ok(reply) => {
+                    if reply.text.is_empty() || reply.text == "(No response)" {
+                        return;
+                    }
+                    if let Err(e) = channel.send(&conversation, &reply.text).await {
+                        error!("[email] send failed: {:#}", e);
+                    }
+                }
+                Err(e) => error!("[email] agent error: {}", e),
+            }
+        }));
+    }
+
+    /// Fetch UNSEEN messages newer than `last_uid` and dispatch them.
+    async fn fetch_new(
+        self: &Arc<Self>,
+        session: &mut ImapSession,
+        dispatcher: &Arc<dyn MessageDispatcher>,
+    ) -> Result<()> {
+        let last = self.last_uid.load(Ordering::Relaxed);
+        let mut uids: Vec<u32> = session
+            .uid_search("UNSEEN")
+            .await?
+            .into_iter()
+            .filter(|uid| *uid > last)
+            .collect();
+        if uids.is_empty() {
+            return Ok(());
+        }
+        uids.sort_unstable();
+        let set = uids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        // BODY.PEEK leaves \Seen alone; it's set below once dispatched.
+        let fetched: Vec<async_imap::types::Fetch> = session
+            .uid_fetch(&set, "(UID BODY.PEEK[])")
+            .await?
+            .try_collect()
+            .await?;
+        for fetch in &fetched {
+            if let Some(raw) = fetch.body() {
+                self.handle_raw(raw, dispatcher.clone());
+            }
+        }
+        if let Some(max) = uids.last() {
+            self.last_uid.store(*max, Ordering::Relaxed);
+        }
+        if self.cfg.mark_seen {
+            let _: Vec<_> = session
+                .uid_store(&set, "+FLAGS (\\Seen)")
+                .await?
+                .try_collect()
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// One IMAP session: connect, select, then fetch / wait forever. Returns
+    /// Err on any connection failure so `run` can reconnect. `failures` is
+    /// reset once the mailbox is selected, so only back-to-back failed
+    /// connects count toward giving up.
+    async fn run_imap_session(
+        self: &Arc<Self>,
+        host: &str,
+        dispatcher: &Arc<dyn MessageDispatcher>,
+        failures: &mut u32,
+    ) -> Result<()> {
+        let mut session = imap_connect(&self.cfg, host).await?;
+        let idle = self.cfg.idle && session.capabilities().await?.has_str("IDLE");
+        session.select(&self.cfg.mailbox).await?;
+        *failures = 0;
+        info!(
+            "[email:{}] watching {} ({})",
+            self.instance_id,
+            self.cfg.mailbox,
+            if idle { "IDLE" } else { "polling" }
+        );
+
+        loop {
+            self.fetch_new(&mut session, dispatcher).await?;
+            if idle {
+                let mut handle = session.idle();
+                handle.init().await?;
+                let (wait, stop) = handle.wait_with_timeout(Duration::from_secs(IDLE_REFRESH_SECS));
+                let outcome = wait.await?;
+                // Dropping `stop` early would interrupt the wait.
+                drop(stop);
+                debug!("[email:{}] IDLE returned {:?}", self.instance_id, outcome);
+                session = handle.done().await?;
+            } else {
+                tokio::time::sleep(Duration::from_secs(self.cfg.poll_interval_secs.max(1))).await;
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for EmailChannel {
+    async fn send(&self, conversation: &str, text: &str) -> Result<()> {
+        if is_thread_id(conversation) {
+            let thread = self
+                .threads
+                .get(conversation)
+                .ok_or_else(|| anyhow!("email: unknown thread {}", conversation))?;
+            let sent = send_mail(&self.cfg, &OutgoingMail::reply(&thread, text)).await?;
+            // Later replies in this run answer our own message, keeping the
+            // chain linear in the recipient's client.
+            self.threads.update(conversation, |t| {
+                t.references = t.reply_references();
+                t.last_message_id = sent;
+            });
+            return Ok(());
+        }
+        let mail = OutgoingMail {
+            to: vec![conversation.to_string()],
+            subject: "(no subject)".to_string(),
+            text: text.to_string(),
+            ..Default::default()
+        };
+        send_mail(&self.cfg, &mail).await.map(|_| ())
+    }
+}
+
+#[async_trait::async_trait]
+impl Channel for EmailChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn kind(&self) -> &str {
+        "email"
+    }
+
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.cfg.limits.as_ref()
+    }
+
+    async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
+        let host = match self.cfg.imap_host.clone() {
+            Some(h) if !h.is_empty() => h,
+            _ => return Ok(()),
+        };
+        info!(
+            "📧 Email channel {} starting ({} via {}:{})",
+            self.instance_id, self.cfg.address, host, self.cfg.imap_port
+        );
+        let dispatcher = Arc::new(super::OriginAwareDispatcher::new(self.clone(), dispatcher))
+            as Arc<dyn MessageDispatcher>;
+
+        let mut consecutive_failures: u32 = 0;
+        loop {
+            match self
+                .run_imap_session(&host, &dispatcher, &mut consecutive_failures)
+                .await
+            {
+                Ok(()) => consecutive_failures = 0,
+                Err(e) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "[email:{}] IMAP error ({}/5): {:#} — reconnecting",
+                        self.instance_id, consecutive_failures, e
+                    );
+                    if consecutive_failures >= 5 {
+                        return Err(e.context("Email IMAP: 5 consecutive failures"));
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+}
+
+// ─── Discovery ──────────────────────────────────────────────────────────────
+
+/// Build [`EmailChannel`] instances from `[channels.email.<id>]`. Thread
+/// state is kept under `<project_root>/.juglans/email/`.
+pub fn discover_channels(
+    config: &JuglansConfig,
+    project_root: &Path,
+) -> Result<Vec<(Arc<dyn Channel>, String)>> {
+    let mut out: Vec<(Arc<dyn Channel>, String)> = Vec::new();
+    for (instance_id, cfg) in &config.channels.email {
+        if !cfg.address.contains('@') {
+            return Err(anyhow!(
+                "[channels.email.{}] address {:?} is not an email address",
+                instance_id,
+                cfg.address
+            ));
+        }
+        if cfg.smtp_host.is_empty() {
+            return Err(anyhow!(
+                "[channels.email.{}] smtp_host is empty",
+                instance_id
+            ));
+        }
+        if !matches!(cfg.smtp_security.as_str(), "starttls" | "tls" | "none") {
+            return Err(anyhow!(
+                "[channels.email.{}] unknown smtp_security \"{}\" (expected \"starttls\", \"tls\" or \"none\")",
+                instance_id,
+                cfg.smtp_security
+            ));
+        }
+        out.push((
+            Arc::new(EmailChannel::new(
+                instance_id.clone(),
+                cfg.clone(),
+                Some(project_root),
+            )),
+            cfg.agent.clone(),
+        ));
+    }
+    Ok(out)
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::BotReply;
+    use std::sync::Mutex;
+
+    fn config(address: &str) -> EmailChannelConfig {
+        EmailChannelConfig {
+            address: address.into(),
+            display_name: Some("Support".into()),
+            username: None,
+            password: String::new(),
+            imap_host: None,
+            imap_port: 993,
+            imap_tls: true,
+            mailbox: "INBOX".into(),
+            smtp_host: "localhost".into(),
+            smtp_port: 587,
+            smtp_security: "starttls".into(),
+            agent: "default".into(),
+            idle: true,
+            poll_interval_secs: 60,
+            mark_seen: true,
+            max_attachment_bytes: 16,
+            limits: None,
+        }
+    }
+
+    const FIRST: &str = "From: Alice <Alice@Example.com>\r\n\
+To: support@acme.test\r\n\
+Subject: Printer on fire\r\n\
+Message-ID: <root@example.com>\r\n\
+Date: Mon, 12 Oct 2026 09:00:00 +0000\r\n\
+\r\n\
+It is on fire.\r\n";
+
+    const REPLY: &str = "From: alice@example.com\r\n\
+To: support@acme.test\r\n\
+Subject: Re: Printer on fire\r\n\
+Message-ID: <third@example.com>\r\n\
+In-Reply-To: <second@acme.test>\r\n\
+References: <root@example.com> <second@acme.test>\r\n\
+\r\n\
+Still burning.\r\n\
+\r\n\
+On Mon, Oct 12, 2026 at 9:05 AM Support <support@acme.test> wrote:\r\n\
+> Have you tried turning it off?\r\n";
+
+    #[test]
+    fn first_message_starts_its_own_thread() {
+        let (msg, thread) = parse_email(FIRST.as_bytes(), "support@acme.test", 1024).unwrap();
+        assert_eq!(msg.platform, "email");
+        assert_eq!(msg.platform_chat_id, "<root@example.com>");
+        assert_eq!(msg.platform_user_id, "alice@example.com");
+        assert_eq!(msg.username.as_deref(), Some("Alice"));
+        assert_eq!(msg.text, "It is on fire.");
+        assert_eq!(msg.event_data["subject"], "Printer on fire");
+        assert_eq!(msg.event_data["message_id"], "<root@example.com>");
+        assert_eq!(thread.last_message_id, "root@example.com");
+        assert!(thread.references.is_empty());
+    }
+
+    #[test]
+    fn replies_join_the_root_thread_without_quotes() {
+        let (msg, thread) = parse_email(REPLY.as_bytes(), "support@acme.test", 1024).unwrap();
+        assert_eq!(msg.platform_chat_id, "<root@example.com>");
+        assert_eq!(msg.text, "Still burning.");
+        assert!(msg.event_data["body"]
+            .as_str()
+            .unwrap()
+            .contains("turning it off"));
+        assert_eq!(
+            thread.reply_references(),
+            vec!["root@example.com", "second@acme.test", "third@example.com"]
+        );
+    }
+
+    #[test]
+    fn thread_store_is_bounded_and_survives_restart() {
+        let root = std::env::temp_dir().join(format!("jg-email-{}", uuid::Uuid::new_v4()));
+        let path = ThreadStore::path(&root, "support");
+        let thread = |id: &str| EmailThread {
+            reply_to: "alice@example.com".into(),
+            subject: "Printer".into(),
+            last_message_id: id.into(),
+            references: vec![],
+        };
+
+        let store = ThreadStore::load(Some(path.clone()), 2);
+        store.insert("<a>", thread("a"));
+        store.insert("<b>", thread("b"));
+        // Touching <a> makes <b> the least recently used.
+        store.update("<a>", |t| t.last_message_id = "a2".into());
+        store.insert("<c>", thread("c"));
+
+        let reloaded = ThreadStore::load(Some(path), 2);
+        assert_eq!(reloaded.get("<a>").unwrap().last_message_id, "a2");
+        assert!(reloaded.get("<b>").is_none());
+        assert!(reloaded.get("<c>").is_some());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn own_mail_auto_replies_and_bulk_are_ignored() {
+        let own = FIRST.replace("Alice <Alice@Example.com>", "support@acme.test");
+        assert!(parse_email(own.as_bytes(), "Support@Acme.test", 1024).is_none());
+        let auto = FIRST.replace("Subject:", "Auto-Submitted: auto-replied\r\nSubject:");
+        assert!(parse_email(auto.as_bytes(), "support@acme.test", 1024).is_none());
+        let bulk = FIRST.replace("Subject:", "Precedence: bulk\r\nSubject:");
+        assert!(parse_email(bulk.as_bytes(), "support@acme.test", 1024).is_none());
+        let human = FIRST.replace("Subject:", "Auto-Submitted: no\r\nSubject:");
+        assert!(parse_email(human.as_bytes(), "support@acme.test", 1024).is_some());
+    }
+
+    #[test]
+    fn attachments_are_inlined_up_to_the_size_limit() {
+        let raw = "From: alice@example.com\r\n\
+Subject: logs\r\n\
+Message-ID: <att@example.com>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+\r\n\
+--b\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+see attached\r\n\
+--b\r\n\
+Content-Type: text/plain; name=\"small.txt\"\r\n\
+Content-Disposition: attachment; filename=\"small.txt\"\r\n\
+\r\n\
+hi\r\n\
+--b\r\n\
+Content-Type: application/octet-stream; name=\"big.bin\"\r\n\
+Content-Disposition: attachment; filename=\"big.bin\"\r\n\
+\r\n\
+0123456789abcdefghijklmnopqrstuvwxyz\r\n\
+--b--\r\n";
+        let (msg, _) = parse_email(raw.as_bytes(), "support@acme.test", 16).unwrap();
+        let atts = msg.event_data["attachments"].as_array().unwrap();
+        assert_eq!(atts.len(), 2);
+        assert_eq!(atts[0]["filename"], "small.txt");
+        assert_eq!(atts[0]["content_base64"], "aGk=");
+        assert_eq!(atts[1]["filename"], "big.bin");
+        assert!(atts[1].get("content_base64").is_none());
+    }
+
+    #[test]
+    fn reply_subject_does_not_stack() {
+        assert_eq!(reply_subject("Printer"), "Re: Printer");
+        assert_eq!(reply_subject("RE: Printer"), "RE: Printer");
+        assert_eq!(reply_subject(""), "Re: (no subject)");
+    }
+
+    #[test]
+    fn reply_message_carries_thread_headers() {
+        let (_, thread) = parse_email(REPLY.as_bytes(), "support@acme.test", 1024).unwrap();
+        let mail = OutgoingMail::reply(&thread, "Evacuate.");
+        let message = build_message(&config("support@acme.test"), &mail, "out@acme.test").unwrap();
+        let raw = String::from_utf8(message.formatted()).unwrap();
+        assert!(raw.contains("To: alice@example.com"));
+        assert!(raw.contains("Subject: Re: Printer on fire"));
+        assert!(raw.contains("In-Reply-To: <third@example.com>"));
+        assert!(
+            raw.contains("References: <root@example.com> <second@acme.test> <third@example.com>")
+        );
+        assert!(raw.contains("Message-ID: <out@acme.test>"));
+        assert!(raw.contains("Evacuate."));
+    }
+
+    #[test]
+    fn discovery_rejects_bad_config() {
+        let mut config: JuglansConfig =
+            toml::from_str("[account]\nid = \"t\"\nname = \"t\"").unwrap();
+        let mut bad = self::config("support@acme.test");
+        bad.smtp_security = "ssl".into();
+        config.channels.email.insert("support".into(), bad);
+        assert!(discover_channels(&config, Path::new(".")).is_err());
+
+        let mut config: JuglansConfig =
+            toml::from_str("[account]\nid = \"t\"\nname = \"t\"").unwrap();
+        config
+            .channels
+            .email
+            .insert("support".into(), self::config("support@acme.test"));
+        let chs = discover_channels(&config, Path::new(".")).unwrap();
+        assert_eq!(chs[0].0.id(), "email:support");
+    }
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Recorder {
+        async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+            self.0.lock().unwrap().push(message.text.clone());
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+    }
+
+    /// Round trip against a local GreenMail container:
+    ///
+    ///   docker compose --profile mail up -d greenmail
+    ///   cargo test -- --ignored email::tests::greenmail_roundtrip
+    #[tokio::test]
+    #[ignore] // Requires a GreenMail server on localhost:3025 (SMTP) / 3143 (IMAP)
+    async fn greenmail_roundtrip() {
+        let local = |address: &str| EmailChannelConfig {
+            password: "secret".into(),
+            imap_host: Some("localhost".into()),
+            imap_port: 3143,
+            imap_tls: false,
+            smtp_port: 3025,
+            smtp_security: "none".into(),
+            poll_interval_secs: 1,
+            max_attachment_bytes: 1024,
+            ..config(address)
+        };
+        let subject = format!("roundtrip {}", uuid::Uuid::new_v4());
+        send_mail(
+            &local("alice@localhost"),
+            &OutgoingMail {
+                to: vec!["bot@localhost".into()],
+                subject: subject.clone(),
+                text: subject.clone(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let channel = Arc::new(EmailChannel::new(
+            "bot".into(),
+            local("bot@localhost"),
+            None,
+        ));
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        tokio::spawn(channel.clone().run(recorder.clone()));
+        for _ in 0..100 {
+            if recorder.0.lock().unwrap().contains(&subject) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(recorder.0.lock().unwrap().contains(&subject));
+
+        let conversation = channel
+            .threads
+            .inner
+            .lock()
+            .unwrap()
+            .threads
+            .iter()
+            .find(|(_, (t, _))| t.subject == subject)
+            .map(|(k, _)| k.clone())
+            .unwrap();
+        use crate::core::context::ChannelEgress;
+        channel.send(&conversation, "got it").await.unwrap();
+    }
+}
@@ -7,10 +7,13 @@ use dashmap::DashSet;
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{error, info, warn};
 
+use super::limits::{gate, InboundLimiter};
 use super::{run_agent_for_message, Channel, MessageDispatcher, PlatformMessage, ToolExecutor};
 use crate::services::config::JuglansConfig;
+use crate::services::outbox::DeliveryError;
 
 /// Feishu platform tool executor -- invokes bill_utils.py via Python subprocess
 struct FeishuToolExecutor {
@@ -133,6 +136,8 @@ pub struct FeishuWebhookHandler {
     approvers: Vec<String>,
     access_token: Mutex<Option<(String, std::time::Instant)>>,
     processed_events: DashSet<String>,
+    /// `[channels.feishu.<id>.limits]`, checked before each run.
+    limiter: Option<Arc<InboundLimiter>>,
 }
 
 impl FeishuWebhookHandler {
@@ -188,7 +193,9 @@ impl FeishuWebhookHandler {
                 if let Some(event) = body.get("event") {
                     let event = event.clone();
                     // Reuse handle_message logic
-                    if let Err(e) = self.handle_message(&event, channel.clone()).await {
+                    let handled =
+                        super::while_delivering(self.handle_message(&event, channel.clone()));
+                    if let Err(e) = handled.await {
                         error!("[Feishu Webhook] Message handling failed: {}", e);
                     }
                 }
@@ -281,6 +288,13 @@ impl FeishuWebhookHandler {
                 channel: ch.clone(),
                 conversation: chat_id.clone(),
             });
+        let _slot = match self.limiter {
+            Some(ref l) => match gate(l, &platform_msg, origin.as_ref()).await {
+                Some(slot) => Some(slot),
+                None => return Ok(()),
+            },
+            None => None,
+        };
         let result = {
             let tool_executor = FeishuToolExecutor::from_handler(self, &platform_msg);
             run_agent_for_message(
@@ -325,6 +339,39 @@ impl FeishuWebhookHandler {
     }
 }
 
+/// Feishu's frequency-limit error code (also sent with HTTP 400).
+const RATE_LIMITED_CODE: i64 = 99991400;
+
+/// Seconds until the rate-limit window resets (`x-ogw-ratelimit-reset`).
+pub(crate) fn rate_limit_reset(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get("x-ogw-ratelimit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Failed send → [`DeliveryError`]. A frequency-limit code counts as 429 so
+/// the outbox retries it.
+pub(crate) fn send_error(
+    status: reqwest::StatusCode,
+    retry_after: Option<Duration>,
+    body: &Value,
+) -> anyhow::Error {
+    let code = body["code"].as_i64().unwrap_or(-1);
+    let status = if code == RATE_LIMITED_CODE {
+        429
+    } else {
+        status.as_u16()
+    };
+    DeliveryError::new(
+        Some(status),
+        retry_after,
+        format!("Feishu send failed: code={}, msg={}", code, body["msg"]),
+    )
+    .into()
+}
+
 /// Send message to Feishu group via webhook URL (custom bot)
 #[allow(dead_code)] // wired up by point 9 (workflow reply(channel=...))
 pub async fn send_webhook(webhook_url: &str, text: &str) -> Result<()> {
@@ -425,6 +472,7 @@ async fn send_feishu_message(token: &str, chat_id: &str, text: &str, base_url: &
         .await?;
 
     let status = resp.status();
+    let retry_after = rate_limit_reset(resp.headers());
     let body: Value = resp.json().await.unwrap_or(json!({}));
     let api_code = body["code"].as_i64().unwrap_or(-1);
 
@@ -433,11 +481,7 @@ async fn send_feishu_message(token: &str, chat_id: &str, text: &str, base_url: &
             "[Feishu] Send message failed: HTTP {} | code: {} | body: {:?}",
             status, api_code, body
         );
-        return Err(anyhow::anyhow!(
-            "Feishu send failed: code={}, msg={}",
-            api_code,
-            body["msg"]
-        ));
+        return Err(send_error(status, retry_after, &body));
     }
 
     info!("[Feishu] Message sent to chat_id: {}", chat_id);
@@ -574,6 +618,7 @@ pub fn discover_channels(
                 approvers: cfg.approvers.clone(),
                 access_token: Mutex::new(None),
                 processed_events: DashSet::new(),
+                limiter: cfg.limits.clone().map(|l| Arc::new(InboundLimiter::new(l))),
             };
             let channel: Arc<dyn Channel> =
                 Arc::new(FeishuEventChannel::new(instance_id.clone(), handler));
@@ -0,0 +1,585 @@
+// src/adapters/limits.rs
+//
+// Inbound abuse protection for channels (`[channels.<kind>.<id>.limits]`).
+//
+// `InboundLimiter` decides whether a `PlatformMessage` may start a run:
+//   1. Deny lists, then allow lists (user or chat). Rejections are silent.
+//   2. Token buckets per user, per chat and per channel instance. A message
+//      must fit every configured bucket; tokens are only taken when it does.
+//   3. A per-conversation concurrency slot. While a conversation is at
+//      `max_concurrent`, new messages wait (`on_busy = "queue"`, bounded by
+//      `max_queued`) or are discarded (`on_busy = "drop"`).
+//
+// Rate and busy rejections answer with `slow_down_message` / `busy_message`,
+// at most once per `notice_interval_secs` per user so a flood doesn't turn
+// into an outbound flood.
+//
+// `RateLimitedDispatcher` applies the limiter in front of any
+// `MessageDispatcher`; `handle_serve` wraps a channel's dispatcher with it
+// when `Channel::limits()` is set. Channels that run workflows without a
+// dispatcher (Telegram webhook, Feishu events) call `gate` directly.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::Result;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+use super::{BotReply, MessageDispatcher, PlatformMessage};
+use crate::core::context::ChannelOrigin;
+use crate::services::config::ChannelLimitsConfig;
+
+/// Map size past which idle buckets / slots are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Classic token bucket; `rate` is tokens per second.
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    rate: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// `per_minute` messages per minute, bursting up to `burst` (default: the
+    /// per-minute rate, at least 1).
+    fn new(per_minute: f64, burst: Option<f64>, now: Instant) -> Self {
+        let capacity = burst.unwrap_or(per_minute).max(1.0);
+        Self {
+            tokens: capacity,
+            capacity,
+            rate: per_minute / 60.0,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.capacity
+    }
+}
+
+/// Concurrency state of one conversation.
+struct ChatSlot {
+    running: Arc<Semaphore>,
+    waiting: AtomicUsize,
+}
+
+/// Held for the duration of an admitted run; releases the conversation's
+/// concurrency slot on drop.
+pub struct RunSlot {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Outcome of [`InboundLimiter::admit`].
+pub enum Admission {
+    Admit(RunSlot),
+    /// Rejected. `notice` is the text to send back, if any (already
+    /// throttled by `notice_interval_secs`).
+    Reject {
+        reason: &'static str,
+        notice: Option<String>,
+    },
+}
+
+/// Per-channel admission control. Cheap to share behind an `Arc`.
+pub struct InboundLimiter {
+    cfg: ChannelLimitsConfig,
+    users: DashMap<String, Bucket>,
+    chats: DashMap<String, Bucket>,
+    channel: Option<Mutex<Bucket>>,
+    slots: DashMap<String, Arc<ChatSlot>>,
+    notices: DashMap<String, Instant>,
+}
+
+impl InboundLimiter {
+    pub fn new(cfg: ChannelLimitsConfig) -> Self {
+        let channel = (cfg.channel_rate > 0.0).then(|| {
+            Mutex::new(Bucket::new(
+                cfg.channel_rate,
+                cfg.channel_burst,
+                Instant::now(),
+            ))
+        });
+        Self {
+            cfg,
+            users: DashMap::new(),
+            chats: DashMap::new(),
+            channel,
+            slots: DashMap::new(),
+            notices: DashMap::new(),
+        }
+    }
+
+    /// Decide whether `msg` may run. Waits while the conversation is busy in
+    /// "queue" mode; everything else answers immediately.
+    pub async fn admit(&self, msg: &PlatformMessage) -> Admission {
+        if !self.is_allowed(msg) {
+            return Admission::Reject {
+                reason: "not allowed",
+                notice: None,
+            };
+        }
+        if !self.take_tokens(msg, Instant::now()) {
+            let notice = self.notice(msg, &self.cfg.slow_down_message);
+            return Admission::Reject {
+                reason: "rate limited",
+                notice,
+            };
+        }
+        match self.acquire_slot(msg).await {
+            Some(slot) => Admission::Admit(slot),
+            None => {
+                let notice = self.notice(msg, &self.cfg.busy_message);
+                Admission::Reject {
+                    reason: "conversation busy",
+                    notice,
+                }
+            }
+        }
+    }
+
+    fn is_allowed(&self, msg: &PlatformMessage) -> bool {
+        let user = &msg.platform_user_id;
+        let chat = &msg.platform_chat_id;
+        if self.cfg.deny_users.contains(user) || self.cfg.deny_chats.contains(chat) {
+            return false;
+        }
+        if self.cfg.allow_users.is_empty() && self.cfg.allow_chats.is_empty() {
+            return true;
+        }
+        self.cfg.allow_users.contains(user) || self.cfg.allow_chats.contains(chat)
+    }
+
+    /// Take one token from every configured bucket, or none at all.
+    fn take_tokens(&self, msg: &PlatformMessage, now: Instant) -> bool {
+        let cfg = &self.cfg;
+        self.prune(now);
+
+        let mut channel = self
+            .channel
+            .as_ref()
+            .map(|m| m.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut user = (cfg.user_rate > 0.0).then(|| {
+            self.users
+                .entry(msg.platform_user_id.clone())
+                .or_insert_with(|| Bucket::new(cfg.user_rate, cfg.user_burst, now))
+        });
+        let mut chat = (cfg.chat_rate > 0.0).then(|| {
+            self.chats
+                .entry(msg.platform_chat_id.clone())
+                .or_insert_with(|| Bucket::new(cfg.chat_rate, cfg.chat_burst, now))
+        });
+
+        let mut buckets: Vec<&mut Bucket> = Vec::with_capacity(3);
+        if let Some(b) = channel.as_deref_mut() {
+            buckets.push(b);
+        }
+        if let Some(b) = user.as_deref_mut() {
+            buckets.push(b);
+        }
+        if let Some(b) = chat.as_deref_mut() {
+            buckets.push(b);
+        }
+        for b in buckets.iter_mut() {
+            b.refill(now);
+        }
+        if buckets.iter().any(|b| b.tokens < 1.0) {
+            return false;
+        }
+        for b in buckets {
+            b.tokens -= 1.0;
+        }
+        true
+    }
+
+    async fn acquire_slot(&self, msg: &PlatformMessage) -> Option<RunSlot> {
+        let max = self.cfg.max_concurrent;
+        if max == 0 {
+            return Some(RunSlot { _permit: None });
+        }
+        let slot = self
+            .slots
+            .entry(msg.platform_chat_id.clone())
+            .or_insert_with(|| {
+                Arc::new(ChatSlot {
+                    running: Arc::new(Semaphore::new(max)),
+                    waiting: AtomicUsize::new(0),
+                })
+            })
+            .clone();
+
+        if let Ok(permit) = slot.running.clone().try_acquire_owned() {
+            return Some(RunSlot {
+                _permit: Some(permit),
+            });
+        }
+        if self.cfg.on_busy != "queue" {
+            return None;
+        }
+        // Reserve a queue position; back out if the queue is full.
+        if slot.waiting.fetch_add(1, Ordering::SeqCst) >= self.cfg.max_queued {
+            slot.waiting.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        debug!(
+            "[limits] queueing message in busy conversation {}",
+            msg.platform_chat_id
+        );
+        let permit = slot.running.clone().acquire_owned().await.ok();
+        slot.waiting.fetch_sub(1, Ordering::SeqCst);
+        permit.map(|p| RunSlot { _permit: Some(p) })
+    }
+
+    /// `text` unless it is empty or this user was notified recently.
+    fn notice(&self, msg: &PlatformMessage, text: &str) -> Option<String> {
+        if text.is_empty() {
+            return None;
+        }
+        let interval = Duration::from_secs(self.cfg.notice_interval_secs);
+        let now = Instant::now();
+        match self.notices.entry(msg.platform_user_id.clone()) {
+            Entry::Occupied(mut last) => {
+                if now.saturating_duration_since(*last.get()) < interval {
+                    return None;
+                }
+                last.insert(now);
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(now);
+            }
+        }
+        Some(text.to_string())
+    }
+
+    /// Drop state for idle keys once the maps grow large (one-off senders in
+    /// big public chats would otherwise accumulate forever).
+    fn prune(&self, now: Instant) {
+        if self.users.len() > PRUNE_THRESHOLD {
+            self.users.retain(|_, b| {
+                b.refill(now);
+                !b.is_full()
+            });
+        }
+        if self.chats.len() > PRUNE_THRESHOLD {
+            self.chats.retain(|_, b| {
+                b.refill(now);
+                !b.is_full()
+            });
+        }
+        if self.slots.len() > PRUNE_THRESHOLD {
+            let max = self.cfg.max_concurrent;
+            self.slots.retain(|_, s| {
+                s.running.available_permits() < max || s.waiting.load(Ordering::SeqCst) > 0
+            });
+        }
+        if self.notices.len() > PRUNE_THRESHOLD {
+            let interval = Duration::from_secs(self.cfg.notice_interval_secs);
+            self.notices
+                .retain(|_, t| now.saturating_duration_since(*t) < interval);
+        }
+    }
+}
+
+fn log_reject(msg: &PlatformMessage, reason: &str) {
+    debug!(
+        "[limits] {} message from {} in {} rejected: {}",
+        msg.platform, msg.platform_user_id, msg.platform_chat_id, reason
+    );
+}
+
+/// Admission check for channels that run workflows themselves. Delivers the
+/// rejection notice through `origin` and returns `None` when `msg` must not
+/// run; otherwise returns the slot to hold until the run finishes.
+pub async fn gate(
+    limiter: &InboundLimiter,
+    msg: &PlatformMessage,
+    origin: Option<&ChannelOrigin>,
+) -> Option<RunSlot> {
+    match limiter.admit(msg).await {
+        Admission::Admit(slot) => Some(slot),
+        Admission::Reject { reason, notice } => {
+            log_reject(msg, reason);
+            if let (Some(text), Some(origin)) = (notice, origin) {
+                if let Err(e) = origin.channel.send(&origin.conversation, &text).await {
+                    warn!("[limits] failed to send notice: {}", e);
+                }
+            }
+            None
+        }
+    }
+}
+
+/// `MessageDispatcher` middleware applying an [`InboundLimiter`] before the
+/// inner dispatcher runs. Rejection notices go out through the message's
+/// origin; without one they come back as the reply text.
+pub struct RateLimitedDispatcher {
+    limiter: Arc<InboundLimiter>,
+    inner: Arc<dyn MessageDispatcher>,
+}
+
+impl RateLimitedDispatcher {
+    pub fn new(cfg: ChannelLimitsConfig, inner: Arc<dyn MessageDispatcher>) -> Self {
+        Self {
+            limiter: Arc::new(InboundLimiter::new(cfg)),
+            inner,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MessageDispatcher for RateLimitedDispatcher {
+    async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+        self.dispatch_with_origin(message, None).await
+    }
+
+    async fn dispatch_with_origin(
+        &self,
+        message: &PlatformMessage,
+        origin: Option<ChannelOrigin>,
+    ) -> Result<BotReply> {
+        let _slot = match self.limiter.admit(message).await {
+            Admission::Admit(slot) => slot,
+            Admission::Reject { reason, notice } => {
+                log_reject(message, reason);
+                let text = match (notice, &origin) {
+                    (Some(text), Some(origin)) => {
+                        if let Err(e) = origin.channel.send(&origin.conversation, &text).await {
+                            warn!("[limits] failed to send notice: {}", e);
+                        }
+                        String::new()
+                    }
+                    (Some(text), None) => text,
+                    (None, _) => String::new(),
+                };
+                return Ok(BotReply { text });
+            }
+        };
+        match origin {
+            Some(origin) => self.inner.dispatch_with_origin(message, Some(origin)).await,
+            None => self.inner.dispatch(message).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::sync::Notify;
+
+    fn msg(user: &str, chat: &str) -> PlatformMessage {
+        PlatformMessage {
+            event_type: "message".into(),
+            event_data: json!({ "text": "hi" }),
+            platform_user_id: user.into(),
+            platform_chat_id: chat.into(),
+            text: "hi".into(),
+            username: None,
+            platform: "telegram".into(),
+        }
+    }
+
+    fn limits() -> ChannelLimitsConfig {
+        ChannelLimitsConfig {
+            max_concurrent: 0,
+            on_busy: "queue".into(),
+            max_queued: 1,
+            slow_down_message: "slow down".into(),
+            busy_message: "busy".into(),
+            notice_interval_secs: 60,
+            ..Default::default()
+        }
+    }
+
+    fn admitted(a: &Admission) -> bool {
+        matches!(a, Admission::Admit(_))
+    }
+
+    #[test]
+    fn bucket_refills_at_rate() {
+        let t0 = Instant::now();
+        let mut b = Bucket::new(60.0, Some(2.0), t0);
+        b.tokens = 0.0;
+        b.refill(t0 + Duration::from_millis(1500));
+        assert!((b.tokens - 1.5).abs() < 1e-9);
+        b.refill(t0 + Duration::from_secs(10));
+        assert!(b.is_full());
+    }
+
+    #[tokio::test]
+    async fn allow_and_deny_lists() {
+        let limiter = InboundLimiter::new(ChannelLimitsConfig {
+            allow_chats: vec!["team".into()],
+            allow_users: vec!["alice".into()],
+            deny_users: vec!["mallory".into()],
+            ..limits()
+        });
+        assert!(admitted(&limiter.admit(&msg("bob", "team")).await));
+        assert!(admitted(&limiter.admit(&msg("alice", "dm")).await));
+        assert!(!admitted(&limiter.admit(&msg("bob", "dm")).await));
+        match limiter.admit(&msg("mallory", "team")).await {
+            Admission::Reject { notice, .. } => assert!(notice.is_none()),
+            Admission::Admit(_) => panic!("denied user admitted"),
+        }
+    }
+
+    #[tokio::test]
+    async fn buckets_are_all_or_nothing_and_notices_throttled() {
+        let limiter = InboundLimiter::new(ChannelLimitsConfig {
+            user_rate: 1.0,
+            user_burst: Some(2.0),
+            chat_rate: 1.0,
+            chat_burst: Some(1.0),
+            ..limits()
+        });
+        assert!(admitted(&limiter.admit(&msg("alice", "c1")).await));
+        // c1's bucket is empty: rejected, and alice's token is not spent.
+        match limiter.admit(&msg("alice", "c1")).await {
+            Admission::Reject { notice, reason } => {
+                assert_eq!(reason, "rate limited");
+                assert_eq!(notice.as_deref(), Some("slow down"));
+            }
+            Admission::Admit(_) => panic!("chat bucket ignored"),
+        }
+        assert!(admitted(&limiter.admit(&msg("alice", "c2")).await));
+        // Both buckets exhausted now; the notice is not repeated.
+        match limiter.admit(&msg("alice", "c3")).await {
+            Admission::Reject { notice, .. } => assert!(notice.is_none()),
+            Admission::Admit(_) => panic!("user bucket ignored"),
+        }
+        assert!(admitted(&limiter.admit(&msg("bob", "c4")).await));
+    }
+
+    #[tokio::test]
+    async fn channel_bucket_is_shared() {
+        let limiter = InboundLimiter::new(ChannelLimitsConfig {
+            channel_rate: 1.0,
+            channel_burst: Some(2.0),
+            ..limits()
+        });
+        assert!(admitted(&limiter.admit(&msg("a", "1")).await));
+        assert!(admitted(&limiter.admit(&msg("b", "2")).await));
+        assert!(!admitted(&limiter.admit(&msg("c", "3")).await));
+    }
+
+    #[tokio::test]
+    async fn busy_conversation_drops_or_queues() {
+        let drop = InboundLimiter::new(ChannelLimitsConfig {
+            max_concurrent: 1,
+            on_busy: "drop".into(),
+            ..limits()
+        });
+        let running = drop.admit(&msg("a", "c")).await;
+        assert!(admitted(&running));
+        match drop.admit(&msg("b", "c")).await {
+            Admission::Reject { notice, reason } => {
+                assert_eq!(reason, "conversation busy");
+                assert_eq!(notice.as_deref(), Some("busy"));
+            }
+            Admission::Admit(_) => panic!("second run admitted"),
+        }
+        assert!(admitted(&drop.admit(&msg("b", "other")).await));
+        std::mem::drop(running);
+        assert!(admitted(&drop.admit(&msg("b", "c")).await));
+
+        let queue = Arc::new(InboundLimiter::new(ChannelLimitsConfig {
+            max_concurrent: 1,
+            ..limits()
+        }));
+        let running = queue.admit(&msg("a", "c")).await;
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move { admitted(&queue.admit(&msg("b", "c")).await) })
+        };
+        while queue.slots.get("c").unwrap().waiting.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        // max_queued = 1: a third message is dropped while one waits.
+        assert!(!admitted(&queue.admit(&msg("c", "c")).await));
+        std::mem::drop(running);
+        assert!(waiter.await.unwrap());
+    }
+
+    struct Recorder {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::core::context::ChannelEgress for Recorder {
+        async fn send(&self, _conversation: &str, text: &str) -> Result<()> {
+            self.sent.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    struct Slow(Arc<Notify>);
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Slow {
+        async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+            self.0.notified().await;
+            Ok(BotReply {
+                text: format!("echo {}", message.text),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatcher_sends_notices_through_origin() {
+        let release = Arc::new(Notify::new());
+        let dispatcher = Arc::new(RateLimitedDispatcher::new(
+            ChannelLimitsConfig {
+                max_concurrent: 1,
+                on_busy: "drop".into(),
+                ..limits()
+            },
+            Arc::new(Slow(release.clone())),
+        ));
+        let recorder = Arc::new(Recorder {
+            sent: Mutex::new(vec![]),
+        });
+        let origin = || ChannelOrigin {
+            channel: recorder.clone(),
+            conversation: "c".into(),
+        };
+
+        let first = {
+            let dispatcher = dispatcher.clone();
+            let origin = origin();
+            tokio::spawn(async move {
+                dispatcher
+                    .dispatch_with_origin(&msg("a", "c"), Some(origin))
+                    .await
+                    .unwrap()
+                    .text
+            })
+        };
+        while dispatcher.limiter.slots.get("c").is_none() {
+            tokio::task::yield_now().await;
+        }
+        let busy = dispatcher
+            .dispatch_with_origin(&msg("b", "c"), Some(origin()))
+            .await
+            .unwrap();
+        assert!(busy.text.is_empty());
+        assert_eq!(*recorder.sent.lock().unwrap(), vec!["busy".to_string()]);
+
+        // Without an origin the notice becomes the reply text.
+        let busy = dispatcher.dispatch(&msg("c", "c")).await.unwrap();
+        assert_eq!(busy.text, "busy");
+
+        release.notify_one();
+        assert_eq!(first.await.unwrap(), "echo hi");
+    }
+}
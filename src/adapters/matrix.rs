@@ -0,0 +1,1084 @@
+// src/adapters/matrix.rs
+//
+// Matrix adapter. One `[channels.matrix.<id>]` entry = one bot account on a
+// homeserver (Synapse, Conduit, Dendrite — anything speaking the
+// Client-Server API v3).
+//
+// Ingress (active `run()`): `/sync` long-polling. The `next_batch` token is
+// persisted at `.juglans/matrix/<instance_id>.json` after every successful
+// sync, so a restart picks up exactly where the last process stopped. On the
+// very first start (no token on disk) the initial sync only records the
+// position; room history from before the bot came up is never answered.
+//
+// Conversations are rooms: the conversation id is the room id
+// (`!abc:example.org`), DMs and group rooms alike.
+//
+// Which events are handled:
+//   - `m.room.message` with `msgtype: m.text` in joined rooms (optionally
+//     narrowed by `rooms` and `mention_only`).
+//   - Our own messages, `m.notice` (the bot convention), and edits
+//     (`m.replace`) are ignored.
+//   - Invites are accepted when `auto_join = true`.
+//
+// Egress: `PUT /rooms/{room}/send/m.room.message/{txnId}` (chunked at
+// MAX_MESSAGE_LEN) for `send`; streaming replies send on the first token and
+// then edit the message (`m.new_content` + `rel_type: m.replace`).
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use dashmap::DashSet;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use super::{Channel, MessageDispatcher, PlatformMessage};
+use crate::services::config::{ChannelLimitsConfig, JuglansConfig, MatrixChannelConfig};
+
+// ─── Constants ──────────────────────────────────────────────────────────────
+
+/// Events are capped at 64 KiB of JSON, and an edit carries the text twice
+/// (`body` and `m.new_content.body`), so chunks stay well below that.
+pub(crate) const MAX_MESSAGE_LEN: usize = 8000;
+
+const STREAM_EDIT_INTERVAL_MS: u128 = 1000;
+
+/// Only message events reach the timeline; presence and account data are
+/// never needed.
+const SYNC_FILTER: &str = r#"{"room":{"timeline":{"types":["m.room.message"]},"state":{"lazy_load_members":true},"ephemeral":{"types":[]},"account_data":{"types":[]}},"presence":{"types":[]},"account_data":{"types":[]}}"#;
+
+// ─── Sync token persistence ─────────────────────────────────────────────────
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncFile {
+    next_batch: Option<String>,
+}
+
+impl SyncFile {
+    fn path(project_root: &Path, instance_id: &str) -> PathBuf {
+        project_root
+            .join(".juglans")
+            .join("matrix")
+            .join(format!("{}.json", instance_id))
+    }
+
+    fn load(project_root: &Path, instance_id: &str) -> Self {
+        fs::read_to_string(Self::path(project_root, instance_id))
+            .ok()
+            .and_then(|s| serde_json::from_str::<SyncFile>(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, project_root: &Path, instance_id: &str) -> Result<()> {
+        let p = Self::path(project_root, instance_id);
+        if let Some(parent) = p.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&p, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// ─── Client-Server API ──────────────────────────────────────────────────────
+
+async fn request(
+    http: &reqwest::Client,
+    homeserver: &str,
+    token: &str,
+    method: Method,
+    path: &str,
+    body: Option<&Value>,
+    timeout: Duration,
+) -> Result<Value> {
+    let url = format!(
+        "{}/_matrix/client/v3{}",
+        homeserver.trim_end_matches('/'),
+        path
+    );
+    // Query strings (sync tokens, filters) stay out of error messages.
+    let endpoint = path.split('?').next().unwrap_or(path);
+    let mut attempt = 0;
+    loop {
+        let mut req = http
+            .request(method.clone(), &url)
+            .bearer_auth(token)
+            .timeout(timeout);
+        if let Some(b) = body {
+            req = req.json(b);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        let v: Value = resp.json().await.unwrap_or(Value::Null);
+        if status.as_u16() == 429 && attempt < 1 {
+            let wait_ms = v["retry_after_ms"].as_u64().unwrap_or(1000);
+            tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+            attempt += 1;
+            continue;
+        }
+        if !status.is_success() {
+            return Err(anyhow!(
+                "matrix {} {} failed (HTTP {}): {} {}",
+                method,
+                endpoint,
+                status.as_u16(),
+                v["errcode"].as_str().unwrap_or(""),
+                v["error"].as_str().unwrap_or("")
+            ));
+        }
+        return Ok(v);
+    }
+}
+
+/// Call a Client-Server API endpoint (`path` is relative to
+/// `/_matrix/client/v3`). Matrix reports failures as `{errcode, error}` with
+/// a non-2xx status. Retries once on HTTP 429 using `retry_after_ms`.
+pub(crate) async fn api_call(
+    http: &reqwest::Client,
+    homeserver: &str,
+    token: &str,
+    method: Method,
+    path: &str,
+    body: Option<&Value>,
+) -> Result<Value> {
+    request(
+        http,
+        homeserver,
+        token,
+        method,
+        path,
+        body,
+        Duration::from_secs(15),
+    )
+    .await
+}
+
+/// Transaction ids only have to be unique per access token; the timestamp
+/// keeps them unique across restarts.
+fn txn_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "juglans{}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Send one room event. Returns its `event_id`.
+pub(crate) async fn send_event(
+    http: &reqwest::Client,
+    homeserver: &str,
+    token: &str,
+    room_id: &str,
+    event_type: &str,
+    content: &Value,
+) -> Result<String> {
+    let path = format!(
+        "/rooms/{}/send/{}/{}",
+        urlencoding::encode(room_id),
+        urlencoding::encode(event_type),
+        txn_id()
+    );
+    let resp = api_call(http, homeserver, token, Method::PUT, &path, Some(content)).await?;
+    resp["event_id"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| anyhow!("matrix send returned no event_id"))
+}
+
+/// Post `text` to a room, chunked at MAX_MESSAGE_LEN. Returns the
+/// `event_id` of every message sent. A failure after the first chunk
+/// carries the unsent rest for the outbox.
+pub(crate) async fn post_message(
+    http: &reqwest::Client,
+    homeserver: &str,
+    token: &str,
+    room_id: &str,
+    text: &str,
+) -> Result<Vec<String>> {
+    let mut posted = Vec::new();
+    let chunks = super::discord::split_message(text, MAX_MESSAGE_LEN);
+    for (i, chunk) in chunks.iter().enumerate() {
+        if chunk.is_empty() {
+            continue;
+        }
+        let content = json!({ "msgtype": "m.text", "body": chunk });
+        let event_id = send_event(http, homeserver, token, room_id, "m.room.message", &content)
+            .await
+            .map_err(|e| crate::services::outbox::partial_failure(e, &chunks, i))?;
+        posted.push(event_id);
+    }
+    Ok(posted)
+}
+
+/// Replace the text of `event_id`. Clients without edit support show the
+/// `* `-prefixed fallback body.
+pub(crate) async fn edit_message(
+    http: &reqwest::Client,
+    homeserver: &str,
+    token: &str,
+    room_id: &str,
+    event_id: &str,
+    text: &str,
+) -> Result<()> {
+    let content = json!({
+        "msgtype": "m.text",
+        "body": format!("* {}", text),
+        "m.new_content": { "msgtype": "m.text", "body": text },
+        "m.relates_to": { "rel_type": "m.replace", "event_id": event_id },
+    });
+    send_event(http, homeserver, token, room_id, "m.room.message", &content)
+        .await
+        .map(|_| ())
+}
+
+/// `m.reaction` content annotating `event_id` with `key` (an emoji).
+pub(crate) fn reaction_content(event_id: &str, key: &str) -> Value {
+    json!({
+        "m.relates_to": { "rel_type": "m.annotation", "event_id": event_id, "key": key }
+    })
+}
+
+// ─── Event parsing ──────────────────────────────────────────────────────────
+
+/// Drop the quoted fallback (`> <@alice:hs> ...` lines plus a blank line)
+/// that clients prepend to the body of a reply.
+fn strip_reply_fallback(body: &str) -> &str {
+    if !body.starts_with("> ") {
+        return body;
+    }
+    match body.find("\n\n") {
+        Some(i) => &body[i + 2..],
+        None => body,
+    }
+}
+
+fn mentions(content: &Value, body: &str, own_user_id: &str) -> bool {
+    let listed = content
+        .pointer("/m.mentions/user_ids")
+        .and_then(|v| v.as_array())
+        .map(|ids| ids.iter().any(|id| id.as_str() == Some(own_user_id)))
+        .unwrap_or(false);
+    listed || body.contains(own_user_id)
+}
+
+/// Turn a timeline event of `room_id` into a PlatformMessage, or None if it
+/// should be ignored.
+pub(crate) fn event_to_message(
+    room_id: &str,
+    event: &Value,
+    own_user_id: &str,
+    mention_only: bool,
+) -> Option<PlatformMessage> {
+    if event["type"].as_str() != Some("m.room.message") {
+        return None;
+    }
+    let sender = event["sender"].as_str()?;
+    if sender == own_user_id {
+        return None;
+    }
+    let content = &event["content"];
+    if content["msgtype"].as_str() != Some("m.text") {
+        return None;
+    }
+    if content
+        .pointer("/m.relates_to/rel_type")
+        .and_then(|v| v.as_str())
+        == Some("m.replace")
+    {
+        return None;
+    }
+    let in_reply_to = content
+        .pointer("/m.relates_to/m.in_reply_to/event_id")
+        .and_then(|v| v.as_str());
+    let raw = content["body"].as_str().unwrap_or("");
+    let body = if in_reply_to.is_some() {
+        strip_reply_fallback(raw)
+    } else {
+        raw
+    };
+    if mention_only && !mentions(content, body, own_user_id) {
+        return None;
+    }
+    let text = body
+        .trim()
+        .strip_prefix(own_user_id)
+        .map(|rest| rest.trim_start_matches(':'))
+        .unwrap_or(body)
+        .trim()
+        .to_string();
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(PlatformMessage {
+        event_type: "message".into(),
+        event_data: json!({
+            "text": &text,
+            "room_id": room_id,
+            "event_id": event["event_id"],
+            "sender": sender,
+            "in_reply_to": in_reply_to,
+            "origin_server_ts": event["origin_server_ts"],
+        }),
+        platform_user_id: sender.to_string(),
+        platform_chat_id: room_id.to_string(),
+        text,
+        // `@alice:example.org` → `alice`
+        username: sender
+            .trim_start_matches('@')
+            .split(':')
+            .next()
+            .map(String::from),
+        platform: "matrix".into(),
+    })
+}
+
+// ─── Channel ────────────────────────────────────────────────────────────────
+
+/// One Matrix account = one [`MatrixChannel`]. `id()` is
+/// `"matrix:<instance_id>"`.
+pub struct MatrixChannel {
+    id: String,
+    instance_id: String,
+    cfg: MatrixChannelConfig,
+    project_root: PathBuf,
+    http: reqwest::Client,
+    /// The bot's mxid: from config, or `/account/whoami` on startup.
+    user_id: OnceLock<String>,
+    /// Event ids already handled. A sync retried after a network error can
+    /// deliver the same events again.
+    seen: DashSet<String>,
+}
+
+impl MatrixChannel {
+    fn new(instance_id: String, cfg: MatrixChannelConfig, project_root: PathBuf) -> Self {
+        let user_id = OnceLock::new();
+        if let Some(ref id) = cfg.user_id {
+            let _ = user_id.set(id.clone());
+        }
+        Self {
+            id: format!("matrix:{}", instance_id),
+            instance_id,
+            cfg,
+            project_root,
+            http: reqwest::Client::new(),
+            user_id,
+            seen: DashSet::new(),
+        }
+    }
+
+    async fn own_user_id(&self) -> Result<String> {
+        if let Some(id) = self.user_id.get() {
+            return Ok(id.clone());
+        }
+        let resp = api_call(
+            &self.http,
+            &self.cfg.homeserver,
+            &self.cfg.access_token,
+            Method::GET,
+            "/account/whoami",
+            None,
+        )
+        .await
+        .context("access_token rejected (GET /account/whoami)")?;
+        let id = resp["user_id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("/account/whoami returned no user_id"))?
+            .to_string();
+        Ok(self.user_id.get_or_init(|| id).clone())
+    }
+
+    fn room_allowed(&self, room_id: &str) -> bool {
+        self.cfg.rooms.is_empty() || self.cfg.rooms.iter().any(|r| r == room_id)
+    }
+
+    /// One `/sync` round. Without `since` this is the initial sync: it returns
+    /// immediately and its timeline (history) is not dispatched. Returns the
+    /// `next_batch` token.
+    async fn sync_once(
+        &self,
+        since: Option<&str>,
+        own_user_id: &str,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> Result<String> {
+        let timeout_ms = if since.is_some() {
+            self.cfg.sync_timeout_secs * 1000
+        } else {
+            0
+        };
+        let mut path = format!(
+            "/sync?timeout={}&filter={}",
+            timeout_ms,
+            urlencoding::encode(SYNC_FILTER)
+        );
+        if let Some(s) = since {
+            path.push_str(&format!("&since={}", urlencoding::encode(s)));
+        }
+        let resp = request(
+            &self.http,
+            &self.cfg.homeserver,
+            &self.cfg.access_token,
+            Method::GET,
+            &path,
+            None,
+            Duration::from_millis(timeout_ms) + Duration::from_secs(30),
+        )
+        .await?;
+        let next_batch = resp["next_batch"]
+            .as_str()
+            .ok_or_else(|| anyhow!("/sync returned no next_batch"))?
+            .to_string();
+
+        if self.cfg.auto_join {
+            if let Some(invites) = resp.pointer("/rooms/invite").and_then(|v| v.as_object()) {
+                for room_id in invites.keys().filter(|r| self.room_allowed(r)) {
+                    self.join(room_id).await;
+                }
+            }
+        }
+
+        if since.is_some() {
+            if let Some(joined) = resp.pointer("/rooms/join").and_then(|v| v.as_object()) {
+                for (room_id, room) in joined {
+                    if !self.room_allowed(room_id) {
+                        continue;
+                    }
+                    let events = room
+                        .pointer("/timeline/events")
+                        .and_then(|v| v.as_array())
+                        .map(|v| v.as_slice())
+                        .unwrap_or(&[]);
+                    for event in events {
+                        self.handle_event(room_id, event, own_user_id, dispatcher.clone());
+                    }
+                }
+            }
+        }
+        Ok(next_batch)
+    }
+
+    async fn join(&self, room_id: &str) {
+        let path = format!("/rooms/{}/join", urlencoding::encode(room_id));
+        match api_call(
+            &self.http,
+            &self.cfg.homeserver,
+            &self.cfg.access_token,
+            Method::POST,
+            &path,
+            Some(&json!({})),
+        )
+        .await
+        {
+            Ok(_) => info!("[matrix:{}] joined {}", self.instance_id, room_id),
+            Err(e) => warn!(
+                "[matrix:{}] could not join {}: {:#}",
+                self.instance_id, room_id, e
+            ),
+        }
+    }
+
+    fn handle_event(
+        &self,
+        room_id: &str,
+        event: &Value,
+        own_user_id: &str,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) {
+        let msg = match event_to_message(room_id, event, own_user_id, self.cfg.mention_only) {
+            Some(m) => m,
+            None => return,
+        };
+        if let Some(event_id) = event["event_id"].as_str() {
+            if !self.seen.insert(event_id.to_string()) {
+                return;
+            }
+            if self.seen.len() > 10_000 {
+                self.seen.clear();
+            }
+        }
+
+        let preview: String = msg.text.chars().take(50).collect();
+        info!(
+            "[matrix:{}] {} in {}: {}",
+            self.instance_id, msg.platform_user_id, msg.platform_chat_id, preview
+        );
+
+        let http = self.http.clone();
+        let homeserver = self.cfg.homeserver.clone();
+        let token = self.cfg.access_token.clone();
+        tokio::spawn(super::while_delivering(async move {
+            let room = msg.platform_chat_id.clone();
+            match dispatcher.dispatch(&msg).await {
+                Ok(reply) => {
+                    if reply.text.is_empty() || reply.text == "(No response)" {
+                        return;
+                    }
+                    if let Err(e) =
+                        post_message(&http, &homeserver, &token, &room, &reply.text).await
+                    {
+                        error!("[matrix] send failed: {}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("[matrix] agent error: {}", e);
+                    let _ =
+                        post_message(&http, &homeserver, &token, &room, &format!("Error: {}", e))
+                            .await;
+                }
+            }
+        }));
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for MatrixChannel {
+    async fn send(&self, conversation: &str, text: &str) -> Result<()> {
+        post_message(
+            &self.http,
+            &self.cfg.homeserver,
+            &self.cfg.access_token,
+            conversation,
+            text,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn start_stream(
+        &self,
+        conversation: &str,
+    ) -> Result<Box<dyn crate::core::context::StreamHandle>> {
+        Ok(Box::new(MatrixStreamHandle {
+            http: self.http.clone(),
+            homeserver: self.cfg.homeserver.clone(),
+            token: self.cfg.access_token.clone(),
+            room_id: conversation.to_string(),
+            buffer: String::new(),
+            event_id: None,
+            last_edit: std::time::Instant::now(),
+            pending: false,
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Channel for MatrixChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn kind(&self) -> &str {
+        "matrix"
+    }
+
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.cfg.limits.as_ref()
+    }
+
+    async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
+        info!(
+            "🤖 Matrix channel {} starting ({})",
+            self.instance_id, self.cfg.homeserver
+        );
+        let own_user_id = self
+            .own_user_id()
+            .await
+            .with_context(|| format!("[channels.matrix.{}]", self.instance_id))?;
+        info!("[matrix:{}] logged in as {}", self.instance_id, own_user_id);
+        let dispatcher = Arc::new(super::OriginAwareDispatcher::new(self.clone(), dispatcher))
+            as Arc<dyn MessageDispatcher>;
+
+        let mut since = SyncFile::load(&self.project_root, &self.instance_id).next_batch;
+        let mut consecutive_failures: u32 = 0;
+        loop {
+            match self
+                .sync_once(since.as_deref(), &own_user_id, dispatcher.clone())
+                .await
+            {
+                Ok(next_batch) => {
+                    consecutive_failures = 0;
+                    if since.as_deref() != Some(next_batch.as_str()) {
+                        let file = SyncFile {
+                            next_batch: Some(next_batch.clone()),
+                        };
+                        if let Err(e) = file.save(&self.project_root, &self.instance_id) {
+                            warn!(
+                                "[matrix:{}] failed to persist sync token: {}",
+                                self.instance_id, e
+                            );
+                        }
+                    }
+                    since = Some(next_batch);
+                }
+                Err(e) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "[matrix:{}] sync error ({}/5): {:#} — retrying",
+                        self.instance_id, consecutive_failures, e
+                    );
+                    if consecutive_failures >= 5 {
+                        return Err(e.context("Matrix /sync: 5 consecutive failures"));
+                    }
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+            }
+        }
+    }
+}
+
+// ─── Streaming reply ────────────────────────────────────────────────────────
+//
+// First token sends the message; later tokens replace it (`m.replace`) at
+// most once per second. Past MAX_MESSAGE_LEN the live message shows the first
+// chunk and finalize sends the remainder as follow-up messages.
+
+struct MatrixStreamHandle {
+    http: reqwest::Client,
+    homeserver: String,
+    token: String,
+    room_id: String,
+    buffer: String,
+    /// `event_id` of the streaming message once sent.
+    event_id: Option<String>,
+    last_edit: std::time::Instant,
+    pending: bool,
+}
+
+impl MatrixStreamHandle {
+    fn head(&self) -> String {
+        super::discord::split_message(&self.buffer, MAX_MESSAGE_LEN)
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        let head = self.head();
+        match self.event_id.clone() {
+            None => {
+                let content = json!({ "msgtype": "m.text", "body": head });
+                self.event_id = Some(
+                    send_event(
+                        &self.http,
+                        &self.homeserver,
+                        &self.token,
+                        &self.room_id,
+                        "m.room.message",
+                        &content,
+                    )
+                    .await?,
+                );
+            }
+            Some(event_id) => {
+                edit_message(
+                    &self.http,
+                    &self.homeserver,
+                    &self.token,
+                    &self.room_id,
+                    &event_id,
+                    &head,
+                )
+                .await?;
+            }
+        }
+        self.last_edit = std::time::Instant::now();
+        self.pending = false;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::StreamHandle for MatrixStreamHandle {
+    async fn push_token(&mut self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.buffer.push_str(text);
+        self.pending = true;
+        if self.event_id.is_none()
+            || self.last_edit.elapsed().as_millis() >= STREAM_EDIT_INTERVAL_MS
+        {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn finalize(mut self: Box<Self>) -> Result<()> {
+        if self.event_id.is_none() && self.buffer.is_empty() {
+            return Ok(());
+        }
+        if self.pending || self.event_id.is_none() {
+            self.flush().await?;
+        }
+        let rest: Vec<String> = super::discord::split_message(&self.buffer, MAX_MESSAGE_LEN)
+            .into_iter()
+            .skip(1)
+            .collect();
+        if !rest.is_empty() {
+            post_message(
+                &self.http,
+                &self.homeserver,
+                &self.token,
+                &self.room_id,
+                &rest.join("\n"),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+// ─── Discovery ──────────────────────────────────────────────────────────────
+
+/// Build [`MatrixChannel`] instances from `[channels.matrix.<id>]`.
+pub fn discover_channels(
+    config: &JuglansConfig,
+    project_root: &Path,
+) -> Result<Vec<(Arc<dyn Channel>, String)>> {
+    let mut out: Vec<(Arc<dyn Channel>, String)> = Vec::new();
+    for (instance_id, cfg) in &config.channels.matrix {
+        if !cfg.homeserver.starts_with("http://") && !cfg.homeserver.starts_with("https://") {
+            return Err(anyhow!(
+                "[channels.matrix.{}] homeserver must be an http(s) URL (e.g. `homeserver = \"https://matrix.example.org\"`)",
+                instance_id
+            ));
+        }
+        if cfg.access_token.is_empty() {
+            return Err(anyhow!(
+                "[channels.matrix.{}] access_token is empty — set it in juglans.toml (e.g. `access_token = \"${{MATRIX_ACCESS_TOKEN}}\"`)",
+                instance_id
+            ));
+        }
+        out.push((
+            Arc::new(MatrixChannel::new(
+                instance_id.clone(),
+                cfg.clone(),
+                project_root.to_path_buf(),
+            )),
+            cfg.agent.clone(),
+        ));
+    }
+    Ok(out)
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::BotReply;
+    use axum::extract::{Path as AxPath, Query};
+    use axum::Json;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    type Calls = Arc<Mutex<Vec<(String, Value)>>>;
+
+    /// Local stand-in for a homeserver. Records sends and joins; `/sync`
+    /// without `since` returns history that must not be answered, plus an
+    /// invite; with `since=s1` it returns one new message.
+    async fn mock_homeserver() -> (String, Calls) {
+        let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+        let sends = calls.clone();
+        let joins = calls.clone();
+        let app = axum::Router::new()
+            .route(
+                "/_matrix/client/v3/account/whoami",
+                axum::routing::get(|| async { Json(json!({ "user_id": "@bot:test" })) }),
+            )
+            .route(
+                "/_matrix/client/v3/rooms/:room/send/:kind/:txn",
+                axum::routing::put(
+                    move |AxPath((room, kind, _txn)): AxPath<(String, String, String)>,
+                          Json(body): Json<Value>| {
+                        let sends = sends.clone();
+                        async move {
+                            let n = {
+                                let mut c = sends.lock().unwrap();
+                                c.push((format!("send {} {}", room, kind), body));
+                                c.len()
+                            };
+                            Json(json!({ "event_id": format!("$e{}", n) }))
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/_matrix/client/v3/rooms/:room/join",
+                axum::routing::post(move |AxPath(room): AxPath<String>| {
+                    let joins = joins.clone();
+                    async move {
+                        joins
+                            .lock()
+                            .unwrap()
+                            .push((format!("join {}", room), Value::Null));
+                        Json(json!({ "room_id": room }))
+                    }
+                }),
+            )
+            .route(
+                "/_matrix/client/v3/sync",
+                axum::routing::get(|Query(q): Query<HashMap<String, String>>| async move {
+                    let message = |id: &str, body: &str| {
+                        json!({
+                            "type": "m.room.message", "event_id": id, "sender": "@alice:test",
+                            "content": { "msgtype": "m.text", "body": body }
+                        })
+                    };
+                    match q.get("since").map(String::as_str) {
+                        None => Json(json!({
+                            "next_batch": "s1",
+                            "rooms": {
+                                "join": { "!a:test": { "timeline": { "events": [message("$old", "history")] } } },
+                                "invite": { "!b:test": { "invite_state": { "events": [] } } }
+                            }
+                        })),
+                        _ => Json(json!({
+                            "next_batch": "s2",
+                            "rooms": {
+                                "join": { "!a:test": { "timeline": { "events": [
+                                    message("$new", "hello bot"),
+                                    message("$new", "hello bot")
+                                ] } } }
+                            }
+                        })),
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}", addr), calls)
+    }
+
+    fn config(homeserver: &str) -> MatrixChannelConfig {
+        toml::from_str(&format!(
+            "homeserver = \"{}\"\naccess_token = \"syt_test\"",
+            homeserver
+        ))
+        .unwrap()
+    }
+
+    fn channel(homeserver: &str) -> Arc<MatrixChannel> {
+        Arc::new(MatrixChannel::new(
+            "main".into(),
+            config(homeserver),
+            std::env::temp_dir(),
+        ))
+    }
+
+    struct Recorder(Mutex<Vec<PlatformMessage>>);
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Recorder {
+        async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+            self.0.lock().unwrap().push(PlatformMessage {
+                event_type: message.event_type.clone(),
+                event_data: message.event_data.clone(),
+                platform_user_id: message.platform_user_id.clone(),
+                platform_chat_id: message.platform_chat_id.clone(),
+                text: message.text.clone(),
+                username: message.username.clone(),
+                platform: message.platform.clone(),
+            });
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+    }
+
+    fn text_event(sender: &str, content: Value) -> Value {
+        json!({ "type": "m.room.message", "event_id": "$1", "sender": sender, "content": content })
+    }
+
+    #[test]
+    fn room_is_the_conversation_and_replies_lose_their_fallback() {
+        let ev = text_event(
+            "@alice:test",
+            json!({
+                "msgtype": "m.text",
+                "body": "> <@bot:test> earlier answer\n\nand why?",
+                "m.relates_to": { "m.in_reply_to": { "event_id": "$0" } }
+            }),
+        );
+        let m = event_to_message("!a:test", &ev, "@bot:test", false).unwrap();
+        assert_eq!(m.text, "and why?");
+        assert_eq!(m.platform_chat_id, "!a:test");
+        assert_eq!(m.platform_user_id, "@alice:test");
+        assert_eq!(m.username.as_deref(), Some("alice"));
+        assert_eq!(m.platform, "matrix");
+        assert_eq!(m.event_data["in_reply_to"], "$0");
+    }
+
+    #[test]
+    fn ignores_own_messages_notices_and_edits() {
+        let own = text_event("@bot:test", json!({ "msgtype": "m.text", "body": "hi" }));
+        assert!(event_to_message("!a:test", &own, "@bot:test", false).is_none());
+        let notice = text_event(
+            "@other:test",
+            json!({ "msgtype": "m.notice", "body": "hi" }),
+        );
+        assert!(event_to_message("!a:test", &notice, "@bot:test", false).is_none());
+        let edit = text_event(
+            "@alice:test",
+            json!({
+                "msgtype": "m.text", "body": "* hi",
+                "m.relates_to": { "rel_type": "m.replace", "event_id": "$0" }
+            }),
+        );
+        assert!(event_to_message("!a:test", &edit, "@bot:test", false).is_none());
+    }
+
+    #[test]
+    fn mention_only_needs_the_bot_mxid() {
+        let chatter = text_event(
+            "@alice:test",
+            json!({ "msgtype": "m.text", "body": "lunch?" }),
+        );
+        assert!(event_to_message("!a:test", &chatter, "@bot:test", true).is_none());
+        let pill = text_event(
+            "@alice:test",
+            json!({
+                "msgtype": "m.text", "body": "bot: status?",
+                "m.mentions": { "user_ids": ["@bot:test"] }
+            }),
+        );
+        assert!(event_to_message("!a:test", &pill, "@bot:test", true).is_some());
+        let plain = text_event(
+            "@alice:test",
+            json!({ "msgtype": "m.text", "body": "@bot:test: status?" }),
+        );
+        assert_eq!(
+            event_to_message("!a:test", &plain, "@bot:test", true)
+                .unwrap()
+                .text,
+            "status?"
+        );
+    }
+
+    #[test]
+    fn sync_token_roundtrip() {
+        let root =
+            std::env::temp_dir().join(format!("juglans-matrix-test-{}", uuid::Uuid::new_v4()));
+        assert!(SyncFile::load(&root, "main").next_batch.is_none());
+        SyncFile {
+            next_batch: Some("s42".into()),
+        }
+        .save(&root, "main")
+        .unwrap();
+        assert!(root.join(".juglans/matrix/main.json").exists());
+        assert_eq!(
+            SyncFile::load(&root, "main").next_batch.as_deref(),
+            Some("s42")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn send_puts_a_text_event_into_the_room() {
+        use crate::core::context::ChannelEgress;
+        let (hs, calls) = mock_homeserver().await;
+        let ch = channel(&hs);
+        ch.send("!a:test", "hello").await.unwrap();
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, "send !a:test m.room.message");
+        assert_eq!(calls[0].1["msgtype"], "m.text");
+        assert_eq!(calls[0].1["body"], "hello");
+    }
+
+    #[tokio::test]
+    async fn stream_sends_then_replaces() {
+        use crate::core::context::ChannelEgress;
+        let (hs, calls) = mock_homeserver().await;
+        let ch = channel(&hs);
+        let mut handle = ch.start_stream("!a:test").await.unwrap();
+        handle.push_token("Hel").await.unwrap();
+        handle.push_token("lo").await.unwrap();
+        handle.finalize().await.unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls[0].1["body"], "Hel");
+        let last = &calls.last().unwrap().1;
+        assert_eq!(last["m.relates_to"]["rel_type"], "m.replace");
+        assert_eq!(last["m.relates_to"]["event_id"], "$e1");
+        assert_eq!(last["m.new_content"]["body"], "Hello");
+        assert_eq!(last["body"], "* Hello");
+    }
+
+    #[tokio::test]
+    async fn initial_sync_skips_history_and_joins_invites() {
+        let (hs, calls) = mock_homeserver().await;
+        let ch = channel(&hs);
+        assert_eq!(ch.own_user_id().await.unwrap(), "@bot:test");
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+
+        let first = ch
+            .sync_once(None, "@bot:test", recorder.clone())
+            .await
+            .unwrap();
+        assert_eq!(first, "s1");
+        assert!(calls
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(c, _)| c == "join !b:test"));
+
+        let next = ch
+            .sync_once(Some(&first), "@bot:test", recorder.clone())
+            .await
+            .unwrap();
+        assert_eq!(next, "s2");
+        for _ in 0..50 {
+            if !recorder.0.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let got = recorder.0.lock().unwrap();
+        // "history" from the initial sync is never dispatched; the repeated
+        // "$new" event is dropped.
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].text, "hello bot");
+        assert_eq!(got[0].platform_chat_id, "!a:test");
+    }
+
+    #[test]
+    fn discovery_validates_homeserver_and_token() {
+        let mut config: JuglansConfig =
+            toml::from_str("[account]\nid = \"t\"\nname = \"t\"").unwrap();
+        config
+            .channels
+            .matrix
+            .insert("main".into(), config_with("matrix.example.org", "syt"));
+        assert!(discover_channels(&config, Path::new(".")).is_err());
+        config
+            .channels
+            .matrix
+            .insert("main".into(), config_with("https://matrix.example.org", ""));
+        assert!(discover_channels(&config, Path::new(".")).is_err());
+        config.channels.matrix.insert(
+            "main".into(),
+            config_with("https://matrix.example.org", "syt"),
+        );
+        let chs = discover_channels(&config, Path::new(".")).unwrap();
+        assert_eq!(chs[0].0.id(), "matrix:main");
+        assert_eq!(chs[0].1, "default");
+    }
+
+    fn config_with(homeserver: &str, token: &str) -> MatrixChannelConfig {
+        let mut cfg = config(homeserver);
+        cfg.access_token = token.into();
+        cfg
+    }
+}
@@ -2,8 +2,14 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 pub mod discord;
+pub mod email;
 pub mod feishu;
+pub mod limits;
+pub mod matrix;
+pub mod simulator;
+pub mod slack;
 pub mod telegram;
+pub mod webhook;
 pub mod wechat;
 
 use anyhow::{anyhow, Result};
@@ -34,7 +40,7 @@ pub struct PlatformMessage {
     /// Convenience field: message text (populated for message events, empty for others)
     pub text: String,
     pub username: Option<String>,
-    /// Platform identifier: "telegram" | "feishu" | "wechat" | "web"
+    /// Platform identifier: "telegram" | "feishu" | "wechat" | "slack" | "matrix" | "email" | "webhook" | "web"
     pub platform: String,
 }
 
@@ -123,6 +129,20 @@ pub trait Channel: crate::core::context::ChannelEgress {
     ) -> axum::Router {
         router
     }
+
+    /// Inbound limits from `[channels.<kind>.<id>.limits]`. When `Some`, the
+    /// server wraps this channel's dispatcher in a `RateLimitedDispatcher`.
+    fn limits(&self) -> Option<&crate::services::config::ChannelLimitsConfig> {
+        None
+    }
+
+    /// Save what the next process needs to resume ingress where this one
+    /// stopped (update offsets, gateway sessions). Called once during a
+    /// graceful shutdown, after the `run()` task has been cancelled.
+    /// Default: nothing to save.
+    async fn persist_resume_state(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 // Note: the egress `send` method lives on `ChannelEgress` in `core::context`,
@@ -215,11 +235,30 @@ impl MessageDispatcher for LocalDispatcher {
     }
 }
 
+tokio::task_local! {
+    /// Set inside `while_delivering`, whose own `ActiveRun` already counts
+    /// the message.
+    static DELIVERING: ();
+}
+
+/// Run `fut` (dispatch plus sending the reply) as one active channel run, so
+/// a draining server waits for the reply to go out and not just for the
+/// agent to finish. For adapters that deliver the returned `BotReply`
+/// themselves.
+pub async fn while_delivering<F: std::future::Future>(fut: F) -> F::Output {
+    let _run = crate::services::metrics::ActiveRun::start("channel");
+    DELIVERING.scope((), fut).await
+}
+
 /// Reuse core logic from web_server handle_chat, without the SSE/HTTP parts:
 /// 1. Load agent -> create executor
 /// 2. Create WorkflowContext, set $input.message
 /// 3. Execute workflow or direct chat
 /// 4. Collect all Token events -> concatenate into reply text
+///
+/// Every channel's ingress ends up here, so this is where inbound messages
+/// are counted (`juglans_channel_messages_received_total`) and the run is
+/// tracked as active.
 pub async fn run_agent_for_message(
     config: &JuglansConfig,
     project_root: &Path,
@@ -228,8 +267,61 @@ pub async fn run_agent_for_message(
     tool_executor: Option<&dyn ToolExecutor>,
     origin: Option<crate::core::context::ChannelOrigin>,
 ) -> Result<BotReply> {
-    // 1. Find workflow file by slug (agent_slug is now a workflow name)
-    let wf_path = {
+    use crate::services::metrics;
+
+    let channel_id = origin
+        .as_ref()
+        .and_then(|o| crate::services::outbox::channel_id_of(&o.channel))
+        .unwrap_or_else(|| message.platform.clone());
+    // Approve / deny buttons on a tool-approval card answer a run that is
+    // already waiting; they don't start one.
+    if message.event_type == "card_action" {
+        let action = message.event_data["action"].as_str().unwrap_or("");
+        if crate::services::tool_policy::resolve_channel_approval(action, &message.platform_user_id)
+        {
+            metrics::record_channel_received(&channel_id, true);
+            return Ok(BotReply {
+                text: String::new(),
+            });
+        }
+    }
+    let _run = DELIVERING
+        .try_with(|_| ())
+        .is_err()
+        .then(|| metrics::ActiveRun::start("channel"));
+    let result = run_agent(
+        config,
+        project_root,
+        agent_slug,
+        message,
+        tool_executor,
+        origin,
+        &channel_id,
+    )
+    .await;
+    metrics::record_channel_received(&channel_id, result.is_ok());
+    result
+}
+
+async fn run_agent(
+    config: &JuglansConfig,
+    project_root: &Path,
+    agent_slug: &str,
+    message: &PlatformMessage,
+    tool_executor: Option<&dyn ToolExecutor>,
+    origin: Option<crate::core::context::ChannelOrigin>,
+    channel_id: &str,
+) -> Result<BotReply> {
+    // 1. Find workflow file by slug (agent_slug is now a workflow name).
+    //    `mount:<name>` is the entry of [server.mounts.<name>].
+    let wf_path = if let Some(mount) = agent_slug.strip_prefix("mount:") {
+        let entry = config
+            .server
+            .mounts
+            .get(mount)
+            .ok_or_else(|| anyhow!("Mount '{}' is not in [server.mounts]", mount))?;
+        project_root.join(&entry.entry)
+    } else {
         let jg_pattern = project_root
             .join(format!("**/{}.jg", agent_slug))
             .to_string_lossy()
@@ -275,6 +367,7 @@ pub async fn run_agent_for_message(
 
     // Parse workflow + expand decorators
     let mut wf_graph = GraphParser::parse(&wf_content)?;
+    wf_graph.default_slug_from(&wf_path);
     crate::core::macro_expand::expand_decorators(&mut wf_graph)?;
     let parsed_workflow = Some(Arc::new(wf_graph));
 
@@ -296,6 +389,13 @@ pub async fn run_agent_for_message(
     if let Err(e) = crate::services::history::init_global(&config.history) {
         warn!("[history] init_global failed: {}", e);
     }
+    crate::services::tool_policy::init_global(&config.tool_policy)?;
+    crate::services::memory::init_global(&config.memory);
+    crate::services::identity::init_global(&config.identity);
+    crate::services::outbox::init_global(&config.outbox);
+    if let Err(e) = crate::services::sandbox::init_global(&config.sandbox) {
+        warn!("[sandbox] init_global failed: {}", e);
+    }
 
     // 3. Create context + event channel (for collecting tokens)
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<WorkflowEvent>();
@@ -309,13 +409,29 @@ pub async fn run_agent_for_message(
         ctx.set_stream_node_events(true);
     }
 
+    // Resolve the sender to a cross-channel user (`input.user`) when
+    // `[identity]` is enabled.
+    let user_id = crate::services::identity::attach_to_context(
+        &ctx,
+        &message.platform,
+        &message.platform_user_id,
+        message.username.as_deref(),
+    );
+
     // Derive a namespaced chat_id for history storage. Keeps different
     // platforms / users / agents on separate threads so a telegram chat
-    // and a wechat chat for the same slug never collide.
-    let derived_chat_id = format!(
-        "{}:{}:{}",
-        message.platform, message.platform_chat_id, agent_slug
-    );
+    // and a wechat chat for the same slug never collide. With
+    // `history_scope = "user"` a linked person shares one thread per agent
+    // across every channel.
+    let derived_chat_id = match user_id {
+        Some(ref uid) if config.identity.history_scope == "user" => {
+            format!("user:{}:{}", uid, agent_slug)
+        }
+        _ => format!(
+            "{}:{}:{}",
+            message.platform, message.platform_chat_id, agent_slug
+        ),
+    };
 
     // Set standardized event input
     ctx.set("input.platform".into(), json!(message.platform))
@@ -342,6 +458,12 @@ pub async fn run_agent_for_message(
     if let Some(ref username) = message.username {
         ctx.set("input.username".into(), json!(username)).ok();
     }
+    // Channels that receive files list them in `event_data.attachments`;
+    // surface them at the top level so workflows don't need to know where.
+    if let Some(attachments) = message.event_data.get("attachments") {
+        ctx.set("input.attachments".into(), attachments.clone())
+            .ok();
+    }
 
     // Inject juglans.toml config into $config
     if let Ok(config_value) = serde_json::to_value(config) {
@@ -463,7 +585,37 @@ pub async fn run_agent_for_message(
             WorkflowEvent::ToolCall {
                 tools, result_tx, ..
             } => {
-                if let Some(executor) = tool_executor {
+                let approval_only = !tools.is_empty()
+                    && tools.iter().all(|t| {
+                        t["name"].as_str() == Some(crate::services::tool_policy::APPROVAL_TOOL_NAME)
+                    });
+                if let (true, Some(channel), Some(conv)) =
+                    (approval_only, &egress_channel, &conversation)
+                {
+                    // Tool-policy `ask`: post an approve / deny card and wait
+                    // for the sender's button press off the event loop.
+                    let channel = channel.clone();
+                    let conv = conv.clone();
+                    let user_id = message.platform_user_id.clone();
+                    tokio::spawn(async move {
+                        let mut results = vec![];
+                        for tool in &tools {
+                            let args = tool["arguments"].as_str().unwrap_or("{}");
+                            let approved = crate::services::tool_policy::ask_on_channel(
+                                channel.as_ref(),
+                                &conv,
+                                &user_id,
+                                args,
+                            )
+                            .await;
+                            results.push(crate::core::context::ToolResultPayload {
+                                tool_call_id: tool["id"].as_str().unwrap_or("").to_string(),
+                                content: if approved { "allow" } else { "deny" }.to_string(),
+                            });
+                        }
+                        let _ = result_tx.send((results, None));
+                    });
+                } else if let Some(executor) = tool_executor {
                     let mut results = vec![];
                     for tool in &tools {
                         let tool_name = tool["name"]
@@ -539,7 +691,12 @@ pub async fn run_agent_for_message(
                                 if Some(&evt.node_id) == streaming_node_id.as_ref() {
                                     streaming_node_id = None;
                                 }
-                                if let Err(e) = handle.finalize().await {
+                                let finalized = handle.finalize().await;
+                                crate::services::metrics::record_channel_sent(
+                                    channel_id,
+                                    finalized.is_ok(),
+                                );
+                                if let Err(e) = finalized {
                                     warn!(
                                         "[channel egress] stream finalize failed on [{}]: {:#}",
                                         evt.node_id, e
@@ -547,7 +704,10 @@ pub async fn run_agent_for_message(
                                 }
                             } else if let Some(text) = extract_speech_text(&evt.result) {
                                 if !text.is_empty() {
-                                    if let Err(e) = channel.send(conv, &text).await {
+                                    if let Err(e) =
+                                        crate::services::outbox::send_reply(channel, conv, &text)
+                                            .await
+                                    {
                                         error!(
                                             "[channel egress] {} send failed for [{}]: {:#}",
                                             evt.tool, evt.node_id, e
@@ -0,0 +1,837 @@
+// src/adapters/simulator.rs
+//
+// Offline channel simulator for `juglans simulate` and the `simulate()` test
+// builtin. No bot token, no network.
+//
+// Ingress: each user turn is built as the raw payload the real platform would
+// deliver (a Telegram `Update`, a Discord `MESSAGE_CREATE` /
+// `INTERACTION_CREATE`, a Slack Events API `event`) and run through that
+// adapter's own parser, so the workflow sees exactly the `PlatformMessage` it
+// would in production. Dispatch goes through `OriginAwareDispatcher`, i.e. the
+// full `run_agent_for_message` egress path with a `ChannelOrigin`.
+//
+// Egress: `SimulatorChannel` stands in for the platform. Every `send` and every
+// finalized `StreamHandle` is captured as a `SimOutput` instead of hitting an
+// API. Telegram and Slack stream like their real channels; Discord batches.
+//
+// Scripts are YAML — one step per user turn, each with optional expectations
+// on the replies that turn produced:
+//
+//   channel: telegram
+//   agent: support
+//   steps:
+//     - say: "hi"
+//       expect:
+//         contains: "Hello"
+//     - action: "confirm"        # inline keyboard / component button
+//       expect:
+//         replies: 1
+//     - command: "/todo add"     # Discord slash command (text elsewhere)
+//       options: { title: "buy milk" }
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::{Channel, MessageDispatcher, OriginAwareDispatcher, PlatformMessage};
+use crate::core::context::{ChannelEgress, StreamHandle};
+
+/// Platforms the simulator can impersonate.
+pub const SUPPORTED_CHANNELS: &[&str] = &["telegram", "discord", "slack", "matrix"];
+
+/// The simulated Matrix bot's own mxid (messages from it are ignored).
+const SIM_MATRIX_BOT: &str = "@bot:simulator";
+
+/// One message the workflow delivered to the simulated platform.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimOutput {
+    pub conversation: String,
+    pub text: String,
+    /// Delivered through `start_stream` rather than `send`.
+    pub streamed: bool,
+    /// `push_token` calls before finalize (0 for batch sends).
+    pub tokens: usize,
+}
+
+type Captured = Arc<Mutex<Vec<SimOutput>>>;
+
+/// Egress stand-in: records everything instead of calling a platform API.
+/// `id()` is `"<kind>:simulator"`.
+pub struct SimulatorChannel {
+    id: String,
+    kind: String,
+    streaming: bool,
+    captured: Captured,
+}
+
+impl SimulatorChannel {
+    pub fn new(kind: &str, streaming: bool) -> Self {
+        Self {
+            id: format!("{}:simulator", kind),
+            kind: kind.to_string(),
+            streaming,
+            captured: Arc::default(),
+        }
+    }
+
+    /// Drain everything captured since the last call.
+    pub fn take(&self) -> Vec<SimOutput> {
+        std::mem::take(&mut *self.captured.lock().unwrap())
+    }
+}
+
+#[async_trait::async_trait]
+impl ChannelEgress for SimulatorChannel {
+    async fn send(&self, conversation: &str, text: &str) -> Result<()> {
+        self.captured.lock().unwrap().push(SimOutput {
+            conversation: conversation.to_string(),
+            text: text.to_string(),
+            streamed: false,
+            tokens: 0,
+        });
+        Ok(())
+    }
+
+    async fn start_stream(&self, conversation: &str) -> Result<Box<dyn StreamHandle>> {
+        if !self.streaming {
+            return Err(anyhow!("channel does not support streaming"));
+        }
+        Ok(Box::new(SimStreamHandle {
+            conversation: conversation.to_string(),
+            buf: String::new(),
+            tokens: 0,
+            captured: self.captured.clone(),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Channel for SimulatorChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+struct SimStreamHandle {
+    conversation: String,
+    buf: String,
+    tokens: usize,
+    captured: Captured,
+}
+
+#[async_trait::async_trait]
+impl StreamHandle for SimStreamHandle {
+    async fn push_token(&mut self, text: &str) -> Result<()> {
+        self.buf.push_str(text);
+        self.tokens += 1;
+        Ok(())
+    }
+
+    async fn finalize(self: Box<Self>) -> Result<()> {
+        // An empty stream never becomes a visible message on real platforms.
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.captured.lock().unwrap().push(SimOutput {
+            conversation: self.conversation,
+            text: self.buf,
+            streamed: true,
+            tokens: self.tokens,
+        });
+        Ok(())
+    }
+}
+
+// ─── Script ─────────────────────────────────────────────────────────────────
+
+/// The simulated sender.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimUser {
+    pub id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+impl Default for SimUser {
+    fn default() -> Self {
+        Self {
+            id: "1001".into(),
+            username: Some("tester".into()),
+        }
+    }
+}
+
+/// A `.yaml` conversation. `channel` / `agent` / `user` / `chat` / `stream`
+/// are defaults the CLI flags override.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimScript {
+    pub channel: Option<String>,
+    pub agent: Option<String>,
+    pub user: Option<SimUser>,
+    pub chat: Option<String>,
+    pub stream: Option<bool>,
+    #[serde(default)]
+    pub steps: Vec<SimStep>,
+}
+
+impl SimScript {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid script {}", path.display()))
+    }
+
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let script: Self = serde_yaml_ng::from_str(yaml)?;
+        for (i, step) in script.steps.iter().enumerate() {
+            step.input().with_context(|| format!("step {}", i + 1))?;
+        }
+        Ok(script)
+    }
+}
+
+/// One user turn: exactly one of `say`, `action` or `command`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimStep {
+    /// Plain text message.
+    pub say: Option<String>,
+    /// Button press: Telegram callback data / Discord component `custom_id`.
+    pub action: Option<String>,
+    /// Slash command line, e.g. `"/todo add"`.
+    pub command: Option<String>,
+    /// Named command options (Discord); appended to the text elsewhere.
+    #[serde(default)]
+    pub options: Map<String, Value>,
+    pub expect: Option<SimExpect>,
+}
+
+impl SimStep {
+    pub fn input(&self) -> Result<SimInput> {
+        match (&self.say, &self.action, &self.command) {
+            (Some(text), None, None) => Ok(SimInput::Say(text.clone())),
+            (None, Some(action), None) => Ok(SimInput::Action(action.clone())),
+            (None, None, Some(line)) => Ok(SimInput::Command {
+                line: line.clone(),
+                options: self.options.clone(),
+            }),
+            _ => Err(anyhow!(
+                "expected exactly one of `say`, `action` or `command`"
+            )),
+        }
+    }
+}
+
+/// What the user does in one turn.
+#[derive(Debug, Clone)]
+pub enum SimInput {
+    Say(String),
+    Action(String),
+    Command {
+        line: String,
+        options: Map<String, Value>,
+    },
+}
+
+impl SimInput {
+    /// Parse a REPL line: plain text is a message, `:action <data>` presses a
+    /// button, `:command /name sub key=value …` runs a slash command.
+    pub fn from_repl(line: &str) -> Result<Self> {
+        let line = line.trim();
+        if let Some(data) = line.strip_prefix(":action ") {
+            return Ok(SimInput::Action(data.trim().to_string()));
+        }
+        if let Some(rest) = line.strip_prefix(":command ") {
+            let mut words = Vec::new();
+            let mut options = Map::new();
+            for word in rest.split_whitespace() {
+                match word.split_once('=') {
+                    Some((k, v)) => {
+                        let v = serde_json::from_str(v).unwrap_or_else(|_| json!(v));
+                        options.insert(k.to_string(), v);
+                    }
+                    None => words.push(word),
+                }
+            }
+            return Ok(SimInput::Command {
+                line: words.join(" "),
+                options,
+            });
+        }
+        if line.starts_with(':') {
+            return Err(anyhow!(
+                "Unknown command '{}' (try :action, :command or :quit)",
+                line
+            ));
+        }
+        Ok(SimInput::Say(line.to_string()))
+    }
+
+    /// Short form for transcripts.
+    pub fn label(&self) -> String {
+        match self {
+            SimInput::Say(text) => text.clone(),
+            SimInput::Action(action) => format!("[action: {}]", action),
+            SimInput::Command { line, options } => std::iter::once(line.clone())
+                .chain(options.iter().map(|(k, v)| format!("{}={}", k, scalar(v))))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// A string or a list of strings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TextList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TextList {
+    fn items(&self) -> &[String] {
+        match self {
+            TextList::One(s) => std::slice::from_ref(s),
+            TextList::Many(v) => v,
+        }
+    }
+}
+
+/// Checks on the replies produced by one turn.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimExpect {
+    /// Exact number of delivered messages.
+    pub replies: Option<usize>,
+    /// Each substring must appear in some reply.
+    pub contains: Option<TextList>,
+    /// No reply may contain any of these.
+    pub not_contains: Option<TextList>,
+    /// Regex matched against the replies joined with newlines.
+    pub matches: Option<String>,
+    /// Every reply was (or was not) streamed.
+    pub streamed: Option<bool>,
+}
+
+impl SimExpect {
+    /// Failure messages; empty when every check holds.
+    pub fn check(&self, replies: &[SimOutput]) -> Vec<String> {
+        let mut failures = Vec::new();
+        if let Some(n) = self.replies {
+            if replies.len() != n {
+                failures.push(format!("expected {} replies, got {}", n, replies.len()));
+            }
+        }
+        for needle in self.contains.iter().flat_map(|c| c.items()) {
+            if !replies.iter().any(|r| r.text.contains(needle.as_str())) {
+                failures.push(format!("no reply contains {:?}", needle));
+            }
+        }
+        for needle in self.not_contains.iter().flat_map(|c| c.items()) {
+            if replies.iter().any(|r| r.text.contains(needle.as_str())) {
+                failures.push(format!("a reply contains {:?}", needle));
+            }
+        }
+        if let Some(ref pattern) = self.matches {
+            let joined = replies
+                .iter()
+                .map(|r| r.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            match regex::Regex::new(pattern) {
+                Ok(re) if re.is_match(&joined) => {}
+                Ok(_) => failures.push(format!("replies do not match /{}/", pattern)),
+                Err(e) => failures.push(format!("invalid regex /{}/: {}", pattern, e)),
+            }
+        }
+        if let Some(want) = self.streamed {
+            if let Some(r) = replies.iter().find(|r| r.streamed != want) {
+                failures.push(format!(
+                    "expected {} replies, got a {} one: {:?}",
+                    if want { "streamed" } else { "batch" },
+                    if r.streamed { "streamed" } else { "batch" },
+                    r.text
+                ));
+            }
+        }
+        failures
+    }
+}
+
+/// Outcome of one turn.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimTurn {
+    pub input: String,
+    pub event_type: String,
+    pub replies: Vec<SimOutput>,
+    pub failures: Vec<String>,
+}
+
+/// Outcome of a whole script.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimReport {
+    pub channel: String,
+    pub passed: bool,
+    pub turns: Vec<SimTurn>,
+    /// Every reply text in delivery order, for quick assertions.
+    pub replies: Vec<String>,
+}
+
+// ─── Simulator ──────────────────────────────────────────────────────────────
+
+/// One simulated conversation between a user and a workflow.
+pub struct Simulator {
+    kind: String,
+    user: SimUser,
+    chat: String,
+    channel: Arc<SimulatorChannel>,
+    dispatcher: Arc<dyn MessageDispatcher>,
+    seq: u64,
+    /// Text of the last delivered reply — the message a button press belongs to.
+    last_reply: Option<String>,
+}
+
+impl Simulator {
+    /// `chat` defaults to a direct conversation with `user`; `stream`
+    /// defaults to what the real channel does.
+    pub fn new(
+        kind: &str,
+        user: SimUser,
+        chat: Option<String>,
+        stream: Option<bool>,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> Result<Self> {
+        if !SUPPORTED_CHANNELS.contains(&kind) {
+            return Err(anyhow!(
+                "Cannot simulate channel '{}' (supported: {})",
+                kind,
+                SUPPORTED_CHANNELS.join(", ")
+            ));
+        }
+        let chat = chat.unwrap_or_else(|| match kind {
+            // Telegram private chats share the user's id.
+            "telegram" => user.id.clone(),
+            "matrix" => format!("!D{}:simulator", user.id),
+            _ => format!("D{}", user.id),
+        });
+        if kind == "telegram" && (user.id.parse::<i64>().is_err() || chat.parse::<i64>().is_err()) {
+            return Err(anyhow!("Telegram user and chat ids must be integers"));
+        }
+        let channel = Arc::new(SimulatorChannel::new(
+            kind,
+            stream.unwrap_or(kind != "discord"),
+        ));
+        let dispatcher = Arc::new(OriginAwareDispatcher::new(channel.clone(), dispatcher));
+        Ok(Self {
+            kind: kind.to_string(),
+            user,
+            chat,
+            channel,
+            dispatcher,
+            seq: 0,
+            last_reply: None,
+        })
+    }
+
+    /// Build the `PlatformMessage` the real adapter would produce for `input`.
+    pub fn envelope(&mut self, input: &SimInput) -> Result<PlatformMessage> {
+        self.seq += 1;
+        let parsed = match self.kind.as_str() {
+            "telegram" => super::telegram::parse_update(&self.telegram_update(input)),
+            "discord" => match input {
+                SimInput::Say(_) => {
+                    super::discord::parse_message_create(&self.discord_message(input))
+                }
+                _ => super::discord::parse_interaction(&self.discord_interaction(input)),
+            },
+            "matrix" => match input {
+                SimInput::Action(_) => {
+                    return Err(anyhow!("The matrix channel has no button actions"));
+                }
+                _ => super::matrix::event_to_message(
+                    &self.chat,
+                    &self.matrix_event(input),
+                    SIM_MATRIX_BOT,
+                    false,
+                ),
+            },
+            _ => match input {
+                SimInput::Action(_) => {
+                    return Err(anyhow!("The slack channel has no button actions"));
+                }
+                _ => super::slack::event_to_message(&self.slack_event(input), false, true),
+            },
+        };
+        parsed.ok_or_else(|| anyhow!("{} ignores this input: {}", self.kind, input.label()))
+    }
+
+    /// Run one turn and return what the workflow delivered.
+    pub async fn turn(&mut self, input: &SimInput) -> Result<(PlatformMessage, Vec<SimOutput>)> {
+        let msg = self.envelope(input)?;
+        let reply = self.dispatcher.dispatch(&msg).await?;
+        let mut outputs = self.channel.take();
+        // Dispatchers that ignore the origin return text the way the real
+        // adapters' legacy path would re-send it.
+        if !reply.text.is_empty() && reply.text != "(No response)" {
+            outputs.push(SimOutput {
+                conversation: msg.platform_chat_id.clone(),
+                text: reply.text,
+                streamed: false,
+                tokens: 0,
+            });
+        }
+        if let Some(last) = outputs.last() {
+            self.last_reply = Some(last.text.clone());
+        }
+        Ok((msg, outputs))
+    }
+
+    /// Play every step of `script`, checking expectations as it goes. Turn
+    /// errors are recorded as failures and the script continues.
+    pub async fn run_script(&mut self, script: &SimScript) -> Result<SimReport> {
+        let mut turns = Vec::new();
+        for step in &script.steps {
+            let input = step.input()?;
+            let (event_type, replies, mut failures) = match self.turn(&input).await {
+                Ok((msg, replies)) => (msg.event_type, replies, Vec::new()),
+                Err(e) => (String::new(), Vec::new(), vec![format!("{:#}", e)]),
+            };
+            if let Some(ref expect) = step.expect {
+                failures.extend(expect.check(&replies));
+            }
+            turns.push(SimTurn {
+                input: input.label(),
+                event_type,
+                replies,
+                failures,
+            });
+        }
+        Ok(SimReport {
+            channel: self.kind.clone(),
+            passed: turns.iter().all(|t| t.failures.is_empty()),
+            replies: turns
+                .iter()
+                .flat_map(|t| t.replies.iter().map(|r| r.text.clone()))
+                .collect(),
+            turns,
+        })
+    }
+
+    fn text_of(input: &SimInput) -> String {
+        match input {
+            SimInput::Say(text) | SimInput::Action(text) => text.clone(),
+            SimInput::Command { .. } => input.label(),
+        }
+    }
+
+    fn telegram_update(&self, input: &SimInput) -> Value {
+        let from = json!({
+            "id": self.user.id.parse::<i64>().unwrap_or(0),
+            "is_bot": false,
+            "username": self.user.username,
+        });
+        let chat_type = if self.chat == self.user.id {
+            "private"
+        } else {
+            "group"
+        };
+        let chat = json!({ "id": self.chat.parse::<i64>().unwrap_or(0), "type": chat_type });
+        match input {
+            SimInput::Action(data) => json!({
+                "update_id": self.seq,
+                "callback_query": {
+                    "id": self.seq.to_string(),
+                    "from": from,
+                    "data": data,
+                    "message": {
+                        "message_id": self.seq,
+                        "chat": chat,
+                        "text": self.last_reply,
+                    },
+                },
+            }),
+            _ => json!({
+                "update_id": self.seq,
+                "message": {
+                    "message_id": self.seq,
+                    "from": from,
+                    "chat": chat,
+                    "date": chrono::Utc::now().timestamp(),
+                    "text": Self::text_of(input),
+                },
+            }),
+        }
+    }
+
+    fn discord_user(&self) -> Value {
+        json!({ "id": self.user.id, "username": self.user.username })
+    }
+
+    fn discord_message(&self, input: &SimInput) -> Value {
+        json!({
+            "id": self.seq.to_string(),
+            "channel_id": self.chat,
+            "content": Self::text_of(input),
+            "author": self.discord_user(),
+        })
+    }
+
+    fn discord_interaction(&self, input: &SimInput) -> Value {
+        let mut d = json!({
+            "id": self.seq.to_string(),
+            "token": "simulator",
+            "application_id": "simulator",
+            "channel_id": self.chat,
+            "user": self.discord_user(),
+        });
+        match input {
+            SimInput::Action(custom_id) => {
+                d["type"] = json!(3);
+                d["message"] =
+                    json!({ "id": (self.seq - 1).to_string(), "content": self.last_reply });
+                d["data"] = json!({ "component_type": 2, "custom_id": custom_id });
+            }
+            SimInput::Command { line, options } => {
+                let mut words = line.trim_start_matches('/').split_whitespace();
+                let name = words.next().unwrap_or_default();
+                // Innermost first: options, then subcommand, then group.
+                let mut nested: Vec<Value> = options
+                    .iter()
+                    .map(|(k, v)| {
+                        let kind = match v {
+                            Value::Bool(_) => 5,
+                            Value::Number(n) if n.is_i64() || n.is_u64() => 4,
+                            Value::Number(_) => 10,
+                            _ => 3,
+                        };
+                        json!({ "type": kind, "name": k, "value": v })
+                    })
+                    .collect();
+                let path: Vec<&str> = words.collect();
+                for (depth, sub) in path.iter().enumerate().rev() {
+                    // The deepest word is the SUB_COMMAND, anything above it a group.
+                    let kind = if depth + 1 == path.len() { 1 } else { 2 };
+                    nested = vec![json!({ "type": kind, "name": sub, "options": nested })];
+                }
+                d["type"] = json!(2);
+                d["data"] = json!({ "name": name, "options": nested });
+            }
+            SimInput::Say(_) => {}
+        }
+        d
+    }
+
+    fn slack_event(&self, input: &SimInput) -> Value {
+        let channel_type = if self.chat.starts_with('D') {
+            "im"
+        } else {
+            "channel"
+        };
+        json!({
+            "type": "message",
+            "channel": self.chat,
+            "channel_type": channel_type,
+            "user": self.user.id,
+            "user_profile": { "name": self.user.username },
+            "ts": format!("{}.{:06}", chrono::Utc::now().timestamp(), self.seq),
+            "text": Self::text_of(input),
+        })
+    }
+
+    fn matrix_event(&self, input: &SimInput) -> Value {
+        json!({
+            "type": "m.room.message",
+            "event_id": format!("$sim{}", self.seq),
+            "sender": format!(
+                "@{}:simulator",
+                self.user.username.as_deref().unwrap_or(&self.user.id)
+            ),
+            "origin_server_ts": chrono::Utc::now().timestamp_millis(),
+            "content": { "msgtype": "m.text", "body": Self::text_of(input) },
+        })
+    }
+}
+
+/// Render a JSON scalar without quotes (for command lines).
+fn scalar(v: &Value) -> String {
+    match v.as_str() {
+        Some(s) => s.to_string(),
+        None => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::BotReply;
+    use crate::core::context::ChannelOrigin;
+
+    /// Echoes the text back through the origin — streamed when the channel
+    /// allows it — the way `run_agent_for_message` drives egress.
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Echo {
+        async fn dispatch(&self, _message: &PlatformMessage) -> Result<BotReply> {
+            Ok(BotReply {
+                text: "legacy".into(),
+            })
+        }
+
+        async fn dispatch_with_origin(
+            &self,
+            message: &PlatformMessage,
+            origin: Option<ChannelOrigin>,
+        ) -> Result<BotReply> {
+            let origin = origin.unwrap();
+            let text = match message.event_type.as_str() {
+                "card_action" => format!("pressed {}", message.event_data["action"]),
+                _ => format!("echo: {}", message.text),
+            };
+            match origin.channel.start_stream(&origin.conversation).await {
+                Ok(mut handle) => {
+                    for word in text.split_inclusive(' ') {
+                        handle.push_token(word).await?;
+                    }
+                    handle.finalize().await?;
+                }
+                Err(_) => origin.channel.send(&origin.conversation, &text).await?,
+            }
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+    }
+
+    fn sim(kind: &str) -> Simulator {
+        Simulator::new(kind, SimUser::default(), None, None, Arc::new(Echo)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn telegram_turns_use_adapter_envelopes_and_stream() {
+        let mut s = sim("telegram");
+        let (msg, out) = s.turn(&SimInput::Say("hi there".into())).await.unwrap();
+        assert_eq!(msg.platform, "telegram");
+        assert_eq!(msg.platform_chat_id, "1001");
+        assert_eq!(msg.username.as_deref(), Some("tester"));
+        assert_eq!(msg.event_data["chat_type"], "private");
+        assert_eq!(
+            out,
+            vec![SimOutput {
+                conversation: "1001".into(),
+                text: "echo: hi there".into(),
+                streamed: true,
+                tokens: 3,
+            }]
+        );
+
+        let (msg, out) = s.turn(&SimInput::Action("confirm".into())).await.unwrap();
+        assert_eq!(msg.event_type, "card_action");
+        assert_eq!(msg.event_data["message_text"], "echo: hi there");
+        assert_eq!(out[0].text, "pressed \"confirm\"");
+    }
+
+    #[tokio::test]
+    async fn matrix_streams_into_a_room() {
+        let mut s = sim("matrix");
+        let (msg, out) = s.turn(&SimInput::Say("hi".into())).await.unwrap();
+        assert_eq!(msg.platform, "matrix");
+        assert_eq!(msg.platform_user_id, "@tester:simulator");
+        assert_eq!(out[0].conversation, "!D1001:simulator");
+        assert!(out[0].streamed);
+        assert!(s.turn(&SimInput::Action("x".into())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn discord_batches_and_maps_slash_commands() {
+        let mut s = sim("discord");
+        let (_, out) = s.turn(&SimInput::Say("yo".into())).await.unwrap();
+        assert!(!out[0].streamed);
+        assert_eq!(out[0].conversation, "D1001");
+
+        let mut options = Map::new();
+        options.insert("title".into(), json!("buy milk"));
+        options.insert("priority".into(), json!(2));
+        let (msg, _) = s
+            .turn(&SimInput::Command {
+                line: "/todo add".into(),
+                options,
+            })
+            .await
+            .unwrap();
+        assert_eq!(msg.event_type, "command");
+        assert_eq!(msg.event_data["subcommand"], "add");
+        assert_eq!(msg.event_data["options"]["title"], "buy milk");
+        assert_eq!(msg.event_data["options"]["priority"], 2);
+    }
+
+    #[test]
+    fn rejects_unknown_channels_and_bad_telegram_ids() {
+        assert!(Simulator::new("irc", SimUser::default(), None, None, Arc::new(Echo)).is_err());
+        let user = SimUser {
+            id: "alice".into(),
+            username: None,
+        };
+        assert!(Simulator::new("telegram", user, None, None, Arc::new(Echo)).is_err());
+        assert!(sim("slack")
+            .envelope(&SimInput::Action("x".into()))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn script_checks_expectations() {
+        let script = SimScript::parse(
+            r#"
+channel: slack
+steps:
+  - say: "hello"
+    expect:
+      replies: 1
+      contains: ["echo", "hello"]
+      streamed: true
+  - say: "bye"
+    expect:
+      contains: "nope"
+      matches: "^echo: bye$"
+"#,
+        )
+        .unwrap();
+        let report = sim("slack").run_script(&script).await.unwrap();
+        assert!(!report.passed);
+        assert!(report.turns[0].failures.is_empty());
+        assert_eq!(report.turns[1].failures, vec!["no reply contains \"nope\""]);
+        assert_eq!(report.replies, vec!["echo: hello", "echo: bye"]);
+    }
+
+    #[test]
+    fn repl_lines() {
+        assert!(matches!(SimInput::from_repl("hi"), Ok(SimInput::Say(t)) if t == "hi"));
+        assert!(matches!(SimInput::from_repl(":action ok"), Ok(SimInput::Action(a)) if a == "ok"));
+        match SimInput::from_repl(":command /todo add title=milk n=2").unwrap() {
+            SimInput::Command { line, options } => {
+                assert_eq!(line, "/todo add");
+                assert_eq!(options["title"], "milk");
+                assert_eq!(options["n"], 2);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(SimInput::from_repl(":nope").is_err());
+    }
+
+    #[test]
+    fn script_steps_need_exactly_one_input() {
+        assert!(SimScript::parse("steps:\n  - say: hi\n    action: x\n").is_err());
+        assert!(SimScript::parse("steps:\n  - expect: { replies: 0 }\n").is_err());
+        assert!(SimScript::parse("steps:\n  - sya: hi\n").is_err());
+    }
+}
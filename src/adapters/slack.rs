@@ -0,0 +1,1154 @@
+// src/adapters/slack.rs
+//
+// Slack adapter. One `[channels.slack.<id>]` entry = one Slack app.
+//
+// Ingress, picked per instance:
+//   - Socket Mode (active `run()`): `apps.connections.open` with the app-level
+//     token, then a websocket that delivers `events_api` envelopes. Each
+//     envelope is acked immediately; the workflow runs in a detached task.
+//   - Events API (passive `install_routes()`): Slack POSTs to
+//     `/webhook/slack/<instance_id>`. Requests are verified with the signing
+//     secret (`v0=` HMAC-SHA256 over `v0:<timestamp>:<body>`, 5 minute
+//     replay window) and answered before the workflow runs.
+//
+// Conversations are threads: a mention in a channel is answered in a thread
+// under it, and the conversation id is `<channel>:<thread_ts>`, so each
+// thread gets its own history. Direct messages stay top-level
+// (conversation = `<channel>`) unless the user is already in a thread.
+//
+// Which events are handled:
+//   - `app_mention` everywhere; `message` in DMs.
+//   - `message` in channels only with `all_messages = true`.
+//   - Anything with a `subtype` (edits, joins) or a `bot_id` (other bots and
+//     our own replies) is ignored.
+//
+// Button presses (`block_actions`, e.g. on a tool-approval card) arrive as
+// `interactive` envelopes in Socket Mode, or as a form-encoded `payload=`
+// POST to the same webhook path in Events mode (set it as the app's
+// Interactivity request URL). They become `card_action` messages whose
+// `event_data.action` is the button's value.
+//
+// Egress: `chat.postMessage` (chunked at 4000 chars) for `send`, and
+// `chat.postMessage` + debounced `chat.update` for streaming replies.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use dashmap::DashSet;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::protocol::Message as WsMessage;
+use tracing::{debug, error, info, warn};
+
+use super::{Channel, MessageDispatcher, PlatformMessage};
+use crate::services::config::{ChannelLimitsConfig, JuglansConfig, SlackChannelConfig};
+
+// ─── Constants ──────────────────────────────────────────────────────────────
+
+/// Slack truncates `text` well above this, but 4000 is the documented
+/// recommendation for readable messages.
+pub(crate) const MAX_MESSAGE_LEN: usize = 4000;
+
+/// Requests whose `X-Slack-Request-Timestamp` is older than this are rejected.
+const SIGNATURE_MAX_AGE_SECS: i64 = 60 * 5;
+
+const STREAM_EDIT_INTERVAL_MS: u128 = 1000;
+
+// ─── Conversation ids ───────────────────────────────────────────────────────
+
+/// Split `<channel>` / `<channel>:<thread_ts>` into its parts.
+pub(crate) fn split_conversation(conversation: &str) -> (&str, Option<&str>) {
+    match conversation.split_once(':') {
+        Some((channel, ts)) if !ts.is_empty() => (channel, Some(ts)),
+        Some((channel, _)) => (channel, None),
+        None => (conversation, None),
+    }
+}
+
+pub(crate) fn conversation_id(channel: &str, thread_ts: Option<&str>) -> String {
+    match thread_ts {
+        Some(ts) => format!("{}:{}", channel, ts),
+        None => channel.to_string(),
+    }
+}
+
+// ─── Web API ────────────────────────────────────────────────────────────────
+
+/// POST a Web API method with a JSON body. Slack reports failures as
+/// `{"ok": false, "error": "..."}` with HTTP 200, so `ok` is checked here.
+/// Retries once on HTTP 429 using `Retry-After`.
+pub(crate) async fn api_call(
+    http: &reqwest::Client,
+    api_base: &str,
+    token: &str,
+    method: &str,
+    body: &Value,
+) -> Result<Value> {
+    let url = format!("{}/{}", api_base.trim_end_matches('/'), method);
+    let mut attempt = 0;
+    loop {
+        let resp = http
+            .post(&url)
+            .bearer_auth(token)
+            .json(body)
+            .timeout(Duration::from_secs(15))
+            .send()
+            .await?;
+        if resp.status().as_u16() == 429 && attempt < 1 {
+            let wait = resp
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(1);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            attempt += 1;
+            continue;
+        }
+        let status = resp.status();
+        let v: Value = resp.json().await.map_err(|e| {
+            anyhow!(
+                "slack {}: unreadable response (HTTP {}): {}",
+                method,
+                status,
+                e
+            )
+        })?;
+        if v["ok"].as_bool() != Some(true) {
+            return Err(anyhow!(
+                "slack {} failed: {}",
+                method,
+                v["error"].as_str().unwrap_or("unknown error")
+            ));
+        }
+        return Ok(v);
+    }
+}
+
+/// Post `text` to a conversation, chunked at MAX_MESSAGE_LEN. Returns the
+/// `ts` of every message posted. A failure after the first chunk carries
+/// the unsent rest for the outbox.
+pub(crate) async fn post_message(
+    http: &reqwest::Client,
+    api_base: &str,
+    token: &str,
+    conversation: &str,
+    text: &str,
+) -> Result<Vec<String>> {
+    let (channel, thread_ts) = split_conversation(conversation);
+    let mut posted = Vec::new();
+    let chunks = super::discord::split_message(text, MAX_MESSAGE_LEN);
+    for (i, chunk) in chunks.iter().enumerate() {
+        if chunk.is_empty() {
+            continue;
+        }
+        let mut body = json!({ "channel": channel, "text": chunk });
+        if let Some(ts) = thread_ts {
+            body["thread_ts"] = json!(ts);
+        }
+        let resp = api_call(http, api_base, token, "chat.postMessage", &body)
+            .await
+            .map_err(|e| crate::services::outbox::partial_failure(e, &chunks, i))?;
+        if let Some(ts) = resp["ts"].as_str() {
+            posted.push(ts.to_string());
+        }
+    }
+    Ok(posted)
+}
+
+/// Post an approve / deny card: the prompt as a section block plus two
+/// buttons whose values are the actions reported back on press.
+async fn post_approval(
+    http: &reqwest::Client,
+    api_base: &str,
+    token: &str,
+    conversation: &str,
+    text: &str,
+    allow_action: &str,
+    deny_action: &str,
+) -> Result<()> {
+    let (channel, thread_ts) = split_conversation(conversation);
+    let mut body = json!({
+        "channel": channel,
+        "text": text,
+        "blocks": [
+            { "type": "section", "text": { "type": "mrkdwn", "text": text } },
+            { "type": "actions", "elements": [
+                { "type": "button", "style": "primary", "action_id": "approve",
+                  "text": { "type": "plain_text", "text": "Approve" }, "value": allow_action },
+                { "type": "button", "style": "danger", "action_id": "deny",
+                  "text": { "type": "plain_text", "text": "Deny" }, "value": deny_action },
+            ]},
+        ],
+    });
+    if let Some(ts) = thread_ts {
+        body["thread_ts"] = json!(ts);
+    }
+    api_call(http, api_base, token, "chat.postMessage", &body)
+        .await
+        .map(|_| ())
+}
+
+pub(crate) async fn update_message(
+    http: &reqwest::Client,
+    api_base: &str,
+    token: &str,
+    channel: &str,
+    ts: &str,
+    text: &str,
+) -> Result<()> {
+    api_call(
+        http,
+        api_base,
+        token,
+        "chat.update",
+        &json!({ "channel": channel, "ts": ts, "text": text }),
+    )
+    .await
+    .map(|_| ())
+}
+
+// ─── Request signing ────────────────────────────────────────────────────────
+
+/// Verify an Events API request. `now` is unix seconds.
+pub(crate) fn verify_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+) -> bool {
+    let ts: i64 = match timestamp.parse() {
+        Ok(t) => t,
+        Err(_) => return false,
+    };
+    if (now - ts).abs() > SIGNATURE_MAX_AGE_SECS {
+        return false;
+    }
+    let expected = match signature
+        .strip_prefix("v0=")
+        .and_then(|h| hex::decode(h).ok())
+    {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    let mut mac = match Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes()) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    // verify_slice compares in constant time.
+    mac.verify_slice(&expected).is_ok()
+}
+
+// ─── Event parsing ──────────────────────────────────────────────────────────
+
+/// Drop a leading `<@U123>` mention (app_mention text always starts with it).
+fn strip_leading_mention(text: &str) -> &str {
+    let mut rest = text.trim_start();
+    while let Some(after) = rest.strip_prefix("<@") {
+        match after.find('>') {
+            Some(end) => rest = after[end + 1..].trim_start(),
+            None => break,
+        }
+    }
+    rest.trim_end()
+}
+
+/// Turn an Events API `event` object into a PlatformMessage, or None if it
+/// should be ignored.
+pub(crate) fn event_to_message(
+    event: &Value,
+    threads: bool,
+    all_messages: bool,
+) -> Option<PlatformMessage> {
+    let kind = event["type"].as_str()?;
+    if !matches!(kind, "message" | "app_mention") {
+        return None;
+    }
+    if event.get("subtype").is_some() || event.get("bot_id").is_some() {
+        return None;
+    }
+    let channel_type = event["channel_type"].as_str().unwrap_or("");
+    let is_dm = channel_type == "im";
+    if kind == "message" && !is_dm && !all_messages {
+        return None;
+    }
+
+    let channel = event["channel"].as_str()?;
+    let user = event["user"].as_str()?;
+    let ts = event["ts"].as_str()?;
+    let text = strip_leading_mention(event["text"].as_str().unwrap_or("")).to_string();
+    if text.is_empty() {
+        return None;
+    }
+    let thread_ts = event["thread_ts"]
+        .as_str()
+        .or(if threads && !is_dm { Some(ts) } else { None });
+
+    Some(PlatformMessage {
+        event_type: "message".into(),
+        event_data: json!({
+            "text": &text,
+            "channel": channel,
+            "channel_type": channel_type,
+            "ts": ts,
+            "thread_ts": thread_ts,
+            "team": event["team"],
+        }),
+        platform_user_id: user.to_string(),
+        platform_chat_id: conversation_id(channel, thread_ts),
+        text,
+        username: event
+            .pointer("/user_profile/name")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        platform: "slack".into(),
+    })
+}
+
+/// Turn a `block_actions` interaction payload into a `card_action`
+/// PlatformMessage for its first action, in the conversation of the message
+/// that carried the buttons.
+pub(crate) fn block_action_to_message(payload: &Value) -> Option<PlatformMessage> {
+    if payload["type"].as_str() != Some("block_actions") {
+        return None;
+    }
+    let action = &payload["actions"][0];
+    let value = action["value"]
+        .as_str()
+        .or_else(|| action["action_id"].as_str())?;
+    let user = payload["user"]["id"].as_str()?;
+    let channel = payload["channel"]["id"]
+        .as_str()
+        .or_else(|| payload["container"]["channel_id"].as_str())?;
+    let thread_ts = payload["message"]["thread_ts"]
+        .as_str()
+        .or_else(|| payload["container"]["thread_ts"].as_str());
+    Some(PlatformMessage {
+        event_type: "card_action".into(),
+        event_data: json!({
+            "action": value,
+            "action_id": action["action_id"],
+            "channel": channel,
+            "message_ts": payload["message"]["ts"],
+            "thread_ts": thread_ts,
+        }),
+        platform_user_id: user.to_string(),
+        platform_chat_id: conversation_id(channel, thread_ts),
+        text: String::new(),
+        username: payload["user"]["username"].as_str().map(String::from),
+        platform: "slack".into(),
+    })
+}
+
+/// Extract the JSON `payload` field of a form-encoded interaction request.
+fn form_payload(body: &[u8]) -> Option<Value> {
+    let body = std::str::from_utf8(body).ok()?;
+    let raw = body
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("payload="))?;
+    let raw = raw.replace('+', " ");
+    let decoded = urlencoding::decode(&raw).ok()?;
+    serde_json::from_str(&decoded).ok()
+}
+
+// ─── Channel ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlackMode {
+    Socket,
+    Events,
+}
+
+/// One Slack app = one [`SlackChannel`]. `id()` is `"slack:<instance_id>"`.
+pub struct SlackChannel {
+    id: String,
+    instance_id: String,
+    cfg: SlackChannelConfig,
+    mode: SlackMode,
+    http: reqwest::Client,
+    /// `<channel>:<ts>` of handled messages. Slack retries deliveries, and a
+    /// mention can arrive as both `message` and `app_mention`.
+    seen: DashSet<String>,
+}
+
+impl SlackChannel {
+    fn new(instance_id: String, cfg: SlackChannelConfig, mode: SlackMode) -> Self {
+        Self {
+            id: format!("slack:{}", instance_id),
+            instance_id,
+            cfg,
+            mode,
+            http: reqwest::Client::new(),
+            seen: DashSet::new(),
+        }
+    }
+
+    /// Handle an `event_callback` payload from either ingress path.
+    fn handle_event_callback(&self, payload: &Value, dispatcher: Arc<dyn MessageDispatcher>) {
+        if payload["type"].as_str() != Some("event_callback") {
+            debug!(
+                "[slack:{}] ignored payload type {}",
+                self.instance_id, payload["type"]
+            );
+            return;
+        }
+        let event = &payload["event"];
+        let msg = match event_to_message(event, self.cfg.threads, self.cfg.all_messages) {
+            Some(m) => m,
+            None => return,
+        };
+        let key = format!(
+            "{}:{}",
+            event["channel"].as_str().unwrap_or(""),
+            event["ts"].as_str().unwrap_or("")
+        );
+        if !self.seen.insert(key) {
+            return;
+        }
+        if self.seen.len() > 10_000 {
+            self.seen.clear();
+        }
+
+        let preview: String = msg.text.chars().take(50).collect();
+        info!(
+            "[slack:{}] {} in {}: {}",
+            self.instance_id, msg.platform_user_id, msg.platform_chat_id, preview
+        );
+        self.spawn_dispatch(msg, dispatcher);
+    }
+
+    /// Handle a `block_actions` interaction payload from either ingress path.
+    fn handle_interaction(&self, payload: &Value, dispatcher: Arc<dyn MessageDispatcher>) {
+        let Some(msg) = block_action_to_message(payload) else {
+            debug!(
+                "[slack:{}] ignored interaction type {}",
+                self.instance_id, payload["type"]
+            );
+            return;
+        };
+        info!(
+            "[slack:{}] {} pressed {} in {}",
+            self.instance_id,
+            msg.platform_user_id,
+            msg.event_data["action_id"],
+            msg.platform_chat_id
+        );
+        self.spawn_dispatch(msg, dispatcher);
+    }
+
+    /// Run the workflow for `msg` in the background and post its reply.
+    fn spawn_dispatch(&self, msg: PlatformMessage, dispatcher: Arc<dyn MessageDispatcher>) {
+        let http = self.http.clone();
+        let api_base = self.cfg.api_base.clone();
+        let token = self.cfg.bot_token.clone();
+        tokio::spawn(super::while_delivering(async move {
+            let conversation = msg.platform_chat_id.clone();
+            match dispatcher.dispatch(&msg).await {
+                Ok(reply) => {
+                    if reply.text.is_empty() || reply.text == "(No response)" {
+                        return;
+                    }
+                    if let Err(e) =
+                        post_message(&http, &api_base, &token, &conversation, &reply.text).await
+                    {
+                        error!("[slack] send failed: {}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("[slack] agent error: {}", e);
+                    let _ = post_message(
+                        &http,
+                        &api_base,
+                        &token,
+                        &conversation,
+                        &format!("Error: {}", e),
+                    )
+                    .await;
+                }
+            }
+        }));
+    }
+
+    /// Events API request handler: verify, answer url_verification, ack,
+    /// then dispatch in the background. Interactivity requests (button
+    /// presses) come in form-encoded on the same path.
+    fn handle_http(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> Response {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        };
+        let secret = self.cfg.signing_secret.as_deref().unwrap_or("");
+        if !verify_signature(
+            secret,
+            header("x-slack-request-timestamp"),
+            body,
+            header("x-slack-signature"),
+            chrono::Utc::now().timestamp(),
+        ) {
+            warn!(
+                "[slack:{}] rejected request with bad signature",
+                self.instance_id
+            );
+            return (StatusCode::UNAUTHORIZED, "invalid signature").into_response();
+        }
+        if header("content-type").starts_with("application/x-www-form-urlencoded") {
+            return match form_payload(body) {
+                Some(payload) => {
+                    self.handle_interaction(&payload, dispatcher);
+                    StatusCode::OK.into_response()
+                }
+                None => (StatusCode::BAD_REQUEST, "invalid payload").into_response(),
+            };
+        }
+        let payload: Value = match serde_json::from_slice(body) {
+            Ok(v) => v,
+            Err(_) => return (StatusCode::BAD_REQUEST, "invalid JSON").into_response(),
+        };
+        if payload["type"].as_str() == Some("url_verification") {
+            return Json(json!({ "challenge": payload["challenge"] })).into_response();
+        }
+        self.handle_event_callback(&payload, dispatcher);
+        StatusCode::OK.into_response()
+    }
+
+    /// Socket Mode: one websocket session. Returns Ok when Slack asks us to
+    /// reconnect (`disconnect`) or the socket closes.
+    async fn run_socket_session(&self, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
+        let app_token = self
+            .cfg
+            .app_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("Socket Mode requires app_token"))?;
+        let opened = api_call(
+            &self.http,
+            &self.cfg.api_base,
+            app_token,
+            "apps.connections.open",
+            &json!({}),
+        )
+        .await?;
+        let url = opened["url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("apps.connections.open returned no url"))?;
+
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .context("Socket Mode connect failed")?;
+        let (mut write, mut read) = ws.split();
+
+        while let Some(frame) = read.next().await {
+            let text = match frame? {
+                WsMessage::Text(t) => t,
+                WsMessage::Close(_) => return Ok(()),
+                _ => continue,
+            };
+            let v: Value = match serde_json::from_str(&text) {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("[slack:{}] bad socket frame: {}", self.instance_id, e);
+                    continue;
+                }
+            };
+            // Every envelope must be acked within 3 seconds, before the
+            // workflow runs.
+            if let Some(envelope_id) = v["envelope_id"].as_str() {
+                write
+                    .send(WsMessage::Text(
+                        json!({ "envelope_id": envelope_id }).to_string(),
+                    ))
+                    .await?;
+            }
+            match v["type"].as_str() {
+                Some("hello") => info!("[slack:{}] Socket Mode connected", self.instance_id),
+                Some("disconnect") => {
+                    info!(
+                        "[slack:{}] Socket Mode disconnect ({}) — reconnecting",
+                        self.instance_id,
+                        v["reason"].as_str().unwrap_or("?")
+                    );
+                    return Ok(());
+                }
+                Some("events_api") => self.handle_event_callback(&v["payload"], dispatcher.clone()),
+                Some("interactive") => self.handle_interaction(&v["payload"], dispatcher.clone()),
+                other => debug!(
+                    "[slack:{}] ignored socket frame {:?}",
+                    self.instance_id, other
+                ),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for SlackChannel {
+    async fn send(&self, conversation: &str, text: &str) -> Result<()> {
+        post_message(
+            &self.http,
+            &self.cfg.api_base,
+            &self.cfg.bot_token,
+            conversation,
+            text,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn start_stream(
+        &self,
+        conversation: &str,
+    ) -> Result<Box<dyn crate::core::context::StreamHandle>> {
+        let (channel, thread_ts) = split_conversation(conversation);
+        Ok(Box::new(SlackStreamHandle {
+            http: self.http.clone(),
+            api_base: self.cfg.api_base.clone(),
+            token: self.cfg.bot_token.clone(),
+            channel: channel.to_string(),
+            thread_ts: thread_ts.map(String::from),
+            buffer: String::new(),
+            ts: None,
+            last_edit: std::time::Instant::now(),
+            pending: false,
+        }))
+    }
+
+    async fn send_approval(
+        &self,
+        conversation: &str,
+        text: &str,
+        allow_action: &str,
+        deny_action: &str,
+    ) -> Result<()> {
+        post_approval(
+            &self.http,
+            &self.cfg.api_base,
+            &self.cfg.bot_token,
+            conversation,
+            text,
+            allow_action,
+            deny_action,
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl Channel for SlackChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn kind(&self) -> &str {
+        "slack"
+    }
+
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.cfg.limits.as_ref()
+    }
+
+    async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
+        if self.mode != SlackMode::Socket {
+            return Ok(());
+        }
+        info!(
+            "🤖 Slack channel {} starting (Socket Mode)",
+            self.instance_id
+        );
+        let dispatcher = Arc::new(super::OriginAwareDispatcher::new(self.clone(), dispatcher))
+            as Arc<dyn MessageDispatcher>;
+
+        let mut consecutive_failures: u32 = 0;
+        loop {
+            match self.run_socket_session(dispatcher.clone()).await {
+                Ok(()) => consecutive_failures = 0,
+                Err(e) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "[slack:{}] socket error ({}/5): {:#} — reconnecting",
+                        self.instance_id, consecutive_failures, e
+                    );
+                    if consecutive_failures >= 5 {
+                        return Err(e.context("Slack Socket Mode: 5 consecutive failures"));
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+
+    fn install_routes(
+        self: Arc<Self>,
+        router: axum::Router,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> axum::Router {
+        if self.mode != SlackMode::Events {
+            return router;
+        }
+        let path = format!("/webhook/slack/{}", self.instance_id);
+        let dispatcher = Arc::new(super::OriginAwareDispatcher::new(self.clone(), dispatcher))
+            as Arc<dyn MessageDispatcher>;
+        router.route(
+            &path,
+            axum::routing::post(move |headers: HeaderMap, body: axum::body::Bytes| {
+                let channel = self.clone();
+                let dispatcher = dispatcher.clone();
+                async move { channel.handle_http(&headers, &body, dispatcher) }
+            }),
+        )
+    }
+}
+
+// ─── Streaming reply ────────────────────────────────────────────────────────
+//
+// First token posts the message; later tokens `chat.update` it at most once
+// per second. Past MAX_MESSAGE_LEN the live message shows the first chunk
+// and finalize posts the remainder as follow-ups in the same thread.
+
+struct SlackStreamHandle {
+    http: reqwest::Client,
+    api_base: String,
+    token: String,
+    channel: String,
+    thread_ts: Option<String>,
+    buffer: String,
+    /// `ts` of the streaming message once posted.
+    ts: Option<String>,
+    last_edit: std::time::Instant,
+    pending: bool,
+}
+
+impl SlackStreamHandle {
+    fn head(&self) -> String {
+        super::discord::split_message(&self.buffer, MAX_MESSAGE_LEN)
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        let head = self.head();
+        match self.ts.clone() {
+            None => {
+                let mut body = json!({ "channel": self.channel, "text": head });
+                if let Some(ref t) = self.thread_ts {
+                    body["thread_ts"] = json!(t);
+                }
+                let resp = api_call(
+                    &self.http,
+                    &self.api_base,
+                    &self.token,
+                    "chat.postMessage",
+                    &body,
+                )
+                .await?;
+                self.ts = resp["ts"].as_str().map(String::from);
+            }
+            Some(ts) => {
+                update_message(
+                    &self.http,
+                    &self.api_base,
+                    &self.token,
+                    &self.channel,
+                    &ts,
+                    &head,
+                )
+                .await?;
+            }
+        }
+        self.last_edit = std::time::Instant::now();
+        self.pending = false;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::StreamHandle for SlackStreamHandle {
+    async fn push_token(&mut self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.buffer.push_str(text);
+        self.pending = true;
+        if self.ts.is_none() || self.last_edit.elapsed().as_millis() >= STREAM_EDIT_INTERVAL_MS {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn finalize(mut self: Box<Self>) -> Result<()> {
+        if self.ts.is_none() && self.buffer.is_empty() {
+            return Ok(());
+        }
+        if self.pending || self.ts.is_none() {
+            self.flush().await?;
+        }
+        let rest: Vec<String> = super::discord::split_message(&self.buffer, MAX_MESSAGE_LEN)
+            .into_iter()
+            .skip(1)
+            .collect();
+        if !rest.is_empty() {
+            let conversation = conversation_id(&self.channel, self.thread_ts.as_deref());
+            post_message(
+                &self.http,
+                &self.api_base,
+                &self.token,
+                &conversation,
+                &rest.join("\n"),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+// ─── Discovery ──────────────────────────────────────────────────────────────
+
+/// Build [`SlackChannel`] instances from `[channels.slack.<id>]`.
+pub fn discover_channels(config: &JuglansConfig) -> Result<Vec<(Arc<dyn Channel>, String)>> {
+    let mut out: Vec<(Arc<dyn Channel>, String)> = Vec::new();
+    for (instance_id, cfg) in &config.channels.slack {
+        if cfg.bot_token.is_empty() {
+            return Err(anyhow!(
+                "[channels.slack.{}] bot_token is empty — set it in juglans.toml (e.g. `bot_token = \"${{SLACK_BOT_TOKEN}}\"`)",
+                instance_id
+            ));
+        }
+        let mode = match cfg.mode.as_deref() {
+            Some("socket") => SlackMode::Socket,
+            Some("events") => SlackMode::Events,
+            Some(other) => {
+                return Err(anyhow!(
+                    "[channels.slack.{}] unknown mode \"{}\" (expected \"socket\" or \"events\")",
+                    instance_id,
+                    other
+                ))
+            }
+            None if cfg.app_token.is_some() => SlackMode::Socket,
+            None => SlackMode::Events,
+        };
+        if mode == SlackMode::Socket && cfg.app_token.as_deref().unwrap_or("").is_empty() {
+            return Err(anyhow!(
+                "[channels.slack.{}] Socket Mode needs app_token (xapp-...)",
+                instance_id
+            ));
+        }
+        if mode == SlackMode::Events && cfg.signing_secret.as_deref().unwrap_or("").is_empty() {
+            return Err(anyhow!(
+                "[channels.slack.{}] Events API mode needs signing_secret",
+                instance_id
+            ));
+        }
+        out.push((
+            Arc::new(SlackChannel::new(instance_id.clone(), cfg.clone(), mode)),
+            cfg.agent.clone(),
+        ));
+    }
+    Ok(out)
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::BotReply;
+    use std::sync::Mutex;
+
+    fn sign(secret: &str, ts: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", ts).as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// Local stand-in for slack.com/api: records every call, answers `ok`.
+    async fn mock_slack_api() -> (String, Arc<Mutex<Vec<(String, Value)>>>) {
+        let calls: Arc<Mutex<Vec<(String, Value)>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let app = axum::Router::new().route(
+            "/api/:method",
+            axum::routing::post(
+                move |axum::extract::Path(method): axum::extract::Path<String>,
+                      Json(body): Json<Value>| {
+                    let recorded = recorded.clone();
+                    async move {
+                        let n = {
+                            let mut c = recorded.lock().unwrap();
+                            c.push((method, body.clone()));
+                            c.len()
+                        };
+                        Json(json!({
+                            "ok": true,
+                            "channel": body["channel"],
+                            "ts": format!("1700000000.{:06}", n),
+                        }))
+                    }
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}/api", addr), calls)
+    }
+
+    fn channel(api_base: &str, mode: SlackMode) -> Arc<SlackChannel> {
+        let cfg = SlackChannelConfig {
+            bot_token: "xoxb-test".into(),
+            app_token: None,
+            signing_secret: Some("s3cret".into()),
+            agent: "default".into(),
+            mode: None,
+            threads: true,
+            all_messages: false,
+            api_base: api_base.into(),
+            limits: None,
+        };
+        Arc::new(SlackChannel::new("main".into(), cfg, mode))
+    }
+
+    struct Recorder(Mutex<Vec<PlatformMessage>>);
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Recorder {
+        async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+            self.0.lock().unwrap().push(PlatformMessage {
+                event_type: message.event_type.clone(),
+                event_data: message.event_data.clone(),
+                platform_user_id: message.platform_user_id.clone(),
+                platform_chat_id: message.platform_chat_id.clone(),
+                text: message.text.clone(),
+                username: message.username.clone(),
+                platform: message.platform.clone(),
+            });
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn signature_roundtrip_tamper_and_replay() {
+        let body = br#"{"type":"event_callback"}"#;
+        let now = 1_700_000_000;
+        let ts = now.to_string();
+        let sig = sign("s3cret", &ts, body);
+        assert!(verify_signature("s3cret", &ts, body, &sig, now));
+        assert!(!verify_signature("other", &ts, body, &sig, now));
+        assert!(!verify_signature("s3cret", &ts, b"{}", &sig, now));
+        assert!(!verify_signature("s3cret", &ts, body, &sig, now + 301));
+        assert!(!verify_signature("s3cret", &ts, body, "v0=zz", now));
+    }
+
+    #[test]
+    fn mentions_start_threads_and_dms_stay_flat() {
+        let mention = json!({
+            "type": "app_mention", "channel": "C1", "user": "U1",
+            "ts": "111.222", "text": "<@UBOT> what's up?"
+        });
+        let m = event_to_message(&mention, true, false).unwrap();
+        assert_eq!(m.text, "what's up?");
+        assert_eq!(m.platform_chat_id, "C1:111.222");
+        assert_eq!(m.platform, "slack");
+
+        let dm = json!({
+            "type": "message", "channel": "D1", "channel_type": "im",
+            "user": "U1", "ts": "333.444", "text": "hi"
+        });
+        assert_eq!(
+            event_to_message(&dm, true, false).unwrap().platform_chat_id,
+            "D1"
+        );
+
+        let in_thread = json!({
+            "type": "message", "channel": "D1", "channel_type": "im",
+            "user": "U1", "ts": "555.666", "thread_ts": "333.444", "text": "more"
+        });
+        assert_eq!(
+            event_to_message(&in_thread, true, false)
+                .unwrap()
+                .platform_chat_id,
+            "D1:333.444"
+        );
+    }
+
+    #[test]
+    fn ignores_bots_edits_and_unmentioned_channel_chatter() {
+        let bot = json!({
+            "type": "message", "channel_type": "im", "channel": "D1",
+            "user": "U1", "ts": "1.0", "text": "hi", "bot_id": "B1"
+        });
+        assert!(event_to_message(&bot, true, false).is_none());
+        let edit = json!({
+            "type": "message", "subtype": "message_changed", "channel": "D1",
+            "channel_type": "im", "ts": "1.0"
+        });
+        assert!(event_to_message(&edit, true, false).is_none());
+        let chatter = json!({
+            "type": "message", "channel_type": "channel", "channel": "C1",
+            "user": "U1", "ts": "1.0", "text": "lunch?"
+        });
+        assert!(event_to_message(&chatter, true, false).is_none());
+        assert!(event_to_message(&chatter, true, true).is_some());
+    }
+
+    #[test]
+    fn conversation_ids_roundtrip() {
+        assert_eq!(split_conversation("C1:1.2"), ("C1", Some("1.2")));
+        assert_eq!(split_conversation("C1"), ("C1", None));
+        assert_eq!(conversation_id("C1", Some("1.2")), "C1:1.2");
+    }
+
+    #[tokio::test]
+    async fn send_posts_into_thread() {
+        use crate::core::context::ChannelEgress;
+        let (api, calls) = mock_slack_api().await;
+        let ch = channel(&api, SlackMode::Events);
+        ch.send("C1:111.222", "hello").await.unwrap();
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, "chat.postMessage");
+        assert_eq!(calls[0].1["channel"], "C1");
+        assert_eq!(calls[0].1["thread_ts"], "111.222");
+        assert_eq!(calls[0].1["text"], "hello");
+    }
+
+    #[tokio::test]
+    async fn stream_posts_then_updates() {
+        use crate::core::context::ChannelEgress;
+        let (api, calls) = mock_slack_api().await;
+        let ch = channel(&api, SlackMode::Events);
+        let mut handle = ch.start_stream("C1:111.222").await.unwrap();
+        handle.push_token("Hel").await.unwrap();
+        handle.push_token("lo").await.unwrap();
+        handle.finalize().await.unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls[0].0, "chat.postMessage");
+        assert_eq!(calls[0].1["text"], "Hel");
+        let last = calls.last().unwrap();
+        assert_eq!(last.0, "chat.update");
+        assert_eq!(last.1["ts"], "1700000000.000001");
+        assert_eq!(last.1["text"], "Hello");
+    }
+
+    #[tokio::test]
+    async fn events_route_verifies_and_dispatches() {
+        let (api, _calls) = mock_slack_api().await;
+        let ch = channel(&api, SlackMode::Events);
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let app = ch.install_routes(axum::Router::new(), recorder.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/webhook/slack/main",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let http = reqwest::Client::new();
+        let post = |body: Value, secret: &'static str| {
+            let body = body.to_string();
+            let ts = chrono::Utc::now().timestamp().to_string();
+            let sig = sign(secret, &ts, body.as_bytes());
+            http.post(&url)
+                .header("x-slack-request-timestamp", ts)
+                .header("x-slack-signature", sig)
+                .header("content-type", "application/json")
+                .body(body)
+                .send()
+        };
+
+        let challenge = post(
+            json!({ "type": "url_verification", "challenge": "abc" }),
+            "s3cret",
+        )
+        .await
+        .unwrap();
+        assert_eq!(challenge.json::<Value>().await.unwrap()["challenge"], "abc");
+
+        let forged = post(json!({ "type": "event_callback" }), "wrong")
+            .await
+            .unwrap();
+        assert_eq!(forged.status(), 401);
+
+        let event = json!({
+            "type": "event_callback",
+            "event": {
+                "type": "app_mention", "channel": "C1", "user": "U1",
+                "ts": "111.222", "text": "<@UBOT> hi"
+            }
+        });
+        assert_eq!(post(event.clone(), "s3cret").await.unwrap().status(), 200);
+        // Slack retry of the same event is dropped.
+        assert_eq!(post(event, "s3cret").await.unwrap().status(), 200);
+
+        for _ in 0..50 {
+            if !recorder.0.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let got = recorder.0.lock().unwrap();
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].text, "hi");
+        assert_eq!(got[0].platform_chat_id, "C1:111.222");
+    }
+
+    #[tokio::test]
+    async fn approval_card_and_button_press() {
+        use crate::core::context::ChannelEgress;
+        let (api, calls) = mock_slack_api().await;
+        let ch = channel(&api, SlackMode::Events);
+        ch.send_approval("C1:111.222", "Allow `bash`?", "yes:1", "no:1")
+            .await
+            .unwrap();
+        {
+            let calls = calls.lock().unwrap();
+            assert_eq!(calls[0].1["thread_ts"], "111.222");
+            let buttons = &calls[0].1["blocks"][1]["elements"];
+            assert_eq!(buttons[0]["value"], "yes:1");
+            assert_eq!(buttons[1]["value"], "no:1");
+        }
+
+        let payload = json!({
+            "type": "block_actions",
+            "user": { "id": "U1", "username": "alice" },
+            "channel": { "id": "C1" },
+            "message": { "ts": "111.333", "thread_ts": "111.222" },
+            "actions": [{ "action_id": "approve", "value": "yes:1" }],
+        });
+        let body = format!(
+            "payload={}",
+            urlencoding::encode(&payload.to_string()).replace("%20", "+")
+        );
+        assert_eq!(form_payload(body.as_bytes()), Some(payload.clone()));
+
+        let msg = block_action_to_message(&payload).unwrap();
+        assert_eq!(msg.event_type, "card_action");
+        assert_eq!(msg.event_data["action"], "yes:1");
+        assert_eq!(msg.platform_user_id, "U1");
+        assert_eq!(msg.platform_chat_id, "C1:111.222");
+    }
+}
@@ -4,14 +4,18 @@
 use anyhow::Result;
 use dashmap::DashSet;
 use serde_json::{json, Value};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::{error, info};
 
 use axum::Json;
 
+use super::limits::{gate, InboundLimiter};
 use super::{run_agent_for_message, Channel, MessageDispatcher, PlatformMessage};
-use crate::services::config::JuglansConfig;
+use crate::services::config::{ChannelLimitsConfig, JuglansConfig};
+use crate::services::outbox::DeliveryError;
 
 // ======================================================================
 // Public webhook handler (for web_server.rs serverless integration)
@@ -27,6 +31,9 @@ pub struct TelegramWebhookHandler {
     agent_slug: String,
     token: String,
     processed_updates: DashSet<i64>,
+    /// `[channels.telegram.<id>.limits]`; webhook runs bypass the
+    /// dispatcher, so the handler gates them itself.
+    limiter: Option<Arc<InboundLimiter>>,
 }
 
 impl TelegramWebhookHandler {
@@ -61,47 +68,19 @@ impl TelegramWebhookHandler {
             return json!({"ok": true, "description": "duplicate"});
         }
 
-        // Extract message
-        let msg = match body.get("message") {
+        let mut platform_msg = match parse_update(&body) {
             Some(m) => m,
             None => return json!({"ok": true}),
         };
-
-        let text = msg["text"].as_str().unwrap_or("").to_string();
-        if text.is_empty() {
-            return json!({"ok": true});
-        }
-
-        let chat_id = msg["chat"]["id"].as_i64().unwrap_or(0);
-        let user_id = msg["from"]["id"].as_i64().unwrap_or(0).to_string();
-        let username = msg["from"]["username"].as_str().map(|s| s.to_string());
-        let first_name = msg["from"]["first_name"].as_str().unwrap_or("User");
+        let chat_id = platform_msg.platform_chat_id.clone();
 
         info!(
-            "[Telegram Webhook] {} (@{}): {}",
-            first_name,
-            username.as_deref().unwrap_or("?"),
-            if text.chars().count() > 50 {
-                &text[..text
-                    .char_indices()
-                    .nth(50)
-                    .map(|(i, _)| i)
-                    .unwrap_or(text.len())]
-            } else {
-                &text
-            }
+            "[Telegram Webhook] @{} {}: {}",
+            platform_msg.username.as_deref().unwrap_or("?"),
+            platform_msg.event_type,
+            preview(&platform_msg)
         );
 
-        let platform_msg = PlatformMessage {
-            event_type: "message".into(),
-            event_data: json!({ "text": &text }),
-            platform_user_id: user_id,
-            platform_chat_id: chat_id.to_string(),
-            text,
-            username,
-            platform: "telegram".into(),
-        };
-
         // Process asynchronously (don't block webhook response)
         let config = self.config.clone();
         let project_root = self.project_root.clone();
@@ -109,13 +88,24 @@ impl TelegramWebhookHandler {
         let token = self.token.clone();
         let origin = channel.map(|ch| crate::core::context::ChannelOrigin {
             channel: ch,
-            conversation: chat_id.to_string(),
+            conversation: chat_id.clone(),
         });
 
-        tokio::spawn(async move {
+        let limiter = self.limiter.clone();
+
+        tokio::spawn(super::while_delivering(async move {
+            let _slot = match limiter {
+                Some(ref l) => match gate(l, &platform_msg, origin.as_ref()).await {
+                    Some(slot) => Some(slot),
+                    None => return,
+                },
+                None => None,
+            };
             let base_url = format!("https://api.telegram.org/bot{}", token);
             let client = reqwest::Client::new();
 
+            prepare_inbound(&client, &token, &mut platform_msg).await;
+
             // Send typing status
             let _ = client
                 .post(format!("{}/sendChatAction", base_url))
@@ -169,7 +159,7 @@ impl TelegramWebhookHandler {
                         .await;
                 }
             }
-        });
+        }));
 
         json!({"ok": true})
     }
@@ -186,6 +176,13 @@ pub struct TelegramChannel {
     id: String,
     token: String,
     client: reqwest::Client,
+    limits: Option<ChannelLimitsConfig>,
+    /// Next `getUpdates` offset.
+    offset: AtomicI64,
+    /// Updates taken but not yet answered. The offset saved on shutdown stops
+    /// at the oldest of them, so the next process picks those up again.
+    pending: Mutex<BTreeSet<i64>>,
+    project_root: Option<PathBuf>,
 }
 
 impl TelegramChannel {
@@ -197,8 +194,83 @@ impl TelegramChannel {
             id: format!("telegram:{}", bot_id),
             token,
             client: reqwest::Client::new(),
+            limits: None,
+            offset: AtomicI64::new(0),
+            pending: Mutex::new(BTreeSet::new()),
+            project_root: None,
+        }
+    }
+
+    /// Inbound limits from `[channels.telegram.<id>.limits]`.
+    pub fn with_limits(mut self, limits: Option<ChannelLimitsConfig>) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Keep the polling offset in `.juglans/telegram/` so a restart resumes
+    /// after the last update this process answered, instead of replaying the
+    /// batch Telegram hasn't seen confirmed yet.
+    pub fn with_project_root(mut self, project_root: &Path) -> Self {
+        self.project_root = Some(project_root.to_path_buf());
+        self
+    }
+
+    fn offset_path(&self) -> Option<PathBuf> {
+        let bot_id = self.id.trim_start_matches("telegram:");
+        self.project_root.as_ref().map(|root| {
+            root.join(".juglans")
+                .join("telegram")
+                .join(format!("{}.offset", bot_id))
+        })
+    }
+
+    fn load_offset(&self) {
+        let saved = self
+            .offset_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| s.trim().parse::<i64>().ok());
+        if let Some(offset) = saved {
+            self.offset.store(offset, Ordering::SeqCst);
         }
     }
+
+    /// Offset the next process should poll from: the oldest unanswered
+    /// update, or past everything taken when all were answered.
+    fn resume_offset(&self) -> i64 {
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending
+            .first()
+            .copied()
+            .unwrap_or_else(|| self.offset.load(Ordering::SeqCst))
+    }
+
+    /// Mark an update as taken but not yet answered, until the returned
+    /// guard is dropped — at the end of its task, or when the task panics.
+    fn hold_pending(self: &Arc<Self>, update_id: i64) -> PendingUpdate {
+        let mut set = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        set.insert(update_id);
+        PendingUpdate {
+            channel: Arc::clone(self),
+            update_id,
+        }
+    }
+}
+
+/// An update being answered; see [`TelegramChannel::hold_pending`].
+struct PendingUpdate {
+    channel: Arc<TelegramChannel>,
+    update_id: i64,
+}
+
+impl Drop for PendingUpdate {
+    fn drop(&mut self) {
+        let mut set = self
+            .channel
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        set.remove(&self.update_id);
+    }
 }
 
 #[async_trait::async_trait]
@@ -210,6 +282,7 @@ impl crate::core::context::ChannelEgress for TelegramChannel {
             conversation,
             text,
             Some("Markdown"),
+            None,
         )
         .await
         .map(|_| ())
@@ -225,6 +298,24 @@ impl crate::core::context::ChannelEgress for TelegramChannel {
             conversation.to_string(),
         )))
     }
+    async fn send_approval(
+        &self,
+        conversation: &str,
+        text: &str,
+        allow_action: &str,
+        deny_action: &str,
+    ) -> Result<()> {
+        send_message_api(
+            &self.client,
+            &self.token,
+            conversation,
+            text,
+            None,
+            Some(&approval_keyboard(allow_action, deny_action)),
+        )
+        .await
+        .map(|_| ())
+    }
 }
 
 #[async_trait::async_trait]
@@ -237,6 +328,22 @@ impl Channel for TelegramChannel {
         "telegram"
     }
 
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.limits.as_ref()
+    }
+
+    async fn persist_resume_state(&self) -> Result<()> {
+        let offset = self.resume_offset();
+        let Some(path) = self.offset_path().filter(|_| offset > 0) else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, offset.to_string())?;
+        Ok(())
+    }
+
     async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
         // Auto-inject ChannelOrigin into every dispatched message so workflows
         // calling `reply()` route their output back through this channel.
@@ -263,8 +370,9 @@ impl Channel for TelegramChannel {
 
         info!("🤖 Telegram Bot @{} ready, waiting for updates", bot_name);
 
-        let mut offset: i64 = 0;
+        self.load_offset();
         loop {
+            let offset = self.offset.load(Ordering::SeqCst);
             let updates: serde_json::Value = match self
                 .client
                 .get(format!("{}/getUpdates", base_url))
@@ -293,44 +401,32 @@ impl Channel for TelegramChannel {
             if let Some(results) = updates["result"].as_array() {
                 for update in results {
                     let update_id = update["update_id"].as_i64().unwrap_or(0);
-                    offset = update_id + 1;
+                    self.offset.store(update_id + 1, Ordering::SeqCst);
 
-                    let msg = match update.get("message") {
+                    let mut platform_msg = match parse_update(update) {
                         Some(m) => m,
                         None => continue,
                     };
-                    let text = msg["text"].as_str().unwrap_or("").to_string();
-                    if text.is_empty() {
-                        continue;
-                    }
-                    let chat_id = msg["chat"]["id"].as_i64().unwrap_or(0);
-                    let user_id = msg["from"]["id"].as_i64().unwrap_or(0).to_string();
-                    let username = msg["from"]["username"].as_str().map(|s| s.to_string());
-                    let first_name = msg["from"]["first_name"].as_str().unwrap_or("User");
+                    let chat_id = platform_msg.platform_chat_id.clone();
 
                     info!(
-                        "📩 [telegram:{}] {} (@{}): {}",
+                        "📩 [telegram:{}] @{} {}: {}",
                         bot_name,
-                        first_name,
-                        username.as_deref().unwrap_or("?"),
-                        if text.len() > 50 { &text[..50] } else { &text }
+                        platform_msg.username.as_deref().unwrap_or("?"),
+                        platform_msg.event_type,
+                        preview(&platform_msg)
                     );
 
                     let dispatcher = dispatcher.clone();
                     let client = self.client.clone();
                     let base_url = base_url.clone();
                     let bot_name = bot_name.clone();
+                    let token = self.token.clone();
+                    let pending = self.hold_pending(update_id);
 
-                    tokio::spawn(async move {
-                        let platform_msg = PlatformMessage {
-                            event_type: "message".into(),
-                            event_data: json!({ "text": &text }),
-                            platform_user_id: user_id,
-                            platform_chat_id: chat_id.to_string(),
-                            text,
-                            username,
-                            platform: "telegram".into(),
-                        };
+                    tokio::spawn(super::while_delivering(async move {
+                        let _pending = pending;
+                        prepare_inbound(&client, &token, &mut platform_msg).await;
 
                         let _ = client
                             .post(format!("{}/sendChatAction", base_url))
@@ -378,7 +474,7 @@ impl Channel for TelegramChannel {
                                     .await;
                             }
                         }
-                    });
+                    }));
                 }
             }
         }
@@ -418,10 +514,15 @@ pub fn discover_channels(
                 agent_slug: cfg.agent.clone(),
                 token: cfg.token.clone(),
                 processed_updates: DashSet::new(),
+                limiter: cfg.limits.clone().map(|l| Arc::new(InboundLimiter::new(l))),
             };
             Arc::new(TelegramWebhookChannel::new(instance_id.clone(), handler))
         } else {
-            Arc::new(TelegramChannel::new(cfg.token.clone()))
+            Arc::new(
+                TelegramChannel::new(cfg.token.clone())
+                    .with_limits(cfg.limits.clone())
+                    .with_project_root(project_root),
+            )
         };
         out.push((channel, cfg.agent.clone()));
     }
@@ -437,55 +538,396 @@ pub(crate) const TELEGRAM_MAX_LEN: usize = 4096;
 
 /// Send a text message to a Telegram chat. Falls back to plain-text if
 /// `parse_mode`-formatted send fails (typical cause: malformed Markdown).
-/// Chunks long messages using `split_message`.
+/// Chunks long messages using `split_message`; a failure after the first
+/// chunk carries the unsent rest for the outbox.
 pub(crate) async fn send_message_api(
     http: &reqwest::Client,
     token: &str,
     chat_id: &str,
     text: &str,
     parse_mode: Option<&str>,
+    reply_markup: Option<&Value>,
 ) -> anyhow::Result<usize> {
     let base_url = format!("{}/bot{}", TELEGRAM_API, token);
     let chunks = split_message(text, TELEGRAM_MAX_LEN);
     let chunk_count = chunks.len();
-    for chunk in chunks {
+    for (i, chunk) in chunks.iter().enumerate() {
         let mut body = serde_json::json!({
             "chat_id": chat_id,
             "text": chunk,
         });
-        if let Some(pm) = parse_mode {
-            body["parse_mode"] = serde_json::json!(pm);
-        }
-        let resp = http
-            .post(format!("{}/sendMessage", base_url))
-            .json(&body)
-            .send()
-            .await?;
-        if resp.status().is_success() {
-            continue;
+        // Buttons go on the last chunk so they sit under the whole reply.
+        if let Some(markup) = reply_markup.filter(|_| i + 1 == chunk_count) {
+            body["reply_markup"] = markup.clone();
         }
-        // Fallback without parse_mode
-        let resp2 = http
-            .post(format!("{}/sendMessage", base_url))
-            .json(&serde_json::json!({
-                "chat_id": chat_id,
-                "text": chunk,
-            }))
-            .send()
-            .await?;
-        if !resp2.status().is_success() {
-            let status = resp2.status();
-            let err = resp2.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Telegram sendMessage failed: {} {}",
-                status,
-                err
-            ));
+        if let Err(e) = send_chunk(http, &base_url, body, parse_mode).await {
+            return Err(crate::services::outbox::partial_failure(e, &chunks, i));
         }
     }
     Ok(chunk_count)
 }
 
+/// One `sendMessage` call, retried without `parse_mode` on a 400.
+async fn send_chunk(
+    http: &reqwest::Client,
+    base_url: &str,
+    mut body: Value,
+    parse_mode: Option<&str>,
+) -> anyhow::Result<()> {
+    let plain = body.clone();
+    if let Some(pm) = parse_mode {
+        body["parse_mode"] = serde_json::json!(pm);
+    }
+    let resp = http
+        .post(format!("{}/sendMessage", base_url))
+        .json(&body)
+        .send()
+        .await?;
+    if resp.status().is_success() {
+        return Ok(());
+    }
+    // Only a 400 can be a formatting problem; anything else (429, 5xx,
+    // 403) fails the same way without parse_mode.
+    if resp.status() != reqwest::StatusCode::BAD_REQUEST || parse_mode.is_none() {
+        let status = resp.status();
+        let err = resp.text().await.unwrap_or_default();
+        return Err(api_error("sendMessage", status, &err));
+    }
+    // Fallback without parse_mode
+    let resp2 = http
+        .post(format!("{}/sendMessage", base_url))
+        .json(&plain)
+        .send()
+        .await?;
+    if !resp2.status().is_success() {
+        let status = resp2.status();
+        let err = resp2.text().await.unwrap_or_default();
+        return Err(api_error("sendMessage", status, &err));
+    }
+    Ok(())
+}
+
+/// Failed Bot API call → [`DeliveryError`], carrying `parameters.retry_after`
+/// from 429 responses so the outbox waits as long as Telegram asks.
+fn api_error(method: &str, status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+    let retry_after = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v["parameters"]["retry_after"].as_u64())
+        .map(std::time::Duration::from_secs);
+    DeliveryError::new(
+        Some(status.as_u16()),
+        retry_after,
+        format!("Telegram {} failed: {} {}", method, status, body),
+    )
+    .into()
+}
+
+/// Inline-keyboard `callback_data` limit, in bytes.
+const CALLBACK_DATA_MAX_LEN: usize = 64;
+
+/// Media downloaded into `attachments` up to this size; larger files keep
+/// only their `file_id`. (The Bot API itself serves files up to 20 MB.)
+const MEDIA_INLINE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Build `reply_markup` from the `buttons` builtin parameter: rows of
+/// buttons, each either a string (label = callback data) or an object with
+/// `text` plus `data` (callback) or `url` (link).
+///
+/// `[["Yes", "No"], [{"text": "Docs", "url": "https://..."}]]`
+pub(crate) fn inline_keyboard(buttons: &Value) -> anyhow::Result<Value> {
+    let rows = buttons
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("buttons must be a list of rows"))?;
+    let mut keyboard = Vec::with_capacity(rows.len());
+    for row in rows {
+        // A flat list of buttons is one row.
+        let cells = match row.as_array() {
+            Some(cells) => cells.clone(),
+            None => vec![row.clone()],
+        };
+        let mut out_row = Vec::with_capacity(cells.len());
+        for cell in cells {
+            let button = match cell {
+                Value::String(label) => json!({ "text": label, "callback_data": label }),
+                Value::Object(ref obj) => {
+                    let text = obj
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| anyhow::anyhow!("button without `text`: {}", cell))?;
+                    if let Some(url) = obj.get("url").and_then(|v| v.as_str()) {
+                        json!({ "text": text, "url": url })
+                    } else {
+                        let data = obj.get("data").and_then(|v| v.as_str()).unwrap_or(text);
+                        json!({ "text": text, "callback_data": data })
+                    }
+                }
+                other => return Err(anyhow::anyhow!("invalid button: {}", other)),
+            };
+            if let Some(data) = button["callback_data"].as_str() {
+                if data.len() > CALLBACK_DATA_MAX_LEN {
+                    return Err(anyhow::anyhow!(
+                        "button data {:?} exceeds Telegram's {}-byte callback_data limit",
+                        data,
+                        CALLBACK_DATA_MAX_LEN
+                    ));
+                }
+            }
+            out_row.push(button);
+        }
+        keyboard.push(Value::Array(out_row));
+    }
+    Ok(json!({ "inline_keyboard": keyboard }))
+}
+
+/// Approve / deny buttons for a tool-approval card.
+fn approval_keyboard(allow_action: &str, deny_action: &str) -> Value {
+    json!({ "inline_keyboard": [[
+        { "text": "✅ Approve", "callback_data": allow_action },
+        { "text": "❌ Deny", "callback_data": deny_action },
+    ]] })
+}
+
+/// Map an `Update` to a PlatformMessage.
+///
+/// - `message` with text, a caption or media → `event_type = "message"`.
+///   Media is listed in `event_data.attachments` (content is filled in later
+///   by [`prepare_inbound`]).
+/// - `callback_query` (inline button press) → `event_type = "card_action"`,
+///   `event_data.action` = the button's callback data.
+///
+/// Anything else yields None.
+pub(crate) fn parse_update(update: &Value) -> Option<PlatformMessage> {
+    if let Some(cq) = update.get("callback_query") {
+        let chat_id = cq.pointer("/message/chat/id")?.as_i64()?;
+        let action = cq["data"].as_str().unwrap_or("").to_string();
+        return Some(PlatformMessage {
+            event_type: "card_action".into(),
+            event_data: json!({
+                "action": action,
+                "callback_query_id": cq["id"],
+                "message_id": cq.pointer("/message/message_id"),
+                "message_text": cq.pointer("/message/text"),
+            }),
+            platform_user_id: cq["from"]["id"].as_i64().unwrap_or(0).to_string(),
+            platform_chat_id: chat_id.to_string(),
+            text: String::new(),
+            username: cq["from"]["username"].as_str().map(String::from),
+            platform: "telegram".into(),
+        });
+    }
+
+    let msg = update.get("message")?;
+    let text = msg["text"]
+        .as_str()
+        .or_else(|| msg["caption"].as_str())
+        .unwrap_or("")
+        .to_string();
+    let mut attachments = Vec::new();
+    for kind in [
+        "photo",
+        "document",
+        "voice",
+        "audio",
+        "video",
+        "video_note",
+        "animation",
+    ] {
+        let media = match msg.get(kind) {
+            // Photos come as several sizes; the last is the largest.
+            Some(Value::Array(sizes)) => match sizes.last() {
+                Some(m) => m,
+                None => continue,
+            },
+            Some(m) => m,
+            None => continue,
+        };
+        let file_id = match media["file_id"].as_str() {
+            Some(id) => id,
+            None => continue,
+        };
+        let content_type = media["mime_type"].as_str().unwrap_or(match kind {
+            "photo" => "image/jpeg",
+            "voice" => "audio/ogg",
+            "video" | "video_note" | "animation" => "video/mp4",
+            _ => "application/octet-stream",
+        });
+        let filename = media["file_name"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| {
+                format!(
+                    "{}_{}",
+                    kind,
+                    media["file_unique_id"].as_str().unwrap_or(file_id)
+                )
+            });
+        attachments.push(json!({
+            "type": kind,
+            "file_id": file_id,
+            "filename": filename,
+            "content_type": content_type,
+            "size": media["file_size"],
+        }));
+    }
+    if text.is_empty() && attachments.is_empty() {
+        return None;
+    }
+
+    let chat_id = msg["chat"]["id"].as_i64().unwrap_or(0);
+    Some(PlatformMessage {
+        event_type: "message".into(),
+        event_data: json!({
+            "text": &text,
+            "message_id": msg["message_id"],
+            "chat_type": msg["chat"]["type"],
+            "attachments": attachments,
+        }),
+        platform_user_id: msg["from"]["id"].as_i64().unwrap_or(0).to_string(),
+        platform_chat_id: chat_id.to_string(),
+        text,
+        username: msg["from"]["username"].as_str().map(String::from),
+        platform: "telegram".into(),
+    })
+}
+
+/// First 50 chars of the text (or the callback action) for logs.
+fn preview(msg: &PlatformMessage) -> String {
+    let s = if msg.text.is_empty() {
+        msg.event_data["action"].as_str().unwrap_or("")
+    } else {
+        &msg.text
+    };
+    s.chars().take(50).collect()
+}
+
+/// Work that needs the network before dispatch: stop the button spinner for
+/// callback queries, and download attached media.
+async fn prepare_inbound(http: &reqwest::Client, token: &str, msg: &mut PlatformMessage) {
+    if msg.event_type == "card_action" {
+        if let Some(id) = msg.event_data["callback_query_id"].as_str() {
+            answer_callback_query(http, token, id).await;
+        }
+        return;
+    }
+    if let Some(attachments) = msg
+        .event_data
+        .get_mut("attachments")
+        .and_then(|a| a.as_array_mut())
+    {
+        for att in attachments.iter_mut() {
+            if let Err(e) = download_attachment(http, token, att).await {
+                tracing::warn!(
+                    "[telegram] media download failed ({}): {}",
+                    att["filename"],
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Acknowledge a button press (best-effort).
+pub(crate) async fn answer_callback_query(http: &reqwest::Client, token: &str, id: &str) {
+    let _ = http
+        .post(format!("{}/bot{}/answerCallbackQuery", TELEGRAM_API, token))
+        .json(&json!({ "callback_query_id": id }))
+        .send()
+        .await;
+}
+
+/// Fill `content_base64` (and `size`) of one attachment via `getFile`.
+async fn download_attachment(
+    http: &reqwest::Client,
+    token: &str,
+    att: &mut Value,
+) -> anyhow::Result<()> {
+    if att["size"].as_u64().unwrap_or(0) > MEDIA_INLINE_MAX_BYTES {
+        return Ok(());
+    }
+    let file_id = att["file_id"].as_str().unwrap_or_default().to_string();
+    let file: Value = http
+        .get(format!("{}/bot{}/getFile", TELEGRAM_API, token))
+        .query(&[("file_id", file_id.as_str())])
+        .send()
+        .await?
+        .json()
+        .await?;
+    let path = file
+        .pointer("/result/file_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("getFile: {}", file))?;
+    let resp = http
+        .get(format!("{}/file/bot{}/{}", TELEGRAM_API, token, path))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(anyhow::anyhow!("download failed: {}", resp.status()));
+    }
+    let bytes = resp.bytes().await?;
+    use base64::Engine;
+    att["size"] = json!(bytes.len());
+    att["content_base64"] = json!(base64::engine::general_purpose::STANDARD.encode(&bytes));
+    Ok(())
+}
+
+/// `sendPhoto` / `sendDocument`. `source` is a local path (uploaded as
+/// multipart), an http(s) URL, or a Telegram `file_id` (both passed through).
+/// Returns the sent message's id.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_media_api(
+    http: &reqwest::Client,
+    token: &str,
+    chat_id: &str,
+    method: &str,
+    field: &str,
+    source: &str,
+    caption: Option<&str>,
+    filename: Option<&str>,
+    reply_markup: Option<&Value>,
+) -> anyhow::Result<i64> {
+    let url = format!("{}/bot{}/{}", TELEGRAM_API, token, method);
+    let local = Path::new(source);
+    let resp = if local.is_file() {
+        let bytes = tokio::fs::read(local)
+            .await
+            .map_err(|e| anyhow::anyhow!("Cannot read file '{}': {}", source, e))?;
+        let name = filename
+            .map(String::from)
+            .or_else(|| local.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| field.to_string());
+        let mut form = reqwest::multipart::Form::new()
+            .text("chat_id", chat_id.to_string())
+            .part(
+                field.to_string(),
+                reqwest::multipart::Part::bytes(bytes).file_name(name),
+            );
+        if let Some(c) = caption {
+            form = form.text("caption", c.to_string());
+        }
+        if let Some(markup) = reply_markup {
+            form = form.text("reply_markup", markup.to_string());
+        }
+        http.post(&url).multipart(form).send().await?
+    } else {
+        let mut body = json!({ "chat_id": chat_id, field: source });
+        if let Some(c) = caption {
+            body["caption"] = json!(c);
+        }
+        if let Some(markup) = reply_markup {
+            body["reply_markup"] = markup.clone();
+        }
+        http.post(&url).json(&body).send().await?
+    };
+    let body: Value = resp.json().await.unwrap_or(json!({}));
+    if body["ok"].as_bool() != Some(true) {
+        return Err(anyhow::anyhow!("Telegram {} failed: {}", method, body));
+    }
+    Ok(body
+        .pointer("/result/message_id")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0))
+}
+
 /// Send typing action (auto-expires after a few seconds, best-effort).
 pub(crate) async fn send_typing(http: &reqwest::Client, token: &str, chat_id: &str) {
     let base_url = format!("{}/bot{}", TELEGRAM_API, token);
@@ -625,6 +1067,7 @@ impl crate::core::context::ChannelEgress for TelegramWebhookChannel {
             conversation,
             text,
             Some("Markdown"),
+            None,
         )
         .await
         .map(|_| ())
@@ -640,6 +1083,24 @@ impl crate::core::context::ChannelEgress for TelegramWebhookChannel {
             conversation.to_string(),
         )))
     }
+    async fn send_approval(
+        &self,
+        conversation: &str,
+        text: &str,
+        allow_action: &str,
+        deny_action: &str,
+    ) -> Result<()> {
+        send_message_api(
+            &reqwest::Client::new(),
+            &self.token,
+            conversation,
+            text,
+            None,
+            Some(&approval_keyboard(allow_action, deny_action)),
+        )
+        .await
+        .map(|_| ())
+    }
 }
 
 // ─── Streaming reply (Phase 4) ──────────────────────────────────────────────
@@ -763,3 +1224,134 @@ impl crate::core::context::StreamHandle for TelegramStreamHandle {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn polling_offset_survives_restart() {
+        let root = std::env::temp_dir().join(format!("juglans-tg-{}", uuid::Uuid::new_v4()));
+        let first = TelegramChannel::new("123:secret".into()).with_project_root(&root);
+        first.persist_resume_state().await.unwrap();
+        assert!(!root.exists(), "nothing to save before the first update");
+
+        first.offset.store(42, Ordering::SeqCst);
+        first.persist_resume_state().await.unwrap();
+        let second = TelegramChannel::new("123:secret".into()).with_project_root(&root);
+        second.load_offset();
+        assert_eq!(second.offset.load(Ordering::SeqCst), 42);
+
+        // Updates still being answered at shutdown are fetched again.
+        let second = Arc::new(second);
+        second.offset.store(45, Ordering::SeqCst);
+        let answered = second.hold_pending(43);
+        let _unanswered = second.hold_pending(44);
+        drop(answered);
+        second.persist_resume_state().await.unwrap();
+        let third = TelegramChannel::new("123:secret".into()).with_project_root(&root);
+        third.load_offset();
+        assert_eq!(third.offset.load(Ordering::SeqCst), 44);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn a_panicking_update_is_no_longer_pending() {
+        let channel = Arc::new(TelegramChannel::new("123:secret".into()));
+        channel.offset.store(8, Ordering::SeqCst);
+        let pending = channel.hold_pending(7);
+        assert_eq!(channel.resume_offset(), 7);
+        let task = tokio::spawn(async move {
+            let _pending = pending;
+            panic!("handler blew up");
+        });
+        assert!(task.await.unwrap_err().is_panic());
+        assert_eq!(channel.resume_offset(), 8);
+    }
+
+    #[test]
+    fn callback_query_becomes_card_action() {
+        let update = json!({
+            "update_id": 7,
+            "callback_query": {
+                "id": "cq1",
+                "from": { "id": 42, "username": "alice" },
+                "data": "approve",
+                "message": { "message_id": 9, "chat": { "id": -100 }, "text": "Deploy?" }
+            }
+        });
+        let msg = parse_update(&update).unwrap();
+        assert_eq!(msg.event_type, "card_action");
+        assert_eq!(msg.event_data["action"], "approve");
+        assert_eq!(msg.event_data["callback_query_id"], "cq1");
+        assert_eq!(msg.platform_chat_id, "-100");
+        assert_eq!(msg.platform_user_id, "42");
+        assert!(msg.text.is_empty());
+    }
+
+    #[test]
+    fn media_messages_list_attachments() {
+        let update = json!({
+            "message": {
+                "message_id": 3,
+                "chat": { "id": 5, "type": "private" },
+                "from": { "id": 42 },
+                "caption": "receipt",
+                "photo": [
+                    { "file_id": "small", "file_unique_id": "u1", "file_size": 100 },
+                    { "file_id": "large", "file_unique_id": "u2", "file_size": 900 }
+                ]
+            }
+        });
+        let msg = parse_update(&update).unwrap();
+        assert_eq!(msg.text, "receipt");
+        let atts = msg.event_data["attachments"].as_array().unwrap();
+        assert_eq!(atts.len(), 1);
+        assert_eq!(atts[0]["type"], "photo");
+        assert_eq!(atts[0]["file_id"], "large");
+        assert_eq!(atts[0]["content_type"], "image/jpeg");
+
+        let voice = json!({
+            "message": {
+                "chat": { "id": 5 }, "from": { "id": 42 },
+                "voice": { "file_id": "v", "file_unique_id": "vu", "mime_type": "audio/ogg" }
+            }
+        });
+        let msg = parse_update(&voice).unwrap();
+        assert_eq!(msg.text, "");
+        assert_eq!(msg.event_data["attachments"][0]["filename"], "voice_vu");
+
+        let sticker_only = json!({ "message": { "chat": { "id": 5 }, "sticker": {} } });
+        assert!(parse_update(&sticker_only).is_none());
+    }
+
+    #[test]
+    fn inline_keyboard_shapes() {
+        let kb = inline_keyboard(&json!([
+            ["Yes", "No"],
+            [{ "text": "Docs", "url": "https://example.com" }],
+            { "text": "Later", "data": "snooze" }
+        ]))
+        .unwrap();
+        let rows = kb["inline_keyboard"].as_array().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][1], json!({ "text": "No", "callback_data": "No" }));
+        assert_eq!(rows[1][0]["url"], "https://example.com");
+        assert_eq!(rows[2][0]["callback_data"], "snooze");
+
+        assert!(inline_keyboard(&json!("Yes")).is_err());
+        assert!(inline_keyboard(&json!([["x".repeat(65)]])).is_err());
+    }
+
+    #[test]
+    fn api_error_carries_retry_after() {
+        let body = r#"{"ok":false,"error_code":429,"description":"Too Many Requests: retry after 7","parameters":{"retry_after":7}}"#;
+        let err = api_error("sendMessage", reqwest::StatusCode::TOO_MANY_REQUESTS, body);
+        let d = err.downcast_ref::<DeliveryError>().unwrap();
+        assert_eq!(d.retry_after, Some(std::time::Duration::from_secs(7)));
+        assert!(!d.is_permanent());
+
+        let err = api_error("sendMessage", reqwest::StatusCode::FORBIDDEN, "{}");
+        assert!(err.downcast_ref::<DeliveryError>().unwrap().is_permanent());
+    }
+}
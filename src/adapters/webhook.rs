@@ -0,0 +1,788 @@
+// src/adapters/webhook.rs
+//
+// Generic webhook channel. One `[channels.webhook.<id>]` entry = one HTTP
+// endpoint (passive `install_routes()`, default path `/webhook/<id>`) for
+// arbitrary SaaS webhooks.
+//
+// Every request is verified before anything runs. Schemes:
+//   - `github`: `X-Hub-Signature-256: sha256=<hex>`, HMAC-SHA256 over the body.
+//   - `stripe`: `Stripe-Signature: t=<unix>,v1=<hex>[,v1=...]`, HMAC-SHA256
+//     over `<t>.<body>`, `t` within `tolerance_secs`.
+//   - `hmac`: HMAC-SHA256 of the body (or `<timestamp>.<body>` when
+//     `timestamp_header` is set) in `signature_header`, hex or base64, with an
+//     optional prefix such as `sha256=`.
+//   - `none`: no verification (local development only).
+//
+// The verified payload (parsed JSON, or `{"raw": "<body>"}`) becomes
+// `input.event_data`; `input.event_type` is the provider's event name
+// (`X-GitHub-Event`, Stripe's `type`, or `event_header`). Each delivery is
+// its own conversation, so deliveries never share chat history.
+//
+// Response modes:
+//   - sync (default): the workflow runs inside the request. Text it speaks
+//     via `reply()` / `chat()` is collected and returned as
+//     `{"status": "ok", "reply": "..."}`.
+//   - `async_ack = true`: answer `202 Accepted` immediately and run the
+//     workflow in the background — for providers with short delivery timeouts.
+//
+// Redeliveries of a delivery id that is running or has succeeded get
+// `{"status": "duplicate"}`. A failed run forgets the id, so the provider's
+// retry runs the workflow again. The last `MAX_SEEN` ids are remembered;
+// older ones are evicted first.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Result};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+
+use super::{Channel, MessageDispatcher, PlatformMessage};
+use crate::services::config::{ChannelLimitsConfig, JuglansConfig, WebhookChannelConfig};
+
+// ─── Signatures ─────────────────────────────────────────────────────────────
+
+fn mac(secret: &str) -> Option<Hmac<Sha256>> {
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()
+}
+
+fn within_tolerance(timestamp: &str, now: i64, tolerance_secs: u64) -> bool {
+    match timestamp.trim().parse::<i64>() {
+        Ok(ts) => (now - ts).unsigned_abs() <= tolerance_secs,
+        Err(_) => false,
+    }
+}
+
+/// GitHub: `X-Hub-Signature-256: sha256=<hex>` over the raw body.
+pub(crate) fn verify_github(secret: &str, body: &[u8], signature: &str) -> bool {
+    let expected = match signature
+        .strip_prefix("sha256=")
+        .and_then(|h| hex::decode(h).ok())
+    {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    let mut mac = match mac(secret) {
+        Some(m) => m,
+        None => return false,
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Stripe: `t=<unix>,v1=<hex>` over `<t>.<body>`. Any `v1` may match (Stripe
+/// sends several while a secret is being rolled).
+pub(crate) fn verify_stripe(
+    secret: &str,
+    body: &[u8],
+    header: &str,
+    now: i64,
+    tolerance_secs: u64,
+) -> bool {
+    let mut timestamp = None;
+    let mut candidates = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = Some(t),
+            Some(("v1", sig)) => candidates.push(sig),
+            _ => {}
+        }
+    }
+    let timestamp = match timestamp {
+        Some(t) if within_tolerance(t, now, tolerance_secs) => t,
+        _ => return false,
+    };
+    candidates.into_iter().any(|sig| {
+        let expected = match hex::decode(sig) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let mut mac = match mac(secret) {
+            Some(m) => m,
+            None => return false,
+        };
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.verify_slice(&expected).is_ok()
+    })
+}
+
+/// Generic HMAC-SHA256. `timestamp` (already range-checked by the caller)
+/// is signed as `<timestamp>.<body>` when present.
+pub(crate) fn verify_hmac(
+    secret: &str,
+    body: &[u8],
+    signature: &str,
+    timestamp: Option<&str>,
+    prefix: &str,
+    encoding: &str,
+) -> bool {
+    let encoded = match signature.trim().strip_prefix(prefix) {
+        Some(s) => s,
+        None => return false,
+    };
+    let expected = match encoding {
+        "base64" => base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok(),
+        _ => hex::decode(encoded).ok(),
+    };
+    let expected = match expected {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    let mut mac = match mac(secret) {
+        Some(m) => m,
+        None => return false,
+    };
+    if let Some(ts) = timestamp {
+        mac.update(ts.as_bytes());
+        mac.update(b".");
+    }
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+// ─── Delivery ids ───────────────────────────────────────────────────────────
+
+/// Delivery ids remembered per endpoint.
+const MAX_SEEN: usize = 10_000;
+
+/// Recently handled delivery ids, oldest evicted first.
+struct SeenDeliveries {
+    max: usize,
+    inner: Mutex<SeenInner>,
+}
+
+#[derive(Default)]
+struct SeenInner {
+    tick: u64,
+    /// id → tick it was inserted at.
+    ids: HashMap<String, u64>,
+    /// Insertion order. Entries whose tick no longer matches `ids` are stale
+    /// (the id was forgotten, possibly re-inserted) and skipped on eviction.
+    order: VecDeque<(String, u64)>,
+}
+
+impl SeenDeliveries {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            inner: Mutex::new(SeenInner::default()),
+        }
+    }
+
+    /// Remember `id`. False if it is already remembered.
+    fn insert(&self, id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.ids.contains_key(id) {
+            return false;
+        }
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.ids.insert(id.to_string(), tick);
+        inner.order.push_back((id.to_string(), tick));
+        while inner.ids.len() > self.max {
+            let Some((old, at)) = inner.order.pop_front() else {
+                break;
+            };
+            if inner.ids.get(&old) == Some(&at) {
+                inner.ids.remove(&old);
+            }
+        }
+        true
+    }
+
+    fn remove(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.ids.remove(id);
+    }
+}
+
+// ─── Channel ────────────────────────────────────────────────────────────────
+
+/// One endpoint = one [`WebhookChannel`]. `id()` is `"webhook:<instance_id>"`.
+pub struct WebhookChannel {
+    id: String,
+    instance_id: String,
+    cfg: WebhookChannelConfig,
+    /// Delivery ids already handled. Providers retry on timeouts.
+    seen: SeenDeliveries,
+}
+
+impl WebhookChannel {
+    fn new(instance_id: String, cfg: WebhookChannelConfig) -> Self {
+        Self {
+            id: format!("webhook:{}", instance_id),
+            instance_id,
+            cfg,
+            seen: SeenDeliveries::new(MAX_SEEN),
+        }
+    }
+
+    fn path(&self) -> String {
+        match self.cfg.path.as_deref() {
+            Some(p) if !p.is_empty() => format!("/{}", p.trim_start_matches('/')),
+            _ => format!("/webhook/{}", self.instance_id),
+        }
+    }
+
+    /// Check the request against the configured scheme. `now` is unix seconds.
+    fn verify(&self, headers: &HeaderMap, body: &[u8], now: i64) -> bool {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        };
+        let secret = self.cfg.secret.as_str();
+        match self.cfg.scheme.as_str() {
+            "none" => true,
+            "github" => verify_github(secret, body, header("x-hub-signature-256")),
+            "stripe" => verify_stripe(
+                secret,
+                body,
+                header("stripe-signature"),
+                now,
+                self.cfg.tolerance_secs,
+            ),
+            _ => {
+                let timestamp = match self.cfg.timestamp_header.as_deref() {
+                    Some(name) => {
+                        let ts = header(name);
+                        if !within_tolerance(ts, now, self.cfg.tolerance_secs) {
+                            return false;
+                        }
+                        Some(ts)
+                    }
+                    None => None,
+                };
+                verify_hmac(
+                    secret,
+                    body,
+                    header(&self.cfg.signature_header),
+                    timestamp,
+                    &self.cfg.signature_prefix,
+                    &self.cfg.encoding,
+                )
+            }
+        }
+    }
+
+    /// Build the PlatformMessage for a verified request.
+    fn to_message(&self, headers: &HeaderMap, body: &[u8]) -> PlatformMessage {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        };
+        let payload: Value = serde_json::from_slice(body)
+            .unwrap_or_else(|_| json!({ "raw": String::from_utf8_lossy(body) }));
+
+        let (event_type, delivery, sender) = match self.cfg.scheme.as_str() {
+            "github" => (
+                header("x-github-event"),
+                header("x-github-delivery"),
+                payload
+                    .pointer("/sender/login")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            ),
+            "stripe" => (
+                payload["type"].as_str().map(String::from),
+                payload["id"].as_str().map(String::from),
+                payload
+                    .pointer("/data/object/customer")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            ),
+            _ => (
+                self.cfg.event_header.as_deref().and_then(header),
+                self.cfg.id_header.as_deref().and_then(header),
+                None,
+            ),
+        };
+
+        PlatformMessage {
+            event_type: event_type.unwrap_or_else(|| "webhook".into()),
+            event_data: payload,
+            platform_user_id: sender.unwrap_or_else(|| self.instance_id.clone()),
+            platform_chat_id: delivery.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            text: String::new(),
+            username: None,
+            platform: "webhook".into(),
+        }
+    }
+
+    async fn handle_http(
+        self: Arc<Self>,
+        headers: HeaderMap,
+        body: axum::body::Bytes,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> Response {
+        if !self.verify(&headers, &body, chrono::Utc::now().timestamp()) {
+            warn!(
+                "[webhook:{}] rejected request with bad signature",
+                self.instance_id
+            );
+            return (StatusCode::UNAUTHORIZED, "invalid signature").into_response();
+        }
+        let msg = self.to_message(&headers, &body);
+        if !self.seen.insert(&msg.platform_chat_id) {
+            return Json(json!({ "status": "duplicate" })).into_response();
+        }
+        info!(
+            "[webhook:{}] {} delivery {}",
+            self.instance_id, msg.event_type, msg.platform_chat_id
+        );
+
+        if self.cfg.async_ack {
+            let channel = self.clone();
+            let delivery = msg.platform_chat_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = dispatcher.dispatch(&msg).await {
+                    error!("[webhook:{}] agent error: {}", channel.instance_id, e);
+                    channel.seen.remove(&msg.platform_chat_id);
+                }
+            });
+            return (
+                StatusCode::ACCEPTED,
+                Json(json!({ "status": "accepted", "delivery": delivery })),
+            )
+                .into_response();
+        }
+
+        // Sync: route the workflow's speech into this response.
+        let collector = Arc::new(ResponseCollector::default());
+        let origin = crate::core::context::ChannelOrigin {
+            channel: collector.clone(),
+            conversation: msg.platform_chat_id.clone(),
+        };
+        match dispatcher.dispatch_with_origin(&msg, Some(origin)).await {
+            Ok(reply) => {
+                let mut parts = collector.take();
+                if !reply.text.is_empty() && reply.text != "(No response)" {
+                    parts.push(reply.text);
+                }
+                Json(json!({ "status": "ok", "reply": parts.join("\n") })).into_response()
+            }
+            Err(e) => {
+                error!("[webhook:{}] agent error: {}", self.instance_id, e);
+                self.seen.remove(&msg.platform_chat_id);
+                // The sender is a third party: it learns only that the
+                // delivery failed and can be retried.
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "status": "error", "error": "delivery failed" })),
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// Egress target for one sync request: buffers whatever the workflow says.
+#[derive(Default)]
+struct ResponseCollector(Mutex<Vec<String>>);
+
+impl ResponseCollector {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for ResponseCollector {
+    async fn send(&self, _conversation: &str, text: &str) -> Result<()> {
+        self.0.lock().unwrap().push(text.to_string());
+        Ok(())
+    }
+}
+
+// Webhook deliveries have no conversation to push into later.
+#[async_trait::async_trait]
+impl crate::core::context::ChannelEgress for WebhookChannel {}
+
+#[async_trait::async_trait]
+impl Channel for WebhookChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn kind(&self) -> &str {
+        "webhook"
+    }
+
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.cfg.limits.as_ref()
+    }
+
+    fn install_routes(
+        self: Arc<Self>,
+        router: axum::Router,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> axum::Router {
+        let path = self.path();
+        info!("🪝 Webhook channel {} at POST {}", self.instance_id, path);
+        router.route(
+            &path,
+            axum::routing::post(move |headers: HeaderMap, body: axum::body::Bytes| {
+                let channel = self.clone();
+                let dispatcher = dispatcher.clone();
+                async move { channel.handle_http(headers, body, dispatcher).await }
+            }),
+        )
+    }
+}
+
+// ─── Discovery ──────────────────────────────────────────────────────────────
+
+/// Build [`WebhookChannel`] instances from `[channels.webhook.<id>]`.
+pub fn discover_channels(config: &JuglansConfig) -> Result<Vec<(Arc<dyn Channel>, String)>> {
+    let mut out: Vec<(Arc<dyn Channel>, String)> = Vec::new();
+    for (instance_id, cfg) in &config.channels.webhook {
+        match cfg.scheme.as_str() {
+            "github" | "stripe" | "hmac" => {
+                if cfg.secret.is_empty() {
+                    return Err(anyhow!(
+                        "[channels.webhook.{}] secret is empty — set it in juglans.toml (e.g. `secret = \"${{WEBHOOK_SECRET}}\"`)",
+                        instance_id
+                    ));
+                }
+            }
+            "none" => warn!(
+                "[channels.webhook.{}] scheme = \"none\": requests are NOT verified",
+                instance_id
+            ),
+            other => {
+                return Err(anyhow!(
+                    "[channels.webhook.{}] unknown scheme \"{}\" (expected \"github\", \"stripe\", \"hmac\" or \"none\")",
+                    instance_id,
+                    other
+                ))
+            }
+        }
+        if !matches!(cfg.encoding.as_str(), "hex" | "base64") {
+            return Err(anyhow!(
+                "[channels.webhook.{}] unknown encoding \"{}\" (expected \"hex\" or \"base64\")",
+                instance_id,
+                cfg.encoding
+            ));
+        }
+        out.push((
+            Arc::new(WebhookChannel::new(instance_id.clone(), cfg.clone())),
+            cfg.agent.clone(),
+        ));
+    }
+    Ok(out)
+}
+
+// ─── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::BotReply;
+
+    fn sign_hex(secret: &str, parts: &[&[u8]]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        for p in parts {
+            mac.update(p);
+        }
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn config(scheme: &str) -> WebhookChannelConfig {
+        WebhookChannelConfig {
+            path: None,
+            scheme: scheme.into(),
+            secret: "s3cret".into(),
+            signature_header: "x-signature".into(),
+            signature_prefix: String::new(),
+            encoding: "hex".into(),
+            timestamp_header: None,
+            event_header: None,
+            id_header: None,
+            tolerance_secs: 300,
+            async_ack: false,
+            agent: "default".into(),
+            limits: None,
+        }
+    }
+
+    #[test]
+    fn github_signature() {
+        let body = br#"{"action":"opened"}"#;
+        let sig = format!("sha256={}", sign_hex("s3cret", &[body]));
+        assert!(verify_github("s3cret", body, &sig));
+        assert!(!verify_github("other", body, &sig));
+        assert!(!verify_github("s3cret", b"{}", &sig));
+        assert!(!verify_github("s3cret", body, &sig.replace("sha256=", "")));
+    }
+
+    #[test]
+    fn stripe_signature_and_replay_window() {
+        let body = br#"{"id":"evt_1","type":"invoice.paid"}"#;
+        let now = 1_700_000_000;
+        let good = sign_hex("s3cret", &[b"1700000000.", body]);
+        let header = format!("t=1700000000,v1=deadbeef,v1={}", good);
+        assert!(verify_stripe("s3cret", body, &header, now, 300));
+        assert!(verify_stripe("s3cret", body, &header, now + 300, 300));
+        assert!(!verify_stripe("s3cret", body, &header, now + 301, 300));
+        assert!(!verify_stripe("other", body, &header, now, 300));
+        assert!(!verify_stripe(
+            "s3cret",
+            body,
+            &format!("v1={}", good),
+            now,
+            300
+        ));
+    }
+
+    #[test]
+    fn generic_hmac_prefix_encoding_and_timestamp() {
+        let body = b"payload";
+        let hex_sig = format!("sha256={}", sign_hex("s3cret", &[body]));
+        assert!(verify_hmac(
+            "s3cret", body, &hex_sig, None, "sha256=", "hex"
+        ));
+        assert!(!verify_hmac("s3cret", body, &hex_sig, None, "", "hex"));
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(b"42.");
+        mac.update(body);
+        let b64 = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        assert!(verify_hmac("s3cret", body, &b64, Some("42"), "", "base64"));
+        assert!(!verify_hmac("s3cret", body, &b64, Some("43"), "", "base64"));
+    }
+
+    #[test]
+    fn generic_scheme_rejects_stale_timestamps() {
+        let mut cfg = config("hmac");
+        cfg.timestamp_header = Some("x-timestamp".into());
+        let ch = WebhookChannel::new("hooks".into(), cfg);
+        let body = b"{}";
+        let mut headers = HeaderMap::new();
+        headers.insert("x-timestamp", "1000".parse().unwrap());
+        headers.insert(
+            "x-signature",
+            sign_hex("s3cret", &[b"1000.", body]).parse().unwrap(),
+        );
+        assert!(ch.verify(&headers, body, 1000));
+        assert!(!ch.verify(&headers, body, 2000));
+    }
+
+    #[test]
+    fn github_delivery_becomes_message() {
+        let ch = WebhookChannel::new("gh".into(), config("github"));
+        assert_eq!(ch.path(), "/webhook/gh");
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-event", "pull_request".parse().unwrap());
+        headers.insert("x-github-delivery", "d-1".parse().unwrap());
+        let msg = ch.to_message(
+            &headers,
+            br#"{"action":"opened","sender":{"login":"octocat"}}"#,
+        );
+        assert_eq!(msg.event_type, "pull_request");
+        assert_eq!(msg.platform_chat_id, "d-1");
+        assert_eq!(msg.platform_user_id, "octocat");
+        assert_eq!(msg.event_data["action"], "opened");
+        assert_eq!(msg.platform, "webhook");
+
+        let raw = ch.to_message(&HeaderMap::new(), b"a=1&b=2");
+        assert_eq!(raw.event_data["raw"], "a=1&b=2");
+        assert_eq!(raw.event_type, "webhook");
+    }
+
+    struct Replier;
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for Replier {
+        async fn dispatch(&self, _message: &PlatformMessage) -> Result<BotReply> {
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+
+        async fn dispatch_with_origin(
+            &self,
+            message: &PlatformMessage,
+            origin: Option<crate::core::context::ChannelOrigin>,
+        ) -> Result<BotReply> {
+            let origin = origin.expect("sync mode passes an origin");
+            origin
+                .channel
+                .send(
+                    &origin.conversation,
+                    &format!("got {}", message.event_data["action"]),
+                )
+                .await?;
+            Ok(BotReply {
+                text: String::new(),
+            })
+        }
+    }
+
+    /// Fails the first dispatch of each delivery, like a workflow hitting a
+    /// transient error.
+    #[derive(Default)]
+    struct FlakyOnce(Mutex<Vec<String>>);
+
+    impl FlakyOnce {
+        fn attempt(&self, message: &PlatformMessage) -> Result<BotReply> {
+            let mut tried = self.0.lock().unwrap();
+            let first = !tried.contains(&message.platform_chat_id);
+            tried.push(message.platform_chat_id.clone());
+            if first {
+                return Err(anyhow!("upstream timeout"));
+            }
+            Ok(BotReply {
+                text: "done".into(),
+            })
+        }
+
+        fn attempts(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MessageDispatcher for FlakyOnce {
+        async fn dispatch(&self, message: &PlatformMessage) -> Result<BotReply> {
+            self.attempt(message)
+        }
+
+        async fn dispatch_with_origin(
+            &self,
+            message: &PlatformMessage,
+            _origin: Option<crate::core::context::ChannelOrigin>,
+        ) -> Result<BotReply> {
+            self.attempt(message)
+        }
+    }
+
+    async fn serve(cfg: WebhookChannelConfig) -> String {
+        serve_with(cfg, Arc::new(Replier)).await
+    }
+
+    async fn serve_with(
+        cfg: WebhookChannelConfig,
+        dispatcher: Arc<dyn MessageDispatcher>,
+    ) -> String {
+        let ch = Arc::new(WebhookChannel::new("gh".into(), cfg));
+        let app = ch.install_routes(axum::Router::new(), dispatcher);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook/gh", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn sync_mode_returns_reply_and_drops_redeliveries() {
+        let url = serve(config("github")).await;
+        let body = r#"{"action":"opened"}"#;
+        let sig = format!("sha256={}", sign_hex("s3cret", &[body.as_bytes()]));
+        let http = reqwest::Client::new();
+        let post = |sig: String| {
+            http.post(&url)
+                .header("x-hub-signature-256", sig)
+                .header("x-github-delivery", "d-1")
+                .body(body)
+                .send()
+        };
+
+        assert_eq!(post("sha256=00".into()).await.unwrap().status(), 401);
+        let ok: Value = post(sig.clone()).await.unwrap().json().await.unwrap();
+        assert_eq!(ok["status"], "ok");
+        assert_eq!(ok["reply"], "got \"opened\"");
+        let dup: Value = post(sig).await.unwrap().json().await.unwrap();
+        assert_eq!(dup["status"], "duplicate");
+    }
+
+    #[tokio::test]
+    async fn async_mode_acks_with_202() {
+        let mut cfg = config("none");
+        cfg.async_ack = true;
+        let url = serve(cfg).await;
+        let resp = reqwest::Client::new()
+            .post(&url)
+            .body("{}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 202);
+        assert_eq!(resp.json::<Value>().await.unwrap()["status"], "accepted");
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_can_be_retried() {
+        let http = reqwest::Client::new();
+        let post = |url: String| http.post(url).header("x-delivery", "d-7").body("{}").send();
+        let mut cfg = config("none");
+        cfg.id_header = Some("x-delivery".into());
+
+        let flaky = Arc::new(FlakyOnce::default());
+        let url = serve_with(cfg.clone(), flaky.clone()).await;
+        let failed = post(url.clone()).await.unwrap();
+        assert_eq!(failed.status(), 500);
+        let body = failed.text().await.unwrap();
+        assert!(!body.contains("upstream timeout"), "{}", body);
+        let retry: Value = post(url.clone()).await.unwrap().json().await.unwrap();
+        assert_eq!(retry["reply"], "done");
+        let dup: Value = post(url).await.unwrap().json().await.unwrap();
+        assert_eq!(dup["status"], "duplicate");
+
+        cfg.async_ack = true;
+        let flaky = Arc::new(FlakyOnce::default());
+        let url = serve_with(cfg, flaky.clone()).await;
+        assert_eq!(post(url.clone()).await.unwrap().status(), 202);
+        for _ in 0..50 {
+            if flaky.attempts() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        // Give the failed run a moment to forget the delivery id.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert_eq!(post(url).await.unwrap().status(), 202);
+        for _ in 0..50 {
+            if flaky.attempts() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(flaky.attempts(), 2);
+    }
+
+    #[test]
+    fn seen_deliveries_evict_the_oldest() {
+        let seen = SeenDeliveries::new(3);
+        for id in ["a", "b", "c"] {
+            assert!(seen.insert(id));
+        }
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("d"));
+        // Only the oldest went; the recent ones are still duplicates.
+        assert!(!seen.insert("b"));
+        assert!(!seen.insert("d"));
+        assert!(seen.insert("a"));
+
+        // A forgotten id can come back without a stale entry evicting it.
+        seen.remove("c");
+        assert!(seen.insert("c"));
+        assert!(seen.insert("e"));
+        assert!(!seen.insert("c"));
+    }
+}
@@ -13,11 +13,11 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, RwLock};
 use tracing::{debug, error, info, warn};
 
 use super::{Channel, MessageDispatcher, PlatformMessage};
-use crate::services::config::JuglansConfig;
+use crate::services::config::{ChannelLimitsConfig, JuglansConfig};
 
 // ── Constants ────────────────────────────────────────────────────────────────
 
@@ -832,6 +832,7 @@ pub struct WechatChannel {
     http: reqwest::Client,
     workspace: PathBuf,
     login: tokio::sync::Mutex<Option<LoginResult>>,
+    limits: Option<ChannelLimitsConfig>,
 }
 
 impl WechatChannel {
@@ -842,8 +843,15 @@ impl WechatChannel {
             http,
             workspace,
             login: tokio::sync::Mutex::new(Some(login)),
+            limits: None,
         }
     }
+
+    /// Inbound limits (`[channels.wechat.limits]`), applied per account.
+    pub fn with_limits(mut self, limits: Option<ChannelLimitsConfig>) -> Self {
+        self.limits = limits;
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -856,6 +864,10 @@ impl Channel for WechatChannel {
         "wechat"
     }
 
+    fn limits(&self) -> Option<&ChannelLimitsConfig> {
+        self.limits.as_ref()
+    }
+
     async fn run(self: Arc<Self>, dispatcher: Arc<dyn MessageDispatcher>) -> Result<()> {
         // `LoginResult` has fields the inner loop moves out of; we hand it over
         // exactly once. A second call to `run()` would find `None` and bail —
@@ -919,6 +931,11 @@ pub async fn discover_channels(
         .as_ref()
         .map(|c| c.agent.clone())
         .unwrap_or_else(|| "default".to_string());
+    let limits = config
+        .channels
+        .wechat
+        .as_ref()
+        .and_then(|c| c.limits.clone());
 
     let mut accounts = list_accounts(workspace);
     if accounts.is_empty() {
@@ -943,11 +960,10 @@ pub async fn discover_channels(
                 user_id: data.user_id,
             };
             (
-                Arc::new(WechatChannel::new(
-                    http.clone(),
-                    workspace.to_path_buf(),
-                    login,
-                )),
+                Arc::new(
+                    WechatChannel::new(http.clone(), workspace.to_path_buf(), login)
+                        .with_limits(limits.clone()),
+                ),
                 agent.clone(),
             )
         })
@@ -972,6 +988,9 @@ pub async fn message_loop(
     } = login;
 
     let context_tokens: ContextTokenStore = Arc::new(RwLock::new(HashMap::new()));
+    // Per sender, resolves once their latest message has been answered. The
+    // next one from them waits on it, so each user gets replies in order.
+    let mut last_turn: HashMap<String, oneshot::Receiver<()>> = HashMap::new();
 
     // Load saved sync_buf
     let mut get_updates_buf = load_sync_buf(workspace, &account_id).unwrap_or_default();
@@ -1109,20 +1128,6 @@ pub async fn message_loop(
                 context_tokens.write().await.insert(key, ct.clone());
             }
 
-            // Send typing indicator
-            let context_token_for_typing = msg.context_token.as_deref();
-            let typing_ticket = get_typing_ticket(
-                http,
-                &base_url,
-                &token,
-                from_user_id,
-                context_token_for_typing,
-            )
-            .await;
-            if let Some(ref ticket) = typing_ticket {
-                send_typing(http, &base_url, &token, from_user_id, ticket, 1).await;
-            }
-
             // Build event_data with media info
             let mut event_data = json!({ "text": &extracted.text });
             if let Some(ref path) = extracted.media_path {
@@ -1146,37 +1151,71 @@ pub async fn message_loop(
                 platform: "wechat".into(),
             };
 
-            let reply = match dispatcher.dispatch(&platform_msg).await {
-                Ok(r) => r.text,
-                Err(e) => {
-                    error!("[wechat] Workflow error: {}", e);
-                    format!("Error: {}", e)
+            // Run the agent and reply in a detached task so the poll loop
+            // keeps reading (and a shutdown can drain in-flight replies).
+            last_turn
+                .retain(|_, rx| matches!(rx.try_recv(), Err(oneshot::error::TryRecvError::Empty)));
+            let (turn_done, turn_rx) = oneshot::channel::<()>();
+            let previous = last_turn.insert(from_user_id.to_string(), turn_rx);
+            let http = http.clone();
+            let base_url = base_url.clone();
+            let token = token.clone();
+            let account_id = account_id.clone();
+            let from_user_id = from_user_id.to_string();
+            let context_token_for_typing = msg.context_token.clone();
+            let context_tokens = context_tokens.clone();
+            let dispatcher = dispatcher.clone();
+            tokio::spawn(super::while_delivering(async move {
+                if let Some(previous) = previous {
+                    let _ = previous.await;
                 }
-            };
 
-            // Send reply
-            let context_token = {
-                let key = context_token_key(&account_id, from_user_id);
-                context_tokens.read().await.get(&key).cloned()
-            };
+                // Send typing indicator
+                let typing_ticket = get_typing_ticket(
+                    &http,
+                    &base_url,
+                    &token,
+                    &from_user_id,
+                    context_token_for_typing.as_deref(),
+                )
+                .await;
+                if let Some(ref ticket) = typing_ticket {
+                    send_typing(&http, &base_url, &token, &from_user_id, ticket, 1).await;
+                }
 
-            // Cancel typing before sending reply
-            if let Some(ref ticket) = typing_ticket {
-                send_typing(http, &base_url, &token, from_user_id, ticket, 2).await;
-            }
+                let reply = match dispatcher.dispatch(&platform_msg).await {
+                    Ok(r) => r.text,
+                    Err(e) => {
+                        error!("[wechat] Workflow error: {}", e);
+                        format!("Error: {}", e)
+                    }
+                };
 
-            if let Err(e) = send_text_message(
-                http,
-                &base_url,
-                &token,
-                from_user_id,
-                &reply,
-                context_token.as_deref(),
-            )
-            .await
-            {
-                error!("[wechat] sendMessage error: {}", e);
-            }
+                // Send reply
+                let context_token = {
+                    let key = context_token_key(&account_id, &from_user_id);
+                    context_tokens.read().await.get(&key).cloned()
+                };
+
+                // Cancel typing before sending reply
+                if let Some(ref ticket) = typing_ticket {
+                    send_typing(&http, &base_url, &token, &from_user_id, ticket, 2).await;
+                }
+
+                if let Err(e) = send_text_message(
+                    &http,
+                    &base_url,
+                    &token,
+                    &from_user_id,
+                    &reply,
+                    context_token.as_deref(),
+                )
+                .await
+                {
+                    error!("[wechat] sendMessage error: {}", e);
+                }
+                drop(turn_done);
+            }));
         }
     }
 }
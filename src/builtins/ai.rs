@@ -13,15 +13,12 @@ use tracing::{debug, error, info, warn};
 use crate::core::context::WorkflowContext;
 use crate::core::graph::WorkflowGraph;
 use crate::core::prompt_parser::PromptParser;
-use crate::services::local_runtime::{ChatOutput, ChatRequest, ChatToolHandler, LocalRuntime};
+use crate::services::history::estimate_tokens;
+use crate::services::local_runtime::{
+    ChatOutput, ChatRequest, ChatToolHandler, Handoff, LocalRuntime,
+};
 use crate::services::prompt_loader::PromptRegistry;
 
-/// Rough token estimate (4 chars ≈ 1 token). Good enough for history
-/// budget accounting; real tokenization is provider-specific.
-fn estimate_tokens(s: &str) -> u32 {
-    s.len().div_ceil(4) as u32
-}
-
 // ─── Native MCP support for `chat(mcp=…)` ────────────────────────────
 //
 // Minimal MCP (Model Context Protocol) client. When a chat() call
@@ -305,6 +302,12 @@ fn parse_mcp_param(raw: &str) -> Result<Vec<(String, String, Option<String>)>> {
     Ok(servers)
 }
 
+/// System prompt for `[history] compaction` summaries.
+const COMPACTION_PROMPT: &str = "Summarize the conversation transcript you are given so it can \
+replace the original messages. Keep facts, decisions, names, numbers, open questions and \
+anything the user asked to remember. Write in the conversation's language, in compact prose \
+or bullet points. Output only the summary.";
+
 lazy_static! {
     static ref TEMPLATE_VAR_RE: Regex = Regex::new(r"\{\{\s*([a-zA-Z0-9_]+)\s*\}\}").unwrap();
 }
@@ -416,6 +419,121 @@ impl Chat {
         None
     }
 
+    /// Kick off `[history] compaction` for `chat_id` in the background. The
+    /// reply is already final; a failed or skipped compaction just leaves
+    /// the thread as is until the next turn.
+    fn spawn_compaction(
+        &self,
+        store: Arc<dyn crate::services::history::ConversationStore>,
+        chat_id: &str,
+        agent_config: &Value,
+    ) {
+        let Some(cfg) = crate::services::history::global_config().compaction else {
+            return;
+        };
+        let model = cfg
+            .model
+            .clone()
+            .or_else(|| {
+                agent_config
+                    .get("model")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_default();
+        let runtime = self.runtime.clone();
+        let chat_id = chat_id.to_string();
+
+        tokio::spawn(async move {
+            let summarize = |transcript: String| async move {
+                let output = runtime
+                    .chat(ChatRequest {
+                        agent_config: json!({
+                            "slug": "history-compaction",
+                            "model": model,
+                            "system_prompt": COMPACTION_PROMPT,
+                        }),
+                        messages: vec![json!({
+                            "type": "text",
+                            "role": "user",
+                            "content": transcript,
+                        })],
+                        tools: None,
+                        token_sender: None,
+                        tool_handler: None,
+                        user_inbox: None,
+                    })
+                    .await?;
+                match output {
+                    ChatOutput::Final { text, .. } => Ok(text),
+                    _ => Err(anyhow!("unexpected non-text summary response")),
+                }
+            };
+            if let Err(e) = crate::services::history::compact_if_needed(
+                store.as_ref(),
+                &chat_id,
+                &cfg,
+                summarize,
+            )
+            .await
+            {
+                warn!("[history] compaction of {} failed: {}", chat_id, e);
+            }
+        });
+    }
+
+    /// Re-run this chat() turn with `handoff.target` as the speaking agent.
+    /// The previous speaker joins the target's roster so control can be
+    /// transferred back.
+    async fn hand_off(
+        &self,
+        params: &HashMap<String, String>,
+        context: &WorkflowContext,
+        roster: &[(String, Value)],
+        from: (&str, Value),
+        handoff: Handoff,
+        depth: usize,
+    ) -> Result<Option<Value>> {
+        if depth >= MAX_HANDOFF_DEPTH {
+            return Err(anyhow!(
+                "chat(): more than {} agent handoffs in one turn",
+                MAX_HANDOFF_DEPTH
+            ));
+        }
+        let target_spec = roster
+            .iter()
+            .find(|(n, _)| *n == handoff.target)
+            .map(|(_, s)| s.clone())
+            .ok_or_else(|| anyhow!("chat(): unknown handoff target '{}'", handoff.target))?;
+
+        let mut next_roster = serde_json::Map::new();
+        for (name, spec) in roster.iter().filter(|(n, _)| *n != handoff.target) {
+            next_roster.insert(name.clone(), spec.clone());
+        }
+        next_roster.insert(from.0.to_string(), from.1);
+
+        let mut next = params.clone();
+        // Node-level overrides were aimed at the agent that was called.
+        for key in ["tools", "system_prompt", "model", "temperature"] {
+            next.remove(key);
+        }
+        next.insert("agent".to_string(), agent_spec_param(&target_spec));
+        next.insert("agents".to_string(), Value::Object(next_roster).to_string());
+        next.insert("__agent_name".to_string(), handoff.target.clone());
+        next.insert("__handoff_depth".to_string(), (depth + 1).to_string());
+        let mut note = format!(
+            "You are the '{}' agent. The '{}' agent handed this conversation to you",
+            handoff.target, from.0
+        );
+        if !handoff.reason.is_empty() {
+            note.push_str(&format!(": {}", handoff.reason));
+        }
+        note.push('.');
+        next.insert("__handoff_note".to_string(), note);
+
+        self.execute(&next, context).await
+    }
+
     /// Try executing a tool in BuiltinRegistry, returns None if not found
     async fn _try_execute_builtin(
         &self,
@@ -948,6 +1066,248 @@ impl MapToolHandler {
     }
 }
 
+// ==================== Multi-agent (agents= / handoff=) ====================
+
+/// Maximum number of agent-to-agent transfers within one chat() call.
+const MAX_HANDOFF_DEPTH: usize = 8;
+
+/// Parse `chat(agents={"researcher": researcher, ...})` into an ordered
+/// roster of (name, spec). A spec is an inline agent map or an agent slug.
+fn parse_agents_param(raw: &str) -> Result<Vec<(String, Value)>> {
+    let parsed: Value = serde_json::from_str(raw)
+        .map_err(|e| anyhow!("chat(agents=…): expected a JSON object: {}", e))?;
+    // A node reference arrives wrapped as {"output": {...}}.
+    let obj = match parsed.as_object() {
+        Some(m) if m.len() == 1 && m.get("output").is_some_and(|o| o.is_object()) => {
+            m["output"].as_object().unwrap()
+        }
+        Some(m) => m,
+        None => return Err(anyhow!("chat(agents=…): must be a JSON object")),
+    };
+
+    let mut roster = Vec::new();
+    for (name, spec) in obj {
+        if name.is_empty()
+            || name.len() > 48
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(anyhow!(
+                "chat(agents=…): agent name '{}' must match [A-Za-z0-9_-]{{1,48}}",
+                name
+            ));
+        }
+        let spec = match spec.get("output").filter(|o| o.is_object()) {
+            Some(inner) => inner.clone(),
+            None => spec.clone(),
+        };
+        if !spec.is_object() && !spec.is_string() {
+            return Err(anyhow!(
+                "chat(agents=…): '{}' must be an inline agent map or an agent slug",
+                name
+            ));
+        }
+        roster.push((name.clone(), spec));
+    }
+    Ok(roster)
+}
+
+/// OpenAI schemas for the roster: one delegation tool per agent (named after
+/// it) and, with `handoff=true`, one `transfer_to_<name>` tool per agent.
+fn agent_tool_schemas(roster: &[(String, Value)], handoff: bool) -> Vec<Value> {
+    let mut schemas = Vec::new();
+    for (name, spec) in roster {
+        let description = spec
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| {
+                format!(
+                    "Delegate a self-contained task to the '{}' agent and get its answer back.",
+                    name
+                )
+            });
+        schemas.push(json!({
+            "type": "function",
+            "function": {
+                "name": name,
+                "description": description,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "task": {
+                            "type": "string",
+                            "description": "The task, with all context the agent needs"
+                        }
+                    },
+                    "required": ["task"]
+                }
+            }
+        }));
+        if handoff {
+            schemas.push(json!({
+                "type": "function",
+                "function": {
+                    "name": format!("transfer_to_{}", name),
+                    "description": format!(
+                        "Hand the conversation over to the '{}' agent. It will answer the user from here on.",
+                        name
+                    ),
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "reason": {
+                                "type": "string",
+                                "description": "Why the conversation is being transferred"
+                            }
+                        },
+                        "required": []
+                    }
+                }
+            }));
+        }
+    }
+    schemas
+}
+
+/// Outermost tool handler when `agents=` is set. Roster tools run the
+/// sub-agent through a nested, stateless chat(); `transfer_to_*` records a
+/// handoff that stops the current agent's tool loop. Everything else goes
+/// to the wrapped handler.
+struct AgentToolHandler {
+    builtin_registry: Option<Weak<super::BuiltinRegistry>>,
+    context: WorkflowContext,
+    roster: Vec<(String, Value)>,
+    handoff: bool,
+    pending_handoff: Mutex<Option<Handoff>>,
+    stream_tool_events: bool,
+    inner: Arc<dyn ChatToolHandler>,
+}
+
+#[async_trait]
+impl ChatToolHandler for AgentToolHandler {
+    async fn handle_tool_call(&self, tool_name: &str, arguments_json: &str) -> Result<String> {
+        let args: Value = serde_json::from_str(arguments_json).unwrap_or(json!({}));
+
+        if self.handoff {
+            if let Some(target) = tool_name.strip_prefix("transfer_to_") {
+                if self.roster.iter().any(|(n, _)| n == target) {
+                    let reason = args
+                        .get("reason")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    info!("  🔀 [Handoff] → {} ({})", target, reason);
+                    *self.pending_handoff.lock() = Some(Handoff {
+                        target: target.to_string(),
+                        reason,
+                    });
+                    return Ok(format!("Transferred to {}.", target));
+                }
+            }
+        }
+
+        let Some((name, spec)) = self.roster.iter().find(|(n, _)| n == tool_name) else {
+            return self.inner.handle_tool_call(tool_name, arguments_json).await;
+        };
+
+        if self.stream_tool_events {
+            self.context
+                .emit_tool_start("chat", tool_name, arguments_json);
+        }
+        let result = self.delegate(name, spec, &args).await;
+        if self.stream_tool_events {
+            match &result {
+                Ok(s) => self
+                    .context
+                    .emit_tool_complete("chat", tool_name, &Ok(Some(json!(s)))),
+                Err(e) => {
+                    self.context
+                        .emit_tool_complete("chat", tool_name, &Err(anyhow!("{}", e)))
+                }
+            }
+        }
+        // Sub-agent failures go back to the caller agent as text.
+        Ok(result.unwrap_or_else(|e| format!("Error: agent '{}' failed: {}", name, e)))
+    }
+
+    fn take_pending_tools(&self) -> Option<Vec<Value>> {
+        self.inner.take_pending_tools()
+    }
+
+    fn take_handoff(&self) -> Option<Handoff> {
+        self.pending_handoff.lock().take()
+    }
+}
+
+impl AgentToolHandler {
+    async fn delegate(&self, name: &str, spec: &Value, args: &Value) -> Result<String> {
+        let task = args
+            .get("task")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("missing 'task' argument"))?;
+        let chat = self
+            .builtin_registry
+            .as_ref()
+            .and_then(|w| w.upgrade())
+            .and_then(|r| r.get("chat"))
+            .ok_or_else(|| anyhow!("chat builtin not available"))?;
+
+        info!("  🤝 [Delegate] {} ← {:.80}", name, task.replace('\n', " "));
+        let mut params = HashMap::new();
+        params.insert("agent".to_string(), agent_spec_param(spec));
+        params.insert("message".to_string(), task.to_string());
+        // Stateless and unstreamed: the caller agent decides what to relay.
+        params.insert("chat_id".to_string(), String::new());
+        params.insert("state".to_string(), "silent".to_string());
+        params.insert("__agent_name".to_string(), name.to_string());
+
+        Ok(match chat.execute(&params, &self.context).await? {
+            Some(Value::String(s)) => s,
+            Some(v) => v.to_string(),
+            None => String::new(),
+        })
+    }
+}
+
+/// Carry what `speaker` said before handing off into the next turn's params
+/// (`__handoff_said`), so it is saved with the final answer.
+fn with_handoff_text(
+    params: &HashMap<String, String>,
+    speaker: &str,
+    text: &str,
+) -> HashMap<String, String> {
+    let mut said = handoff_said(params);
+    said.push((speaker.to_string(), text.to_string()));
+    let said: Vec<Value> = said
+        .into_iter()
+        .map(|(agent, text)| json!({ "agent": agent, "text": text }))
+        .collect();
+    let mut next = params.clone();
+    next.insert("__handoff_said".to_string(), Value::Array(said).to_string());
+    next
+}
+
+/// `(agent, text)` spoken earlier in this turn by agents that handed off.
+fn handoff_said(params: &HashMap<String, String>) -> Vec<(String, String)> {
+    params
+        .get("__handoff_said")
+        .and_then(|raw| serde_json::from_str::<Vec<Value>>(raw).ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| Some((v["agent"].as_str()?.into(), v["text"].as_str()?.into())))
+        .collect()
+}
+
+/// Render a roster spec back into a chat(agent=…) parameter value.
+fn agent_spec_param(spec: &Value) -> String {
+    match spec {
+        Value::String(slug) => slug.clone(),
+        other => other.to_string(),
+    }
+}
+
 // ==================== ExecuteWorkflow Tool ====================
 
 pub struct ExecuteWorkflow {
@@ -1073,6 +1433,42 @@ impl Tool for Chat {
                 }
             });
 
+        // Multi-agent: `agents={...}` exposes each roster entry as a tool and
+        // `handoff=true` lets the speaking agent transfer the conversation.
+        // `__agent_name` / `__handoff_*` are only set when chat() re-enters
+        // itself for a handoff or a delegated task.
+        let roster: Option<Vec<(String, Value)>> = params
+            .get("agents")
+            .map(|raw| parse_agents_param(raw))
+            .transpose()?;
+        let handoff_enabled = roster.is_some()
+            && params
+                .get("handoff")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false);
+        let handoff_depth: usize = params
+            .get("__handoff_depth")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let speaker: String = params
+            .get("__agent_name")
+            .cloned()
+            .or_else(|| {
+                inline_agent
+                    .as_ref()
+                    .and_then(|a| a.get("name"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| {
+                if inline_agent.is_some() {
+                    "supervisor".to_string()
+                } else {
+                    agent_param.to_string()
+                }
+            });
+        let speaker_spec = inline_agent.clone().unwrap_or_else(|| json!(agent_param));
+
         // Extract tools from inline agent config (serialized back to string for downstream parsing)
         let inline_agent_tools: Option<String> =
             inline_agent.as_ref().and_then(|a| a.get("tools")).map(|t| {
@@ -1290,7 +1686,7 @@ impl Tool for Chat {
         //   - should_persist (state allows it)
         //   - a chat_id was resolved
         //   - a global store is configured
-        let auto_loaded_history: Vec<Value> =
+        let loaded_messages: Vec<crate::services::history::ChatMessage> =
             if history_param.is_none() && should_persist && active_chat_id.is_some() {
                 if let Some(store) = crate::services::history::global_store() {
                     let cid = active_chat_id.as_deref().unwrap();
@@ -1302,15 +1698,7 @@ impl Tool for Chat {
                                 msgs.len(),
                                 cid
                             );
-                            msgs.into_iter()
-                                .map(|m| {
-                                    json!({
-                                        "type": "text",
-                                        "role": m.role,
-                                        "content": m.content,
-                                    })
-                                })
-                                .collect()
+                            msgs
                         }
                         Err(e) => {
                             warn!("│   history.load failed for {}: {}", cid, e);
@@ -1324,6 +1712,66 @@ impl Tool for Chat {
                 Vec::new()
             };
 
+        // With handoff, whoever spoke last in the stored thread keeps the
+        // floor: re-enter as that agent instead of the entry agent.
+        if let (true, 0, Some(roster)) = (handoff_enabled, handoff_depth, roster.as_ref()) {
+            let last_speaker = loaded_messages
+                .iter()
+                .rev()
+                .find(|m| m.role == "assistant")
+                .and_then(|m| m.meta.as_ref())
+                .and_then(|meta| meta.get("agent"))
+                .and_then(|v| v.as_str());
+            if let Some(last) = last_speaker {
+                if last != speaker && roster.iter().any(|(n, _)| n == last) {
+                    info!("│   handoff: resuming conversation with [{}]", last);
+                    let handoff = Handoff {
+                        target: last.to_string(),
+                        reason: "it was handed over earlier in this conversation".to_string(),
+                    };
+                    return self
+                        .hand_off(
+                            params,
+                            context,
+                            roster,
+                            (&speaker, speaker_spec),
+                            handoff,
+                            handoff_depth,
+                        )
+                        .await;
+                }
+            }
+        }
+
+        // Compaction summaries are stored as `system` messages. Several
+        // providers drop system turns from history, so they ride along in
+        // the system prompt instead.
+        let (summary_messages, loaded_messages): (Vec<_>, Vec<_>) = loaded_messages
+            .into_iter()
+            .partition(|m| m.role == "system");
+        let history_summary: Option<String> = if summary_messages.is_empty() {
+            None
+        } else {
+            Some(
+                summary_messages
+                    .into_iter()
+                    .map(|m| m.content)
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            )
+        };
+
+        let auto_loaded_history: Vec<Value> = loaded_messages
+            .into_iter()
+            .map(|m| {
+                json!({
+                    "type": "text",
+                    "role": m.role,
+                    "content": m.content,
+                })
+            })
+            .collect();
+
         // Build message buffer. Explicit `history` param wins over auto-load.
         let mut chat_messages_buffer: Vec<Value> = if let Some(history_str) = history_param {
             if let Ok(Value::Array(arr)) = serde_json::from_str::<Value>(history_str) {
@@ -1352,7 +1800,7 @@ impl Tool for Chat {
             "content": user_message_body
         }));
 
-        let final_agent_config = if let Some(ref agent_obj) = inline_agent {
+        let mut final_agent_config = if let Some(ref agent_obj) = inline_agent {
             // Inline agent map: extract config from JSON object
             let model = agent_obj
                 .get("model")
@@ -1406,6 +1854,74 @@ impl Tool for Chat {
             base_config
         };
 
+        // memory=true: recall long-term memories about the user (scoped to
+        // this agent plus shared ones) relevant to the incoming message.
+        let memory_section: Option<String> = if params
+            .get("memory")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false)
+        {
+            let user = params
+                .get("memory_user")
+                .cloned()
+                .or_else(|| crate::services::memory::default_user(context));
+            match (user, crate::services::memory::global_bank()) {
+                (Some(user), Some(bank)) => {
+                    let agent = final_agent_config.get("slug").and_then(|v| v.as_str());
+                    let limit = crate::services::memory::global_config().recall_limit;
+                    match bank
+                        .for_prompt(&user, agent, user_message_body, limit)
+                        .await
+                    {
+                        Ok(mems) if !mems.is_empty() => Some(format!(
+                            "Relevant memories about the user:\n{}",
+                            mems.iter()
+                                .map(|m| format!("- {}", m.content))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        )),
+                        Ok(_) => None,
+                        Err(e) => {
+                            warn!("│   Memory recall failed: {}", e);
+                            None
+                        }
+                    }
+                }
+                (None, _) => {
+                    debug!("│   memory=true but no user in scope; skipping recall");
+                    None
+                }
+                (_, None) => None,
+            }
+        } else {
+            None
+        };
+
+        // Extra system-prompt sections: recalled memories, the
+        // compacted-history summary, and for an agent that was handed the
+        // conversation, who handed it over and why.
+        for extra in [
+            memory_section.as_ref(),
+            history_summary.as_ref(),
+            params.get("__handoff_note"),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let Some(map) = final_agent_config.as_object_mut() {
+                let base = map
+                    .get("system_prompt")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let prompt = if base.is_empty() {
+                    extra.clone()
+                } else {
+                    format!("{}\n\n{}", base, extra)
+                };
+                map.insert("system_prompt".to_string(), json!(prompt));
+            }
+        }
+
         // on_token=[handler] — extract handler name for per-token callback
         let on_token_handler = params.get("on_token").map(|s| {
             s.trim()
@@ -1529,6 +2045,23 @@ impl Tool for Chat {
                 None
             };
 
+        if let Some(ref roster) = roster {
+            info!(
+                "│   agents: {:?}{} (speaking: {})",
+                roster.iter().map(|(n, _)| n).collect::<Vec<_>>(),
+                if handoff_enabled { " +handoff" } else { "" },
+                speaker
+            );
+            let schemas = agent_tool_schemas(roster, handoff_enabled);
+            custom_tools_json_schema = Some(match custom_tools_json_schema.take() {
+                Some(mut existing) => {
+                    existing.extend(schemas);
+                    existing
+                }
+                None => schemas,
+            });
+        }
+
         // Create tool execution callback — tool_call handled within SSE stream, no tool loop needed
         // Priority: map handler (from declarative tools) > on_tool=[node] > on_tool_call=path > default
         let base_handler: Arc<dyn ChatToolHandler> = if let Some(map) = tool_handler_map.take() {
@@ -1609,14 +2142,51 @@ impl Tool for Chat {
             None => base_handler,
         };
 
+        // Tool-call approval policy: `[tool_policy]` from juglans.toml,
+        // optionally layered with a per-call `tool_policy={...}` override.
+        let policy_cfg = match params.get("tool_policy") {
+            Some(raw) => crate::services::tool_policy::ToolPolicy::merge_override(
+                crate::services::tool_policy::global_config(),
+                raw,
+            )?,
+            None => crate::services::tool_policy::global_config(),
+        };
+        let policy = crate::services::tool_policy::ToolPolicy::new(policy_cfg);
+        let handler: Arc<dyn ChatToolHandler> = if policy.is_noop() {
+            handler
+        } else {
+            info!("│   tool_policy: enforcing approval rules");
+            Arc::new(crate::services::tool_policy::PolicyToolHandler::new(
+                policy,
+                context.clone(),
+                handler,
+            ))
+        };
+
+        // Roster tools are intercepted before policy / MCP / workflow
+        // dispatch: they are part of the orchestration, not devtools.
+        let handler: Arc<dyn ChatToolHandler> = match roster {
+            Some(ref roster) => Arc::new(AgentToolHandler {
+                builtin_registry: self.builtin_registry.clone(),
+                context: context.clone(),
+                roster: roster.clone(),
+                handoff: handoff_enabled,
+                pending_handoff: Mutex::new(None),
+                stream_tool_events,
+                inner: handler,
+            }),
+            None => handler,
+        };
+
         let api_result = self
             .runtime
             .chat(ChatRequest {
-                agent_config: final_agent_config,
+                agent_config: final_agent_config.clone(),
                 messages: chat_messages_buffer,
                 tools: custom_tools_json_schema,
                 token_sender: effective_token_sender,
                 tool_handler: Some(handler),
+                user_inbox: Some(context.user_inbox().clone()),
             })
             .await?;
 
@@ -1638,6 +2208,9 @@ impl Tool for Chat {
                     // stay on the same stored thread.
                     let exposed_chat_id = active_chat_id.clone().unwrap_or_else(|| chat_id.clone());
                     context.set("reply.chat_id".to_string(), json!(exposed_chat_id))?;
+                    if roster.is_some() {
+                        context.set("reply.agent".to_string(), json!(speaker))?;
+                    }
 
                     let current_display_buffer = context
                         .resolve_path("reply.output")
@@ -1658,15 +2231,27 @@ impl Tool for Chat {
                             use crate::services::history::ChatMessage;
                             let user_msg = ChatMessage::new("user", user_message_body.clone())
                                 .with_tokens(estimate_tokens(user_message_body));
-                            let asst_msg = ChatMessage::new("assistant", text.clone())
+                            let mut asst_msg = ChatMessage::new("assistant", text.clone())
                                 .with_tokens(estimate_tokens(&text));
+                            if roster.is_some() {
+                                asst_msg = asst_msg.with_meta(json!({ "agent": speaker }));
+                            }
                             if let Err(e) = store.append(cid, user_msg).await {
                                 warn!("│   history.append(user) failed: {}", e);
                             }
+                            for (agent, said) in handoff_said(params) {
+                                let msg = ChatMessage::new("assistant", said.clone())
+                                    .with_tokens(estimate_tokens(&said))
+                                    .with_meta(json!({ "agent": agent }));
+                                if let Err(e) = store.append(cid, msg).await {
+                                    warn!("│   history.append(assistant) failed: {}", e);
+                                }
+                            }
                             if let Err(e) = store.append(cid, asst_msg).await {
                                 warn!("│   history.append(assistant) failed: {}", e);
                             } else {
                                 debug!("│   Persisted turn to chat_id={}", cid);
+                                self.spawn_compaction(store, cid, &final_agent_config);
                             }
                         }
                     }
@@ -1716,6 +2301,41 @@ impl Tool for Chat {
 
                 Ok(Some(json!(text)))
             }
+            ChatOutput::Handoff { handoff, text } => {
+                // Text written before the transfer call is part of the reply:
+                // shown in reply.output now, saved to history with the
+                // target agent's answer.
+                let with_text;
+                let params = if text.is_empty() {
+                    params
+                } else {
+                    debug!("│   [{}] before handoff: {:.200}", speaker, text);
+                    if should_persist {
+                        let shown = context
+                            .resolve_path("reply.output")
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.as_str().map(|s| s.to_string()))
+                            .unwrap_or_default();
+                        context.set(
+                            "reply.output".to_string(),
+                            json!(format!("{}{}", shown, text)),
+                        )?;
+                    }
+                    with_text = with_handoff_text(params, &speaker, &text);
+                    &with_text
+                };
+                let roster = roster.unwrap_or_default();
+                self.hand_off(
+                    params,
+                    context,
+                    &roster,
+                    (&speaker, speaker_spec),
+                    handoff,
+                    handoff_depth,
+                )
+                .await
+            }
             ChatOutput::ToolCalls { .. } => {
                 // Should not reach this branch when tool_handler is provided
                 Err(anyhow!(
@@ -1909,4 +2529,57 @@ mod tests {
     fn extract_returns_none_for_empty_string() {
         assert!(Chat::extract_last_json_block("").is_none());
     }
+
+    #[test]
+    fn agents_param_accepts_inline_maps_and_slugs() {
+        let raw = r#"{"researcher": {"output": {"model": "gpt-4o"}}, "coder": "coder-agent"}"#;
+        let roster = parse_agents_param(raw).unwrap();
+        assert_eq!(roster.len(), 2);
+        let researcher = roster.iter().find(|(n, _)| n == "researcher").unwrap();
+        assert_eq!(researcher.1["model"], json!("gpt-4o"));
+        let coder = roster.iter().find(|(n, _)| n == "coder").unwrap();
+        assert_eq!(agent_spec_param(&coder.1), "coder-agent");
+    }
+
+    #[test]
+    fn agents_param_rejects_bad_names_and_specs() {
+        assert!(parse_agents_param(r#"{"has space": "x"}"#).is_err());
+        assert!(parse_agents_param(r#"{"a": 42}"#).is_err());
+        assert!(parse_agents_param("[]").is_err());
+    }
+
+    #[test]
+    fn text_before_a_handoff_is_carried_to_the_next_turn() {
+        let params = HashMap::from([("message".to_string(), "refund please".to_string())]);
+        assert!(handoff_said(&params).is_empty());
+        let first = with_handoff_text(&params, "triage", "Let me get billing.");
+        let second = with_handoff_text(&first, "billing", "One moment.");
+        assert_eq!(
+            handoff_said(&second),
+            vec![
+                ("triage".to_string(), "Let me get billing.".to_string()),
+                ("billing".to_string(), "One moment.".to_string()),
+            ]
+        );
+        assert_eq!(second["message"], "refund please");
+    }
+
+    #[test]
+    fn agent_tools_include_transfers_only_with_handoff() {
+        let roster = vec![("researcher".to_string(), json!({"model": "gpt-4o"}))];
+        let names = |schemas: Vec<Value>| -> Vec<String> {
+            schemas
+                .iter()
+                .map(|s| s["function"]["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(agent_tool_schemas(&roster, false)),
+            vec!["researcher"]
+        );
+        assert_eq!(
+            names(agent_tool_schemas(&roster, true)),
+            vec!["researcher", "transfer_to_researcher"]
+        );
+    }
 }
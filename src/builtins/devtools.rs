@@ -6,6 +6,7 @@
 
 use super::Tool;
 use crate::core::context::WorkflowContext;
+use crate::services::sandbox;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -72,7 +73,8 @@ impl Tool for ReadFile {
             .and_then(|v| v.parse().ok())
             .unwrap_or(2000);
 
-        let content = tokio::fs::read_to_string(path)
+        let real_path = sandboxed_path("read_file", path)?;
+        let content = tokio::fs::read_to_string(&real_path)
             .await
             .with_context(|| format!("Failed to read file: {}", path))?;
 
@@ -163,7 +165,7 @@ impl Tool for WriteFile {
             .ok_or_else(|| anyhow!("write_file() requires 'content' parameter"))?;
 
         // Create parent directories
-        let file_path = std::path::Path::new(path);
+        let file_path = sandboxed_path("write_file", path)?;
         if let Some(parent) = file_path.parent() {
             if !parent.exists() {
                 tokio::fs::create_dir_all(parent)
@@ -172,7 +174,7 @@ impl Tool for WriteFile {
             }
         }
 
-        tokio::fs::write(path, content)
+        tokio::fs::write(&file_path, content)
             .await
             .with_context(|| format!("Failed to write file: {}", path))?;
 
@@ -255,7 +257,8 @@ impl Tool for EditFile {
             .unwrap_or(false);
 
         // Read file
-        let content = tokio::fs::read_to_string(path)
+        let real_path = sandboxed_path("edit_file", path)?;
+        let content = tokio::fs::read_to_string(&real_path)
             .await
             .with_context(|| format!("Failed to read file: {}", path))?;
 
@@ -283,7 +286,7 @@ impl Tool for EditFile {
             content.replacen(old_string, new_string, 1)
         };
 
-        tokio::fs::write(path, &new_content)
+        tokio::fs::write(&real_path, &new_content)
             .await
             .with_context(|| format!("Failed to write file: {}", path))?;
 
@@ -342,7 +345,12 @@ impl Tool for GlobSearch {
             .get("pattern")
             .ok_or_else(|| anyhow!("glob() requires 'pattern' parameter"))?;
 
-        let base_path = params.get("path").map(|s| s.as_str()).unwrap_or(".");
+        let sandbox = sandbox::current("glob")?;
+        let base_path = match (&sandbox, params.get("path")) {
+            (Some(sb), Some(p)) => sb.resolve("glob", p)?.display().to_string(),
+            (Some(sb), None) => sb.root().display().to_string(),
+            (None, p) => p.map(|s| s.as_str()).unwrap_or(".").to_string(),
+        };
 
         let full_pattern = if pattern.starts_with('/') {
             pattern.to_string()
@@ -357,6 +365,11 @@ impl Tool for GlobSearch {
         {
             match entry {
                 Ok(path) => {
+                    // Absolute patterns, `..` and symlinks can all reach
+                    // outside the sandbox; drop anything that does.
+                    if sandbox.as_ref().is_some_and(|sb| !sb.contains(&path)) {
+                        continue;
+                    }
                     matches.push(path.display().to_string());
                 }
                 Err(e) => {
@@ -432,7 +445,13 @@ impl Tool for GrepSearch {
             .get("pattern")
             .ok_or_else(|| anyhow!("grep() requires 'pattern' parameter"))?;
 
-        let search_path = params.get("path").map(|s| s.as_str()).unwrap_or(".");
+        let sandbox = sandbox::current("grep")?;
+        let search_path = match (&sandbox, params.get("path")) {
+            (Some(sb), Some(p)) => sb.resolve("grep", p)?.display().to_string(),
+            (Some(sb), None) => sb.root().display().to_string(),
+            (None, p) => p.map(|s| s.as_str()).unwrap_or(".").to_string(),
+        };
+        let search_path = search_path.as_str();
         let include = params.get("include").map(|s| s.as_str());
         let context_lines: usize = params
             .get("context_lines")
@@ -447,7 +466,10 @@ impl Tool for GrepSearch {
             .with_context(|| format!("Invalid regex pattern: {}", pattern_str))?;
 
         // Collect files to search
-        let files = collect_files(search_path, include)?;
+        let mut files = collect_files(search_path, include)?;
+        if let Some(sb) = &sandbox {
+            files.retain(|f| sb.contains(std::path::Path::new(f)));
+        }
 
         let mut results: Vec<Value> = Vec::new();
         let mut total_matches = 0;
@@ -502,6 +524,14 @@ impl Tool for GrepSearch {
     }
 }
 
+/// Resolve a file tool's path through the active sandbox, if any.
+fn sandboxed_path(tool: &str, path: &str) -> Result<std::path::PathBuf> {
+    match sandbox::current(tool)? {
+        Some(sb) => sb.resolve(tool, path),
+        None => Ok(std::path::PathBuf::from(path)),
+    }
+}
+
 /// Collect file list under the specified directory
 fn collect_files(path: &str, include: Option<&str>) -> Result<Vec<String>> {
     let p = std::path::Path::new(path);
@@ -596,14 +626,16 @@ impl Tool for Bash {
 
         let timeout_duration = tokio::time::Duration::from_millis(timeout_ms);
 
-        let output_result = tokio::time::timeout(
-            timeout_duration,
-            tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(cmd)
-                .output(),
-        )
-        .await;
+        let mut command = match sandbox::current("bash")? {
+            Some(sb) => sb.command(cmd),
+            None => {
+                let mut c = tokio::process::Command::new("sh");
+                c.arg("-c").arg(cmd);
+                c
+            }
+        };
+
+        let output_result = tokio::time::timeout(timeout_duration, command.output()).await;
 
         match output_result {
             Ok(Ok(output)) => {
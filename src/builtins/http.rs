@@ -1,9 +1,10 @@
 // src/builtins/http.rs
 //
-// HTTP backend builtins: serve() and response()
+// HTTP backend builtins: serve(), response() and render()
 //
 // serve()    — Starts inline HTTP server (CLI mode) or pass-through (request mode)
 // response() — Controls HTTP response status/body/headers
+// render()   — Renders a .jgx template into an HTML response
 
 use super::Tool;
 use crate::core::context::WorkflowContext;
@@ -47,8 +48,16 @@ impl Serve {
 pub struct InlineRoute {
     // fields are pub for web_server access
     pub method: String,
+    /// May contain typed segments: `/users/{id:int}`.
     pub path: String,
     pub handler: String,
+    /// Struct the JSON body is validated against (`body=CreateUser`).
+    pub body: Option<String>,
+    /// Struct the query string is validated against (`query=Paging`).
+    pub query: Option<String>,
+    /// `@auth(roles=[...])`: roles allowed to call the route (empty = any
+    /// authenticated caller). `None` when the handler isn't decorated.
+    pub auth: Option<Vec<String>>,
 }
 
 /// Scan workflow for route declarations: function annotations (new) + _deco_N nodes (legacy).
@@ -61,10 +70,21 @@ pub fn extract_routes_from_graph(workflow: &crate::core::graph::WorkflowGraph) -
             let method = route_val["method"].as_str().unwrap_or("GET").to_uppercase();
             let path = route_val["path"].as_str().unwrap_or("/").to_string();
             if !path.is_empty() {
+                let class = |key: &str| route_val[key].as_str().map(str::to_string);
                 routes.push(InlineRoute {
                     method,
                     path,
                     handler: fn_name.clone(),
+                    body: class("body"),
+                    query: class("query"),
+                    auth: fn_def.annotations.get("auth").map(|a| {
+                        a["roles"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|r| r.as_str().map(str::to_string))
+                            .collect()
+                    }),
                 });
             }
         }
@@ -119,6 +139,13 @@ impl Tool for Serve {
             })));
         }
 
+        // Channel runs (e.g. a channel targeting a [server.mounts] entry) have
+        // no request to serve: pass through to the nodes after serve().
+        if let Some(Value::String(platform)) = context.resolve_path("input.platform").ok().flatten()
+        {
+            return Ok(Some(json!({ "channel": platform })));
+        }
+
         // Server mode: start inline HTTP server
         let registry = self
             .builtin_registry
@@ -156,8 +183,19 @@ impl Tool for Serve {
             })
             .unwrap_or(8080);
 
+        // [server.auth], if configured
+        let auth = context
+            .resolve_path("config.server.auth")
+            .ok()
+            .flatten()
+            .filter(|v| !v.is_null())
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| anyhow!("serve(): invalid [server.auth]: {}", e))?;
+
         // Start server (blocks indefinitely)
-        crate::services::web_server::start_inline_server(routes, workflow, executor, port).await?;
+        crate::services::web_server::start_inline_server(routes, workflow, executor, port, auth)
+            .await?;
 
         Ok(None) // never reached
     }
@@ -212,3 +250,82 @@ impl Tool for HttpResponse {
         Ok(body.or(Some(json!({"status": status}))))
     }
 }
+
+/// render(template="pages/home.jgx", title="Home", items=$items, status=200)
+///
+/// Render a `.jgx` template as an HTML page and make it the HTTP response:
+/// sets $response.status / $response.body / $response.headers with
+/// `content-type: text/html; charset=utf-8` (extra `headers={...}` are merged
+/// in). The template path is relative to the project root, like
+/// `p(file=...)`; the frontmatter is optional. Every other parameter is a
+/// template variable, alongside `input` (the request). Interpolations are
+/// HTML-escaped; `{{ html | safe }}` opts out.
+pub struct Render;
+
+#[async_trait]
+impl Tool for Render {
+    fn name(&self) -> &str {
+        "render"
+    }
+
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        context: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let template = params
+            .get("template")
+            .map(|t| t.trim_matches('"'))
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| anyhow!("render: missing 'template'"))?;
+        let source = std::fs::read_to_string(template)
+            .map_err(|e| anyhow!("render: failed to read template '{}': {}", template, e))?;
+        // Pages rarely need prompt metadata; supply an empty frontmatter.
+        let source = if source.trim_start().starts_with("---") {
+            source
+        } else {
+            format!("---\nslug: \"page\"\n---\n{}", source)
+        };
+        let parsed = crate::core::prompt_parser::PromptParser::parse(&source)
+            .map_err(|e| anyhow!("render: {}: {}", template, e))?;
+
+        let mut vars = serde_json::Map::new();
+        for (k, v) in params {
+            if !matches!(k.as_str(), "template" | "status" | "headers") {
+                let val = serde_json::from_str(v).unwrap_or_else(|_| Value::String(v.clone()));
+                vars.insert(k.clone(), val);
+            }
+        }
+        if !vars.contains_key("input") {
+            if let Ok(Some(input)) = context.resolve_path("input") {
+                vars.insert("input".to_string(), input);
+            }
+        }
+        let html = crate::core::renderer::JwlRenderer::html()
+            .render(&parsed.ast, &Value::Object(vars))
+            .map_err(|e| anyhow!("render: {}: {}", template, e))?;
+
+        let status: u16 = params
+            .get("status")
+            .and_then(|s| s.trim_matches('"').parse().ok())
+            .unwrap_or(200);
+        let mut headers = json!({ "content-type": "text/html; charset=utf-8" });
+        if let Some(Value::Object(extra)) = params
+            .get("headers")
+            .and_then(|h| serde_json::from_str::<Value>(h).ok())
+        {
+            for (k, v) in extra {
+                // A caller-supplied content type replaces the default.
+                if k.eq_ignore_ascii_case("content-type") {
+                    headers.as_object_mut().unwrap().remove("content-type");
+                }
+                headers[k] = v;
+            }
+        }
+
+        context.set("response.status".to_string(), json!(status))?;
+        context.set("response.body".to_string(), json!(html))?;
+        context.set("response.headers".to_string(), headers)?;
+        Ok(Some(json!(html)))
+    }
+}
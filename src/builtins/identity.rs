@@ -0,0 +1,192 @@
+// src/builtins/identity.rs
+//
+// DSL-callable cross-channel identity (services/identity.rs).
+//
+// Tools act on the sender of the current run (`input.platform` +
+// `input.platform_user_id`). When identity is disabled, every tool fails
+// with a clear error except identity.whoami, which returns null.
+//
+// Exposed names:
+//   identity.whoami()                 → { id, profile, accounts } | null
+//   identity.link_code()              → { code, expires_in }
+//   identity.link(code)               → { id, profile, accounts, merged_from }
+//   identity.unlink(platform?, platform_user_id?) → { ok, user_id }
+//   identity.set_profile(profile)     → the updated profile (null values remove)
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::identity::{global_config, global_store, IdentityStore};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn store(name: &str) -> Result<Arc<IdentityStore>> {
+    global_store().ok_or_else(|| {
+        anyhow!(
+            "{}: identity is disabled — set [identity] enabled = true in juglans.toml",
+            name
+        )
+    })
+}
+
+fn input_str(ctx: &WorkflowContext, path: &str) -> Option<String> {
+    ctx.resolve_path(path).ok().flatten().and_then(|v| match v {
+        Value::String(s) if !s.is_empty() => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// `(platform, platform_user_id)` of the current run's sender.
+fn sender(name: &str, ctx: &WorkflowContext) -> Result<(String, String)> {
+    let platform = input_str(ctx, "input.platform");
+    let user = input_str(ctx, "input.platform_user_id").or_else(|| input_str(ctx, "input.user_id"));
+    match (platform, user) {
+        (Some(p), Some(u)) => Ok((p, u)),
+        _ => Err(anyhow!(
+            "{}: no sender in scope — run from a channel workflow",
+            name
+        )),
+    }
+}
+
+fn param<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    params
+        .get(key)
+        .map(|s| s.trim_matches('"').trim())
+        .filter(|s| !s.is_empty())
+}
+
+/// Resolve the sender to a user id, creating it on first contact.
+fn current_user(name: &str, store: &IdentityStore, ctx: &WorkflowContext) -> Result<String> {
+    let (platform, user) = sender(name, ctx)?;
+    let username = input_str(ctx, "input.username");
+    Ok(store.resolve(&platform, &user, username.as_deref())?.id)
+}
+
+pub struct IdentityWhoami;
+#[async_trait]
+impl Tool for IdentityWhoami {
+    fn name(&self) -> &str {
+        "identity.whoami"
+    }
+    async fn execute(
+        &self,
+        _params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let store = match global_store() {
+            Some(s) => s,
+            None => return Ok(Some(Value::Null)),
+        };
+        let id = current_user("identity.whoami", &store, ctx)?;
+        Ok(Some(
+            store.get(&id)?.map(|u| u.to_json()).unwrap_or(Value::Null),
+        ))
+    }
+}
+
+pub struct IdentityLinkCode;
+#[async_trait]
+impl Tool for IdentityLinkCode {
+    fn name(&self) -> &str {
+        "identity.link_code"
+    }
+    async fn execute(
+        &self,
+        _params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let store = store("identity.link_code")?;
+        let id = current_user("identity.link_code", &store, ctx)?;
+        let ttl = global_config().link_code_ttl_secs;
+        let code = store.create_link_code(&id, ttl)?;
+        Ok(Some(json!({ "code": code, "expires_in": ttl })))
+    }
+}
+
+pub struct IdentityLink;
+#[async_trait]
+impl Tool for IdentityLink {
+    fn name(&self) -> &str {
+        "identity.link"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let code = param(params, "code").ok_or_else(|| anyhow!("identity.link: missing `code`"))?;
+        let store = store("identity.link")?;
+        let (platform, user) = sender("identity.link", ctx)?;
+        let outcome = store.link(code, &platform, &user)?;
+        // Memories follow the person into the surviving user.
+        if let Some(ref from) = outcome.merged_from {
+            if let Some(bank) = crate::services::memory::global_bank() {
+                bank.reassign_user(from, &outcome.user.id)?;
+            }
+        }
+        ctx.set("input.user".into(), outcome.user.to_json()).ok();
+        let mut out = outcome.user.to_json();
+        out["merged_from"] = json!(outcome.merged_from);
+        Ok(Some(out))
+    }
+}
+
+pub struct IdentityUnlink;
+#[async_trait]
+impl Tool for IdentityUnlink {
+    fn name(&self) -> &str {
+        "identity.unlink"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let store = store("identity.unlink")?;
+        let (platform, user) = match (param(params, "platform"), param(params, "platform_user_id"))
+        {
+            (Some(p), Some(u)) => (p.to_string(), u.to_string()),
+            (None, None) => sender("identity.unlink", ctx)?,
+            _ => {
+                return Err(anyhow!(
+                    "identity.unlink: pass both `platform` and `platform_user_id`, or neither"
+                ))
+            }
+        };
+        let new_id = store.unlink(&platform, &user)?;
+        Ok(Some(json!({ "ok": new_id.is_some(), "user_id": new_id })))
+    }
+}
+
+pub struct IdentitySetProfile;
+#[async_trait]
+impl Tool for IdentitySetProfile {
+    fn name(&self) -> &str {
+        "identity.set_profile"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let raw = params
+            .get("profile")
+            .ok_or_else(|| anyhow!("identity.set_profile: missing `profile`"))?;
+        let patch: Value = serde_json::from_str(raw)
+            .map_err(|e| anyhow!("identity.set_profile: `profile` must be an object: {}", e))?;
+        let patch = patch
+            .as_object()
+            .ok_or_else(|| anyhow!("identity.set_profile: `profile` must be an object"))?;
+        let store = store("identity.set_profile")?;
+        let id = current_user("identity.set_profile", &store, ctx)?;
+        let profile = store.update_profile(&id, patch)?;
+        ctx.set("input.user.profile".into(), profile.clone()).ok();
+        Ok(Some(profile))
+    }
+}
@@ -0,0 +1,91 @@
+// src/builtins/jobs.rs
+//
+// DSL-callable background jobs (services/jobs.rs).
+//
+// Exposed names:
+//   enqueue(workflow, input?, delay?, priority?, unique_key?, max_attempts?)
+//     → { id, created, workflow, run_at }
+//
+// The job runs later in `juglans worker` (or a `juglans serve` with
+// `[jobs] serve_workers`), so a serve() handler can enqueue heavy work and
+// answer 202 right away.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::jobs::{self, parse_delay, NewJob};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct Enqueue;
+#[async_trait]
+impl Tool for Enqueue {
+    fn name(&self) -> &str {
+        "enqueue"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let workflow = params
+            .get("workflow")
+            .map(|w| w.trim())
+            .filter(|w| !w.is_empty())
+            .ok_or_else(|| anyhow!("enqueue: missing 'workflow'"))?;
+        let input = match params.get("input").map(|s| s.trim()) {
+            None | Some("") | Some("null") => json!({}),
+            Some(raw) => match serde_json::from_str::<Value>(raw) {
+                Ok(v @ Value::Object(_)) => v,
+                _ => return Err(anyhow!("enqueue: 'input' must be an object")),
+            },
+        };
+        let delay = match params.get("delay").filter(|d| !d.is_empty()) {
+            Some(d) => parse_delay(d).map_err(|e| anyhow!("enqueue: {}", e))?,
+            None => Duration::ZERO,
+        };
+        let priority = match params.get("priority") {
+            Some(p) => p
+                .parse::<i64>()
+                .map_err(|_| anyhow!("enqueue: 'priority' must be an integer"))?,
+            None => 0,
+        };
+        let max_attempts = match params.get("max_attempts") {
+            Some(n) => Some(
+                n.parse::<u32>()
+                    .map_err(|_| anyhow!("enqueue: 'max_attempts' must be a positive integer"))?,
+            ),
+            None => None,
+        };
+
+        let queued = jobs::enqueue(NewJob {
+            workflow: workflow.to_string(),
+            input,
+            priority,
+            delay,
+            unique_key: params
+                .get("unique_key")
+                .filter(|k| !k.is_empty() && k.as_str() != "null")
+                .cloned(),
+            max_attempts,
+            // The job runs as the caller of this run, if it has one.
+            auth: ctx
+                .resolve_path("input.auth")
+                .ok()
+                .flatten()
+                .filter(Value::is_object)
+                .unwrap_or(Value::Null),
+        })
+        .map_err(|e| anyhow!("enqueue: {:#}", e))?;
+        Ok(Some(json!({
+            "id": queued.id,
+            "created": queued.created,
+            "workflow": workflow,
+            "run_at": chrono::Utc::now().timestamp() + delay.as_secs() as i64,
+        })))
+    }
+}
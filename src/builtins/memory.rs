@@ -0,0 +1,172 @@
+// src/builtins/memory.rs
+//
+// DSL-callable long-term memory (services/memory.rs).
+//
+// Every tool takes optional `user` and `agent` scope parameters. `user`
+// defaults to the channel user of the current run (`<platform>:<user_id>`);
+// `agent` defaults to "" — shared by all agents. When memory is disabled,
+// reads return empty arrays and writes return `{ ok: false }`.
+//
+// Exposed names:
+//   memory.remember(content, key?, user?, agent?) → the stored memory
+//   memory.recall(query, limit?, user?, agent?)   → Array of memories, best first
+//   memory.forget(id | key | query | all=true, user?, agent?) → { ok, removed }
+//   memory.list(limit=20, user?, agent?)          → Array of memories, newest first
+//   memory_search(query, limit?, user?, agent?)   → alias of memory.recall
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::memory::{default_user, global_bank, global_config, Forget, MemoryRecord};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn scope_user(
+    name: &str,
+    params: &HashMap<String, String>,
+    ctx: &WorkflowContext,
+) -> Result<String> {
+    params
+        .get("user")
+        .filter(|u| !u.is_empty())
+        .cloned()
+        .or_else(|| default_user(ctx))
+        .ok_or_else(|| {
+            anyhow!(
+                "{}: no user in scope; pass user=... outside of channel runs",
+                name
+            )
+        })
+}
+
+fn records_to_json(records: &[MemoryRecord]) -> Value {
+    Value::Array(records.iter().map(MemoryRecord::to_json).collect())
+}
+
+fn parse_usize(s: Option<&String>, default: usize) -> usize {
+    s.and_then(|v| v.parse::<usize>().ok()).unwrap_or(default)
+}
+
+pub struct MemoryRemember;
+#[async_trait]
+impl Tool for MemoryRemember {
+    fn name(&self) -> &str {
+        "memory.remember"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let content = params
+            .get("content")
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| anyhow!("memory.remember: missing 'content'"))?;
+        let user = scope_user("memory.remember", params, ctx)?;
+        let agent = params.get("agent").map(|s| s.as_str()).unwrap_or("");
+        let key = params
+            .get("key")
+            .map(|s| s.as_str())
+            .filter(|k| !k.is_empty());
+
+        let bank = match global_bank() {
+            Some(b) => b,
+            None => return Ok(Some(json!({ "ok": false, "reason": "memory disabled" }))),
+        };
+        let rec = bank.remember(&user, agent, key, content).await?;
+        Ok(Some(rec.to_json()))
+    }
+}
+
+pub struct MemoryRecall;
+#[async_trait]
+impl Tool for MemoryRecall {
+    fn name(&self) -> &str {
+        "memory.recall"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let query = params
+            .get("query")
+            .ok_or_else(|| anyhow!("memory.recall: missing 'query'"))?;
+        let user = scope_user("memory.recall", params, ctx)?;
+        let limit = parse_usize(params.get("limit"), global_config().recall_limit);
+
+        let bank = match global_bank() {
+            Some(b) => b,
+            None => return Ok(Some(json!([]))),
+        };
+        let records = bank
+            .recall(&user, params.get("agent").map(|s| s.as_str()), query, limit)
+            .await?;
+        Ok(Some(records_to_json(&records)))
+    }
+}
+
+pub struct MemoryForget;
+#[async_trait]
+impl Tool for MemoryForget {
+    fn name(&self) -> &str {
+        "memory.forget"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let what = if let Some(id) = params.get("id") {
+            Forget::Id(id)
+        } else if let Some(key) = params.get("key") {
+            Forget::Key(key)
+        } else if let Some(query) = params.get("query") {
+            Forget::Query(query)
+        } else if params.get("all").map(|v| v == "true").unwrap_or(false) {
+            Forget::All
+        } else {
+            return Err(anyhow!(
+                "memory.forget: pass one of id=, key=, query= or all=true"
+            ));
+        };
+        let user = scope_user("memory.forget", params, ctx)?;
+
+        let bank = match global_bank() {
+            Some(b) => b,
+            None => return Ok(Some(json!({ "ok": false, "reason": "memory disabled" }))),
+        };
+        let removed = bank
+            .forget(&user, params.get("agent").map(|s| s.as_str()), what)
+            .await?;
+        Ok(Some(json!({ "ok": true, "removed": removed })))
+    }
+}
+
+pub struct MemoryList;
+#[async_trait]
+impl Tool for MemoryList {
+    fn name(&self) -> &str {
+        "memory.list"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let user = scope_user("memory.list", params, ctx)?;
+        let limit = parse_usize(params.get("limit"), 20);
+
+        let bank = match global_bank() {
+            Some(b) => b,
+            None => return Ok(Some(json!([]))),
+        };
+        let records = bank
+            .list(&user, params.get("agent").map(|s| s.as_str()), limit)
+            .await?;
+        Ok(Some(records_to_json(&records)))
+    }
+}
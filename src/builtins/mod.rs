@@ -75,14 +75,22 @@ impl BuiltinRegistry {
         reg!(system::SetContext);
         reg!(system::Return);
 
-        // Platform messaging (dotted): telegram.*, discord.*, wechat.*, feishu.*
+        // Platform messaging (dotted): telegram.*, discord.*, slack.*, matrix.*, email.*, wechat.*, feishu.*
         reg!(platforms::telegram::SendMessage);
         reg!(platforms::telegram::Typing);
         reg!(platforms::telegram::EditMessage);
+        reg!(platforms::telegram::SendPhoto);
+        reg!(platforms::telegram::SendDocument);
         reg!(platforms::discord::SendMessage);
         reg!(platforms::discord::Typing);
         reg!(platforms::discord::EditMessage);
         reg!(platforms::discord::React);
+        reg!(platforms::slack::SendMessage);
+        reg!(platforms::slack::EditMessage);
+        reg!(platforms::slack::React);
+        reg!(platforms::matrix::SendMessage);
+        reg!(platforms::matrix::React);
+        reg!(platforms::email::SendEmail);
         reg!(platforms::wechat::SendMessage);
         reg!(platforms::feishu::SendMessage);
         reg!(platforms::feishu::SendImage);
@@ -91,6 +99,7 @@ impl BuiltinRegistry {
         // HTTP backend
         // Serve is registered post-construction (needs Weak<BuiltinRegistry>)
         reg!(http::HttpResponse);
+        reg!(http::Render);
 
         // Devtools (Claude Code style)
         reg!(devtools::ReadFile);
@@ -105,6 +114,7 @@ impl BuiltinRegistry {
 
         // Testing tools
         reg!(testing::Config);
+        reg!(testing::Simulate);
         // Mock is registered post-construction (needs Weak<BuiltinRegistry>)
 
         // Device control (requires "device" feature — skipped on headless ARM64)
@@ -154,6 +164,27 @@ impl BuiltinRegistry {
         reg!(history::HistoryStats);
         reg!(history::HistoryListChats);
 
+        // Long-term memory
+        reg!(memory::MemoryRemember);
+        reg!(memory::MemoryRecall);
+        reg!(memory::MemoryForget);
+        reg!(memory::MemoryList);
+        // "memory_search" alias: the name the validator has long reserved
+        tool_map.insert(
+            "memory_search".to_string(),
+            Arc::new(Box::new(memory::MemoryRecall)),
+        );
+
+        // Cross-channel identity
+        reg!(identity::IdentityWhoami);
+        reg!(identity::IdentityLinkCode);
+        reg!(identity::IdentityLink);
+        reg!(identity::IdentityUnlink);
+        reg!(identity::IdentitySetProfile);
+
+        // Background jobs
+        reg!(jobs::Enqueue);
+
         let registry_arc = Arc::new(Self {
             tools: RwLock::new(tool_map),
             executor: RwLock::new(None),
@@ -334,6 +365,9 @@ pub mod devtools;
 pub mod history;
 pub mod http;
 pub mod http_client;
+pub mod identity;
+pub mod jobs;
+pub mod memory;
 pub mod network;
 pub mod oauth;
 pub mod platforms;
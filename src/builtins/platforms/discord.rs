@@ -27,6 +27,19 @@ fn param_str<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a s
     params.get(key).map(|s| s.trim_matches('"'))
 }
 
+/// `buttons=[[...], ...]` → message `components` (action rows).
+fn components(params: &HashMap<String, String>, tool: &str) -> Result<Option<Value>> {
+    let raw = match params.get("buttons").map(|s| s.trim()) {
+        Some(raw) if !raw.is_empty() && raw != "null" => raw,
+        _ => return Ok(None),
+    };
+    let buttons: Value = serde_json::from_str(raw)
+        .map_err(|e| anyhow!("{}: `buttons` must be a list of rows: {}", tool, e))?;
+    dc::action_rows(&buttons)
+        .map(Some)
+        .map_err(|e| anyhow!("{}: {}", tool, e))
+}
+
 pub struct SendMessage;
 #[async_trait]
 impl Tool for SendMessage {
@@ -42,9 +55,10 @@ impl Tool for SendMessage {
             .ok_or_else(|| anyhow!("discord.send_message: missing `text`"))?
             .to_string();
         let channel_id = resolve_target(params, ctx, &["channel_id", "chat_id"], "discord")?;
+        let components = components(params, "discord.send_message")?;
         let token = load_token()?;
         let http = reqwest::Client::new();
-        dc::send_channel_message(&http, &token, &channel_id, &text).await?;
+        dc::send_channel_message(&http, &token, &channel_id, &text, components.as_ref()).await?;
         let chunks = dc::split_message(&text, dc::MAX_MESSAGE_LEN).len();
         Ok(Some(json!({
             "status": "sent",
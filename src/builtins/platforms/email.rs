@@ -0,0 +1,140 @@
+// src/builtins/platforms/email.rs
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use crate::adapters::email as em;
+use crate::builtins::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::config::{EmailChannelConfig, JuglansConfig};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The `[channels.email.<id>]` entry to send through: `instance=` when
+/// given, otherwise the first one configured.
+fn load_config(params: &HashMap<String, String>) -> Result<EmailChannelConfig> {
+    let config = JuglansConfig::load().map_err(|e| anyhow!("load config: {}", e))?;
+    if let Some(instance) = param_str(params, "instance") {
+        return config
+            .channels
+            .email
+            .get(instance)
+            .cloned()
+            .ok_or_else(|| anyhow!("No [channels.email.{}] configured", instance));
+    }
+    config
+        .channels
+        .email
+        .values()
+        .next()
+        .cloned()
+        .ok_or_else(|| anyhow!("No email channel configured ([channels.email.<id>])"))
+}
+
+fn param_str<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    params
+        .get(key)
+        .map(|s| s.trim_matches('"').trim())
+        .filter(|s| !s.is_empty() && !s.starts_with("[Missing:"))
+}
+
+/// `"a@x, b@y"` → `["a@x", "b@y"]`.
+fn address_list(raw: Option<&str>) -> Vec<String> {
+    raw.map(|s| {
+        s.split(',')
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// The triggering email (`input.event_data`) when this run came from an
+/// email channel.
+fn inbound_email(ctx: &WorkflowContext) -> Option<Value> {
+    let platform = ctx.resolve_path("input.platform").ok()??;
+    if platform.as_str() != Some("email") {
+        return None;
+    }
+    ctx.resolve_path("input.event_data").ok()?
+}
+
+pub struct SendEmail;
+#[async_trait]
+impl Tool for SendEmail {
+    fn name(&self) -> &str {
+        "email.send"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let text = param_str(params, "text")
+            .ok_or_else(|| anyhow!("email.send: missing `text`"))?
+            .to_string();
+        let cc = address_list(param_str(params, "cc"));
+        let html = param_str(params, "html").map(String::from);
+
+        // Explicit `to` sends a new message (optionally threaded under
+        // `in_reply_to`); without it, reply in the triggering email's thread.
+        let mail = if let Some(to) = param_str(params, "to") {
+            let in_reply_to = param_str(params, "in_reply_to")
+                .map(|id| id.trim_matches(|c| c == '<' || c == '>').to_string());
+            em::OutgoingMail {
+                to: address_list(Some(to)),
+                cc,
+                subject: param_str(params, "subject")
+                    .unwrap_or("(no subject)")
+                    .to_string(),
+                text,
+                html,
+                references: in_reply_to.iter().cloned().collect(),
+                in_reply_to,
+            }
+        } else if let Some(inbound) = inbound_email(ctx) {
+            let ids = |key: &str| -> Vec<String> {
+                inbound[key]
+                    .as_array()
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|v| v.as_str())
+                            .map(|id| id.trim_matches(|c| c == '<' || c == '>').to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            let thread = em::EmailThread {
+                reply_to: inbound["reply_to"].as_str().unwrap_or("").to_string(),
+                subject: inbound["subject"].as_str().unwrap_or("").to_string(),
+                last_message_id: inbound["message_id"]
+                    .as_str()
+                    .unwrap_or("")
+                    .trim_matches(|c| c == '<' || c == '>')
+                    .to_string(),
+                references: ids("references"),
+            };
+            let mut mail = em::OutgoingMail::reply(&thread, &text);
+            if let Some(subject) = param_str(params, "subject") {
+                mail.subject = subject.to_string();
+            }
+            mail.cc = cc;
+            mail.html = html;
+            mail
+        } else {
+            return Err(anyhow!(
+                "email.send: no recipient — pass `to` explicitly, or run from an email-triggered workflow"
+            ));
+        };
+
+        let cfg = load_config(params)?;
+        let message_id = em::send_mail(&cfg, &mail).await?;
+        Ok(Some(json!({
+            "status": "sent",
+            "to": mail.to,
+            "subject": mail.subject,
+            "message_id": format!("<{}>", message_id),
+        })))
+    }
+}
@@ -8,9 +8,11 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use super::resolve_target;
+use crate::adapters::feishu;
 use crate::builtins::Tool;
 use crate::core::context::WorkflowContext;
 use crate::services::config::JuglansConfig;
+use crate::services::outbox::{self, Delivery};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -69,6 +71,42 @@ async fn fetch_tenant_token(
 
 // ─── feishu.send_message ────────────────────────────────────────────────────
 
+/// Text send shared by `feishu.send_message` and outbox redelivery.
+pub(crate) async fn deliver(chat_id: &str, text: &str) -> Result<()> {
+    deliver_with(load_feishu()?, chat_id, text).await
+}
+
+async fn deliver_with(
+    (app_id, app_secret, base_url): (String, String, String),
+    chat_id: &str,
+    text: &str,
+) -> Result<()> {
+    let http = reqwest::Client::new();
+    let token = fetch_tenant_token(&http, &base_url, &app_id, &app_secret).await?;
+
+    let content = json!({ "text": text }).to_string();
+    let resp = http
+        .post(format!(
+            "{}/open-apis/im/v1/messages?receive_id_type=chat_id",
+            base_url
+        ))
+        .bearer_auth(&token)
+        .json(&json!({
+            "receive_id": chat_id,
+            "msg_type": "text",
+            "content": content,
+        }))
+        .send()
+        .await?;
+    let status = resp.status();
+    let retry_after = feishu::rate_limit_reset(resp.headers());
+    let body: Value = resp.json().await.unwrap_or(json!({}));
+    if !status.is_success() || body["code"].as_i64() != Some(0) {
+        return Err(feishu::send_error(status, retry_after, &body));
+    }
+    Ok(())
+}
+
 pub struct SendMessage;
 #[async_trait]
 impl Tool for SendMessage {
@@ -85,28 +123,26 @@ impl Tool for SendMessage {
             .ok_or_else(|| anyhow!("feishu.send_message: missing `text`"))?
             .to_string();
         let chat_id = resolve_target(params, ctx, &["chat_id"], "feishu")?;
+        // Config errors fail the call; only delivery failures are queued.
+        let creds = load_feishu()?;
 
-        let (app_id, app_secret, base_url) = load_feishu()?;
-        let http = reqwest::Client::new();
-        let token = fetch_tenant_token(&http, &base_url, &app_id, &app_secret).await?;
-
-        let content = json!({ "text": text }).to_string();
-        let resp = http
-            .post(format!(
-                "{}/open-apis/im/v1/messages?receive_id_type=chat_id",
-                base_url
-            ))
-            .bearer_auth(&token)
-            .json(&json!({
-                "receive_id": chat_id,
-                "msg_type": "text",
-                "content": content,
-            }))
-            .send()
-            .await?;
-        let body: Value = resp.json().await?;
-        if body["code"].as_i64() != Some(0) {
-            return Err(anyhow!("Feishu send error: {:?}", body));
+        match outbox::submit(
+            "feishu.send_message",
+            &chat_id,
+            &text,
+            Value::Null,
+            deliver_with(creds, &chat_id, &text),
+        )
+        .await?
+        {
+            Delivery::Sent(()) => {}
+            Delivery::Queued(id) => {
+                return Ok(Some(json!({
+                    "status": "queued",
+                    "target": chat_id,
+                    "outbox_id": id,
+                })))
+            }
         }
 
         Ok(Some(json!({
@@ -0,0 +1,108 @@
+// src/builtins/platforms/matrix.rs
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::resolve_target;
+use crate::adapters::matrix as mx;
+use crate::builtins::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::config::{JuglansConfig, MatrixChannelConfig};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The `[channels.matrix.<id>]` entry to send through: `instance=` when
+/// given, otherwise the first one with an access token.
+fn load_config(params: &HashMap<String, String>) -> Result<MatrixChannelConfig> {
+    let config = JuglansConfig::load().map_err(|e| anyhow!("load config: {}", e))?;
+    if let Some(instance) = param_str(params, "instance") {
+        return config
+            .channels
+            .matrix
+            .get(instance)
+            .cloned()
+            .ok_or_else(|| anyhow!("No [channels.matrix.{}] configured", instance));
+    }
+    config
+        .channels
+        .matrix
+        .values()
+        .find(|c| !c.access_token.is_empty())
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!("No matrix access_token configured ([channels.matrix.<id>].access_token)")
+        })
+}
+
+fn param_str<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    params.get(key).map(|s| s.trim_matches('"'))
+}
+
+pub struct SendMessage;
+#[async_trait]
+impl Tool for SendMessage {
+    fn name(&self) -> &str {
+        "matrix.send_message"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let text = param_str(params, "text")
+            .ok_or_else(|| anyhow!("matrix.send_message: missing `text`"))?
+            .to_string();
+        let room = resolve_target(params, ctx, &["room_id", "chat_id"], "matrix")?;
+        let cfg = load_config(params)?;
+        let http = reqwest::Client::new();
+        let posted =
+            mx::post_message(&http, &cfg.homeserver, &cfg.access_token, &room, &text).await?;
+        Ok(Some(json!({
+            "status": "sent",
+            "target": room,
+            "event_id": posted.first(),
+            "chunks": posted.len(),
+        })))
+    }
+}
+
+pub struct React;
+#[async_trait]
+impl Tool for React {
+    fn name(&self) -> &str {
+        "matrix.react"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let room = resolve_target(params, ctx, &["room_id", "chat_id"], "matrix")?;
+        let event_id = param_str(params, "event_id")
+            .ok_or_else(|| anyhow!("matrix.react: missing `event_id`"))?
+            .to_string();
+        // Reactions are annotations keyed by the emoji itself ("👍").
+        let emoji = param_str(params, "emoji")
+            .ok_or_else(|| anyhow!("matrix.react: missing `emoji`"))?
+            .to_string();
+        let cfg = load_config(params)?;
+        let http = reqwest::Client::new();
+        let reaction_id = mx::send_event(
+            &http,
+            &cfg.homeserver,
+            &cfg.access_token,
+            &room,
+            "m.reaction",
+            &mx::reaction_content(&event_id, &emoji),
+        )
+        .await?;
+        Ok(Some(json!({
+            "status": "reacted",
+            "target": room,
+            "event_id": event_id,
+            "reaction_id": reaction_id,
+            "emoji": emoji,
+        })))
+    }
+}
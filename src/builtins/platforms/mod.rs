@@ -1,7 +1,7 @@
 // src/builtins/platforms/mod.rs
 //
 // Per-platform outbound messaging builtins, registered under dotted names
-// (`telegram.send_message`, `discord.send_message`, ...). Matches the
+// (`telegram.send_message`, `discord.send_message`, `slack.send_message`, ...). Matches the
 // `db.*` / `history.*` convention. Each platform module defines its own
 // Tool impls that delegate to `pub(crate)` helpers in the corresponding
 // `src/adapters/<platform>.rs`.
@@ -13,6 +13,9 @@
 //     workflow push without re-threading the target.
 //   - Credentials come from `JuglansConfig::load()` → `config.bot.<platform>`.
 //   - Return value: `{ "status": "sent", "target": "<resolved>", ... }`.
+//     With `[outbox]` enabled, `telegram.send_message` and
+//     `feishu.send_message` return `{ "status": "queued", "outbox_id": N }`
+//     instead of failing when the platform is unreachable or rate-limits.
 
 #![cfg(not(target_arch = "wasm32"))]
 
@@ -20,7 +23,10 @@ use crate::core::context::WorkflowContext;
 use anyhow::{anyhow, Result};
 
 pub mod discord;
+pub mod email;
 pub mod feishu;
+pub mod matrix;
+pub mod slack;
 pub mod telegram;
 pub mod wechat;
 
@@ -55,6 +61,21 @@ pub(crate) fn resolve_target(
     ))
 }
 
+/// Redeliver a queued `<platform>.send_message` (see services/outbox.rs).
+/// None when `tool` isn't an outbox-backed builtin.
+pub(crate) async fn redeliver(
+    tool: &str,
+    target: &str,
+    text: &str,
+    options: &serde_json::Value,
+) -> Option<Result<()>> {
+    match tool {
+        "telegram.send_message" => Some(telegram::deliver(target, text, options).await),
+        "feishu.send_message" => Some(feishu::deliver(target, text).await),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
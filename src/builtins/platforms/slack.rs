@@ -0,0 +1,141 @@
+// src/builtins/platforms/slack.rs
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use super::resolve_target;
+use crate::adapters::slack as sl;
+use crate::builtins::Tool;
+use crate::core::context::WorkflowContext;
+use crate::services::config::{JuglansConfig, SlackChannelConfig};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The `[channels.slack.<id>]` entry to send through: `instance=` when
+/// given, otherwise the first one with a bot token.
+fn load_config(params: &HashMap<String, String>) -> Result<SlackChannelConfig> {
+    let config = JuglansConfig::load().map_err(|e| anyhow!("load config: {}", e))?;
+    if let Some(instance) = param_str(params, "instance") {
+        return config
+            .channels
+            .slack
+            .get(instance)
+            .cloned()
+            .ok_or_else(|| anyhow!("No [channels.slack.{}] configured", instance));
+    }
+    config
+        .channels
+        .slack
+        .values()
+        .find(|c| !c.bot_token.is_empty())
+        .cloned()
+        .ok_or_else(|| anyhow!("No slack bot_token configured ([channels.slack.<id>].bot_token)"))
+}
+
+fn param_str<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    params.get(key).map(|s| s.trim_matches('"'))
+}
+
+pub struct SendMessage;
+#[async_trait]
+impl Tool for SendMessage {
+    fn name(&self) -> &str {
+        "slack.send_message"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let text = param_str(params, "text")
+            .ok_or_else(|| anyhow!("slack.send_message: missing `text`"))?
+            .to_string();
+        let target = resolve_target(params, ctx, &["channel", "chat_id"], "slack")?;
+        // An explicit thread_ts wins over the thread carried in the target.
+        let conversation = match param_str(params, "thread_ts").filter(|t| !t.is_empty()) {
+            Some(ts) => sl::conversation_id(sl::split_conversation(&target).0, Some(ts)),
+            None => target,
+        };
+        let cfg = load_config(params)?;
+        let http = reqwest::Client::new();
+        let posted =
+            sl::post_message(&http, &cfg.api_base, &cfg.bot_token, &conversation, &text).await?;
+        Ok(Some(json!({
+            "status": "sent",
+            "target": conversation,
+            "ts": posted.first(),
+            "chunks": posted.len(),
+        })))
+    }
+}
+
+pub struct EditMessage;
+#[async_trait]
+impl Tool for EditMessage {
+    fn name(&self) -> &str {
+        "slack.edit_message"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let target = resolve_target(params, ctx, &["channel", "chat_id"], "slack")?;
+        let channel = sl::split_conversation(&target).0.to_string();
+        let ts = param_str(params, "ts")
+            .ok_or_else(|| anyhow!("slack.edit_message: missing `ts`"))?
+            .to_string();
+        let text = param_str(params, "text")
+            .ok_or_else(|| anyhow!("slack.edit_message: missing `text`"))?
+            .to_string();
+        let cfg = load_config(params)?;
+        let http = reqwest::Client::new();
+        sl::update_message(&http, &cfg.api_base, &cfg.bot_token, &channel, &ts, &text).await?;
+        Ok(Some(json!({
+            "status": "edited",
+            "target": channel,
+            "ts": ts,
+        })))
+    }
+}
+
+pub struct React;
+#[async_trait]
+impl Tool for React {
+    fn name(&self) -> &str {
+        "slack.react"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        let target = resolve_target(params, ctx, &["channel", "chat_id"], "slack")?;
+        let channel = sl::split_conversation(&target).0.to_string();
+        let ts = param_str(params, "ts")
+            .ok_or_else(|| anyhow!("slack.react: missing `ts`"))?
+            .to_string();
+        // Slack wants the emoji name without colons ("thumbsup").
+        let emoji = param_str(params, "emoji")
+            .ok_or_else(|| anyhow!("slack.react: missing `emoji`"))?
+            .trim_matches(':')
+            .to_string();
+        let cfg = load_config(params)?;
+        let http = reqwest::Client::new();
+        sl::api_call(
+            &http,
+            &cfg.api_base,
+            &cfg.bot_token,
+            "reactions.add",
+            &json!({ "channel": channel, "timestamp": ts, "name": emoji }),
+        )
+        .await?;
+        Ok(Some(json!({
+            "status": "reacted",
+            "target": channel,
+            "ts": ts,
+            "emoji": emoji,
+        })))
+    }
+}
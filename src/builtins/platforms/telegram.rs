@@ -7,6 +7,7 @@ use crate::adapters::telegram as tg;
 use crate::builtins::Tool;
 use crate::core::context::WorkflowContext;
 use crate::services::config::JuglansConfig;
+use crate::services::outbox::{self, Delivery};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -30,6 +31,39 @@ fn param_str<'a>(params: &'a HashMap<String, String>, key: &str) -> Option<&'a s
     params.get(key).map(|s| s.trim_matches('"'))
 }
 
+/// `buttons=[[...], ...]` → inline-keyboard `reply_markup`.
+fn reply_markup(params: &HashMap<String, String>, tool: &str) -> Result<Option<Value>> {
+    let raw = match params.get("buttons").map(|s| s.trim()) {
+        Some(raw) if !raw.is_empty() && raw != "null" => raw,
+        _ => return Ok(None),
+    };
+    let buttons: Value = serde_json::from_str(raw)
+        .map_err(|e| anyhow!("{}: `buttons` must be a list of rows: {}", tool, e))?;
+    tg::inline_keyboard(&buttons)
+        .map(Some)
+        .map_err(|e| anyhow!("{}: {}", tool, e))
+}
+
+/// Text send shared by `telegram.send_message` and outbox redelivery.
+/// `options` carries `parse_mode` and `reply_markup`.
+pub(crate) async fn deliver(chat_id: &str, text: &str, options: &Value) -> Result<()> {
+    deliver_with(&load_token()?, chat_id, text, options)
+        .await
+        .map(|_| ())
+}
+
+async fn deliver_with(token: &str, chat_id: &str, text: &str, options: &Value) -> Result<usize> {
+    tg::send_message_api(
+        &reqwest::Client::new(),
+        token,
+        chat_id,
+        text,
+        options["parse_mode"].as_str(),
+        options.get("reply_markup").filter(|m| !m.is_null()),
+    )
+    .await
+}
+
 pub struct SendMessage;
 #[async_trait]
 impl Tool for SendMessage {
@@ -45,15 +79,32 @@ impl Tool for SendMessage {
             .ok_or_else(|| anyhow!("telegram.send_message: missing `text`"))?
             .to_string();
         let chat_id = resolve_target(params, ctx, &["chat_id"], "telegram")?;
-        let parse_mode = param_str(params, "parse_mode");
+        let options = json!({
+            "parse_mode": param_str(params, "parse_mode"),
+            "reply_markup": reply_markup(params, "telegram.send_message")?,
+        });
+        // Config errors fail the call; only delivery failures are queued.
         let token = load_token()?;
-        let http = reqwest::Client::new();
-        let chunks = tg::send_message_api(&http, &token, &chat_id, &text, parse_mode).await?;
-        Ok(Some(json!({
-            "status": "sent",
-            "target": chat_id,
-            "chunks": chunks,
-        })))
+        match outbox::submit(
+            "telegram.send_message",
+            &chat_id,
+            &text,
+            options.clone(),
+            deliver_with(&token, &chat_id, &text, &options),
+        )
+        .await?
+        {
+            Delivery::Sent(chunks) => Ok(Some(json!({
+                "status": "sent",
+                "target": chat_id,
+                "chunks": chunks,
+            }))),
+            Delivery::Queued(id) => Ok(Some(json!({
+                "status": "queued",
+                "target": chat_id,
+                "outbox_id": id,
+            }))),
+        }
     }
 }
 
@@ -106,3 +157,77 @@ impl Tool for EditMessage {
         })))
     }
 }
+
+/// Shared body of `telegram.send_photo` / `telegram.send_document`.
+async fn send_media(
+    params: &HashMap<String, String>,
+    ctx: &WorkflowContext,
+    tool: &str,
+    method: &str,
+    field: &str,
+) -> Result<Option<Value>> {
+    let source = param_str(params, field)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| anyhow!("{}: missing `{}` (local path, URL or file_id)", tool, field))?
+        .to_string();
+    let chat_id = resolve_target(params, ctx, &["chat_id"], "telegram")?;
+    let caption = param_str(params, "caption");
+    let filename = param_str(params, "filename");
+    let markup = reply_markup(params, tool)?;
+    let token = load_token()?;
+    let http = reqwest::Client::new();
+    let message_id = tg::send_media_api(
+        &http,
+        &token,
+        &chat_id,
+        method,
+        field,
+        &source,
+        caption,
+        filename,
+        markup.as_ref(),
+    )
+    .await?;
+    Ok(Some(json!({
+        "status": "sent",
+        "target": chat_id,
+        "message_id": message_id,
+    })))
+}
+
+pub struct SendPhoto;
+#[async_trait]
+impl Tool for SendPhoto {
+    fn name(&self) -> &str {
+        "telegram.send_photo"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        send_media(params, ctx, "telegram.send_photo", "sendPhoto", "photo").await
+    }
+}
+
+pub struct SendDocument;
+#[async_trait]
+impl Tool for SendDocument {
+    fn name(&self) -> &str {
+        "telegram.send_document"
+    }
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        ctx: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        send_media(
+            params,
+            ctx,
+            "telegram.send_document",
+            "sendDocument",
+            "document",
+        )
+        .await
+    }
+}
@@ -3,12 +3,13 @@
 // Builtin tools for the `juglans test` framework.
 // - `config`: Stores test configuration into context
 // - `mock`: Execute a workflow with injected node outputs
+// - `simulate`: Play a conversation through a simulated chat channel
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Weak;
+use std::sync::{Arc, Weak};
 use tracing::info;
 
 use super::Tool;
@@ -110,3 +111,91 @@ impl Tool for Mock {
         Ok(Some(output))
     }
 }
+
+/// `simulate` builtin — run an agent through a simulated chat channel
+/// (see `adapters::simulator`) and return what it sent back.
+///
+/// Usage: simulate(script="tests/greeting.yaml")
+///        simulate(channel="telegram", agent="support", messages=["hi", {"action": "ok"}])
+///
+/// `messages` entries are plain text or script steps; they run after the
+/// script's own steps. Returns `{channel, passed, replies, turns}`.
+pub struct Simulate;
+
+#[async_trait]
+impl Tool for Simulate {
+    fn name(&self) -> &str {
+        "simulate"
+    }
+
+    async fn execute(
+        &self,
+        params: &HashMap<String, String>,
+        _context: &WorkflowContext,
+    ) -> Result<Option<Value>> {
+        use crate::adapters::simulator::{SimScript, SimStep, Simulator};
+        use crate::adapters::LocalDispatcher;
+
+        let mut script = match params.get("script") {
+            Some(path) => SimScript::load(std::path::Path::new(path))?,
+            None => SimScript::default(),
+        };
+        if let Some(raw) = params.get("messages") {
+            let items = match serde_json::from_str(raw) {
+                Ok(Value::Array(items)) => items,
+                Ok(other) => vec![other],
+                Err(_) => vec![Value::String(raw.clone())],
+            };
+            for item in items {
+                let step = match item {
+                    Value::String(text) => SimStep {
+                        say: Some(text),
+                        ..Default::default()
+                    },
+                    other => serde_json::from_value(other)
+                        .map_err(|e| anyhow!("simulate: invalid message: {}", e))?,
+                };
+                step.input().map_err(|e| anyhow!("simulate: {}", e))?;
+                script.steps.push(step);
+            }
+        }
+        if script.steps.is_empty() {
+            return Err(anyhow!("simulate: pass 'script' or 'messages'"));
+        }
+
+        let kind = params
+            .get("channel")
+            .cloned()
+            .or_else(|| script.channel.clone())
+            .ok_or_else(|| anyhow!("simulate: Missing 'channel' parameter"))?;
+        let agent_slug = params
+            .get("agent")
+            .cloned()
+            .or_else(|| script.agent.clone())
+            .unwrap_or_else(|| "default".to_string());
+        let mut user = script.user.clone().unwrap_or_default();
+        if let Some(id) = params.get("user_id") {
+            user.id = id.clone();
+        }
+        if let Some(name) = params.get("username") {
+            user.username = Some(name.clone());
+        }
+        let stream = match params.get("stream").map(|s| s.as_str()) {
+            Some("false") => Some(false),
+            Some("true") => Some(true),
+            _ => script.stream,
+        };
+
+        let dispatcher = Arc::new(LocalDispatcher {
+            config: crate::services::config::JuglansConfig::load()?,
+            project_root: std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")),
+            agent_slug,
+        });
+        let chat = params.get("chat").cloned().or_else(|| script.chat.clone());
+        let mut sim = Simulator::new(&kind, user, chat, stream, dispatcher)?;
+
+        info!("  💬 simulate: {} turn(s) on {}", script.steps.len(), kind);
+        let report = sim.run_script(&script).await?;
+        Ok(Some(serde_json::to_value(report)?))
+    }
+}
@@ -126,6 +126,19 @@ pub trait ChannelEgress: Send + Sync {
     async fn start_stream(&self, _conversation: &str) -> Result<Box<dyn StreamHandle>> {
         Err(anyhow!("channel does not support streaming"))
     }
+
+    /// Post a tool-approval card: `text` plus approve / deny buttons whose
+    /// presses come back as `card_action` events with `allow_action` /
+    /// `deny_action`. Default: not supported, so `ask` policies deny.
+    async fn send_approval(
+        &self,
+        _conversation: &str,
+        _text: &str,
+        _allow_action: &str,
+        _deny_action: &str,
+    ) -> Result<()> {
+        Err(anyhow!("channel does not support approval buttons"))
+    }
 }
 
 /// Where a workflow run came from. Set on `WorkflowContext` by the channel
@@ -151,6 +164,23 @@ impl std::fmt::Debug for ChannelOrigin {
     }
 }
 
+/// User messages sent to a run while it is executing (WebSocket `message`
+/// frames). `chat()` drains it between tool rounds, so the agent sees them
+/// before its next model call. Shared by every fork of a context.
+#[derive(Debug, Clone, Default)]
+pub struct UserInbox(Arc<Mutex<Vec<String>>>);
+
+impl UserInbox {
+    pub fn push(&self, text: String) {
+        self.0.lock().push(text);
+    }
+
+    /// Remove and return every queued message, oldest first.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
 /// Real-time events during workflow execution
 pub enum WorkflowEvent {
     Token(String),
@@ -215,6 +245,8 @@ pub struct WorkflowContext {
     /// driver. `None` for CLI / scheduled / test invocations.
     #[cfg(not(target_arch = "wasm32"))]
     origin: Arc<RwLock<Option<ChannelOrigin>>>,
+    /// Messages injected into the running conversation (see [`UserInbox`]).
+    user_inbox: UserInbox,
 }
 
 impl Default for WorkflowContext {
@@ -244,6 +276,7 @@ impl WorkflowContext {
             typed_store: Arc::new(RwLock::new(HashMap::new())),
             #[cfg(not(target_arch = "wasm32"))]
             origin: Arc::new(RwLock::new(None)),
+            user_inbox: UserInbox::default(),
         }
     }
 
@@ -271,6 +304,7 @@ impl WorkflowContext {
             method_scopes: Arc::new(RwLock::new(Vec::new())),
             typed_store: Arc::new(RwLock::new(HashMap::new())),
             origin: Arc::new(RwLock::new(None)),
+            user_inbox: UserInbox::default(),
         }
     }
 
@@ -295,6 +329,7 @@ impl WorkflowContext {
             typed_store: Arc::new(RwLock::new(self.typed_store.read().clone())),
             #[cfg(not(target_arch = "wasm32"))]
             origin: self.origin.clone(),
+            user_inbox: self.user_inbox.clone(),
         }
     }
 
@@ -543,6 +578,17 @@ impl WorkflowContext {
         self.current_workflow.read().clone()
     }
 
+    /// Name of the top-level workflow for metrics labels: its slug, which
+    /// the loaders default to the file stem.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn workflow_label(&self) -> String {
+        match self.root_workflow.read().as_ref() {
+            Some(wf) if !wf.slug.is_empty() => wf.slug.clone(),
+            Some(wf) if !wf.name.is_empty() => wf.name.clone(),
+            _ => "unknown".to_string(),
+        }
+    }
+
     /// Set the root (top-level) workflow — only sets if not already set
     pub fn set_root_workflow(&self, workflow: Arc<WorkflowGraph>) {
         let mut w = self.root_workflow.write();
@@ -577,6 +623,11 @@ impl WorkflowContext {
         self.origin.read().clone()
     }
 
+    /// Messages injected into this run while it executes.
+    pub fn user_inbox(&self) -> &UserInbox {
+        &self.user_inbox
+    }
+
     /// Set tool event verbosity level: 0=silent, 1=info, 2=verbose
     pub fn set_tool_event_level(&self, level: u8) {
         self.tool_event_level.store(level, Ordering::Relaxed);
@@ -652,6 +703,15 @@ impl WorkflowContext {
 
         self.tool_trace.lock().push(entry);
 
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::services::metrics::record_tool(
+            &self.workflow_label(),
+            node_id,
+            tool,
+            result.is_ok(),
+            duration,
+        );
+
         // Stream event to frontend based on level
         let level = self.tool_event_level.load(Ordering::Relaxed);
         if level == 0 {
@@ -737,6 +797,15 @@ impl WorkflowContext {
 
         self.tool_trace.lock().push(entry);
 
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::services::metrics::record_node(
+            &self.workflow_label(),
+            node_id,
+            tool,
+            result.is_ok(),
+            duration,
+        );
+
         if !self.stream_node_events.load(Ordering::Relaxed) {
             return;
         }
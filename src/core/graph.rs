@@ -124,6 +124,9 @@ pub struct WorkflowGraph {
     pub pending_methods: Vec<(String, String, FunctionDef)>,
     /// Decorator applications recorded during parsing, processed in macro expand phase.
     pub decorator_applications: Vec<DecoratorApplication>,
+    /// Built-in decorator annotations on plain nodes (e.g. `@static` on
+    /// `[start]: serve()`): `(node_id, key, value)` in source order.
+    pub node_annotations: Vec<(String, String, Value)>,
 }
 
 /// .jgflow Manifest — pure configuration struct, no DAG
@@ -260,6 +263,16 @@ impl WorkflowGraph {
     pub fn empty() -> Self {
         Self::default()
     }
+
+    /// Give a workflow without a `slug:` the stem of the file it was loaded
+    /// from, so logs and metrics can name it.
+    pub fn default_slug_from(&mut self, path: &std::path::Path) {
+        if self.slug.is_empty() {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                self.slug = stem.to_string();
+            }
+        }
+    }
 }
 
 /// Check if a node ID belongs to the test framework (`test_*` prefix)
@@ -291,6 +304,7 @@ impl Default for WorkflowGraph {
             classes: HashMap::new(),
             pending_methods: Vec::new(),
             decorator_applications: Vec::new(),
+            node_annotations: Vec::new(),
         }
     }
 }
@@ -600,16 +600,17 @@ impl<'a> JwlParser<'a> {
             match &self.tokens[pos].kind {
                 TokenKind::At => {
                     pos += 1; // skip @
-                              // Skip decorator path and args
+                              // Skip decorator path and args. Brackets inside the
+                              // args (`roles=["admin"]`) aren't the target node.
+                    let mut depth = 0usize;
                     while pos < self.tokens.len() {
                         match &self.tokens[pos].kind {
-                            TokenKind::LBracket | TokenKind::At => break,
-                            TokenKind::Newline => {
-                                pos += 1;
-                                continue;
-                            }
-                            _ => pos += 1,
+                            TokenKind::LBracket | TokenKind::At if depth == 0 => break,
+                            TokenKind::LParen => depth += 1,
+                            TokenKind::RParen => depth = depth.saturating_sub(1),
+                            _ => {}
                         }
+                        pos += 1;
                     }
                 }
                 TokenKind::LBracket => {
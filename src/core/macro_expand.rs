@@ -74,6 +74,12 @@ pub fn expand_decorators(wf: &mut WorkflowGraph) -> Result<()> {
                         );
                         target_fn.annotations.insert(key.clone(), value.clone());
                     }
+                } else {
+                    // A plain node, e.g. `@static(...)` on `[start]: serve()`.
+                    for (key, value) in annotations {
+                        wf.node_annotations
+                            .push((app.target_node_id.clone(), key, value));
+                    }
                 }
             } else {
                 warn!(
@@ -215,14 +221,88 @@ fn resolve_builtin_decorator(name: &str, args: &[String]) -> HashMap<String, Val
                 .first()
                 .map(|s| s.trim_matches('"').to_string())
                 .unwrap_or_else(|| "/".to_string());
-            annotations.insert(
-                "route".to_string(),
-                json!({ "method": method, "path": path }),
-            );
+            let mut route = json!({ "method": method, "path": path });
+            // `body=Struct` / `query=Struct` — validate the request against a
+            // struct before the handler runs.
+            for arg in args.iter().skip(1) {
+                if let Some((key, value)) = arg.split_once('=') {
+                    let key = key.trim();
+                    if key == "body" || key == "query" {
+                        route[key] = json!(value.trim().trim_matches('"'));
+                    }
+                }
+            }
+            annotations.insert("route".to_string(), route);
+        }
+        "auth" => {
+            // @auth / @auth(roles=["admin", "ops"]) — the route needs verified
+            // credentials (and one of the roles). `required=false` opts out.
+            let mut roles = json!([]);
+            let mut required = true;
+            for arg in args {
+                if let Some((key, value)) = arg.split_once('=') {
+                    match key.trim() {
+                        "roles" => {
+                            roles = serde_json::from_str(value.trim())
+                                .unwrap_or_else(|_| json!([value.trim().trim_matches('"')]))
+                        }
+                        "required" => required = value.trim() != "false",
+                        _ => {}
+                    }
+                }
+            }
+            if required {
+                annotations.insert("auth".to_string(), json!({ "roles": roles }));
+            }
+        }
+        "static" => {
+            // @static("/assets", dir="public", index="index.html", max_age=3600)
+            // — serve a directory (services/static_files.rs).
+            let path = args
+                .first()
+                .filter(|a| !a.contains('='))
+                .map(|s| s.trim().trim_matches('"').to_string())
+                .unwrap_or_else(|| "/".to_string());
+            let mut mount = json!({ "path": path });
+            for arg in args {
+                if let Some((key, value)) = arg.split_once('=') {
+                    let key = key.trim();
+                    let value = value.trim();
+                    match key {
+                        "dir" | "path" => mount[key] = json!(value.trim_matches('"')),
+                        "index" => {
+                            mount[key] = match value {
+                                "false" => json!(false),
+                                v => json!(v.trim_matches('"')),
+                            }
+                        }
+                        "max_age" => {
+                            if let Ok(secs) = value.parse::<u64>() {
+                                mount[key] = json!(secs);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            annotations.insert("static".to_string(), mount);
         }
         "test" => {
             annotations.insert("test".to_string(), json!(true));
         }
+        "command" => {
+            // @command("name", "description") — Discord slash command. Name
+            // may be omitted; the channel falls back to the function name.
+            let arg = |i: usize| {
+                args.get(i)
+                    .map(|s| s.trim().trim_matches('"').to_string())
+                    .unwrap_or_default()
+            };
+            annotations.insert(
+                "command".to_string(),
+                json!({ "name": arg(0), "description": arg(1) }),
+            );
+        }
         "cron" => {
             if let Some(schedule) = args.first() {
                 annotations.insert(
@@ -7,6 +7,8 @@ use std::collections::HashMap;
 
 pub struct JwlRenderer {
     eval: ExprEvaluator,
+    /// HTML mode: interpolations are entity-escaped unless marked `| safe`.
+    escape_html: bool,
 }
 
 impl Default for JwlRenderer {
@@ -19,6 +21,16 @@ impl JwlRenderer {
     pub fn new() -> Self {
         Self {
             eval: ExprEvaluator::new(),
+            escape_html: false,
+        }
+    }
+
+    /// Renderer for HTML pages (`render()`): `{{ expr }}` output is escaped,
+    /// `{{ expr | safe }}` is written as-is.
+    pub fn html() -> Self {
+        Self {
+            eval: ExprEvaluator::new(),
+            escape_html: true,
         }
     }
 
@@ -44,19 +56,28 @@ impl JwlRenderer {
                 TemplateNode::Text(t) => output.push_str(t),
 
                 TemplateNode::Interpolation(expr) => {
+                    let (expr, safe) = match strip_safe_filter(expr) {
+                        Some(inner) if self.escape_html => (inner, true),
+                        _ => (expr.trim(), false),
+                    };
                     let result = {
                         let resolver = make_resolver(scope);
                         self.eval
-                            .eval(expr.trim(), &resolver)
+                            .eval(expr, &resolver)
                             .map_err(|e| anyhow!("Interpolation error in '{}': {}", expr, e))?
                     };
                     // Convert to display string
-                    match &result {
-                        Value::String(s) => output.push_str(s),
-                        Value::Null => {} // null renders as empty
-                        Value::Bool(b) => output.push_str(&b.to_string()),
-                        Value::Number(n) => output.push_str(&n.to_string()),
-                        _ => output.push_str(&serde_json::to_string(&result).unwrap_or_default()),
+                    let text = match &result {
+                        Value::String(s) => s.clone(),
+                        Value::Null => String::new(), // null renders as empty
+                        Value::Bool(b) => b.to_string(),
+                        Value::Number(n) => n.to_string(),
+                        _ => serde_json::to_string(&result).unwrap_or_default(),
+                    };
+                    if self.escape_html && !safe {
+                        output.push_str(&escape_html(&text));
+                    } else {
+                        output.push_str(&text);
                     }
                 }
 
@@ -164,6 +185,28 @@ impl JwlRenderer {
     }
 }
 
+/// `expr | safe` → `expr`; `None` when the interpolation has no `safe` filter.
+fn strip_safe_filter(expr: &str) -> Option<&str> {
+    let (inner, filter) = expr.trim().rsplit_once('|')?;
+    (filter.trim() == "safe" && !inner.ends_with('|')).then(|| inner.trim())
+}
+
+/// Escape text for use in HTML element content and quoted attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 /// Create a resolver from a scope HashMap.
 /// The resolver looks up bare identifiers directly in scope,
 /// and handles dot-path access into nested values.
@@ -188,3 +231,40 @@ fn make_resolver<'a>(scope: &'a HashMap<String, Value>) -> impl Fn(&str) -> Opti
         Some(current)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::prompt_parser::PromptParser;
+
+    fn render(renderer: &JwlRenderer, body: &str, context: Value) -> String {
+        let template = PromptParser::parse(&format!("---\nslug: \"t\"\n---\n{}", body)).unwrap();
+        renderer.render(&template.ast, &context).unwrap()
+    }
+
+    #[test]
+    fn html_mode_escapes_interpolations() {
+        let context = json!({"name": "<b>\"Tom\" & 'Jerry'</b>", "items": ["<i>"]});
+        let body = "<p>{{ name }}</p>{% for i in items %}<li>{{ i }}</li>{% endfor %}";
+        assert_eq!(
+            render(&JwlRenderer::html(), body, context.clone()),
+            "<p>&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;</p><li>&lt;i&gt;</li>"
+        );
+        // Prompt rendering is untouched.
+        assert_eq!(
+            render(&JwlRenderer::new(), "{{ name }}", context),
+            "<b>\"Tom\" & 'Jerry'</b>"
+        );
+    }
+
+    #[test]
+    fn safe_filter_skips_escaping() {
+        let context = json!({"html": "<em>hi</em>", "a": true, "b": false});
+        let html = JwlRenderer::html();
+        assert_eq!(
+            render(&html, "{{ html | safe }}", context.clone()),
+            "<em>hi</em>"
+        );
+        assert_eq!(render(&html, "{{ a || b }}", context), "true");
+    }
+}
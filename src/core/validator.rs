@@ -393,6 +393,7 @@ impl WorkflowValidator {
             // HTTP backend tools
             "serve",
             "response",
+            "render",
             // Devtools
             "read_file",
             "write_file",
@@ -405,6 +406,7 @@ impl WorkflowValidator {
             "assert",
             "config",
             "mock",
+            "simulate",
             // Database ORM
             "db.connect",
             "db.disconnect",
@@ -435,14 +437,36 @@ impl WorkflowValidator {
             "history.clear",
             "history.stats",
             "history.list_chats",
+            // Long-term memory
+            "memory.remember",
+            "memory.recall",
+            "memory.forget",
+            "memory.list",
+            "memory_search",
+            // Cross-channel identity
+            "identity.whoami",
+            "identity.link_code",
+            "identity.link",
+            "identity.unlink",
+            "identity.set_profile",
+            // Background jobs
+            "enqueue",
             // Platform messaging
             "telegram.send_message",
             "telegram.typing",
             "telegram.edit_message",
+            "telegram.send_photo",
+            "telegram.send_document",
             "discord.send_message",
             "discord.typing",
             "discord.edit_message",
             "discord.react",
+            "slack.send_message",
+            "slack.edit_message",
+            "slack.react",
+            "matrix.send_message",
+            "matrix.react",
+            "email.send",
             "wechat.send_message",
             "feishu.send_message",
             "feishu.send_image",
@@ -512,11 +536,11 @@ impl WorkflowValidator {
                     );
                 }
             }
-            "memory_search" => {
+            "memory_search" | "memory.recall" => {
                 if !params.contains_key("query") {
                     result.add_error(
                         "E009",
-                        "memory_search() requires 'query' parameter",
+                        &format!("{}() requires 'query' parameter", tool_name),
                         Some(node_id),
                     );
                 }
@@ -1162,6 +1186,21 @@ mod tests {
         assert!(result.errors.iter().any(|e| e.code == "E011"));
     }
 
+    #[test]
+    fn test_missing_required_param_memory_search() {
+        for tool in ["memory_search", "memory.recall"] {
+            let content = format!("[start]: {}(user=\"u1\", limit=3)\n", tool);
+            let graph = GraphParser::parse(&content).unwrap();
+            let result = WorkflowValidator::validate(&graph);
+            assert!(result.errors.iter().any(|e| e.code == "E009"), "{}", tool);
+
+            let content = format!("[start]: {}(query=\"tea\", user=\"u1\")\n", tool);
+            let graph = GraphParser::parse(&content).unwrap();
+            let result = WorkflowValidator::validate(&graph);
+            assert!(result.is_valid, "{}: {:?}", tool, result.errors);
+        }
+    }
+
     #[test]
     fn test_unknown_variable_prefix() {
         let content = r#"
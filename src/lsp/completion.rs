@@ -32,6 +32,7 @@ const BUILTIN_TOOLS: &[(&str, &str)] = &[
     ("timer", "Delay execution"),
     ("serve", "HTTP server entry point"),
     ("response", "HTTP response builder"),
+    ("render", "Render .jgx template as HTML response"),
     ("assert", "Test assertion"),
     ("config", "Test configuration"),
     ("read_file", "Read file contents"),
@@ -42,7 +43,8 @@ const BUILTIN_TOOLS: &[(&str, &str)] = &[
     ("bash", "Execute shell command"),
     ("sh", "Execute shell command (alias)"),
     ("execute_workflow", "Run sub-workflow"),
-    ("memory_search", "Search memory store"),
+    ("memory_search", "Search long-term memories (query, user?)"),
+    ("memory.recall", "Recall long-term memories (query, user?)"),
     ("history", "Chat history"),
     ("vector_create_space", "Create vector space"),
     ("vector_upsert", "Upsert vectors"),
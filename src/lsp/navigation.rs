@@ -253,6 +253,7 @@ fn tool_description(name: &str) -> Option<&'static str> {
         "set" => Some("Set a context variable.\n\nParams: `key`, `value`"),
         "serve" => Some("Mark node as HTTP entry point.\n\nParams: `method`, `path`"),
         "response" => Some("Build HTTP response.\n\nParams: `status`, `body`, `headers`"),
+        "render" => Some("Render a .jgx template as an HTML response.\n\nParams: `template`, `status`, `headers`, template variables"),
         "assert" => Some("Test assertion.\n\nParams: `contains`, `eq`, `true`"),
         "bash" | "sh" => Some("Execute shell command.\n\nParams: `command`"),
         "read_file" => Some("Read file contents.\n\nParams: `path`"),
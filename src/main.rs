@@ -115,6 +115,12 @@ enum Commands {
         #[arg(long)]
         entry: Option<PathBuf>,
     },
+    /// Run background jobs from the [jobs] queue (enqueue(), POST /api/jobs)
+    Worker {
+        /// Jobs executed at the same time
+        #[arg(short, long, default_value_t = 4)]
+        concurrency: usize,
+    },
     /// Start bot adapter (telegram, feishu, wechat, discord)
     Bot {
         /// Platform: telegram, feishu, wechat, discord
@@ -126,6 +132,48 @@ enum Commands {
         #[arg(long)]
         port: Option<u16>,
     },
+    /// Inspect and manage the durable outbound message queue ([outbox])
+    Outbox {
+        #[command(subcommand)]
+        action: OutboxAction,
+    },
+    /// Chat with an agent through a simulated channel (no bot token needed)
+    Simulate {
+        /// Scripted conversation (.yaml); omit for an interactive REPL
+        script: Option<PathBuf>,
+        /// Channel to impersonate: telegram, discord, slack, matrix
+        #[arg(long, short = 'c')]
+        channel: Option<String>,
+        /// Agent slug to run (default: "default")
+        #[arg(long)]
+        agent: Option<String>,
+        /// Simulated sender's platform user id
+        #[arg(long)]
+        user: Option<String>,
+        /// Simulated sender's username
+        #[arg(long)]
+        username: Option<String>,
+        /// Conversation id (default: a direct chat with the user)
+        #[arg(long)]
+        chat: Option<String>,
+        /// Deliver replies in one piece even on channels that stream
+        #[arg(long)]
+        no_stream: bool,
+        /// Output format for scripts: text, json
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Generate an OpenAPI 3 document for a workflow's @get/@post routes
+    Openapi {
+        /// Workflow entry file (.jg)
+        file: PathBuf,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// json or yaml (default: from the output extension, else json)
+        #[arg(long)]
+        format: Option<String>,
+    },
     /// Manage Agent Skills (fetch from GitHub, convert to .jgx)
     Skills {
         #[command(subcommand)]
@@ -245,6 +293,38 @@ enum SkillsAction {
     },
 }
 
+#[derive(Subcommand)]
+enum OutboxAction {
+    /// List queued and dead-lettered messages
+    List {
+        /// Only show dead letters
+        #[arg(long)]
+        dead: bool,
+        /// Maximum rows per table
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Move dead letters back into the queue (delivered by `juglans serve`)
+    Retry {
+        /// Dead-letter id
+        id: Option<i64>,
+        /// Requeue every dead letter
+        #[arg(long)]
+        all: bool,
+    },
+    /// Delete dead letters
+    Purge {
+        /// Dead-letter id (or queued message id with --pending)
+        id: Option<i64>,
+        /// Delete every matching row
+        #[arg(long)]
+        all: bool,
+        /// Delete from the live queue instead of the dead letters
+        #[arg(long)]
+        pending: bool,
+    },
+}
+
 /// Resolve input data from --input or --input-file
 fn resolve_input_data(cli: &Cli) -> Result<Option<String>> {
     if let Some(input_file_path) = &cli.input_file {
@@ -359,6 +439,14 @@ async fn handle_file_logic(cli: &Cli) -> Result<()> {
             if let Err(e) = crate::services::history::init_global(&local_config.history) {
                 tracing::warn!("[history] init_global failed: {}", e);
             }
+            crate::services::tool_policy::init_global(&local_config.tool_policy)?;
+            crate::services::memory::init_global(&local_config.memory);
+            crate::services::identity::init_global(&local_config.identity);
+            crate::services::outbox::init_global(&local_config.outbox);
+            crate::services::jobs::init_global(&local_config.jobs);
+            if let Err(e) = crate::services::sandbox::init_global(&local_config.sandbox) {
+                tracing::warn!("[sandbox] init_global failed: {}", e);
+            }
 
             // Compute base directory for @ path alias
             let at_base: Option<PathBuf> = local_config
@@ -405,6 +493,8 @@ async fn handle_file_logic(cli: &Cli) -> Result<()> {
                 at_base.as_deref(),
             )?;
 
+            workflow_definition_obj.default_slug_from(&absolute_target_path);
+
             // Macro expand: process @decorator applications
             core::macro_expand::expand_decorators(&mut workflow_definition_obj)?;
 
@@ -1239,7 +1329,7 @@ async fn handle_serve(
 
         // Discord Gateway (WebSocket — no webhook alternative).
         if !config.channels.discord.is_empty() {
-            match adapters::discord::discover_channels(&config, &project_root) {
+            match adapters::discord::discover_channels(&config, &project_root, &entry_file) {
                 Ok(chs) => {
                     for (ch, agent) in chs {
                         channels.push((ch, agent));
@@ -1259,6 +1349,64 @@ async fn handle_serve(
             }
         }
 
+        // Slack — Socket Mode channels run a websocket loop; Events API
+        // channels mount /webhook/slack/<id> via install_routes.
+        if !config.channels.slack.is_empty() {
+            match adapters::slack::discover_channels(&config) {
+                Ok(chs) => {
+                    for (ch, agent) in chs {
+                        channels.push((ch, agent));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Slack channel discovery failed: {:#}", e);
+                }
+            }
+        }
+
+        // Matrix — /sync long-polling per account; sync tokens persist
+        // under .juglans/matrix/.
+        if !config.channels.matrix.is_empty() {
+            match adapters::matrix::discover_channels(&config, &project_root) {
+                Ok(chs) => {
+                    for (ch, agent) in chs {
+                        channels.push((ch, agent));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Matrix channel discovery failed: {:#}", e);
+                }
+            }
+        }
+
+        // Email — IMAP IDLE/poll ingress when imap_host is set; SMTP egress.
+        if !config.channels.email.is_empty() {
+            match adapters::email::discover_channels(&config, &project_root) {
+                Ok(chs) => {
+                    for (ch, agent) in chs {
+                        channels.push((ch, agent));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Email channel discovery failed: {:#}", e);
+                }
+            }
+        }
+
+        // Generic webhooks — passive, mounted at each instance's path.
+        if !config.channels.webhook.is_empty() {
+            match adapters::webhook::discover_channels(&config) {
+                Ok(chs) => {
+                    for (ch, agent) in chs {
+                        channels.push((ch, agent));
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Webhook channel discovery failed: {:#}", e);
+                }
+            }
+        }
+
         // Pair each channel with a dispatcher (cached per agent_slug so many
         // channels sharing the same agent reuse one allocation).
         let mut dispatchers: std::collections::HashMap<
@@ -1273,6 +1421,17 @@ async fn handle_serve(
         )> = Vec::with_capacity(channels.len());
         for (ch, channel_agent) in channels {
             *by_kind.entry(ch.kind().to_string()).or_insert(0) += 1;
+            // `mount:<name>` runs the entry workflow of [server.mounts.<name>].
+            if let Some(mount) = channel_agent.strip_prefix("mount:") {
+                if !config.server.mounts.contains_key(mount) {
+                    return Err(anyhow!(
+                        "{} channel '{}' targets mount '{}', which is not in [server.mounts]",
+                        ch.kind(),
+                        ch.id(),
+                        mount
+                    ));
+                }
+            }
             let agent_slug = if channel_agent == "default" {
                 entry_slug.clone()
             } else {
@@ -1288,6 +1447,15 @@ async fn handle_serve(
                     })
                 })
                 .clone();
+            // Limits are per channel instance, so each gets its own wrapper
+            // around the shared per-agent dispatcher.
+            let dispatcher = match ch.limits() {
+                Some(limits) => Arc::new(adapters::limits::RateLimitedDispatcher::new(
+                    limits.clone(),
+                    dispatcher,
+                )) as Arc<dyn adapters::MessageDispatcher>,
+                None => dispatcher,
+            };
             channel_pairs.push((ch, dispatcher));
         }
         for (kind, count) in &by_kind {
@@ -1316,6 +1484,332 @@ async fn handle_serve(
     Ok(())
 }
 
+/// `juglans worker`: consume the job queue until SIGTERM or Ctrl-C, then let running
+/// jobs finish.
+async fn handle_worker(concurrency: usize) -> Result<()> {
+    let config = JuglansConfig::load()?;
+    let project_root = find_project_root(&env::current_dir()?)?;
+    if !config.jobs.enabled {
+        return Err(anyhow!("[jobs] is disabled in juglans.toml"));
+    }
+
+    if let Err(e) = services::history::init_global(&config.history) {
+        tracing::warn!("[history] init_global failed: {}", e);
+    }
+    services::tool_policy::init_global(&config.tool_policy)?;
+    services::memory::init_global(&config.memory);
+    services::identity::init_global(&config.identity);
+    services::outbox::init_global(&config.outbox);
+    services::jobs::init_global(&config.jobs);
+    if let Err(e) = services::sandbox::init_global(&config.sandbox) {
+        tracing::warn!("[sandbox] init_global failed: {}", e);
+    }
+    // Sends that fail inside a job are redelivered from here too.
+    services::outbox::spawn_worker();
+
+    services::jobs::run_worker(project_root, concurrency, services::shutdown::signal()).await
+}
+
+fn handle_outbox(action: &OutboxAction) -> Result<()> {
+    use services::outbox::{store_path, OutboxStore};
+
+    let config = JuglansConfig::load()?;
+    let path = store_path(&config.outbox);
+    if !config.outbox.enabled {
+        println!("Note: [outbox] is disabled in juglans.toml — nothing new is queued.");
+    }
+    let store = OutboxStore::open(&path)?;
+    let clip = |text: &str| -> String {
+        let line = text.replace('\n', " ");
+        if line.chars().count() > 60 {
+            format!("{}…", line.chars().take(60).collect::<String>())
+        } else {
+            line
+        }
+    };
+    let when = |ts: i64| {
+        chrono::DateTime::from_timestamp(ts, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| ts.to_string())
+    };
+    let require_target = |id: &Option<i64>, all: bool, verb: &str| -> Result<()> {
+        match (id, all) {
+            (Some(_), true) => Err(anyhow!("Pass an id or --all, not both")),
+            (None, false) => Err(anyhow!("Pass an id to {}, or --all", verb)),
+            _ => Ok(()),
+        }
+    };
+
+    match action {
+        OutboxAction::List { dead, limit } => {
+            if !*dead {
+                let pending = store.list_pending(*limit)?;
+                println!("Queued ({}):", store.pending_count()?);
+                for job in &pending {
+                    println!(
+                        "  #{:<6} {} → {}  attempts={}  queued={}  next={}  {}",
+                        job.id,
+                        job.sender,
+                        job.conversation,
+                        job.attempts,
+                        when(job.created_at),
+                        when(job.next_attempt_at),
+                        clip(&job.text)
+                    );
+                    if let Some(ref err) = job.last_error {
+                        println!("          last error: {}", clip(err));
+                    }
+                }
+            }
+            let dead_letters = store.list_dead(*limit)?;
+            println!("Dead letters ({}):", dead_letters.len());
+            for d in &dead_letters {
+                println!(
+                    "  #{:<6} {} → {}  attempts={}  queued={}  failed={}  {}",
+                    d.id,
+                    d.sender,
+                    d.conversation,
+                    d.attempts,
+                    when(d.created_at),
+                    when(d.failed_at),
+                    clip(&d.text)
+                );
+                if let Some(ref err) = d.last_error {
+                    println!("          error: {}", clip(err));
+                }
+                if !d.options.is_null() {
+                    println!("          options: {}", clip(&d.options.to_string()));
+                }
+            }
+        }
+        OutboxAction::Retry { id, all } => {
+            require_target(id, *all, "retry")?;
+            let moved = store.retry_dead(*id)?;
+            if moved == 0 {
+                return Err(anyhow!("No matching dead letters"));
+            }
+            println!(
+                "Requeued {} message(s); a running `juglans serve` delivers them within 30s.",
+                moved
+            );
+        }
+        OutboxAction::Purge { id, all, pending } => {
+            require_target(id, *all, "purge")?;
+            let deleted = store.purge(*id, *pending)?;
+            println!(
+                "Deleted {} {}.",
+                deleted,
+                if *pending {
+                    "queued message(s)"
+                } else {
+                    "dead letter(s)"
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `juglans openapi <entry.jg>`: parse, resolve imports and expand
+/// decorators (nothing runs), then render the spec.
+fn handle_openapi(file: &Path, output: Option<&Path>, format: Option<&str>) -> Result<()> {
+    let absolute = file
+        .canonicalize()
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let source = fs::read_to_string(&absolute)?;
+    let mut graph = GraphParser::parse(&source)?;
+
+    let base_dir = absolute.parent().unwrap_or(Path::new("."));
+    let at_base: Option<PathBuf> = find_project_root(&absolute).ok().and_then(|root| {
+        JuglansConfig::load()
+            .ok()
+            .and_then(|c| c.paths.base)
+            .map(|b| root.join(b))
+    });
+    resolver::resolve_lib_imports(
+        &mut graph,
+        base_dir,
+        &mut vec![absolute.clone()],
+        at_base.as_deref(),
+    )?;
+    resolver::resolve_flow_imports(
+        &mut graph,
+        base_dir,
+        &mut vec![absolute.clone()],
+        at_base.as_deref(),
+    )?;
+    core::macro_expand::expand_decorators(&mut graph)?;
+
+    if builtins::http::extract_routes_from_graph(&graph).is_empty() {
+        warn!(
+            "{} declares no @get/@post/... routes; the spec has no paths",
+            file.display()
+        );
+    }
+    let title = absolute
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("juglans");
+    let spec = services::openapi::build_spec(&graph, title);
+
+    let format = format.unwrap_or_else(|| {
+        match output.and_then(|p| p.extension()).and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => "yaml",
+            _ => "json",
+        }
+    });
+    let text = match format {
+        "json" => serde_json::to_string_pretty(&spec)? + "\n",
+        "yaml" => serde_yaml_ng::to_string(&spec)?,
+        other => {
+            return Err(anyhow!(
+                "Unknown format '{}' (expected json or yaml)",
+                other
+            ))
+        }
+    };
+    match output {
+        Some(path) => {
+            fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Wrote {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+struct SimulateArgs {
+    script: Option<PathBuf>,
+    channel: Option<String>,
+    agent: Option<String>,
+    user: Option<String>,
+    username: Option<String>,
+    chat: Option<String>,
+    no_stream: bool,
+    format: String,
+}
+
+async fn handle_simulate(args: SimulateArgs) -> Result<()> {
+    use adapters::simulator::{SimInput, SimOutput, SimScript, Simulator, SUPPORTED_CHANNELS};
+    use tokio::io::AsyncBufReadExt;
+
+    let script = args.script.as_deref().map(SimScript::load).transpose()?;
+    let defaults = script.clone().unwrap_or_default();
+    let kind = args.channel.or(defaults.channel).ok_or_else(|| {
+        anyhow!(
+            "Pass --channel <{}> (or set `channel:` in the script)",
+            SUPPORTED_CHANNELS.join("|")
+        )
+    })?;
+    let agent = args
+        .agent
+        .or(defaults.agent)
+        .unwrap_or_else(|| "default".to_string());
+    let mut user = defaults.user.unwrap_or_default();
+    if let Some(id) = args.user {
+        user.id = id;
+    }
+    if let Some(name) = args.username {
+        user.username = Some(name);
+    }
+    let stream = if args.no_stream {
+        Some(false)
+    } else {
+        defaults.stream
+    };
+
+    let config = JuglansConfig::load()?;
+    let project_root = find_project_root(&env::current_dir()?)?;
+    let dispatcher = Arc::new(adapters::LocalDispatcher {
+        config,
+        project_root,
+        agent_slug: agent.clone(),
+    });
+    let mut sim = Simulator::new(&kind, user, args.chat.or(defaults.chat), stream, dispatcher)?;
+
+    let print_replies = |replies: &[SimOutput]| {
+        if replies.is_empty() {
+            println!("  \x1b[2m(no reply)\x1b[0m");
+        }
+        for r in replies {
+            let how = if r.streamed {
+                format!("streamed, {} token(s)", r.tokens)
+            } else {
+                "sent".to_string()
+            };
+            println!("bot> {}  \x1b[2m[{}]\x1b[0m", r.text, how);
+        }
+    };
+
+    if let Some(script) = script {
+        let report = sim.run_script(&script).await?;
+        if args.format == "json" {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            for turn in &report.turns {
+                println!("you> {}", turn.input);
+                print_replies(&turn.replies);
+                for failure in &turn.failures {
+                    println!("  \x1b[31m✗ {}\x1b[0m", failure);
+                }
+            }
+            let failed = report
+                .turns
+                .iter()
+                .filter(|t| !t.failures.is_empty())
+                .count();
+            println!(
+                "\n  {} turn(s), {}",
+                report.turns.len(),
+                if failed == 0 {
+                    "\x1b[32mall expectations met\x1b[0m".to_string()
+                } else {
+                    format!("\x1b[31m{} failed\x1b[0m", failed)
+                }
+            );
+        }
+        if !report.passed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!(
+        "Simulating {} → agent '{}'. Type a message; `:action <data>` presses a button,\n\
+         `:command /name sub key=value` runs a slash command, `:quit` exits.",
+        kind, agent
+    );
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("you> ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let line = match lines.next_line().await? {
+            Some(l) => l,
+            None => break,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if matches!(line, ":quit" | ":q" | ":exit") {
+            break;
+        }
+        let result = match SimInput::from_repl(line) {
+            Ok(input) => sim.turn(&input).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((_, replies)) => print_replies(&replies),
+            Err(e) => eprintln!("  \x1b[31mError: {:#}\x1b[0m", e),
+        }
+    }
+    Ok(())
+}
+
 async fn handle_skills(action: &SkillsAction) -> Result<()> {
     match action {
         SkillsAction::Add {
@@ -1801,6 +2295,9 @@ async fn main() -> Result<()> {
             Commands::Serve { port, host, entry } => {
                 handle_serve(host.clone(), *port, entry.clone()).await?;
             }
+            Commands::Worker { concurrency } => {
+                handle_worker(*concurrency).await?;
+            }
             Commands::Bot {
                 platform,
                 agent,
@@ -1808,6 +2305,38 @@ async fn main() -> Result<()> {
             } => {
                 handle_bot(platform, agent.clone(), *port).await?;
             }
+            Commands::Outbox { action } => {
+                handle_outbox(action)?;
+            }
+            Commands::Simulate {
+                script,
+                channel,
+                agent,
+                user,
+                username,
+                chat,
+                no_stream,
+                format,
+            } => {
+                handle_simulate(SimulateArgs {
+                    script: script.clone(),
+                    channel: channel.clone(),
+                    agent: agent.clone(),
+                    user: user.clone(),
+                    username: username.clone(),
+                    chat: chat.clone(),
+                    no_stream: *no_stream,
+                    format: format.clone(),
+                })
+                .await?;
+            }
+            Commands::Openapi {
+                file,
+                output,
+                format,
+            } => {
+                handle_openapi(file, output.as_deref(), format.as_deref())?;
+            }
             Commands::Skills { action } => {
                 handle_skills(action).await?;
             }
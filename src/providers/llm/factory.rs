@@ -113,25 +113,31 @@ impl ProviderFactory {
     /// Supports `provider/model` format (e.g. `byteplus/deepseek-v3`).
     /// Without prefix, falls back to substring matching.
     pub fn get_provider(&self, model: &str) -> (Arc<dyn LlmProvider>, String) {
+        let (provider, actual_model, _) = self.resolve(model);
+        (provider, actual_model)
+    }
+
+    /// Like `get_provider`, plus the provider's name (`openai`, `anthropic`,
+    /// an extra provider's key, ...) for metrics and logs.
+    pub fn resolve(&self, model: &str) -> (Arc<dyn LlmProvider>, String, String) {
         // Explicit provider/model format
         if let Some((provider_name, actual_model)) = model.split_once('/') {
             let pn = provider_name.to_lowercase();
             // Check extra providers first (e.g. claude-code)
             if let Some(p) = self.extra.get(&pn) {
-                return (p.value().clone(), actual_model.to_string());
+                return (p.value().clone(), actual_model.to_string(), pn);
             }
-            let p: Arc<dyn LlmProvider> = match pn.as_str() {
-                "openai" | "chatgpt" => self.chatgpt.clone(),
-                "anthropic" | "claude" => self.anthropic.clone(),
-                "deepseek" => self.deepseek.clone(),
-                "qwen" => self.qwen.clone(),
-                "gemini" => self.gemini.clone(),
-                "byteplus" | "ark" => self.byteplus.clone(),
-                "xai" => self.xai.clone(),
-                "juglans" => self.juglans.clone(),
-                _ => self.chatgpt.clone(),
+            let (p, name): (Arc<dyn LlmProvider>, &str) = match pn.as_str() {
+                "anthropic" | "claude" => (self.anthropic.clone(), "anthropic"),
+                "deepseek" => (self.deepseek.clone(), "deepseek"),
+                "qwen" => (self.qwen.clone(), "qwen"),
+                "gemini" => (self.gemini.clone(), "gemini"),
+                "byteplus" | "ark" => (self.byteplus.clone(), "byteplus"),
+                "xai" => (self.xai.clone(), "xai"),
+                "juglans" => (self.juglans.clone(), "juglans"),
+                _ => (self.chatgpt.clone(), "openai"),
             };
-            return (p, actual_model.to_string());
+            return (p, actual_model.to_string(), name.to_string());
         }
 
         // Legacy: substring matching on model name
@@ -139,41 +145,42 @@ impl ProviderFactory {
         let default_provider = env::var("DEFAULT_LLM_PROVIDER")
             .unwrap_or_default()
             .to_lowercase();
+        let pick = |p: Arc<dyn LlmProvider>, name: &str| (p, model.to_string(), name.to_string());
 
         // Check extra providers (e.g. claude-code)
         if m.contains("claude-code") || (m == "default" && default_provider == "claude-code") {
             if let Some(p) = self.extra.get("claude-code") {
-                return (p.value().clone(), model.to_string());
+                return pick(p.value().clone(), "claude-code");
             }
         }
 
         if m.contains("claude") || (m == "default" && default_provider == "anthropic") {
-            return (self.anthropic.clone(), model.to_string());
+            return pick(self.anthropic.clone(), "anthropic");
         }
 
         if m.contains("qwen") || (m == "default" && default_provider == "qwen") {
-            return (self.qwen.clone(), model.to_string());
+            return pick(self.qwen.clone(), "qwen");
         }
 
         if m.contains("gemini") {
-            return (self.gemini.clone(), model.to_string());
+            return pick(self.gemini.clone(), "gemini");
         }
 
         if m.contains("deepseek") {
-            return (self.deepseek.clone(), model.to_string());
+            return pick(self.deepseek.clone(), "deepseek");
         }
 
         if m.contains("grok") {
-            return (self.xai.clone(), model.to_string());
+            return pick(self.xai.clone(), "xai");
         }
 
         if m.contains("doubao")
             || m.starts_with("ep-")
             || (m == "default" && default_provider == "byteplus")
         {
-            return (self.byteplus.clone(), model.to_string());
+            return pick(self.byteplus.clone(), "byteplus");
         }
 
-        (self.chatgpt.clone(), model.to_string())
+        pick(self.chatgpt.clone(), "openai")
     }
 }
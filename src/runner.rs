@@ -126,6 +126,7 @@ impl RunBuilder {
         // 1. Parse
         let source = std::fs::read_to_string(&self.file_path)?;
         let mut workflow = GraphParser::parse(&source)?;
+        workflow.default_slug_from(&self.file_path);
 
         // 2. Resolve imports
         let mut import_stack = vec![self.file_path.clone()];
@@ -182,6 +183,11 @@ impl RunBuilder {
         if let Err(e) = crate::services::history::init_global(&self.config.history) {
             tracing::warn!("[history] init_global failed: {}", e);
         }
+        crate::services::tool_policy::init_global(&self.config.tool_policy)?;
+        crate::services::memory::init_global(&self.config.memory);
+        if let Err(e) = crate::services::sandbox::init_global(&self.config.sandbox) {
+            tracing::warn!("[sandbox] init_global failed: {}", e);
+        }
 
         let runtime: Arc<LocalRuntime> = match self.runtime {
             Some(rt) => rt,
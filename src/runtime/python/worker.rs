@@ -3,6 +3,7 @@
 // Python worker process management
 
 use super::protocol::{PythonRequest, PythonResponse};
+use crate::services::metrics;
 use anyhow::{anyhow, Context, Result};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
@@ -203,6 +204,7 @@ impl PythonWorkerPool {
         // Start with one worker, scale up as needed
         let worker = PythonWorker::spawn(0)?;
         workers.push(Arc::new(Mutex::new(worker)));
+        metrics::python_workers_changed(workers.len() as i64);
 
         Ok(Self {
             workers,
@@ -230,6 +232,7 @@ impl PythonWorkerPool {
         let mut worker = worker_lock
             .lock()
             .map_err(|e| anyhow!("Worker lock poisoned: {}", e))?;
+        let _busy = metrics::PythonBusy::start();
 
         // Check if worker is still alive
         if !worker.is_alive() {
@@ -247,6 +250,12 @@ impl PythonWorkerPool {
     }
 }
 
+impl Drop for PythonWorkerPool {
+    fn drop(&mut self) {
+        metrics::python_workers_changed(-(self.workers.len() as i64));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
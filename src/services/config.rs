@@ -42,6 +42,111 @@ pub struct ServerConfig {
     pub port: u16,
     /// Public endpoint URL for this server. Example: "https://agent.juglans.ai"
     pub endpoint_url: Option<String>,
+    /// `[server.auth]` — credentials required by the HTTP API and serve()
+    /// routes. Absent means no authentication (localhost use).
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    /// `[server.mounts.<name>]` — extra serve() workflows, each reached by a
+    /// path prefix and/or a Host header.
+    #[serde(default)]
+    pub mounts: HashMap<String, MountConfig>,
+    /// Expose `GET /metrics` in Prometheus text format (default true).
+    #[serde(default = "default_server_metrics")]
+    pub metrics: bool,
+    /// Seconds in-flight runs get to finish after SIGTERM before the server
+    /// fails or requeues them and exits (default 30).
+    #[serde(default = "default_server_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+    /// Bind with `SO_REUSEPORT` so a new process can listen on the same port
+    /// while the old one drains (Unix only, default false).
+    #[serde(default)]
+    pub reuse_port: bool,
+}
+
+/// One `[server.mounts.<name>]` entry. Requests matching `path` and/or
+/// `host` go to `entry` instead of the project's main serve() workflow.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MountConfig {
+    /// Entry workflow containing a serve() node, relative to the project root.
+    pub entry: String,
+    /// Path prefix such as `/billing`.
+    pub path: Option<String>,
+    /// Host header to match: `billing.example.com`, or `*.example.com` for
+    /// any subdomain.
+    pub host: Option<String>,
+    /// Remove `path` before the workflow sees the request, so its routes are
+    /// written without the prefix (default true).
+    #[serde(default = "default_mount_strip_prefix")]
+    pub strip_prefix: bool,
+}
+
+/// `[server.auth]`. A request authenticates with any one configured method:
+/// an API key, an HMAC signature, or a JWT.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AuthConfig {
+    /// Reject unauthenticated requests to every non-public path. When false,
+    /// credentials are still verified if sent, but only `@auth` routes
+    /// demand them.
+    #[serde(default = "default_auth_required")]
+    pub required: bool,
+    /// Paths reachable without credentials. A trailing `*` matches a prefix.
+    /// Channel webhooks are never behind auth (they verify their own
+    /// signatures).
+    #[serde(default = "default_auth_public")]
+    pub public: Vec<String>,
+    /// `[[server.auth.api_keys]]` — sent as `X-API-Key` or `Authorization: Bearer`.
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub hmac: Option<HmacAuthConfig>,
+    pub jwt: Option<JwtAuthConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiKeyConfig {
+    pub key: String,
+    /// Shown to the workflow as `input.auth.sub`.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// HMAC-SHA256 request signing. The signature covers
+/// `<timestamp>.<METHOD>.<path?query>.<body>`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HmacAuthConfig {
+    pub secret: String,
+    /// Shown to the workflow as `input.auth.sub`.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Header carrying `sha256=<hex>` (default `X-Signature`).
+    #[serde(default = "default_hmac_header")]
+    pub header: String,
+    /// Header carrying the unix timestamp (default `X-Timestamp`).
+    #[serde(default = "default_hmac_timestamp_header")]
+    pub timestamp_header: String,
+    /// Reject signatures older or newer than this (default 300).
+    #[serde(default = "default_hmac_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+/// JWT bearer tokens: HS256 with `secret`, RS256 with keys from `jwks_file`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JwtAuthConfig {
+    pub secret: Option<String>,
+    /// JWKS JSON file (relative to the project root) holding RSA public keys.
+    pub jwks_file: Option<String>,
+    /// Required `iss` claim.
+    pub issuer: Option<String>,
+    /// Required `aud` claim.
+    pub audience: Option<String>,
+    /// Claim holding the caller's roles: a list, or a space-separated string
+    /// such as `scope` (default `roles`).
+    #[serde(default = "default_jwt_roles_claim")]
+    pub roles_claim: String,
+    /// Clock tolerance for `exp` / `nbf` (default 60).
+    #[serde(default = "default_jwt_leeway_secs")]
+    pub leeway_secs: u64,
 }
 
 // Debug configuration section
@@ -168,6 +273,116 @@ pub struct ChannelsConfig {
     /// the discovery code picks which `Channel` impl to instantiate.
     #[serde(default)]
     pub feishu: HashMap<String, FeishuChannelConfig>,
+
+    /// One entry per Slack app. Socket Mode (active websocket) when
+    /// `app_token` is set, Events API (passive webhook verified with
+    /// `signing_secret`) otherwise, unless `mode` overrides.
+    #[serde(default)]
+    pub slack: HashMap<String, SlackChannelConfig>,
+
+    /// One entry per Matrix account. `/sync` long-polling (active ingress);
+    /// every joined room is a conversation.
+    #[serde(default)]
+    pub matrix: HashMap<String, MatrixChannelConfig>,
+
+    /// One entry per mailbox. IMAP ingress (IDLE, or polling when the server
+    /// lacks it) when `imap_host` is set; SMTP egress always.
+    #[serde(default)]
+    pub email: HashMap<String, EmailChannelConfig>,
+
+    /// One entry per inbound webhook endpoint (GitHub, Stripe, any HMAC-signed
+    /// sender). Passive: mounted at `path` via `install_routes`.
+    #[serde(default)]
+    pub webhook: HashMap<String, WebhookChannelConfig>,
+}
+
+/// `[channels.<kind>.<id>.limits]` — applied by `RateLimitedDispatcher`
+/// before a message reaches the workflow. Rates are messages per minute
+/// (token buckets; 0 disables); bursts default to the rate.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChannelLimitsConfig {
+    #[serde(default)]
+    pub user_rate: f64,
+    pub user_burst: Option<f64>,
+    #[serde(default)]
+    pub chat_rate: f64,
+    pub chat_burst: Option<f64>,
+    /// Across every user and chat of the channel instance.
+    #[serde(default)]
+    pub channel_rate: f64,
+    pub channel_burst: Option<f64>,
+    /// Runs in flight per conversation. 0 disables the limit.
+    #[serde(default = "default_limits_max_concurrent")]
+    pub max_concurrent: usize,
+    /// While a conversation is at `max_concurrent`: "queue" (wait for the
+    /// run to finish) or "drop" (discard the message).
+    #[serde(default = "default_limits_on_busy")]
+    pub on_busy: String,
+    /// Messages allowed to wait per conversation in "queue" mode; further
+    /// ones are dropped.
+    #[serde(default = "default_limits_max_queued")]
+    pub max_queued: usize,
+    /// Platform user ids / chat ids. A non-empty allow list admits only its
+    /// members (a match in either list is enough); deny always wins.
+    #[serde(default)]
+    pub allow_users: Vec<String>,
+    #[serde(default)]
+    pub allow_chats: Vec<String>,
+    #[serde(default)]
+    pub deny_users: Vec<String>,
+    #[serde(default)]
+    pub deny_chats: Vec<String>,
+    /// Sent (at most once per `notice_interval_secs` per user) when a rate
+    /// limit rejects a message. Empty: reject silently.
+    #[serde(default = "default_limits_slow_down_message")]
+    pub slow_down_message: String,
+    /// Sent when a message is dropped because the conversation is busy.
+    #[serde(default = "default_limits_busy_message")]
+    pub busy_message: String,
+    #[serde(default = "default_limits_notice_interval_secs")]
+    pub notice_interval_secs: u64,
+}
+
+impl Default for ChannelLimitsConfig {
+    fn default() -> Self {
+        Self {
+            user_rate: 0.0,
+            user_burst: None,
+            chat_rate: 0.0,
+            chat_burst: None,
+            channel_rate: 0.0,
+            channel_burst: None,
+            max_concurrent: default_limits_max_concurrent(),
+            on_busy: default_limits_on_busy(),
+            max_queued: default_limits_max_queued(),
+            allow_users: vec![],
+            allow_chats: vec![],
+            deny_users: vec![],
+            deny_chats: vec![],
+            slow_down_message: default_limits_slow_down_message(),
+            busy_message: default_limits_busy_message(),
+            notice_interval_secs: default_limits_notice_interval_secs(),
+        }
+    }
+}
+
+fn default_limits_max_concurrent() -> usize {
+    1
+}
+fn default_limits_on_busy() -> String {
+    "queue".to_string()
+}
+fn default_limits_max_queued() -> usize {
+    5
+}
+fn default_limits_slow_down_message() -> String {
+    "You're sending messages too fast — please wait a moment.".to_string()
+}
+fn default_limits_busy_message() -> String {
+    "Still working on your previous message…".to_string()
+}
+fn default_limits_notice_interval_secs() -> u64 {
+    60
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -180,6 +395,10 @@ pub struct TelegramChannelConfig {
     /// otherwise. Set to `"polling"` or `"webhook"` to force one mode.
     #[serde(default)]
     pub mode: Option<String>,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
 }
 
 /// Feishu channel config. Two flavors share one struct; the discovery code
@@ -203,12 +422,19 @@ pub struct FeishuChannelConfig {
     /// Approvers (open_id) — used by approval-flow workflows.
     #[serde(default)]
     pub approvers: Vec<String>,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct WechatChannelConfig {
     #[serde(default = "default_channel_agent")]
     pub agent: String,
+    /// Inbound limits for every WeChat account (`[channels.wechat.limits]`).
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -227,6 +453,15 @@ pub struct DiscordChannelConfig {
     /// Execution mode (reserved, currently always local).
     #[serde(default)]
     pub mode: Option<String>,
+    /// Slash commands registered through the application-commands API on
+    /// READY. When empty, derived from `@command(...)` functions in the entry
+    /// workflow.
+    #[serde(default)]
+    pub commands: Vec<DiscordCommandConfig>,
+    /// Register commands per guild instead of globally (guild commands show
+    /// up immediately; global ones can take up to an hour to propagate).
+    #[serde(default)]
+    pub command_guilds: Vec<String>,
     // ── v2 placeholders: parsed so copy-pasted openclaw-style TOML doesn't
     //    fail to deserialize, but not enforced by v1. Setting any of them
     //    emits a warning at startup. See plans/discord-virtual-waffle.md.
@@ -236,6 +471,197 @@ pub struct DiscordChannelConfig {
     pub group_policy: Option<String>,
     #[serde(default)]
     pub guilds: Vec<String>,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
+}
+
+/// One `/command` entry in `[channels.discord.<id>] commands = [...]`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct DiscordCommandConfig {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub options: Vec<DiscordCommandOption>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct DiscordCommandOption {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// "string" | "integer" | "number" | "boolean" | "user" | "channel" | "role"
+    #[serde(rename = "type", default = "default_discord_option_type")]
+    pub kind: String,
+    #[serde(default)]
+    pub required: bool,
+    /// Fixed choices for string options (shown as a picker in the client).
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SlackChannelConfig {
+    /// Bot token (`xoxb-...`) used for all Web API calls.
+    pub bot_token: String,
+    /// App-level token (`xapp-...`) with `connections:write`. Enables Socket Mode.
+    pub app_token: Option<String>,
+    /// Signing secret for verifying Events API requests.
+    pub signing_secret: Option<String>,
+    #[serde(default = "default_channel_agent")]
+    pub agent: String,
+    /// Ingress mode override: `"socket"` or `"events"`. Auto: socket when
+    /// `app_token` is set, events otherwise.
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Reply in a thread under the triggering message (each thread is its own
+    /// conversation). When false, replies go to the channel top level; messages
+    /// already inside a thread are still answered in that thread.
+    #[serde(default = "default_slack_threads")]
+    pub threads: bool,
+    /// Handle every channel message the app can see, not only @mentions.
+    /// Direct messages are always handled.
+    #[serde(default)]
+    pub all_messages: bool,
+    /// Web API base URL. Override for tests or proxies.
+    #[serde(default = "default_slack_api_base")]
+    pub api_base: String,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MatrixChannelConfig {
+    /// Client-Server API base, e.g. `https://matrix.example.org`.
+    pub homeserver: String,
+    /// Access token of the bot account (Element: Settings → Help & About).
+    pub access_token: String,
+    /// The bot's own mxid (`@bot:example.org`). Looked up with
+    /// `/account/whoami` when unset.
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default = "default_channel_agent")]
+    pub agent: String,
+    /// Only answer in these rooms (room ids, `!abc:example.org`). Empty = every
+    /// joined room.
+    #[serde(default)]
+    pub rooms: Vec<String>,
+    /// Accept room invites automatically.
+    #[serde(default = "default_matrix_auto_join")]
+    pub auto_join: bool,
+    /// Only answer messages that mention the bot (its mxid in the body or in
+    /// `m.mentions`). Useful in busy group rooms.
+    #[serde(default)]
+    pub mention_only: bool,
+    /// How long one `/sync` request waits for new events.
+    #[serde(default = "default_matrix_sync_timeout_secs")]
+    pub sync_timeout_secs: u64,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmailChannelConfig {
+    /// The mailbox's own address. Used as `From:` on replies, and mail from
+    /// this address is never dispatched (no reply loops).
+    pub address: String,
+    /// Display name for `From:` (e.g. `"Acme Support"`).
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Login for both IMAP and SMTP. Defaults to `address`.
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: String,
+    /// IMAP server. Unset = egress-only channel (SMTP sends, no ingress).
+    #[serde(default)]
+    pub imap_host: Option<String>,
+    #[serde(default = "default_imap_port")]
+    pub imap_port: u16,
+    /// Implicit TLS for IMAP (port 993). Set false for plaintext test servers.
+    #[serde(default = "default_email_tls")]
+    pub imap_tls: bool,
+    /// Folder to watch.
+    #[serde(default = "default_email_mailbox")]
+    pub mailbox: String,
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    /// `"starttls"` (default), `"tls"` (implicit, port 465) or `"none"`.
+    #[serde(default = "default_smtp_security")]
+    pub smtp_security: String,
+    #[serde(default = "default_channel_agent")]
+    pub agent: String,
+    /// Use IMAP IDLE for push delivery when the server supports it. When false
+    /// (or unsupported), the mailbox is polled every `poll_interval_secs`.
+    #[serde(default = "default_email_idle")]
+    pub idle: bool,
+    #[serde(default = "default_email_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Flag handled messages `\Seen`. When false, handled Message-IDs are only
+    /// remembered in memory, so a restart re-dispatches unread mail.
+    #[serde(default = "default_email_mark_seen")]
+    pub mark_seen: bool,
+    /// Attachments larger than this are listed in `event_data.attachments`
+    /// without their content.
+    #[serde(default = "default_email_max_attachment_bytes")]
+    pub max_attachment_bytes: usize,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookChannelConfig {
+    /// Route path. Default: `/webhook/<instance_id>`.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Signature scheme: `"github"`, `"stripe"`, `"hmac"` (default) or
+    /// `"none"` (unverified — local development only).
+    #[serde(default = "default_webhook_scheme")]
+    pub scheme: String,
+    /// Shared signing secret. Required unless `scheme = "none"`.
+    #[serde(default)]
+    pub secret: String,
+    /// `hmac` scheme: header carrying the signature.
+    #[serde(default = "default_webhook_signature_header")]
+    pub signature_header: String,
+    /// `hmac` scheme: prefix stripped before decoding (e.g. `"sha256="`).
+    #[serde(default)]
+    pub signature_prefix: String,
+    /// `hmac` scheme: `"hex"` (default) or `"base64"`.
+    #[serde(default = "default_webhook_encoding")]
+    pub encoding: String,
+    /// `hmac` scheme: header with a unix timestamp. When set, the signature
+    /// covers `<timestamp>.<body>` and stale requests are rejected.
+    #[serde(default)]
+    pub timestamp_header: Option<String>,
+    /// `hmac` scheme: header naming the event (becomes `input.event_type`).
+    #[serde(default)]
+    pub event_header: Option<String>,
+    /// `hmac` scheme: header with a unique delivery id, used to drop retries.
+    #[serde(default)]
+    pub id_header: Option<String>,
+    /// Replay window for signed timestamps (`stripe`, `hmac` + `timestamp_header`).
+    #[serde(default = "default_webhook_tolerance_secs")]
+    pub tolerance_secs: u64,
+    /// Answer `202 Accepted` at once and run the workflow in the background,
+    /// instead of returning the workflow's reply in the response.
+    #[serde(default)]
+    pub async_ack: bool,
+    #[serde(default = "default_channel_agent")]
+    pub agent: String,
+    /// Inbound rate limits, concurrency and allow/deny lists
+    /// (`[channels.<kind>.<id>.limits]`). Unlimited when absent.
+    #[serde(default)]
+    pub limits: Option<ChannelLimitsConfig>,
 }
 
 fn default_channel_agent() -> String {
@@ -249,6 +675,60 @@ fn default_discord_intents() -> Vec<String> {
         "direct_messages".into(),
     ]
 }
+fn default_discord_option_type() -> String {
+    "string".to_string()
+}
+fn default_slack_threads() -> bool {
+    true
+}
+fn default_slack_api_base() -> String {
+    "https://slack.com/api".to_string()
+}
+fn default_matrix_auto_join() -> bool {
+    true
+}
+fn default_matrix_sync_timeout_secs() -> u64 {
+    30
+}
+fn default_imap_port() -> u16 {
+    993
+}
+fn default_email_tls() -> bool {
+    true
+}
+fn default_email_mailbox() -> String {
+    "INBOX".to_string()
+}
+fn default_smtp_port() -> u16 {
+    587
+}
+fn default_smtp_security() -> String {
+    "starttls".to_string()
+}
+fn default_email_idle() -> bool {
+    true
+}
+fn default_email_poll_interval_secs() -> u64 {
+    60
+}
+fn default_email_mark_seen() -> bool {
+    true
+}
+fn default_email_max_attachment_bytes() -> usize {
+    5 * 1024 * 1024
+}
+fn default_webhook_scheme() -> String {
+    "hmac".to_string()
+}
+fn default_webhook_signature_header() -> String {
+    "x-signature".to_string()
+}
+fn default_webhook_encoding() -> String {
+    "hex".to_string()
+}
+fn default_webhook_tolerance_secs() -> u64 {
+    300
+}
 fn default_feishu_base_url() -> String {
     "https://open.feishu.cn".to_string()
 }
@@ -282,6 +762,35 @@ pub struct HistoryConfig {
     /// Days after which old messages are eligible for GC. 0 disables.
     #[serde(default = "default_history_retention_days")]
     pub retention_days: u32,
+
+    /// Automatic summarization of long threads. Off when absent.
+    #[serde(default)]
+    pub compaction: Option<CompactionConfig>,
+}
+
+/// `[history] compaction = { trigger_tokens = 6000, keep_recent = 8 }`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CompactionConfig {
+    /// Compact once a thread's stored messages exceed this many tokens.
+    #[serde(default = "default_compaction_trigger_tokens")]
+    pub trigger_tokens: u32,
+
+    /// Most recent messages left verbatim; everything older is summarized.
+    #[serde(default = "default_compaction_keep_recent")]
+    pub keep_recent: usize,
+
+    /// Model for the summarization call. Default: the chat() call's model.
+    pub model: Option<String>,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            trigger_tokens: default_compaction_trigger_tokens(),
+            keep_recent: default_compaction_keep_recent(),
+            model: None,
+        }
+    }
 }
 
 impl Default for HistoryConfig {
@@ -294,6 +803,7 @@ impl Default for HistoryConfig {
             max_messages: default_history_max_messages(),
             max_tokens: default_history_max_tokens(),
             retention_days: default_history_retention_days(),
+            compaction: None,
         }
     }
 }
@@ -313,6 +823,369 @@ fn default_history_max_tokens() -> u32 {
 fn default_history_retention_days() -> u32 {
     30
 }
+fn default_compaction_trigger_tokens() -> u32 {
+    6000
+}
+fn default_compaction_keep_recent() -> usize {
+    8
+}
+
+// Tool-call approval policy for LLM agents (`[tool_policy]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolPolicyConfig {
+    /// Action when no rule matches: "allow" | "ask" | "deny".
+    #[serde(default = "default_tool_policy_action")]
+    pub default: String,
+
+    /// Deny `write_file` / `edit_file` calls whose target resolves outside
+    /// the project root (the working directory of the juglans process).
+    #[serde(default)]
+    pub restrict_writes_to_project: bool,
+
+    /// Ordered rules; the first match wins.
+    #[serde(default)]
+    pub rules: Vec<ToolPolicyRule>,
+}
+
+impl Default for ToolPolicyConfig {
+    fn default() -> Self {
+        Self {
+            default: default_tool_policy_action(),
+            restrict_writes_to_project: false,
+            rules: vec![],
+        }
+    }
+}
+
+impl ToolPolicyConfig {
+    /// True when the policy can never change the outcome of a tool call.
+    pub fn is_noop(&self) -> bool {
+        self.default == "allow" && !self.restrict_writes_to_project && self.rules.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolPolicyRule {
+    /// Tool name glob, e.g. "bash", "write_file", "*".
+    pub tool: String,
+    /// "allow" | "ask" | "deny".
+    pub action: String,
+    /// Glob matched against the tool's primary argument (`command` for
+    /// bash, `file_path` for file tools). Omitted = match any arguments.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Override which argument `pattern` is matched against.
+    #[serde(default)]
+    pub arg: Option<String>,
+}
+
+fn default_tool_policy_action() -> String {
+    "allow".to_string()
+}
+
+// Sandbox for devtools file access and bash execution (`[sandbox]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SandboxConfig {
+    /// Master switch. When false, devtools behave as before: any path, full
+    /// environment, no resource limits.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Directory file tools are confined to and bash runs in. Relative paths
+    /// resolve against the project root. Default: the project root.
+    pub root: Option<String>,
+
+    /// Environment variables passed through to bash. Everything else is
+    /// stripped.
+    #[serde(default = "default_sandbox_env_allowlist")]
+    pub env_allowlist: Vec<String>,
+
+    /// Process isolation for bash: "auto" | "bwrap" | "unshare" | "none".
+    /// "auto" uses bubblewrap when it is on PATH and rlimits only otherwise.
+    #[serde(default = "default_sandbox_isolation")]
+    pub isolation: String,
+
+    /// Allow network access from bash. Only enforceable with bwrap/unshare.
+    #[serde(default)]
+    pub network: bool,
+
+    /// CPU time limit per bash command, in seconds. 0 disables.
+    #[serde(default = "default_sandbox_cpu_secs")]
+    pub cpu_secs: u64,
+
+    /// Address-space limit per bash command, in MiB. 0 disables.
+    #[serde(default = "default_sandbox_memory_mb")]
+    pub memory_mb: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            root: None,
+            env_allowlist: default_sandbox_env_allowlist(),
+            isolation: default_sandbox_isolation(),
+            network: false,
+            cpu_secs: default_sandbox_cpu_secs(),
+            memory_mb: default_sandbox_memory_mb(),
+        }
+    }
+}
+
+fn default_sandbox_env_allowlist() -> Vec<String> {
+    ["PATH", "HOME", "LANG", "LC_ALL", "TERM", "TZ"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+fn default_sandbox_isolation() -> String {
+    "auto".to_string()
+}
+fn default_sandbox_cpu_secs() -> u64 {
+    60
+}
+fn default_sandbox_memory_mb() -> u64 {
+    1024
+}
+
+// Long-term user memory (`[memory]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MemoryConfig {
+    /// Master switch. When false, memory.* tools are no-ops and
+    /// chat(memory=true) injects nothing.
+    #[serde(default = "default_memory_enabled")]
+    pub enabled: bool,
+
+    /// SQLite database path. Default: `.juglans/memory.db`.
+    pub path: Option<String>,
+
+    /// Memories injected per chat(memory=true) call and default
+    /// memory.recall limit.
+    #[serde(default = "default_memory_recall_limit")]
+    pub recall_limit: usize,
+
+    /// Embedding-based recall. Keyword matching when absent.
+    #[serde(default)]
+    pub embedding: Option<EmbeddingConfig>,
+}
+
+/// `[memory.embedding]` — any OpenAI-compatible `/embeddings` endpoint.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmbeddingConfig {
+    /// e.g. "text-embedding-3-small".
+    pub model: String,
+
+    /// Default: `$OPENAI_API_BASE` or `https://api.openai.com/v1`.
+    pub base_url: Option<String>,
+
+    /// Default: `$OPENAI_API_KEY`.
+    pub api_key: Option<String>,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_memory_enabled(),
+            path: None,
+            recall_limit: default_memory_recall_limit(),
+            embedding: None,
+        }
+    }
+}
+
+// Cross-channel identity (`[identity]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct IdentityConfig {
+    /// Master switch. Off by default: channel runs then carry no `input.user`.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// SQLite database path. Default: `.juglans/identity.db`.
+    pub path: Option<String>,
+
+    /// History thread per "chat" (platform conversation, the default) or
+    /// per "user" (one thread per person per agent, across channels).
+    #[serde(default = "default_identity_history_scope")]
+    pub history_scope: String,
+
+    /// Lifetime of codes from identity.link_code.
+    #[serde(default = "default_identity_link_code_ttl_secs")]
+    pub link_code_ttl_secs: u64,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            history_scope: default_identity_history_scope(),
+            link_code_ttl_secs: default_identity_link_code_ttl_secs(),
+        }
+    }
+}
+
+fn default_identity_history_scope() -> String {
+    "chat".to_string()
+}
+fn default_identity_link_code_ttl_secs() -> u64 {
+    600
+}
+
+// Durable outbound queue (`[outbox]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OutboxConfig {
+    /// Master switch. Off by default: failed sends are logged and dropped.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// SQLite database path. Default: `.juglans/outbox.db`.
+    pub path: Option<String>,
+
+    /// Delivery attempts before a message moves to the dead-letter table.
+    #[serde(default = "default_outbox_max_attempts")]
+    pub max_attempts: u32,
+
+    /// First retry delay; doubles per attempt up to `max_delay_secs`.
+    #[serde(default = "default_outbox_base_delay_secs")]
+    pub base_delay_secs: u64,
+
+    #[serde(default = "default_outbox_max_delay_secs")]
+    pub max_delay_secs: u64,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            max_attempts: default_outbox_max_attempts(),
+            base_delay_secs: default_outbox_base_delay_secs(),
+            max_delay_secs: default_outbox_max_delay_secs(),
+        }
+    }
+}
+
+fn default_outbox_max_attempts() -> u32 {
+    8
+}
+fn default_outbox_base_delay_secs() -> u64 {
+    2
+}
+fn default_outbox_max_delay_secs() -> u64 {
+    600
+}
+
+// Run store for `/api/runs` (`[runs]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunsConfig {
+    /// Mount `/api/runs` in `juglans serve` and record runs started there.
+    #[serde(default = "default_runs_enabled")]
+    pub enabled: bool,
+
+    /// SQLite database path. Default: `.juglans/runs.db`.
+    pub path: Option<String>,
+
+    /// Finished runs older than this are deleted at startup. 0 keeps them forever.
+    #[serde(default = "default_runs_retention_days")]
+    pub retention_days: u32,
+}
+
+impl Default for RunsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_runs_enabled(),
+            path: None,
+            retention_days: default_runs_retention_days(),
+        }
+    }
+}
+
+fn default_runs_enabled() -> bool {
+    true
+}
+fn default_runs_retention_days() -> u32 {
+    30
+}
+
+// Background job queue (`[jobs]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JobsConfig {
+    /// Allow `enqueue()` and mount `/api/jobs` in `juglans serve`.
+    #[serde(default = "default_jobs_enabled")]
+    pub enabled: bool,
+
+    /// SQLite database path, shared by producers and workers.
+    /// Default: `.juglans/jobs.db`.
+    pub path: Option<String>,
+
+    /// Attempts per job before it is dead-lettered. `enqueue(max_attempts=)`
+    /// overrides it per job.
+    #[serde(default = "default_jobs_max_attempts")]
+    pub max_attempts: u32,
+
+    /// First retry delay; doubles per attempt up to `max_delay_secs`.
+    #[serde(default = "default_jobs_base_delay_secs")]
+    pub base_delay_secs: u64,
+
+    #[serde(default = "default_jobs_max_delay_secs")]
+    pub max_delay_secs: u64,
+
+    /// A job running longer than this fails its attempt. A job whose worker
+    /// died is picked up again once this much time has passed.
+    #[serde(default = "default_jobs_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Workers `juglans serve` runs in-process. 0 leaves the queue to
+    /// `juglans worker`.
+    #[serde(default)]
+    pub serve_workers: usize,
+
+    /// Succeeded jobs older than this are deleted when a worker starts.
+    /// 0 keeps them forever. Dead jobs are kept until retried or purged.
+    #[serde(default = "default_jobs_retention_days")]
+    pub retention_days: u32,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_jobs_enabled(),
+            path: None,
+            max_attempts: default_jobs_max_attempts(),
+            base_delay_secs: default_jobs_base_delay_secs(),
+            max_delay_secs: default_jobs_max_delay_secs(),
+            timeout_secs: default_jobs_timeout_secs(),
+            serve_workers: 0,
+            retention_days: default_jobs_retention_days(),
+        }
+    }
+}
+
+fn default_jobs_enabled() -> bool {
+    true
+}
+fn default_jobs_max_attempts() -> u32 {
+    3
+}
+fn default_jobs_base_delay_secs() -> u64 {
+    10
+}
+fn default_jobs_max_delay_secs() -> u64 {
+    3600
+}
+fn default_jobs_timeout_secs() -> u64 {
+    600
+}
+fn default_jobs_retention_days() -> u32 {
+    7
+}
+
+fn default_memory_enabled() -> bool {
+    true
+}
+fn default_memory_recall_limit() -> usize {
+    5
+}
 
 // Package Registry configuration
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -336,6 +1209,36 @@ fn default_server_host() -> String {
 fn default_server_port() -> u16 {
     3000
 }
+fn default_mount_strip_prefix() -> bool {
+    true
+}
+fn default_server_metrics() -> bool {
+    true
+}
+fn default_server_drain_timeout_secs() -> u64 {
+    30
+}
+fn default_auth_required() -> bool {
+    true
+}
+fn default_auth_public() -> Vec<String> {
+    vec!["/health".to_string(), "/ready".to_string()]
+}
+fn default_hmac_header() -> String {
+    "x-signature".to_string()
+}
+fn default_hmac_timestamp_header() -> String {
+    "x-timestamp".to_string()
+}
+fn default_hmac_max_skew_secs() -> u64 {
+    300
+}
+fn default_jwt_roles_claim() -> String {
+    "roles".to_string()
+}
+fn default_jwt_leeway_secs() -> u64 {
+    60
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct JuglansConfig {
@@ -411,6 +1314,34 @@ pub struct JuglansConfig {
     // Conversation history configuration
     #[serde(default)]
     pub history: HistoryConfig,
+
+    // Tool-call approval policy for chat() agents
+    #[serde(default)]
+    pub tool_policy: ToolPolicyConfig,
+
+    // Sandbox for devtools (file tools + bash)
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+
+    // Long-term user memory (memory.* builtins, chat(memory=true))
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    // Cross-channel user identity (input.user, identity.* builtins)
+    #[serde(default)]
+    pub identity: IdentityConfig,
+
+    // Durable outbound queue for channel replies and *.send_message
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+
+    // Run store behind /api/runs
+    #[serde(default)]
+    pub runs: RunsConfig,
+
+    // Background job queue consumed by `juglans worker`
+    #[serde(default)]
+    pub jobs: JobsConfig,
 }
 
 fn default_env_file() -> Vec<String> {
@@ -424,6 +1355,11 @@ impl Default for ServerConfig {
             host: default_server_host(),
             port: default_server_port(),
             endpoint_url: None,
+            auth: None,
+            mounts: HashMap::new(),
+            metrics: default_server_metrics(),
+            drain_timeout_secs: default_server_drain_timeout_secs(),
+            reuse_port: false,
         }
     }
 }
@@ -460,6 +1396,13 @@ impl JuglansConfig {
                 registry: None,
                 ai: AiConfig::default(),
                 history: HistoryConfig::default(),
+                tool_policy: ToolPolicyConfig::default(),
+                sandbox: SandboxConfig::default(),
+                memory: MemoryConfig::default(),
+                identity: IdentityConfig::default(),
+                outbox: OutboxConfig::default(),
+                runs: RunsConfig::default(),
+                jobs: JobsConfig::default(),
             });
         }
 
@@ -516,6 +1459,7 @@ impl JuglansConfig {
                     agent: default_channel_agent(),
                     base_url: default_feishu_base_url(),
                     approvers: vec![],
+                    limits: None,
                 });
             if let Some(v) = feishu_app_id {
                 entry.app_id = Some(v);
@@ -556,6 +1500,7 @@ impl JuglansConfig {
                     token: String::new(),
                     agent: default_channel_agent(),
                     mode: None,
+                    limits: None,
                 });
             entry.token = token;
         }
@@ -7,7 +7,10 @@
 //   - SqliteStore: single .db with an indexed messages table
 //   - MemoryStore: in-process HashMap (tests / ephemeral)
 //
-// Compaction is out of scope here — this module only stores and retrieves.
+// Compaction (`[history] compaction = {...}`) lives at the bottom: once a
+// thread grows past `trigger_tokens`, everything but the last `keep_recent`
+// messages is summarized by a caller-supplied function and swapped for one
+// `system` message via `replace`, which each backend applies atomically.
 
 #![cfg(not(target_arch = "wasm32"))]
 
@@ -22,7 +25,13 @@ use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use super::config::HistoryConfig;
+use super::config::{CompactionConfig, HistoryConfig};
+
+/// Rough token estimate (4 chars ≈ 1 token). Good enough for history
+/// budget accounting; real tokenization is provider-specific.
+pub fn estimate_tokens(s: &str) -> u32 {
+    s.len().div_ceil(4) as u32
+}
 
 /// A single message in a conversation thread.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,7 +62,6 @@ impl ChatMessage {
         self
     }
 
-    #[allow(dead_code)]
     pub fn with_meta(mut self, meta: Value) -> Self {
         self.meta = Some(meta);
         self
@@ -84,8 +92,8 @@ pub trait ConversationStore: Send + Sync {
     async fn trim(&self, chat_id: &str, keep_recent: usize) -> Result<()>;
 
     /// Replace messages in the index range [from, to) with a single new
-    /// message. `from` and `to` are 0-based indexes into the full history
-    /// (oldest = 0). Used by compaction workflows.
+    /// message, in place and atomically. `from` and `to` are 0-based indexes
+    /// into the full history (oldest = 0). Used by compaction.
     async fn replace(&self, chat_id: &str, from: usize, to: usize, with: ChatMessage)
         -> Result<()>;
 
@@ -582,26 +590,21 @@ impl ConversationStore for SqliteStore {
                 tx.commit()?;
                 return Ok(());
             }
-            let targets: Vec<i64> = ids[from..end].to_vec();
-            // Insert the replacement first so it takes a later id, then delete
-            // the range. The replacement ends up chronologically after the
-            // range it summarizes — not ideal for ordering but acceptable
-            // since summaries typically live at the head anyway. Callers
-            // that want a head summary should clear + append.
+            // Overwrite the first row of the range so the replacement keeps
+            // its position (ids are the ordering key), then drop the rest.
             tx.execute(
-                "INSERT INTO messages (chat_id, role, content, tokens, created_at, meta)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "UPDATE messages SET role = ?1, content = ?2, tokens = ?3, created_at = ?4, meta = ?5
+                 WHERE id = ?6",
                 params![
-                    chat_id,
                     with.role,
                     with.content,
                     with.tokens,
                     with.created_at,
                     meta_str,
+                    ids[from],
                 ],
             )?;
-            // Delete the old range.
-            for id in targets {
+            for id in &ids[from + 1..end] {
                 tx.execute("DELETE FROM messages WHERE id = ?1", params![id])?;
             }
             tx.commit()?;
@@ -677,6 +680,97 @@ fn compute_stats(chat_id: &str, msgs: &[ChatMessage]) -> ChatStats {
     }
 }
 
+// ─── Compaction ──────────────────────────────────────────────────────────────
+
+/// Threads with a compaction in flight; a second trigger for the same thread
+/// is skipped rather than racing the first.
+static COMPACTING: OnceLock<DashMap<String, ()>> = OnceLock::new();
+
+/// Holds a thread's `COMPACTING` entry; dropping it (also on panic or
+/// cancellation) lets the thread be compacted again.
+struct CompactingMark<'a> {
+    in_flight: &'a DashMap<String, ()>,
+    chat_id: &'a str,
+}
+
+impl Drop for CompactingMark<'_> {
+    fn drop(&mut self) {
+        self.in_flight.remove(self.chat_id);
+    }
+}
+
+/// Summarize and collapse the older part of `chat_id` when its stored size
+/// exceeds `cfg.trigger_tokens`. `summarize` receives a plain-text transcript
+/// of the range and returns the summary. Returns whether the thread was
+/// compacted.
+///
+/// Earlier summaries sit at the head of the thread, so they are folded into
+/// the next one. Messages appended while `summarize` runs are unaffected:
+/// they land after the range being replaced.
+pub async fn compact_if_needed<F, Fut>(
+    store: &dyn ConversationStore,
+    chat_id: &str,
+    cfg: &CompactionConfig,
+    summarize: F,
+) -> Result<bool>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String>>,
+{
+    let in_flight = COMPACTING.get_or_init(DashMap::new);
+    if in_flight.insert(chat_id.to_string(), ()).is_some() {
+        return Ok(false);
+    }
+    let _mark = CompactingMark { in_flight, chat_id };
+    compact_locked(store, chat_id, cfg, summarize).await
+}
+
+async fn compact_locked<F, Fut>(
+    store: &dyn ConversationStore,
+    chat_id: &str,
+    cfg: &CompactionConfig,
+    summarize: F,
+) -> Result<bool>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String>>,
+{
+    let all = store.load(chat_id, 0).await?;
+    let total: u64 = all
+        .iter()
+        .map(|m| m.tokens.unwrap_or_else(|| estimate_tokens(&m.content)) as u64)
+        .sum();
+    if total <= cfg.trigger_tokens as u64 || all.len() <= cfg.keep_recent + 1 {
+        return Ok(false);
+    }
+
+    let to = all.len() - cfg.keep_recent;
+    let transcript = all[..to]
+        .iter()
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let summary = summarize(transcript).await?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(anyhow!("compaction produced an empty summary"));
+    }
+
+    let content = format!("Summary of the earlier conversation:\n{}", summary);
+    let mut msg = ChatMessage::new("system", content.clone())
+        .with_tokens(estimate_tokens(&content))
+        .with_meta(serde_json::json!({ "compacted": to }));
+    msg.created_at = all[to - 1].created_at;
+    store.replace(chat_id, 0, to, msg).await?;
+    tracing::info!(
+        "[history] compacted {} message(s) of {} (~{} tokens)",
+        to,
+        chat_id,
+        total
+    );
+    Ok(true)
+}
+
 // ─── tests ───────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -760,6 +854,138 @@ mod tests {
         assert_eq!(all[2].content, "m4");
     }
 
+    async fn replace_keeps_position<S: ConversationStore>(store: S) {
+        let c = "t";
+        for i in 0..5 {
+            store
+                .append(c, mk("user", &format!("m{}", i)))
+                .await
+                .unwrap();
+        }
+        store
+            .replace(c, 0, 3, ChatMessage::new("system", "[summary]"))
+            .await
+            .unwrap();
+        store.append(c, mk("user", "m5")).await.unwrap();
+        let contents: Vec<String> = store
+            .load(c, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(contents, vec!["[summary]", "m3", "m4", "m5"]);
+    }
+
+    #[tokio::test]
+    async fn replace_keeps_position_all_backends() {
+        replace_keeps_position(MemoryStore::new()).await;
+        replace_keeps_position(JsonlStore::open(&tempdir()).unwrap()).await;
+        replace_keeps_position(SqliteStore::open(&tempdir().join("h.db")).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn compaction_summarizes_older_range() {
+        let store = MemoryStore::new();
+        let c = "t";
+        for i in 0..10 {
+            store
+                .append(
+                    c,
+                    mk("user", &format!("message number {}", i)).with_tokens(100),
+                )
+                .await
+                .unwrap();
+        }
+        let cfg = CompactionConfig {
+            trigger_tokens: 500,
+            keep_recent: 4,
+            model: None,
+        };
+
+        let compacted = compact_if_needed(&store, c, &cfg, |transcript| async move {
+            assert!(transcript.contains("user: message number 0"));
+            assert!(!transcript.contains("message number 6"));
+            Ok("they counted to five".to_string())
+        })
+        .await
+        .unwrap();
+        assert!(compacted);
+
+        let all = store.load(c, 0).await.unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[0].role, "system");
+        assert!(all[0].content.contains("they counted to five"));
+        assert_eq!(all[1].content, "message number 6");
+
+        // Below the threshold now: no second summarization.
+        let again = compact_if_needed(&store, c, &cfg, |_| async {
+            Err(anyhow!("should not summarize again"))
+        })
+        .await
+        .unwrap();
+        assert!(!again);
+    }
+
+    #[tokio::test]
+    async fn compaction_failure_leaves_thread_untouched() {
+        let store = MemoryStore::new();
+        for i in 0..6 {
+            store
+                .append("t", mk("user", &format!("m{}", i)).with_tokens(100))
+                .await
+                .unwrap();
+        }
+        let cfg = CompactionConfig {
+            trigger_tokens: 100,
+            keep_recent: 2,
+            model: None,
+        };
+        let res = compact_if_needed(&store, "t", &cfg, |_| async {
+            Err(anyhow!("provider down"))
+        })
+        .await;
+        assert!(res.is_err());
+        assert_eq!(store.load("t", 0).await.unwrap().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn cancelled_compaction_can_run_again() {
+        let store = Arc::new(MemoryStore::new());
+        for i in 0..6 {
+            store
+                .append("cancel", mk("user", &format!("m{}", i)).with_tokens(100))
+                .await
+                .unwrap();
+        }
+        let cfg = CompactionConfig {
+            trigger_tokens: 100,
+            keep_recent: 2,
+            model: None,
+        };
+        let (started_tx, started) = tokio::sync::oneshot::channel();
+        let task = {
+            let (store, cfg) = (store.clone(), cfg.clone());
+            tokio::spawn(async move {
+                compact_if_needed(store.as_ref(), "cancel", &cfg, |_| async move {
+                    let _ = started_tx.send(());
+                    std::future::pending::<Result<String>>().await
+                })
+                .await
+            })
+        };
+        started.await.unwrap();
+        task.abort();
+        let _ = task.await;
+
+        let compacted = compact_if_needed(store.as_ref(), "cancel", &cfg, |_| async {
+            Ok("summary".to_string())
+        })
+        .await
+        .unwrap();
+        assert!(compacted);
+    }
+
     #[tokio::test]
     async fn list_chats_enumerates() {
         let tmp = tempdir();
@@ -0,0 +1,704 @@
+// src/services/identity.rs
+//
+// Cross-channel identity: maps `(platform, platform_user_id)` accounts to a
+// stable user id, so the same person on Telegram, Discord and the web UI is
+// one user with one profile.
+//
+//   [identity]
+//   enabled = true
+//   path = ".juglans/identity.db"
+//   history_scope = "chat"        # or "user": one thread per person per agent
+//   link_code_ttl_secs = 600
+//
+// Every account seen by a channel gets a user on first contact. Linking
+// merges two users:
+//
+//   1. On channel A the workflow calls identity.link_code() and shows the
+//      code to the user.
+//   2. The user sends the code on channel B; the workflow there calls
+//      identity.link(code=...).
+//   3. B's user is folded into A's: its accounts move over, its profile
+//      fills fields A's profile doesn't have, and it is deleted.
+//
+// Codes are single-use and expire after `link_code_ttl_secs`.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::warn;
+
+use super::auth::AuthClaims;
+use super::config::IdentityConfig;
+use crate::core::context::WorkflowContext;
+
+/// Link codes avoid look-alike characters (0/O, 1/I/L).
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const CODE_LEN: usize = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+    pub platform: String,
+    pub platform_user_id: String,
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserIdentity {
+    pub id: String,
+    pub profile: Value,
+    pub accounts: Vec<Account>,
+    pub created_at: i64,
+}
+
+impl UserIdentity {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "profile": self.profile,
+            "accounts": self.accounts.iter().map(|a| json!({
+                "platform": a.platform,
+                "platform_user_id": a.platform_user_id,
+                "username": a.username,
+            })).collect::<Vec<_>>(),
+            "created_at": self.created_at,
+        })
+    }
+}
+
+/// Result of [`IdentityStore::link`].
+#[derive(Debug)]
+pub struct LinkOutcome {
+    /// The surviving user.
+    pub user: UserIdentity,
+    /// The user that was folded into it; None when both accounts already
+    /// belonged to the same user.
+    pub merged_from: Option<String>,
+}
+
+// ─── Global ──────────────────────────────────────────────────────────────────
+
+static CONFIG: OnceLock<IdentityConfig> = OnceLock::new();
+static STORE: OnceLock<Option<Arc<IdentityStore>>> = OnceLock::new();
+
+/// Install the `[identity]` section. Idempotent — only the first call takes
+/// effect. The database is opened lazily on first use.
+pub fn init_global(cfg: &IdentityConfig) {
+    let _ = CONFIG.set(cfg.clone());
+}
+
+/// The active config (defaults when init_global has not been called).
+pub fn global_config() -> IdentityConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// The shared identity store. None when identity is disabled, init_global
+/// has not been called, or the database can't be opened.
+pub fn global_store() -> Option<Arc<IdentityStore>> {
+    STORE
+        .get_or_init(|| {
+            let cfg = CONFIG.get()?;
+            if !cfg.enabled {
+                return None;
+            }
+            let path = cfg
+                .path
+                .clone()
+                .unwrap_or_else(|| ".juglans/identity.db".into());
+            match IdentityStore::open(Path::new(&path)) {
+                Ok(store) => Some(Arc::new(store)),
+                Err(e) => {
+                    warn!("[identity] disabled: {:#}", e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Resolve the sender of a channel run and expose it as `input.user`
+/// (`id`, `profile`, `accounts`). Returns the user id, or None when identity
+/// is disabled or the lookup fails (the run proceeds without a user).
+pub fn attach_to_context(
+    ctx: &WorkflowContext,
+    platform: &str,
+    platform_user_id: &str,
+    username: Option<&str>,
+) -> Option<String> {
+    if platform_user_id.is_empty() {
+        return None;
+    }
+    let store = global_store()?;
+    attach_with(&store, ctx, platform, platform_user_id, username)
+}
+
+/// `input.*` fields a web request may not set itself: who the caller is is
+/// decided by the server.
+const WEB_RESERVED_INPUTS: &[&str] = &["user", "user_id", "platform", "auth"];
+
+/// Copy fields a web request sent (chat message JSON, `variables`, run or
+/// job input) into `input.*`, skipping the ones that say who the caller is.
+/// Every HTTP entry point goes through here before `attach_web_caller`.
+pub fn set_request_inputs(ctx: &WorkflowContext, fields: &Map<String, Value>) {
+    for (k, v) in fields {
+        if !WEB_RESERVED_INPUTS.contains(&k.as_str()) {
+            ctx.set(format!("input.{}", k), v.clone()).ok();
+        }
+    }
+}
+
+/// The `user_id` a web client claims for itself, from request fields.
+pub fn claimed_user_id(fields: &Map<String, Value>) -> Option<String> {
+    match fields.get("user_id")? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Resolve the caller of a web request. The account always lives in the
+/// `web` namespace (`input.platform` is "web"), so a web request never
+/// resolves to someone's Telegram or Discord account — only identity.link()
+/// joins those. With `[server.auth]` configured (`auth_enabled`) the id is
+/// the verified `sub` and nothing else; an unauthenticated request has no
+/// user. Without it, the `user_id` the client sent is used.
+pub fn attach_web_caller(
+    ctx: &WorkflowContext,
+    auth: Option<&AuthClaims>,
+    auth_enabled: bool,
+    claimed_user_id: Option<&str>,
+    username: Option<&str>,
+) -> Option<String> {
+    let store = global_store();
+    let auth_sub = auth.and_then(|c| c.0["sub"].as_str());
+    attach_web_caller_with(
+        store.as_deref(),
+        ctx,
+        auth_sub,
+        auth_enabled,
+        claimed_user_id,
+        username,
+    )
+}
+
+fn attach_web_caller_with(
+    store: Option<&IdentityStore>,
+    ctx: &WorkflowContext,
+    auth_sub: Option<&str>,
+    auth_enabled: bool,
+    claimed_user_id: Option<&str>,
+    username: Option<&str>,
+) -> Option<String> {
+    ctx.set("input.platform".into(), json!("web")).ok();
+    let user_id = if auth_enabled {
+        auth_sub
+    } else {
+        claimed_user_id
+    };
+    let user_id = user_id.filter(|id| !id.is_empty())?;
+    ctx.set("input.user_id".into(), json!(user_id)).ok();
+    attach_with(store?, ctx, "web", user_id, username)
+}
+
+fn attach_with(
+    store: &IdentityStore,
+    ctx: &WorkflowContext,
+    platform: &str,
+    platform_user_id: &str,
+    username: Option<&str>,
+) -> Option<String> {
+    match store.resolve(platform, platform_user_id, username) {
+        Ok(user) => {
+            ctx.set("input.user".into(), user.to_json()).ok();
+            Some(user.id)
+        }
+        Err(e) => {
+            warn!(
+                "[identity] resolve {}:{} failed: {:#}",
+                platform, platform_user_id, e
+            );
+            None
+        }
+    }
+}
+
+/// `input.user.id` of the current run, if identity resolved one.
+pub fn current_user_id(ctx: &WorkflowContext) -> Option<String> {
+    ctx.resolve_path("input.user.id")
+        .ok()
+        .flatten()
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty())
+}
+
+// ─── IdentityStore ───────────────────────────────────────────────────────────
+
+pub struct IdentityStore {
+    conn: Mutex<Connection>,
+}
+
+impl IdentityStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS users (
+                id         TEXT PRIMARY KEY,
+                profile    TEXT NOT NULL DEFAULT '{}',
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS accounts (
+                platform         TEXT NOT NULL,
+                platform_user_id TEXT NOT NULL,
+                user_id          TEXT NOT NULL,
+                username         TEXT,
+                first_seen       INTEGER NOT NULL,
+                last_seen        INTEGER NOT NULL,
+                PRIMARY KEY (platform, platform_user_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_accounts_user ON accounts(user_id);
+            CREATE TABLE IF NOT EXISTS link_codes (
+                code       TEXT PRIMARY KEY,
+                user_id    TEXT NOT NULL,
+                expires_at INTEGER NOT NULL
+            );
+            ",
+        )
+        .context("Failed to initialize identity schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Connection) -> Result<R>,
+    {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("identity db lock poisoned"))?;
+        f(&mut conn)
+    }
+
+    /// The user behind an account, creating both on first contact. Refreshes
+    /// the account's username and last-seen time.
+    pub fn resolve(
+        &self,
+        platform: &str,
+        platform_user_id: &str,
+        username: Option<&str>,
+    ) -> Result<UserIdentity> {
+        let now = chrono::Utc::now().timestamp();
+        let user_id = self.with_conn(|c| {
+            let tx = c.transaction()?;
+            let existing: Option<String> = tx
+                .query_row(
+                    "SELECT user_id FROM accounts WHERE platform = ?1 AND platform_user_id = ?2",
+                    params![platform, platform_user_id],
+                    |r| r.get(0),
+                )
+                .optional()?;
+            let user_id = match existing {
+                Some(id) => {
+                    tx.execute(
+                        "UPDATE accounts SET last_seen = ?3, username = COALESCE(?4, username)
+                         WHERE platform = ?1 AND platform_user_id = ?2",
+                        params![platform, platform_user_id, now, username],
+                    )?;
+                    id
+                }
+                None => {
+                    let id = uuid::Uuid::new_v4().to_string();
+                    tx.execute(
+                        "INSERT INTO users (id, profile, created_at, updated_at)
+                         VALUES (?1, '{}', ?2, ?2)",
+                        params![id, now],
+                    )?;
+                    tx.execute(
+                        "INSERT INTO accounts
+                         (platform, platform_user_id, user_id, username, first_seen, last_seen)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                        params![platform, platform_user_id, id, username, now],
+                    )?;
+                    id
+                }
+            };
+            tx.commit()?;
+            Ok(user_id)
+        })?;
+        self.get(&user_id)?
+            .ok_or_else(|| anyhow!("user {} vanished during resolve", user_id))
+    }
+
+    /// A user with all linked accounts.
+    pub fn get(&self, user_id: &str) -> Result<Option<UserIdentity>> {
+        self.with_conn(|c| {
+            let row: Option<(String, i64)> = c
+                .query_row(
+                    "SELECT profile, created_at FROM users WHERE id = ?1",
+                    params![user_id],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .optional()?;
+            let Some((profile, created_at)) = row else {
+                return Ok(None);
+            };
+            let mut stmt = c.prepare(
+                "SELECT platform, platform_user_id, username FROM accounts
+                 WHERE user_id = ?1 ORDER BY first_seen",
+            )?;
+            let accounts = stmt
+                .query_map(params![user_id], |r| {
+                    Ok(Account {
+                        platform: r.get(0)?,
+                        platform_user_id: r.get(1)?,
+                        username: r.get(2)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(Some(UserIdentity {
+                id: user_id.to_string(),
+                profile: serde_json::from_str(&profile).unwrap_or_else(|_| json!({})),
+                accounts,
+                created_at,
+            }))
+        })
+    }
+
+    /// Merge `patch` into the user's profile; `null` values remove a field.
+    pub fn update_profile(&self, user_id: &str, patch: &Map<String, Value>) -> Result<Value> {
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            let raw: String = c
+                .query_row(
+                    "SELECT profile FROM users WHERE id = ?1",
+                    params![user_id],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| anyhow!("unknown user {}", user_id))?;
+            let mut profile: Map<String, Value> = serde_json::from_str(&raw).unwrap_or_default();
+            for (k, v) in patch {
+                if v.is_null() {
+                    profile.remove(k);
+                } else {
+                    profile.insert(k.clone(), v.clone());
+                }
+            }
+            let profile = Value::Object(profile);
+            c.execute(
+                "UPDATE users SET profile = ?2, updated_at = ?3 WHERE id = ?1",
+                params![user_id, profile.to_string(), now],
+            )?;
+            Ok(profile)
+        })
+    }
+
+    /// Issue a single-use code that links another account to `user_id`.
+    pub fn create_link_code(&self, user_id: &str, ttl_secs: u64) -> Result<String> {
+        let now = chrono::Utc::now().timestamp();
+        let mut rng = rand::rng();
+        let code: String = (0..CODE_LEN)
+            .map(|_| CODE_ALPHABET[rng.random_range(0..CODE_ALPHABET.len())] as char)
+            .collect();
+        self.with_conn(|c| {
+            c.execute("DELETE FROM link_codes WHERE expires_at < ?1", params![now])?;
+            c.execute(
+                "INSERT OR REPLACE INTO link_codes (code, user_id, expires_at) VALUES (?1, ?2, ?3)",
+                params![code, user_id, now + ttl_secs as i64],
+            )?;
+            Ok(())
+        })?;
+        Ok(code)
+    }
+
+    /// Redeem a link code from `(platform, platform_user_id)`: that account's
+    /// user is folded into the user who issued the code.
+    pub fn link(&self, code: &str, platform: &str, platform_user_id: &str) -> Result<LinkOutcome> {
+        let now = chrono::Utc::now().timestamp();
+        let code = code.trim().to_ascii_uppercase();
+        let (target, merged_from) = self.with_conn(|c| {
+            let tx = c.transaction()?;
+            let target: String = tx
+                .query_row(
+                    "SELECT user_id FROM link_codes WHERE code = ?1 AND expires_at >= ?2",
+                    params![code, now],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| anyhow!("link code is invalid or has expired"))?;
+            tx.execute("DELETE FROM link_codes WHERE code = ?1", params![code])?;
+
+            let source: Option<String> = tx
+                .query_row(
+                    "SELECT user_id FROM accounts WHERE platform = ?1 AND platform_user_id = ?2",
+                    params![platform, platform_user_id],
+                    |r| r.get(0),
+                )
+                .optional()?;
+            let merged_from = match source {
+                Some(source) if source == target => None,
+                Some(source) => {
+                    merge_users(&tx, &source, &target, now)?;
+                    Some(source)
+                }
+                None => {
+                    tx.execute(
+                        "INSERT INTO accounts
+                         (platform, platform_user_id, user_id, first_seen, last_seen)
+                         VALUES (?1, ?2, ?3, ?4, ?4)",
+                        params![platform, platform_user_id, target, now],
+                    )?;
+                    None
+                }
+            };
+            tx.commit()?;
+            Ok((target, merged_from))
+        })?;
+        let user = self
+            .get(&target)?
+            .ok_or_else(|| anyhow!("link target user {} no longer exists", target))?;
+        Ok(LinkOutcome { user, merged_from })
+    }
+
+    /// Detach an account into a user of its own (empty profile). Returns the
+    /// new user id, or None when the account is already its user's only one.
+    pub fn unlink(&self, platform: &str, platform_user_id: &str) -> Result<Option<String>> {
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            let tx = c.transaction()?;
+            let current: String = tx
+                .query_row(
+                    "SELECT user_id FROM accounts WHERE platform = ?1 AND platform_user_id = ?2",
+                    params![platform, platform_user_id],
+                    |r| r.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| anyhow!("unknown account {}:{}", platform, platform_user_id))?;
+            let siblings: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM accounts WHERE user_id = ?1",
+                params![current],
+                |r| r.get(0),
+            )?;
+            if siblings <= 1 {
+                return Ok(None);
+            }
+            let id = uuid::Uuid::new_v4().to_string();
+            tx.execute(
+                "INSERT INTO users (id, profile, created_at, updated_at) VALUES (?1, '{}', ?2, ?2)",
+                params![id, now],
+            )?;
+            tx.execute(
+                "UPDATE accounts SET user_id = ?3 WHERE platform = ?1 AND platform_user_id = ?2",
+                params![platform, platform_user_id, id],
+            )?;
+            tx.commit()?;
+            Ok(Some(id))
+        })
+    }
+}
+
+/// Move every account of `source` to `target`, fill gaps in the target's
+/// profile from the source's, and delete `source`.
+fn merge_users(tx: &rusqlite::Transaction, source: &str, target: &str, now: i64) -> Result<()> {
+    let load = |id: &str| -> Result<Map<String, Value>> {
+        let raw: Option<String> = tx
+            .query_row(
+                "SELECT profile FROM users WHERE id = ?1",
+                params![id],
+                |r| r.get(0),
+            )
+            .optional()?;
+        Ok(raw
+            .and_then(|r| serde_json::from_str(&r).ok())
+            .unwrap_or_default())
+    };
+    let mut profile = load(target)?;
+    for (k, v) in load(source)? {
+        profile.entry(k).or_insert(v);
+    }
+    tx.execute(
+        "UPDATE users SET profile = ?2, updated_at = ?3 WHERE id = ?1",
+        params![target, Value::Object(profile).to_string(), now],
+    )?;
+    tx.execute(
+        "UPDATE accounts SET user_id = ?2 WHERE user_id = ?1",
+        params![source, target],
+    )?;
+    tx.execute(
+        "UPDATE link_codes SET user_id = ?2 WHERE user_id = ?1",
+        params![source, target],
+    )?;
+    tx.execute("DELETE FROM users WHERE id = ?1", params![source])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_is_stable_and_refreshes_username() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let a = store.resolve("telegram", "42", Some("ann")).unwrap();
+        let b = store.resolve("telegram", "42", Some("ann_new")).unwrap();
+        assert_eq!(a.id, b.id);
+        assert_eq!(b.accounts.len(), 1);
+        assert_eq!(b.accounts[0].username.as_deref(), Some("ann_new"));
+        let other = store.resolve("discord", "42", None).unwrap();
+        assert_ne!(a.id, other.id);
+    }
+
+    #[test]
+    fn web_callers_stay_in_the_web_namespace() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let tg = store.resolve("telegram", "42", Some("ann")).unwrap();
+
+        // A client claiming Ann's Telegram id gets a separate web user.
+        let ctx = WorkflowContext::new();
+        let spoofed =
+            attach_web_caller_with(Some(&store), &ctx, None, false, Some("42"), None).unwrap();
+        assert_ne!(spoofed, tg.id);
+        assert_eq!(
+            ctx.resolve_path("input.platform").unwrap(),
+            Some(json!("web"))
+        );
+
+        // Under [server.auth] the verified subject wins over the claim.
+        let ctx = WorkflowContext::new();
+        let authed =
+            attach_web_caller_with(Some(&store), &ctx, Some("ann@sso"), true, Some("42"), None)
+                .unwrap();
+        assert_eq!(
+            ctx.resolve_path("input.user_id").unwrap(),
+            Some(json!("ann@sso"))
+        );
+        assert_eq!(store.resolve("web", "ann@sso", None).unwrap().id, authed);
+        assert_ne!(authed, spoofed);
+
+        // ... and without credentials the claim is ignored: no user at all.
+        let ctx = WorkflowContext::new();
+        assert!(attach_web_caller_with(Some(&store), &ctx, None, true, Some("42"), None).is_none());
+        assert_eq!(ctx.resolve_path("input.user").unwrap(), Some(Value::Null));
+        assert_eq!(
+            ctx.resolve_path("input.user_id").unwrap(),
+            Some(Value::Null)
+        );
+    }
+
+    #[test]
+    fn request_inputs_cannot_name_the_caller() {
+        let ctx = WorkflowContext::new();
+        let fields = json!({
+            "topic": "tea",
+            "user": { "id": "u-admin" },
+            "user_id": "42",
+            "platform": "telegram",
+            "auth": { "sub": "admin" }
+        });
+        let fields = fields.as_object().unwrap();
+        set_request_inputs(&ctx, fields);
+        assert_eq!(ctx.resolve_path("input.topic").unwrap(), Some(json!("tea")));
+        for key in WEB_RESERVED_INPUTS {
+            let value = ctx.resolve_path(&format!("input.{}", key)).unwrap();
+            assert_eq!(value, Some(Value::Null));
+        }
+        assert_eq!(claimed_user_id(fields).as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn link_merges_accounts_and_profiles() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let tg = store.resolve("telegram", "42", Some("ann")).unwrap();
+        let dc = store.resolve("discord", "777", Some("ann#1")).unwrap();
+        store
+            .update_profile(&tg.id, json!({ "name": "Ann" }).as_object().unwrap())
+            .unwrap();
+        store
+            .update_profile(
+                &dc.id,
+                json!({ "name": "Annie", "tz": "UTC+8" })
+                    .as_object()
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let code = store.create_link_code(&tg.id, 600).unwrap();
+        assert_eq!(code.len(), CODE_LEN);
+        let out = store.link(&code.to_lowercase(), "discord", "777").unwrap();
+        assert_eq!(out.user.id, tg.id);
+        assert_eq!(out.merged_from.as_deref(), Some(dc.id.as_str()));
+        assert_eq!(out.user.accounts.len(), 2);
+        assert_eq!(out.user.profile["name"], "Ann");
+        assert_eq!(out.user.profile["tz"], "UTC+8");
+        assert!(store.get(&dc.id).unwrap().is_none());
+        assert_eq!(store.resolve("discord", "777", None).unwrap().id, tg.id);
+
+        // Single use.
+        assert!(store.link(&code, "web", "u1").is_err());
+    }
+
+    #[test]
+    fn expired_codes_are_rejected() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let tg = store.resolve("telegram", "42", None).unwrap();
+        let code = store.create_link_code(&tg.id, 0).unwrap();
+        store
+            .with_conn(|c| {
+                c.execute("UPDATE link_codes SET expires_at = expires_at - 10", [])?;
+                Ok(())
+            })
+            .unwrap();
+        assert!(store.link(&code, "discord", "777").is_err());
+    }
+
+    #[test]
+    fn unlink_splits_off_a_fresh_user() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let tg = store.resolve("telegram", "42", None).unwrap();
+        let code = store.create_link_code(&tg.id, 600).unwrap();
+        store.link(&code, "web", "ann@example.com").unwrap();
+
+        let new_id = store.unlink("web", "ann@example.com").unwrap().unwrap();
+        assert_ne!(new_id, tg.id);
+        assert_eq!(store.get(&tg.id).unwrap().unwrap().accounts.len(), 1);
+        // The last account of a user stays put.
+        assert!(store.unlink("telegram", "42").unwrap().is_none());
+    }
+
+    #[test]
+    fn profile_patch_removes_null_fields() {
+        let store = IdentityStore::open_in_memory().unwrap();
+        let u = store.resolve("web", "u1", None).unwrap();
+        store
+            .update_profile(&u.id, json!({ "a": 1, "b": 2 }).as_object().unwrap())
+            .unwrap();
+        let p = store
+            .update_profile(&u.id, json!({ "a": null }).as_object().unwrap())
+            .unwrap();
+        assert_eq!(p, json!({ "b": 2 }));
+    }
+}
@@ -0,0 +1,934 @@
+// src/services/jobs.rs
+//
+// Background job queue. `enqueue(workflow="...", input=..., delay="5m")` and
+// `POST /api/jobs` write a job to SQLite and return at once; `juglans worker`
+// (or `juglans serve` with `serve_workers > 0`) claims due jobs and runs the
+// workflow with the job's input as `input.*`:
+//
+//   [jobs]
+//   enabled = true             # default
+//   path = ".juglans/jobs.db"
+//   max_attempts = 3
+//   base_delay_secs = 10       # retry delay, doubles per attempt...
+//   max_delay_secs = 3600      # ...up to this
+//   timeout_secs = 600         # per attempt
+//   serve_workers = 0          # in-process workers for `juglans serve`
+//   retention_days = 7         # succeeded jobs are pruned after this
+//
+// Jobs run highest `priority` first, then oldest `run_at`. A job with a
+// `unique_key` is not enqueued twice while one with the same key is queued or
+// running; the existing id is returned instead. Failed attempts are retried
+// with exponential backoff, and a job that exhausts `max_attempts` is marked
+// `dead` until `POST /api/jobs/{id}/retry` requeues it.
+//
+// Any number of worker processes can share one database: a claim is a single
+// UPDATE, and it leases the job for `timeout_secs`. If a worker dies mid-job
+// the lease expires and another worker picks the job up again. Jobs still
+// running when `juglans serve` gives up draining are released at once,
+// without counting the interrupted attempt.
+//
+// A job keeps the verified claims of whoever enqueued it (`auth`). The worker
+// treats its input like a web request's: `user`, `user_id`, `platform` and
+// `auth` are never taken from the input, the caller is resolved from those
+// claims (`identity::attach_web_caller`), and `input.auth` is set last.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{Notify, Semaphore};
+use tracing::{error, info, warn};
+
+use super::auth::{self, AuthClaims};
+use super::config::{JobsConfig, JuglansConfig};
+use super::identity;
+use crate::core::context::WorkflowContext;
+
+/// Longest an idle worker waits before polling the database again. Jobs
+/// enqueued by another process are only seen by polling.
+const IDLE_POLL_SECS: i64 = 1;
+/// Extra lease on top of `timeout_secs`, so a worker that is about to time a
+/// job out still owns it.
+const LEASE_GRACE_SECS: i64 = 30;
+
+pub const STATUS_QUEUED: &str = "queued";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+pub const STATUS_DEAD: &str = "dead";
+
+/// One row of `jobs`. `output` is `null` until the job succeeds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: i64,
+    pub workflow: String,
+    pub input: Value,
+    pub status: String,
+    pub priority: i64,
+    pub run_at: i64,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub unique_key: Option<String>,
+    pub last_error: Option<String>,
+    pub output: Value,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+    /// Verified claims of the caller that enqueued the job, or `null`.
+    /// Never listed: only the worker sees them.
+    #[serde(skip)]
+    pub auth: Value,
+}
+
+impl Job {
+    /// The job without input and output — what job listings show.
+    pub fn summary(&self) -> Value {
+        json!({
+            "id": self.id,
+            "workflow": self.workflow,
+            "status": self.status,
+            "priority": self.priority,
+            "run_at": self.run_at,
+            "attempts": self.attempts,
+            "max_attempts": self.max_attempts,
+            "unique_key": self.unique_key,
+            "last_error": self.last_error,
+            "created_at": self.created_at,
+            "finished_at": self.finished_at,
+        })
+    }
+}
+
+/// A job to enqueue. `max_attempts` falls back to `[jobs] max_attempts`.
+#[derive(Debug, Clone, Default)]
+pub struct NewJob {
+    pub workflow: String,
+    pub input: Value,
+    pub priority: i64,
+    pub delay: Duration,
+    pub unique_key: Option<String>,
+    pub max_attempts: Option<u32>,
+    /// Verified claims of the caller (`input.auth`), or `null`.
+    pub auth: Value,
+}
+
+/// Outcome of an enqueue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enqueued {
+    pub id: i64,
+    /// False when an active job with the same `unique_key` was returned.
+    pub created: bool,
+}
+
+/// Filters for [`JobStore::list`].
+#[derive(Debug, Default)]
+pub struct JobFilter<'a> {
+    pub workflow: Option<&'a str>,
+    pub status: Option<&'a str>,
+    pub limit: usize,
+}
+
+/// Parse a delay such as `"90"` (seconds), `"30s"`, `"5m"`, `"2h"` or `"1d"`.
+pub fn parse_delay(raw: &str) -> Result<Duration> {
+    let raw = raw.trim();
+    let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (num, unit) = raw.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| anyhow!("invalid delay '{}' (expected e.g. 30s, 5m, 2h)", raw))?;
+    let secs = match unit.trim() {
+        "" | "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86_400,
+        _ => bail!("invalid delay unit in '{}' (use s, m, h or d)", raw),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+/// Delay before attempt `attempts + 1`: exponential from `base_delay_secs`,
+/// capped at `max_delay_secs`.
+pub fn backoff(cfg: &JobsConfig, attempts: u32) -> Duration {
+    let exp = attempts.saturating_sub(1).min(30);
+    let secs = cfg
+        .base_delay_secs
+        .saturating_mul(1u64 << exp)
+        .min(cfg.max_delay_secs);
+    Duration::from_secs(secs)
+}
+
+// ─── Global ──────────────────────────────────────────────────────────────────
+
+static CONFIG: OnceLock<JobsConfig> = OnceLock::new();
+static STORE: OnceLock<Option<Arc<JobStore>>> = OnceLock::new();
+static WAKE: OnceLock<Notify> = OnceLock::new();
+static RUNNING: OnceLock<Mutex<HashSet<i64>>> = OnceLock::new();
+
+/// Install the `[jobs]` section. Idempotent — only the first call takes
+/// effect. The database is opened lazily on first use.
+pub fn init_global(cfg: &JobsConfig) {
+    let _ = CONFIG.set(cfg.clone());
+}
+
+/// The active config (defaults when init_global has not been called).
+pub fn global_config() -> JobsConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// The shared queue. None when jobs are disabled, init_global has not been
+/// called, or the database can't be opened.
+pub fn global_store() -> Option<Arc<JobStore>> {
+    STORE
+        .get_or_init(|| {
+            let cfg = CONFIG.get()?;
+            if !cfg.enabled {
+                return None;
+            }
+            match JobStore::open(&store_path(cfg)) {
+                Ok(store) => Some(Arc::new(store)),
+                Err(e) => {
+                    warn!("[jobs] disabled: {:#}", e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Database location for `cfg`.
+pub fn store_path(cfg: &JobsConfig) -> PathBuf {
+    cfg.path
+        .clone()
+        .unwrap_or_else(|| ".juglans/jobs.db".into())
+        .into()
+}
+
+fn wake_signal() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+fn running() -> &'static Mutex<HashSet<i64>> {
+    RUNNING.get_or_init(Default::default)
+}
+
+/// Requeue the jobs this process is still running, for a shutdown that
+/// can't wait for them. Returns how many were released.
+pub fn release_running() -> usize {
+    let ids: Vec<i64> = running().lock().unwrap().drain().collect();
+    let Some(store) = global_store() else {
+        return 0;
+    };
+    let now = chrono::Utc::now().timestamp();
+    ids.into_iter()
+        .filter(|id| match store.release(*id, now) {
+            Ok(released) => released,
+            Err(e) => {
+                warn!("[jobs] #{} could not be released: {:#}", id, e);
+                false
+            }
+        })
+        .count()
+}
+
+/// Enqueue on the shared queue and wake in-process workers.
+pub fn enqueue(job: NewJob) -> Result<Enqueued> {
+    let store =
+        global_store().ok_or_else(|| anyhow!("job queue is disabled ([jobs] enabled = false)"))?;
+    let now = chrono::Utc::now().timestamp();
+    let result = store.enqueue(&job, global_config().max_attempts, now)?;
+    if result.created {
+        wake_signal().notify_waiters();
+    }
+    Ok(result)
+}
+
+// ─── Worker ──────────────────────────────────────────────────────────────────
+
+/// Consume the queue with up to `concurrency` jobs in flight until `shutdown`
+/// resolves, then wait for running jobs to finish.
+pub async fn run_worker(
+    project_root: PathBuf,
+    concurrency: usize,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let store =
+        global_store().ok_or_else(|| anyhow!("job queue is disabled ([jobs] enabled = false)"))?;
+    let cfg = global_config();
+    let concurrency = concurrency.max(1);
+    if cfg.retention_days > 0 {
+        let cutoff = chrono::Utc::now().timestamp() - i64::from(cfg.retention_days) * 86_400;
+        match store.prune(cutoff) {
+            Ok(0) => {}
+            Ok(n) => info!("[jobs] pruned {} succeeded job(s)", n),
+            Err(e) => warn!("[jobs] retention prune failed: {:#}", e),
+        }
+    }
+    info!(
+        "[jobs] worker started ({} slot(s), {})",
+        concurrency,
+        store_path(&cfg).display()
+    );
+
+    let slots = Arc::new(Semaphore::new(concurrency));
+    let project_root = Arc::new(project_root);
+    tokio::pin!(shutdown);
+    loop {
+        let permit = tokio::select! {
+            _ = &mut shutdown => break,
+            permit = slots.clone().acquire_owned() => permit?,
+        };
+        let now = chrono::Utc::now().timestamp();
+        let lease = cfg.timeout_secs as i64 + LEASE_GRACE_SECS;
+        let claimed = match store.claim(now, now + lease) {
+            Ok(job) => job,
+            Err(e) => {
+                warn!("[jobs] claim failed: {:#}", e);
+                None
+            }
+        };
+        let Some(job) = claimed else {
+            drop(permit);
+            let sleep = match store.next_run_at() {
+                Ok(Some(at)) => (at - now).clamp(0, IDLE_POLL_SECS),
+                _ => IDLE_POLL_SECS,
+            };
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = wake_signal().notified() => {}
+                _ = tokio::time::sleep(Duration::from_millis((sleep * 1000).max(200) as u64)) => {}
+            }
+            continue;
+        };
+        let store = store.clone();
+        let cfg = cfg.clone();
+        let project_root = project_root.clone();
+        tokio::spawn(async move {
+            process(&store, &cfg, &project_root, job).await;
+            drop(permit);
+        });
+    }
+
+    info!("[jobs] worker stopping; waiting for running jobs");
+    let _ = slots.acquire_many(concurrency as u32).await;
+    Ok(())
+}
+
+/// Start `count` in-process workers for `juglans serve`; they stop claiming
+/// once the server starts draining. No-op when the queue is disabled or
+/// `count` is 0.
+pub fn spawn_workers(project_root: PathBuf, count: usize) {
+    if count == 0 || global_store().is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        if let Err(e) = run_worker(project_root, count, super::shutdown::drain_started()).await {
+            error!("[jobs] worker exited: {:#}", e);
+        }
+    });
+}
+
+/// Run one claimed attempt and record the outcome.
+async fn process(store: &JobStore, cfg: &JobsConfig, project_root: &Path, job: Job) {
+    let now = chrono::Utc::now().timestamp();
+    // A worker died holding the job's final attempt.
+    if job.attempts > job.max_attempts {
+        let error = job
+            .last_error
+            .clone()
+            .unwrap_or_else(|| "worker stopped before the job finished".into());
+        if let Err(e) = store.fail(job.id, &error, None, now) {
+            warn!("[jobs] #{} could not be dead-lettered: {:#}", job.id, e);
+        }
+        return;
+    }
+
+    info!(
+        "▶️  [jobs] #{} {} (attempt {}/{})",
+        job.id, job.workflow, job.attempts, job.max_attempts
+    );
+    let started = std::time::Instant::now();
+    let timeout = Duration::from_secs(cfg.timeout_secs.max(1));
+    running().lock().unwrap().insert(job.id);
+    let result = match tokio::time::timeout(timeout, execute(project_root, &job)).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow!("timed out after {}s", timeout.as_secs())),
+    };
+    if !running().lock().unwrap().remove(&job.id) {
+        // Released by a shutdown in the meantime; the queue owns it again.
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    let recorded = match result {
+        Ok(output) => {
+            info!(
+                "✅ [jobs] #{} {} succeeded in {}ms",
+                job.id,
+                job.workflow,
+                started.elapsed().as_millis()
+            );
+            store.complete(job.id, &output, now)
+        }
+        Err(e) => {
+            let error = format!("{:#}", e);
+            if job.attempts >= job.max_attempts {
+                error!(
+                    "❌ [jobs] #{} {} dead after {} attempt(s): {}",
+                    job.id, job.workflow, job.attempts, error
+                );
+                store.fail(job.id, &error, None, now)
+            } else {
+                let delay = backoff(cfg, job.attempts);
+                warn!(
+                    "[jobs] #{} {} failed ({}); retry in {}s",
+                    job.id,
+                    job.workflow,
+                    error,
+                    delay.as_secs()
+                );
+                store.fail(job.id, &error, Some(now + delay.as_secs() as i64), now)
+            }
+        }
+    };
+    if let Err(e) = recorded {
+        warn!("[jobs] #{} outcome not recorded: {:#}", job.id, e);
+    }
+}
+
+/// Execute the job's workflow; returns the final `output` variable.
+async fn execute(project_root: &Path, job: &Job) -> Result<Value> {
+    use crate::services::web_server::{build_request_executor, load_workflow};
+
+    let _run = crate::services::metrics::ActiveRun::start("job");
+    let graph = load_workflow(project_root, &job.workflow)?;
+    let config = JuglansConfig::load()?;
+    let executor = build_request_executor(project_root, &config).await;
+
+    let ctx = WorkflowContext::new();
+    let mut claimed_user_id = None;
+    if let Some(obj) = job.input.as_object() {
+        claimed_user_id = identity::claimed_user_id(obj);
+        identity::set_request_inputs(&ctx, obj);
+    }
+    ctx.set("input.job_id".into(), json!(job.id)).ok();
+    ctx.set("input.job_attempt".into(), json!(job.attempts))
+        .ok();
+    let claims = job.auth.is_object().then(|| AuthClaims(job.auth.clone()));
+    let username = job.input["username"].as_str();
+    identity::attach_web_caller(
+        &ctx,
+        claims.as_ref(),
+        config.server.auth.is_some(),
+        claimed_user_id.as_deref(),
+        username,
+    );
+    // Set last so the input can't spoof the verified identity.
+    auth::set_input_auth(&ctx, claims.as_ref());
+
+    executor.execute_graph(Arc::new(graph), &ctx).await?;
+    Ok(ctx
+        .resolve_path("output")
+        .ok()
+        .flatten()
+        .unwrap_or(Value::Null))
+}
+
+// ─── Store ───────────────────────────────────────────────────────────────────
+
+pub struct JobStore {
+    conn: Mutex<Connection>,
+}
+
+const JOB_COLUMNS: &str = "id, workflow, input, status, priority, run_at, attempts, max_attempts,
+     unique_key, last_error, output, created_at, started_at, finished_at, auth";
+
+fn json_from(raw: Option<String>) -> Value {
+    raw.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn job_from_row(row: &Row) -> rusqlite::Result<Job> {
+    Ok(Job {
+        id: row.get(0)?,
+        workflow: row.get(1)?,
+        input: json_from(row.get(2)?),
+        status: row.get(3)?,
+        priority: row.get(4)?,
+        run_at: row.get(5)?,
+        attempts: row.get(6)?,
+        max_attempts: row.get(7)?,
+        unique_key: row.get(8)?,
+        last_error: row.get(9)?,
+        output: json_from(row.get(10)?),
+        created_at: row.get(11)?,
+        started_at: row.get(12)?,
+        finished_at: row.get(13)?,
+        auth: json_from(row.get(14)?),
+    })
+}
+
+impl JobStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        // Producers and workers may be separate processes.
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        Self::from_connection(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS jobs (
+                id           INTEGER PRIMARY KEY AUTOINCREMENT,
+                workflow     TEXT NOT NULL,
+                input        TEXT,
+                status       TEXT NOT NULL,
+                priority     INTEGER NOT NULL DEFAULT 0,
+                run_at       INTEGER NOT NULL,
+                attempts     INTEGER NOT NULL DEFAULT 0,
+                max_attempts INTEGER NOT NULL,
+                unique_key   TEXT,
+                last_error   TEXT,
+                output       TEXT,
+                locked_until INTEGER,
+                created_at   INTEGER NOT NULL,
+                started_at   INTEGER,
+                finished_at  INTEGER,
+                auth         TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_jobs_due
+                ON jobs(status, priority DESC, run_at, id);
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_unique_active
+                ON jobs(unique_key)
+                WHERE unique_key IS NOT NULL AND status IN ('queued', 'running');
+            ",
+        )
+        .context("Failed to initialize jobs schema")?;
+        // Databases created before jobs kept the caller's claims.
+        if conn.prepare("SELECT auth FROM jobs LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE jobs ADD COLUMN auth TEXT", [])
+                .context("Failed to migrate jobs schema")?;
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Connection) -> Result<R>,
+    {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("jobs db lock poisoned"))?;
+        f(&mut conn)
+    }
+
+    /// Insert a job due at `now + delay`, or return the active job holding
+    /// the same `unique_key`.
+    pub fn enqueue(&self, job: &NewJob, default_max_attempts: u32, now: i64) -> Result<Enqueued> {
+        let input = (!job.input.is_null()).then(|| job.input.to_string());
+        let auth = (!job.auth.is_null()).then(|| job.auth.to_string());
+        let max_attempts = job.max_attempts.unwrap_or(default_max_attempts).max(1);
+        let run_at = now + job.delay.as_secs() as i64;
+        self.with_conn(|c| {
+            let tx = c.transaction()?;
+            if let Some(key) = &job.unique_key {
+                let existing: Option<i64> = tx
+                    .query_row(
+                        "SELECT id FROM jobs
+                         WHERE unique_key = ?1 AND status IN ('queued', 'running')",
+                        params![key],
+                        |r| r.get(0),
+                    )
+                    .optional()?;
+                if let Some(id) = existing {
+                    return Ok(Enqueued { id, created: false });
+                }
+            }
+            tx.execute(
+                "INSERT INTO jobs (workflow, input, status, priority, run_at, max_attempts,
+                                   unique_key, created_at, auth)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    job.workflow,
+                    input,
+                    STATUS_QUEUED,
+                    job.priority,
+                    run_at,
+                    max_attempts,
+                    job.unique_key,
+                    now,
+                    auth
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            tx.commit()?;
+            Ok(Enqueued { id, created: true })
+        })
+    }
+
+    /// Lease the next due job until `locked_until`, counting the attempt.
+    /// Running jobs whose lease has expired are due again.
+    pub fn claim(&self, now: i64, locked_until: i64) -> Result<Option<Job>> {
+        self.with_conn(|c| {
+            Ok(c.query_row(
+                &format!(
+                    "UPDATE jobs
+                     SET status = ?3, attempts = attempts + 1, locked_until = ?2,
+                         started_at = ?1
+                     WHERE id = (SELECT id FROM jobs
+                                 WHERE (status = ?4 AND run_at <= ?1)
+                                    OR (status = ?3 AND locked_until <= ?1)
+                                 ORDER BY priority DESC, run_at, id LIMIT 1)
+                     RETURNING {}",
+                    JOB_COLUMNS
+                ),
+                params![now, locked_until, STATUS_RUNNING, STATUS_QUEUED],
+                job_from_row,
+            )
+            .optional()?)
+        })
+    }
+
+    pub fn complete(&self, id: i64, output: &Value, now: i64) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute(
+                "UPDATE jobs SET status = ?2, output = ?3, last_error = NULL,
+                                 locked_until = NULL, finished_at = ?4
+                 WHERE id = ?1",
+                params![id, STATUS_SUCCEEDED, output.to_string(), now],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Record a failed attempt: requeue at `retry_at`, or dead-letter with `None`.
+    pub fn fail(&self, id: i64, error: &str, retry_at: Option<i64>, now: i64) -> Result<()> {
+        self.with_conn(|c| {
+            match retry_at {
+                Some(at) => c.execute(
+                    "UPDATE jobs SET status = ?2, run_at = ?3, last_error = ?4,
+                                     locked_until = NULL
+                     WHERE id = ?1",
+                    params![id, STATUS_QUEUED, at, error],
+                )?,
+                None => c.execute(
+                    "UPDATE jobs SET status = ?2, last_error = ?3, locked_until = NULL,
+                                     finished_at = ?4
+                     WHERE id = ?1",
+                    params![id, STATUS_DEAD, error, now],
+                )?,
+            };
+            Ok(())
+        })
+    }
+
+    /// Hand a running job back to the queue, due at `now`, without counting
+    /// the attempt. Returns false when the job isn't running.
+    pub fn release(&self, id: i64, now: i64) -> Result<bool> {
+        self.with_conn(|c| {
+            let changed = c.execute(
+                "UPDATE jobs SET status = ?2, run_at = ?3, attempts = MAX(attempts - 1, 0),
+                                 locked_until = NULL, started_at = NULL
+                 WHERE id = ?1 AND status = ?4",
+                params![id, STATUS_QUEUED, now, STATUS_RUNNING],
+            )?;
+            Ok(changed > 0)
+        })
+    }
+
+    /// Requeue a dead job for immediate execution with a fresh attempt budget.
+    /// Returns false when the job doesn't exist or isn't dead.
+    pub fn retry(&self, id: i64, now: i64) -> Result<bool> {
+        self.with_conn(|c| {
+            let changed = c
+                .execute(
+                    "UPDATE jobs SET status = ?2, attempts = 0, run_at = ?3,
+                                     finished_at = NULL
+                     WHERE id = ?1 AND status = ?4",
+                    params![id, STATUS_QUEUED, now, STATUS_DEAD],
+                )
+                .map_err(|e| match e {
+                    rusqlite::Error::SqliteFailure(f, _)
+                        if f.code == rusqlite::ErrorCode::ConstraintViolation =>
+                    {
+                        anyhow!("another job with the same unique_key is already active")
+                    }
+                    e => e.into(),
+                })?;
+            Ok(changed > 0)
+        })
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<Job>> {
+        self.with_conn(|c| {
+            Ok(c.query_row(
+                &format!("SELECT {} FROM jobs WHERE id = ?1", JOB_COLUMNS),
+                params![id],
+                job_from_row,
+            )
+            .optional()?)
+        })
+    }
+
+    /// Newest first.
+    pub fn list(&self, filter: &JobFilter) -> Result<Vec<Job>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(&format!(
+                "SELECT {} FROM jobs
+                 WHERE (?1 IS NULL OR workflow = ?1) AND (?2 IS NULL OR status = ?2)
+                 ORDER BY id DESC LIMIT ?3",
+                JOB_COLUMNS
+            ))?;
+            let rows = stmt.query_map(
+                params![filter.workflow, filter.status, filter.limit as i64],
+                job_from_row,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// Job count per status.
+    pub fn counts(&self) -> Result<Value> {
+        self.with_conn(|c| {
+            let mut counts = json!({
+                STATUS_QUEUED: 0,
+                STATUS_RUNNING: 0,
+                STATUS_SUCCEEDED: 0,
+                STATUS_DEAD: 0,
+            });
+            let mut stmt = c.prepare("SELECT status, COUNT(*) FROM jobs GROUP BY status")?;
+            let rows = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))?;
+            for row in rows {
+                let (status, n) = row?;
+                counts[status] = json!(n);
+            }
+            Ok(counts)
+        })
+    }
+
+    /// When the next queued job becomes due.
+    pub fn next_run_at(&self) -> Result<Option<i64>> {
+        self.with_conn(|c| {
+            Ok(c.query_row(
+                "SELECT MIN(run_at) FROM jobs WHERE status = ?1",
+                params![STATUS_QUEUED],
+                |r| r.get(0),
+            )?)
+        })
+    }
+
+    /// Delete succeeded jobs finished before `cutoff`.
+    pub fn prune(&self, cutoff: i64) -> Result<usize> {
+        self.with_conn(|c| {
+            Ok(c.execute(
+                "DELETE FROM jobs WHERE status = ?1 AND finished_at < ?2",
+                params![STATUS_SUCCEEDED, cutoff],
+            )?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(workflow: &str) -> NewJob {
+        NewJob {
+            workflow: workflow.into(),
+            input: json!({ "n": 1 }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_delays() {
+        assert_eq!(parse_delay("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_delay("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_delay("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_delay(" 2h ").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_delay("1d").unwrap(), Duration::from_secs(86_400));
+        assert!(parse_delay("5 weeks").is_err());
+        assert!(parse_delay("m").is_err());
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let cfg = JobsConfig::default();
+        assert_eq!(backoff(&cfg, 1), Duration::from_secs(10));
+        assert_eq!(backoff(&cfg, 3), Duration::from_secs(40));
+        assert_eq!(backoff(&cfg, 40), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn claims_by_priority_then_due_time() {
+        let store = JobStore::open_in_memory().unwrap();
+        let low = store.enqueue(&job("low"), 3, 100).unwrap().id;
+        let later = store
+            .enqueue(
+                &NewJob {
+                    priority: 10,
+                    delay: Duration::from_secs(60),
+                    ..job("later")
+                },
+                3,
+                100,
+            )
+            .unwrap()
+            .id;
+        let high = store
+            .enqueue(
+                &NewJob {
+                    priority: 5,
+                    ..job("high")
+                },
+                3,
+                100,
+            )
+            .unwrap()
+            .id;
+
+        let first = store.claim(100, 700).unwrap().unwrap();
+        assert_eq!((first.id, first.status.as_str()), (high, STATUS_RUNNING));
+        assert_eq!(first.attempts, 1);
+        assert_eq!(first.input["n"], 1);
+        assert_eq!(store.claim(100, 700).unwrap().unwrap().id, low);
+        // `later` isn't due yet.
+        assert!(store.claim(100, 700).unwrap().is_none());
+        assert_eq!(store.next_run_at().unwrap(), Some(160));
+        assert_eq!(store.claim(160, 700).unwrap().unwrap().id, later);
+    }
+
+    #[test]
+    fn unique_key_dedupes_active_jobs() {
+        let store = JobStore::open_in_memory().unwrap();
+        let keyed = || NewJob {
+            unique_key: Some("order-1".into()),
+            ..job("sync")
+        };
+        let first = store.enqueue(&keyed(), 3, 0).unwrap();
+        assert!(first.created);
+        let dup = store.enqueue(&keyed(), 3, 0).unwrap();
+        assert_eq!(
+            dup,
+            Enqueued {
+                id: first.id,
+                created: false
+            }
+        );
+
+        store.claim(0, 600).unwrap().unwrap();
+        store.complete(first.id, &json!("ok"), 5).unwrap();
+        let again = store.enqueue(&keyed(), 3, 10).unwrap();
+        assert!(again.created);
+        assert_ne!(again.id, first.id);
+        assert_eq!(store.get(first.id).unwrap().unwrap().output, json!("ok"));
+    }
+
+    #[test]
+    fn failed_jobs_retry_then_dead_letter() {
+        let store = JobStore::open_in_memory().unwrap();
+        let id = store.enqueue(&job("flaky"), 2, 0).unwrap().id;
+
+        store.claim(0, 600).unwrap().unwrap();
+        store.fail(id, "boom", Some(20), 0).unwrap();
+        let queued = store.get(id).unwrap().unwrap();
+        assert_eq!((queued.status.as_str(), queued.run_at), (STATUS_QUEUED, 20));
+        assert!(store.claim(10, 600).unwrap().is_none());
+
+        let second = store.claim(20, 600).unwrap().unwrap();
+        assert_eq!(second.attempts, 2);
+        store.fail(id, "boom again", None, 21).unwrap();
+        let dead = store.get(id).unwrap().unwrap();
+        assert_eq!(dead.status, STATUS_DEAD);
+        assert_eq!(dead.last_error.as_deref(), Some("boom again"));
+        assert_eq!(store.counts().unwrap()[STATUS_DEAD], 1);
+
+        assert!(store.retry(id, 30).unwrap());
+        assert!(!store.retry(id, 30).unwrap());
+        let requeued = store.claim(30, 600).unwrap().unwrap();
+        assert_eq!((requeued.id, requeued.attempts), (id, 1));
+    }
+
+    #[test]
+    fn released_jobs_keep_their_attempt() {
+        let store = JobStore::open_in_memory().unwrap();
+        let id = store.enqueue(&job("drained"), 3, 0).unwrap().id;
+        store.claim(0, 600).unwrap().unwrap();
+
+        assert!(store.release(id, 50).unwrap());
+        assert!(!store.release(id, 50).unwrap());
+        let queued = store.get(id).unwrap().unwrap();
+        assert_eq!(
+            (queued.status.as_str(), queued.attempts),
+            (STATUS_QUEUED, 0)
+        );
+        let again = store.claim(50, 600).unwrap().unwrap();
+        assert_eq!((again.id, again.attempts), (id, 1));
+    }
+
+    #[test]
+    fn expired_leases_are_reclaimed() {
+        let store = JobStore::open_in_memory().unwrap();
+        let id = store.enqueue(&job("long"), 3, 0).unwrap().id;
+        store.claim(0, 100).unwrap().unwrap();
+        assert!(store.claim(50, 150).unwrap().is_none());
+        let reclaimed = store.claim(100, 200).unwrap().unwrap();
+        assert_eq!((reclaimed.id, reclaimed.attempts), (id, 2));
+    }
+
+    #[test]
+    fn claims_travel_with_the_job_but_are_not_listed() {
+        let store = JobStore::open_in_memory().unwrap();
+        let mut new = job("report");
+        new.auth = json!({ "sub": "ann", "method": "jwt" });
+        store.enqueue(&new, 3, 0).unwrap();
+        let claimed = store.claim(0, 100).unwrap().unwrap();
+        assert_eq!(claimed.auth["sub"], "ann");
+        assert!(serde_json::to_value(&claimed)
+            .unwrap()
+            .get("auth")
+            .is_none());
+    }
+
+    #[test]
+    fn old_databases_gain_the_auth_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, workflow TEXT NOT NULL, input TEXT,
+                status TEXT NOT NULL, priority INTEGER NOT NULL DEFAULT 0,
+                run_at INTEGER NOT NULL, attempts INTEGER NOT NULL DEFAULT 0,
+                max_attempts INTEGER NOT NULL, unique_key TEXT, last_error TEXT,
+                output TEXT, locked_until INTEGER, created_at INTEGER NOT NULL,
+                started_at INTEGER, finished_at INTEGER
+            );
+            INSERT INTO jobs (workflow, status, run_at, max_attempts, created_at)
+                VALUES ('old', 'queued', 0, 3, 0);",
+        )
+        .unwrap();
+        let store = JobStore::from_connection(conn).unwrap();
+        let old = store.claim(0, 100).unwrap().unwrap();
+        assert_eq!((old.workflow.as_str(), &old.auth), ("old", &Value::Null));
+    }
+}
@@ -4,14 +4,17 @@
 // the providers layer using API keys configured locally. juglans is local-first;
 // there is no remote backend dependency.
 
+use crate::core::context::UserInbox;
 use crate::providers::llm::{Message, ToolCallChunk};
 use crate::providers::ProviderFactory;
 use crate::services::config::AiConfig;
+use crate::services::metrics;
 use anyhow::Result;
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde_json::{json, Value};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc::UnboundedSender;
 
 // ─── Public types (moved from the former services::interface module) ────────
@@ -26,6 +29,16 @@ pub enum ChatOutput {
         _calls: Vec<Value>,
         _chat_id: String,
     },
+    /// A tool call transferred control to another agent; `text` is whatever
+    /// the current agent said before handing off.
+    Handoff { handoff: Handoff, text: String },
+}
+
+/// Control transfer requested from inside a tool call (multi-agent handoff).
+#[derive(Debug, Clone)]
+pub struct Handoff {
+    pub target: String,
+    pub reason: String,
 }
 
 /// Tool execution callback — provided by the caller, invoked inline when
@@ -39,6 +52,12 @@ pub trait ChatToolHandler: Send + Sync {
     fn take_pending_tools(&self) -> Option<Vec<Value>> {
         None
     }
+    /// Take a handoff requested by one of the tool calls just executed.
+    /// When `Some`, the tool loop stops and `chat()` returns
+    /// `ChatOutput::Handoff` instead of re-invoking the model.
+    fn take_handoff(&self) -> Option<Handoff> {
+        None
+    }
 }
 
 /// Chat request parameters for `LocalRuntime::chat()`.
@@ -48,6 +67,9 @@ pub struct ChatRequest {
     pub tools: Option<Vec<Value>>,
     pub token_sender: Option<UnboundedSender<String>>,
     pub tool_handler: Option<Arc<dyn ChatToolHandler>>,
+    /// Messages sent by the user while this call runs; appended to the
+    /// conversation after each tool round.
+    pub user_inbox: Option<UserInbox>,
 }
 
 // ─── LocalRuntime ───────────────────────────────────────────────────────────
@@ -165,23 +187,49 @@ impl LocalRuntime {
 
         // Tool call loop (max 50 iterations)
         for _ in 0..50 {
-            let (provider, actual_model) = self.factory.get_provider(&model);
+            let (provider, actual_model, provider_name) = self.factory.resolve(&model);
 
-            let mut stream = provider
+            let started = Instant::now();
+            let record = |ok: bool| {
+                metrics::record_llm_request(&provider_name, &actual_model, ok, started.elapsed())
+            };
+            let mut stream = match provider
                 .stream_chat(
                     &actual_model,
                     system_prompt.clone(),
                     history.clone(),
                     tools.clone(),
                 )
-                .await?;
+                .await
+            {
+                Ok(stream) => stream,
+                Err(e) => {
+                    record(false);
+                    return Err(e);
+                }
+            };
 
             let mut text_acc = String::new();
             let mut tool_accs: Vec<ToolCallAccumulator> = Vec::new();
             let mut has_tool_finish = false;
 
             while let Some(chunk_result) = stream.next().await {
-                let chunk = chunk_result?;
+                let chunk = match chunk_result {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        record(false);
+                        return Err(e);
+                    }
+                };
+
+                if let Some(usage) = &chunk.usage {
+                    metrics::record_llm_tokens(
+                        &provider_name,
+                        &actual_model,
+                        usage.input_tokens.max(0) as u64,
+                        usage.output_tokens.max(0) as u64,
+                    );
+                }
 
                 // Stream text tokens to caller
                 if let Some(content) = &chunk.content {
@@ -204,6 +252,7 @@ impl LocalRuntime {
                     }
                 }
             }
+            record(true);
 
             // No tool calls → return final text
             if !has_tool_finish || tool_accs.is_empty() {
@@ -267,11 +316,31 @@ impl LocalRuntime {
                 });
             }
 
+            // A handoff ends this agent's turn; the caller re-runs the
+            // conversation with the target agent.
+            if let Some(handoff) = handler.take_handoff() {
+                return Ok(ChatOutput::Handoff {
+                    handoff,
+                    text: text_acc,
+                });
+            }
+
             // Check for dynamic tools from handler
             if let Some(new_tools) = handler.take_pending_tools() {
                 tools = Some(new_tools);
             }
 
+            // Messages the user sent mid-run join the conversation before
+            // the next model call.
+            for text in req.user_inbox.iter().flat_map(|inbox| inbox.take()) {
+                history.push(Message {
+                    role: "user".to_string(),
+                    parts: json!([{"type": "text", "content": text}]),
+                    tool_calls: None,
+                    tool_call_id: None,
+                });
+            }
+
             // Loop: re-invoke LLM with updated history
         }
 
@@ -0,0 +1,783 @@
+// src/services/memory.rs
+//
+// Long-term memory: facts about a user that outlive any one chat_id.
+//
+//   [memory]
+//   enabled = true
+//   path = ".juglans/memory.db"
+//   recall_limit = 5
+//
+//   [memory.embedding]               # optional; keyword recall otherwise
+//   model = "text-embedding-3-small"
+//
+// Each memory is scoped to a user and, optionally, an agent. A memory saved
+// with an empty agent is shared by every agent talking to that user. An
+// optional `key` makes `remember` an upsert ("favorite_color" is replaced,
+// not duplicated).
+//
+// History (services/history.rs) is the transcript of one thread; this is
+// what the bot knows about the person across threads and channels.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::warn;
+
+use super::config::{EmbeddingConfig, MemoryConfig};
+use crate::core::context::WorkflowContext;
+
+/// Upper bound on rows scored per recall.
+const MAX_CANDIDATES: usize = 2000;
+
+/// Embedding similarity a memory needs before `Forget::Query` deletes it.
+/// Keyword matches must contain every query term instead.
+const FORGET_MIN_SIMILARITY: f32 = 0.8;
+
+#[derive(Debug, Clone)]
+pub struct MemoryRecord {
+    pub id: String,
+    pub user: String,
+    pub agent: String,
+    pub key: Option<String>,
+    pub content: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Relevance for recall results; None for list().
+    pub score: Option<f32>,
+}
+
+impl MemoryRecord {
+    pub fn to_json(&self) -> Value {
+        let mut obj = json!({
+            "id": self.id,
+            "user": self.user,
+            "agent": self.agent,
+            "content": self.content,
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+        });
+        if let Some(ref k) = self.key {
+            obj["key"] = json!(k);
+        }
+        if let Some(s) = self.score {
+            obj["score"] = json!(s);
+        }
+        obj
+    }
+}
+
+/// What `forget` removes, always within one user (and agent, if given).
+pub enum Forget<'a> {
+    Id(&'a str),
+    Key(&'a str),
+    /// The single best match for the query, and only if it matches closely:
+    /// embedding similarity of at least FORGET_MIN_SIMILARITY, or every
+    /// query term present. A vague query removes nothing.
+    Query(&'a str),
+    All,
+}
+
+// ─── Global ──────────────────────────────────────────────────────────────────
+
+static CONFIG: OnceLock<MemoryConfig> = OnceLock::new();
+static BANK: OnceLock<Option<Arc<MemoryBank>>> = OnceLock::new();
+
+/// Install the `[memory]` section. Idempotent — only the first call takes
+/// effect. The database is opened lazily on first use, so projects that
+/// never touch memory don't get a memory.db.
+pub fn init_global(cfg: &MemoryConfig) {
+    let _ = CONFIG.set(cfg.clone());
+}
+
+/// The active config (defaults when init_global has not been called).
+pub fn global_config() -> MemoryConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// The shared memory bank. None when memory is disabled, init_global has
+/// not been called, or the database can't be opened.
+pub fn global_bank() -> Option<Arc<MemoryBank>> {
+    BANK.get_or_init(|| {
+        let cfg = CONFIG.get()?;
+        if !cfg.enabled {
+            return None;
+        }
+        let path = cfg
+            .path
+            .clone()
+            .unwrap_or_else(|| ".juglans/memory.db".into());
+        match MemoryBank::open(Path::new(&path), cfg.embedding.as_ref()) {
+            Ok(bank) => Some(Arc::new(bank)),
+            Err(e) => {
+                warn!("[memory] disabled: {:#}", e);
+                None
+            }
+        }
+    })
+    .clone()
+}
+
+/// Default user scope for the current run: the cross-channel `input.user.id`
+/// when identity is enabled, otherwise `<platform>:<user_id>` — the same
+/// person on Telegram and Discord then gets separate scopes unless the
+/// workflow passes an explicit `user=`.
+pub fn default_user(ctx: &WorkflowContext) -> Option<String> {
+    if let Some(id) = super::identity::current_user_id(ctx) {
+        return Some(id);
+    }
+    let get = |path: &str| {
+        ctx.resolve_path(path).ok().flatten().and_then(|v| match v {
+            Value::String(s) if !s.is_empty() => Some(s),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+    };
+    let user_id = get("input.user_id")?;
+    Some(match get("input.platform") {
+        Some(platform) => format!("{}:{}", platform, user_id),
+        None => user_id,
+    })
+}
+
+// ─── MemoryBank ──────────────────────────────────────────────────────────────
+
+pub struct MemoryBank {
+    conn: Mutex<Connection>,
+    embedder: Option<Embedder>,
+}
+
+impl MemoryBank {
+    pub fn open(path: &Path, embedding: Option<&EmbeddingConfig>) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(conn, embedding)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?, None)
+    }
+
+    fn from_connection(conn: Connection, embedding: Option<&EmbeddingConfig>) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS memories (
+                id              TEXT PRIMARY KEY,
+                user_id         TEXT NOT NULL,
+                agent           TEXT NOT NULL DEFAULT '',
+                key             TEXT,
+                content         TEXT NOT NULL,
+                embedding       BLOB,
+                embedding_model TEXT,
+                created_at      INTEGER NOT NULL,
+                updated_at      INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_memories_scope
+                ON memories(user_id, agent, updated_at);
+            ",
+        )
+        .context("Failed to initialize memory schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            embedder: embedding.map(Embedder::new),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Connection) -> Result<R>,
+    {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("memory db lock poisoned"))?;
+        f(&conn)
+    }
+
+    /// Embed `text` if an embedding model is configured. Failures degrade
+    /// to keyword recall instead of failing the caller.
+    async fn embed(&self, text: &str) -> Option<(Vec<f32>, String)> {
+        let embedder = self.embedder.as_ref()?;
+        match embedder.embed(text).await {
+            Ok(v) => Some((v, embedder.model.clone())),
+            Err(e) => {
+                warn!("[memory] embedding failed, using keyword recall: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Store a memory. With a `key`, replaces the existing memory with the
+    /// same user/agent/key; without one, an identical memory is refreshed
+    /// rather than duplicated.
+    pub async fn remember(
+        &self,
+        user: &str,
+        agent: &str,
+        key: Option<&str>,
+        content: &str,
+    ) -> Result<MemoryRecord> {
+        let embedding = self.embed(content).await;
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            let existing: Option<(String, i64)> = match key {
+                Some(k) => c
+                    .query_row(
+                        "SELECT id, created_at FROM memories
+                         WHERE user_id = ?1 AND agent = ?2 AND key = ?3",
+                        params![user, agent, k],
+                        |r| Ok((r.get(0)?, r.get(1)?)),
+                    )
+                    .optional()?,
+                None => c
+                    .query_row(
+                        "SELECT id, created_at FROM memories
+                         WHERE user_id = ?1 AND agent = ?2 AND key IS NULL AND content = ?3",
+                        params![user, agent, content],
+                        |r| Ok((r.get(0)?, r.get(1)?)),
+                    )
+                    .optional()?,
+            };
+            let (blob, model) = match &embedding {
+                Some((v, m)) => (Some(encode_embedding(v)), Some(m.clone())),
+                None => (None, None),
+            };
+            let (id, created_at) = match existing {
+                Some((id, created_at)) => {
+                    c.execute(
+                        "UPDATE memories
+                         SET content = ?2, embedding = ?3, embedding_model = ?4, updated_at = ?5
+                         WHERE id = ?1",
+                        params![id, content, blob, model, now],
+                    )?;
+                    (id, created_at)
+                }
+                None => {
+                    let id = uuid::Uuid::new_v4().to_string();
+                    c.execute(
+                        "INSERT INTO memories
+                         (id, user_id, agent, key, content, embedding, embedding_model,
+                          created_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+                        params![id, user, agent, key, content, blob, model, now],
+                    )?;
+                    (id, now)
+                }
+            };
+            Ok(MemoryRecord {
+                id,
+                user: user.to_string(),
+                agent: agent.to_string(),
+                key: key.map(|k| k.to_string()),
+                content: content.to_string(),
+                created_at,
+                updated_at: now,
+                score: None,
+            })
+        })
+    }
+
+    /// Most recently updated memories. `agent = Some(a)` returns the
+    /// agent's own memories plus the shared ones; `None` returns all.
+    pub async fn list(
+        &self,
+        user: &str,
+        agent: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<MemoryRecord>> {
+        Ok(self
+            .candidates(user, agent, limit)?
+            .into_iter()
+            .map(|(rec, _)| rec)
+            .collect())
+    }
+
+    /// Memories most relevant to `query`, best first. Uses embeddings when
+    /// configured (and stored with the same model), keyword overlap
+    /// otherwise. Memories with no relevance at all are left out; an empty
+    /// query behaves like list().
+    pub async fn recall(
+        &self,
+        user: &str,
+        agent: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryRecord>> {
+        if query.trim().is_empty() {
+            return self.list(user, agent, limit).await;
+        }
+        let mut scored = self.rank(user, agent, query).await?;
+        scored.truncate(limit);
+        Ok(scored.into_iter().map(|(rec, _)| rec).collect())
+    }
+
+    /// Every memory with some relevance to `query`, best first, each with
+    /// whether it was scored by embedding (true) or keyword overlap.
+    async fn rank(
+        &self,
+        user: &str,
+        agent: Option<&str>,
+        query: &str,
+    ) -> Result<Vec<(MemoryRecord, bool)>> {
+        let query_embedding = self.embed(query).await;
+        let query_terms = terms(query);
+
+        let mut scored: Vec<(MemoryRecord, bool)> = self
+            .candidates(user, agent, MAX_CANDIDATES)?
+            .into_iter()
+            .filter_map(|(mut rec, stored)| {
+                let (score, by_embedding) = match (&query_embedding, stored) {
+                    (Some((q, qm)), Some((v, vm))) if *qm == vm => (cosine(q, &v), true),
+                    _ => (keyword_score(&query_terms, &rec.content), false),
+                };
+                if score <= 0.0 {
+                    return None;
+                }
+                rec.score = Some(score);
+                Some((rec, by_embedding))
+            })
+            .collect();
+        scored.sort_by(|(a, _), (b, _)| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.updated_at.cmp(&a.updated_at))
+        });
+        Ok(scored)
+    }
+
+    /// What chat(memory=true) injects: the best matches for `query`, topped
+    /// up with the most recent memories so short profiles ("name is Ana",
+    /// "allergic to nuts") are present even when no word overlaps.
+    pub async fn for_prompt(
+        &self,
+        user: &str,
+        agent: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MemoryRecord>> {
+        let mut out = self.recall(user, agent, query, limit).await?;
+        if out.len() < limit {
+            let seen: HashSet<String> = out.iter().map(|m| m.id.clone()).collect();
+            for rec in self.list(user, agent, limit).await? {
+                if out.len() >= limit {
+                    break;
+                }
+                if !seen.contains(&rec.id) {
+                    out.push(rec);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Move every memory of `from` to `to` (identity merges). Returns how
+    /// many were moved.
+    pub fn reassign_user(&self, from: &str, to: &str) -> Result<usize> {
+        self.with_conn(|c| {
+            Ok(c.execute(
+                "UPDATE memories SET user_id = ?2 WHERE user_id = ?1",
+                params![from, to],
+            )?)
+        })
+    }
+
+    /// Delete memories; returns how many were removed.
+    pub async fn forget(&self, user: &str, agent: Option<&str>, what: Forget<'_>) -> Result<usize> {
+        let ids: Vec<String> = match what {
+            Forget::Id(id) => vec![id.to_string()],
+            Forget::Key(key) => self
+                .candidates(user, agent, MAX_CANDIDATES)?
+                .into_iter()
+                .filter(|(r, _)| r.key.as_deref() == Some(key))
+                .map(|(r, _)| r.id)
+                .collect(),
+            Forget::Query(q) if q.trim().is_empty() => vec![],
+            Forget::Query(q) => self
+                .rank(user, agent, q)
+                .await?
+                .into_iter()
+                .take(1)
+                .filter(|(r, by_embedding)| {
+                    let score = r.score.unwrap_or(0.0);
+                    if *by_embedding {
+                        score >= FORGET_MIN_SIMILARITY
+                    } else {
+                        score >= 1.0
+                    }
+                })
+                .map(|(r, _)| r.id)
+                .collect(),
+            Forget::All => self
+                .candidates(user, agent, usize::MAX)?
+                .into_iter()
+                .map(|(r, _)| r.id)
+                .collect(),
+        };
+        self.with_conn(|c| {
+            let mut removed = 0;
+            for id in &ids {
+                removed += c.execute(
+                    "DELETE FROM memories WHERE id = ?1 AND user_id = ?2",
+                    params![id, user],
+                )?;
+            }
+            Ok(removed)
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn candidates(
+        &self,
+        user: &str,
+        agent: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(MemoryRecord, Option<(Vec<f32>, String)>)>> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(
+                "SELECT id, user_id, agent, key, content, created_at, updated_at,
+                        embedding, embedding_model
+                 FROM memories
+                 WHERE user_id = ?1 AND (?2 IS NULL OR agent = ?2 OR agent = '')
+                 ORDER BY updated_at DESC, rowid DESC
+                 LIMIT ?3",
+            )?;
+            let rows = stmt.query_map(params![user, agent, limit], |r| {
+                let blob: Option<Vec<u8>> = r.get(7)?;
+                let model: Option<String> = r.get(8)?;
+                Ok((
+                    MemoryRecord {
+                        id: r.get(0)?,
+                        user: r.get(1)?,
+                        agent: r.get(2)?,
+                        key: r.get(3)?,
+                        content: r.get(4)?,
+                        created_at: r.get(5)?,
+                        updated_at: r.get(6)?,
+                        score: None,
+                    },
+                    blob.zip(model).map(|(b, m)| (decode_embedding(&b), m)),
+                ))
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+}
+
+// ─── Scoring ─────────────────────────────────────────────────────────────────
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "do", "does", "for", "from", "has", "have", "i",
+    "in", "is", "it", "me", "my", "of", "on", "or", "the", "to", "was", "what", "who", "with",
+    "you", "your",
+];
+
+/// Lowercased words (CJK split per character), minus stopwords.
+fn terms(text: &str) -> HashSet<String> {
+    let mut out = HashSet::new();
+    let mut word = String::new();
+    let flush = |w: &mut String, out: &mut HashSet<String>| {
+        if !w.is_empty() && !STOPWORDS.contains(&w.as_str()) {
+            out.insert(std::mem::take(w));
+        }
+        w.clear();
+    };
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(ch) {
+            flush(&mut word, &mut out);
+            out.insert(ch.to_string());
+        } else if ch.is_alphanumeric() {
+            word.push(ch);
+        } else {
+            flush(&mut word, &mut out);
+        }
+    }
+    flush(&mut word, &mut out);
+    out
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF)
+}
+
+/// Fraction of query terms that appear in `content`.
+fn keyword_score(query: &HashSet<String>, content: &str) -> f32 {
+    if query.is_empty() {
+        return 0.0;
+    }
+    let doc = terms(content);
+    let hits = query.iter().filter(|t| doc.contains(*t)).count();
+    hits as f32 / query.len() as f32
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let na: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let nb: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na * nb)
+    }
+}
+
+fn encode_embedding(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn decode_embedding(b: &[u8]) -> Vec<f32> {
+    b.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+// ─── Embeddings ──────────────────────────────────────────────────────────────
+
+/// OpenAI-compatible `POST {base}/embeddings` client.
+struct Embedder {
+    model: String,
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Embedder {
+    fn new(cfg: &EmbeddingConfig) -> Self {
+        let base_url = cfg
+            .base_url
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_BASE").ok())
+            .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+        Self {
+            model: cfg.model.clone(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: cfg
+                .api_key
+                .clone()
+                .or_else(|| std::env::var("OPENAI_API_KEY").ok()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut req = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&json!({ "model": self.model, "input": text }));
+        if let Some(ref key) = self.api_key {
+            req = req.bearer_auth(key);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(anyhow!("embeddings API returned {}: {}", status, body));
+        }
+        body.pointer("/data/0/embedding")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|x| x.as_f64().map(|f| f as f32))
+                    .collect()
+            })
+            .ok_or_else(|| anyhow!("embeddings API response has no data[0].embedding"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keyed_remember_upserts() {
+        let bank = MemoryBank::open_in_memory().unwrap();
+        let first = bank
+            .remember("tg:1", "", Some("color"), "Favorite color is blue")
+            .await
+            .unwrap();
+        let second = bank
+            .remember("tg:1", "", Some("color"), "Favorite color is green")
+            .await
+            .unwrap();
+        assert_eq!(first.id, second.id);
+        let all = bank.list("tg:1", None, 10).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].content, "Favorite color is green");
+    }
+
+    #[tokio::test]
+    async fn recall_ranks_by_keyword_overlap_and_respects_scope() {
+        let bank = MemoryBank::open_in_memory().unwrap();
+        bank.remember("u", "", None, "Lives in Lisbon")
+            .await
+            .unwrap();
+        bank.remember("u", "", None, "Prefers vegetarian food")
+            .await
+            .unwrap();
+        bank.remember("u", "sales", None, "Ordered the blue plan in March")
+            .await
+            .unwrap();
+        bank.remember("other", "", None, "Prefers vegetarian food too")
+            .await
+            .unwrap();
+
+        let hits = bank
+            .recall("u", None, "any vegetarian food nearby?", 5)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].content, "Prefers vegetarian food");
+
+        // Agent scope sees its own memories plus shared ones, not others'.
+        let support = bank.list("u", Some("support"), 10).await.unwrap();
+        assert_eq!(support.len(), 2);
+        let sales = bank.recall("u", Some("sales"), "plan", 5).await.unwrap();
+        assert_eq!(sales.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn for_prompt_tops_up_with_recent_memories() {
+        let bank = MemoryBank::open_in_memory().unwrap();
+        bank.remember("u", "", None, "Name is Ana").await.unwrap();
+        bank.remember("u", "", None, "Allergic to peanuts")
+            .await
+            .unwrap();
+        let mems = bank
+            .for_prompt("u", None, "what should I cook tonight", 5)
+            .await
+            .unwrap();
+        assert_eq!(mems.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn forget_by_key_query_and_all() {
+        let bank = MemoryBank::open_in_memory().unwrap();
+        bank.remember("u", "", Some("city"), "Lives in Lisbon")
+            .await
+            .unwrap();
+        bank.remember("u", "", None, "Has two cats").await.unwrap();
+        bank.remember("u", "", None, "Works night shifts")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bank.forget("u", None, Forget::Key("city")).await.unwrap(),
+            1
+        );
+        // A query that only partly matches removes nothing.
+        assert_eq!(
+            bank.forget("u", None, Forget::Query("cats and dogs"))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            bank.forget("u", None, Forget::Query("cats")).await.unwrap(),
+            1
+        );
+        assert_eq!(bank.forget("other", None, Forget::All).await.unwrap(), 0);
+        assert_eq!(bank.forget("u", None, Forget::All).await.unwrap(), 1);
+        assert!(bank.list("u", None, 10).await.unwrap().is_empty());
+    }
+
+    /// Stand-in `/embeddings` endpoint: a 3-d vector per known phrase.
+    async fn mock_embeddings() -> String {
+        let app = axum::Router::new().route(
+            "/embeddings",
+            axum::routing::post(|axum::Json(body): axum::Json<Value>| async move {
+                let v = match body["input"].as_str().unwrap_or("") {
+                    "Has two cats" => [1.0, 0.0, 0.0],
+                    "Has a cat named Tom" => [0.9, 0.3, 0.0],
+                    "forget my cat" => [1.0, 0.05, 0.0],
+                    "Works night shifts" => [0.0, 1.0, 0.0],
+                    _ => [0.5, 0.5, 0.7],
+                };
+                axum::Json(json!({ "data": [{ "embedding": v }] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn forget_query_with_embeddings_removes_only_the_closest_match() {
+        let cfg = EmbeddingConfig {
+            model: "test-embed".into(),
+            base_url: Some(mock_embeddings().await),
+            api_key: None,
+        };
+        let bank =
+            MemoryBank::from_connection(Connection::open_in_memory().unwrap(), Some(&cfg)).unwrap();
+        bank.remember("u", "", None, "Has two cats").await.unwrap();
+        bank.remember("u", "", None, "Has a cat named Tom")
+            .await
+            .unwrap();
+        bank.remember("u", "", None, "Works night shifts")
+            .await
+            .unwrap();
+
+        // Every memory has some similarity to a vague query; none is close.
+        assert_eq!(
+            bank.recall("u", None, "anything", 10).await.unwrap().len(),
+            3
+        );
+        assert_eq!(
+            bank.forget("u", None, Forget::Query("anything"))
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(
+            bank.forget("u", None, Forget::Query("forget my cat"))
+                .await
+                .unwrap(),
+            1
+        );
+        let left: Vec<String> = bank
+            .list("u", None, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.content)
+            .collect();
+        assert_eq!(left.len(), 2);
+        assert!(!left.contains(&"Has two cats".to_string()));
+    }
+
+    #[test]
+    fn terms_split_cjk_and_drop_stopwords() {
+        let t = terms("What is my favorite 咖啡?");
+        assert!(t.contains("favorite"));
+        assert!(t.contains("咖"));
+        assert!(!t.contains("what"));
+    }
+
+    #[test]
+    fn embedding_roundtrip_and_cosine() {
+        let v = vec![0.5f32, -1.0, 2.0];
+        assert_eq!(decode_embedding(&encode_embedding(&v)), v);
+        assert!((cosine(&v, &v) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&v, &[1.0, 2.0]), 0.0);
+    }
+}
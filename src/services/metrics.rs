@@ -0,0 +1,404 @@
+// src/services/metrics.rs
+//
+// Process-wide metrics, exposed by `juglans serve` as `GET /metrics` in the
+// Prometheus text format:
+//
+//   [server]
+//   metrics = true             # default; false removes the route
+//
+// Series are recorded where the work happens rather than by a collector:
+//   juglans_node_executions_total{workflow,node,tool,status}
+//   juglans_node_duration_seconds{workflow,node,tool}        (histogram)
+//   juglans_tool_calls_total{workflow,node,tool,status}      AI sub-tool calls
+//   juglans_tool_duration_seconds{workflow,node,tool}        (histogram)
+//     — from WorkflowContext::emit_node_complete / emit_tool_complete, i.e.
+//       the same points that produce NodeComplete / ToolComplete events.
+//   juglans_llm_requests_total{provider,model,status}
+//   juglans_llm_request_duration_seconds{provider,model}     (histogram)
+//   juglans_llm_tokens_total{provider,model,type}            type=input|output
+//     — from LocalRuntime::chat, one request per model round trip.
+//   juglans_channel_messages_received_total{channel}
+//   juglans_channel_receive_failures_total{channel}
+//   juglans_channel_messages_sent_total{channel}
+//   juglans_channel_send_failures_total{channel}
+//     — ingress from adapters::run_agent_for_message, where every adapter
+//       loop and webhook hands off its messages; egress from the outbox and
+//       reply streams.
+//   juglans_active_runs{kind}                                 (gauge)
+//   juglans_python_workers / juglans_python_workers_busy     (gauge)
+//
+// `/metrics` sits behind `[server.auth]` like the rest of the API; add it to
+// `public` for an unauthenticated scraper.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Buckets for node and tool latency, in seconds.
+const NODE_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+/// Buckets for LLM round trips, in seconds.
+const LLM_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0];
+
+/// Run kinds tracked by `juglans_active_runs`.
+pub const RUN_KINDS: &[&str] = &["http", "chat", "run", "job", "channel"];
+
+type Labels = Vec<(&'static str, String)>;
+
+struct Histogram {
+    buckets: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+static ACTIVE_RUNS: [AtomicI64; 5] = [const { AtomicI64::new(0) }; 5];
+static PYTHON_WORKERS: AtomicI64 = AtomicI64::new(0);
+static PYTHON_BUSY: AtomicI64 = AtomicI64::new(0);
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn labels(pairs: &[(&'static str, &str)]) -> Labels {
+    pairs.iter().map(|(k, v)| (*k, v.to_string())).collect()
+}
+
+fn inc(name: &'static str, pairs: &[(&'static str, &str)], by: u64) {
+    *registry()
+        .counters
+        .entry(name)
+        .or_default()
+        .entry(labels(pairs))
+        .or_default() += by;
+}
+
+fn observe(
+    name: &'static str,
+    buckets: &'static [f64],
+    pairs: &[(&'static str, &str)],
+    elapsed: Duration,
+) {
+    let secs = elapsed.as_secs_f64();
+    let mut reg = registry();
+    let h = reg
+        .histograms
+        .entry(name)
+        .or_default()
+        .entry(labels(pairs))
+        .or_insert_with(|| Histogram {
+            buckets,
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        });
+    if let Some(i) = h.buckets.iter().position(|b| secs <= *b) {
+        h.counts[i] += 1;
+    }
+    h.sum += secs;
+    h.count += 1;
+}
+
+fn status(ok: bool) -> &'static str {
+    if ok {
+        "success"
+    } else {
+        "error"
+    }
+}
+
+// ─── Recording ──────────────────────────────────────────────────────────────
+
+/// A workflow node finished (`NodeComplete`).
+pub fn record_node(workflow: &str, node: &str, tool: &str, ok: bool, elapsed: Duration) {
+    let key = [("workflow", workflow), ("node", node), ("tool", tool)];
+    inc(
+        "juglans_node_executions_total",
+        &[key[0], key[1], key[2], ("status", status(ok))],
+        1,
+    );
+    observe("juglans_node_duration_seconds", NODE_BUCKETS, &key, elapsed);
+}
+
+/// A tool called by an AI node finished (`ToolComplete`).
+pub fn record_tool(workflow: &str, node: &str, tool: &str, ok: bool, elapsed: Duration) {
+    let key = [("workflow", workflow), ("node", node), ("tool", tool)];
+    inc(
+        "juglans_tool_calls_total",
+        &[key[0], key[1], key[2], ("status", status(ok))],
+        1,
+    );
+    observe("juglans_tool_duration_seconds", NODE_BUCKETS, &key, elapsed);
+}
+
+/// One model round trip, from request to the end of its stream.
+pub fn record_llm_request(provider: &str, model: &str, ok: bool, elapsed: Duration) {
+    inc(
+        "juglans_llm_requests_total",
+        &[
+            ("provider", provider),
+            ("model", model),
+            ("status", status(ok)),
+        ],
+        1,
+    );
+    observe(
+        "juglans_llm_request_duration_seconds",
+        LLM_BUCKETS,
+        &[("provider", provider), ("model", model)],
+        elapsed,
+    );
+}
+
+/// Token usage reported by a provider for one round trip.
+pub fn record_llm_tokens(provider: &str, model: &str, input: u64, output: u64) {
+    for (kind, n) in [("input", input), ("output", output)] {
+        if n > 0 {
+            inc(
+                "juglans_llm_tokens_total",
+                &[("provider", provider), ("model", model), ("type", kind)],
+                n,
+            );
+        }
+    }
+}
+
+/// An inbound channel message was handed to its agent; `ok` is false when
+/// dispatching it failed.
+pub fn record_channel_received(channel: &str, ok: bool) {
+    inc(
+        "juglans_channel_messages_received_total",
+        &[("channel", channel)],
+        1,
+    );
+    if !ok {
+        inc(
+            "juglans_channel_receive_failures_total",
+            &[("channel", channel)],
+            1,
+        );
+    }
+}
+
+/// An outbound reply was delivered (or failed to be) on a channel.
+pub fn record_channel_sent(channel: &str, ok: bool) {
+    let name = if ok {
+        "juglans_channel_messages_sent_total"
+    } else {
+        "juglans_channel_send_failures_total"
+    };
+    inc(name, &[("channel", channel)], 1);
+}
+
+/// Counts one in-flight run of `kind` (one of `RUN_KINDS`) until dropped.
+pub struct ActiveRun(usize);
+
+impl ActiveRun {
+    pub fn start(kind: &str) -> Self {
+        let idx = RUN_KINDS
+            .iter()
+            .position(|k| *k == kind)
+            .unwrap_or_else(|| panic!("unknown run kind {:?}", kind));
+        ACTIVE_RUNS[idx].fetch_add(1, Ordering::SeqCst);
+        ActiveRun(idx)
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        ACTIVE_RUNS[self.0].fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Runs of every kind currently in flight in this process.
+pub fn active_runs() -> i64 {
+    ACTIVE_RUNS.iter().map(|n| n.load(Ordering::SeqCst)).sum()
+}
+
+/// Python workers spawned (positive) or shut down (negative).
+pub fn python_workers_changed(delta: i64) {
+    PYTHON_WORKERS.fetch_add(delta, Ordering::SeqCst);
+}
+
+/// Marks one Python worker busy until dropped.
+pub struct PythonBusy(());
+
+impl PythonBusy {
+    pub fn start() -> Self {
+        PYTHON_BUSY.fetch_add(1, Ordering::SeqCst);
+        PythonBusy(())
+    }
+}
+
+impl Drop for PythonBusy {
+    fn drop(&mut self) {
+        PYTHON_BUSY.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// ─── Exposition ─────────────────────────────────────────────────────────────
+
+fn help(name: &str) -> &'static str {
+    match name {
+        "juglans_node_executions_total" => "Workflow node executions.",
+        "juglans_node_duration_seconds" => "Workflow node execution time.",
+        "juglans_tool_calls_total" => "Tool calls made by AI nodes.",
+        "juglans_tool_duration_seconds" => "Tool call execution time.",
+        "juglans_llm_requests_total" => "LLM requests (one per model round trip).",
+        "juglans_llm_request_duration_seconds" => "LLM request time, including streaming.",
+        "juglans_llm_tokens_total" => "Tokens reported by LLM providers.",
+        "juglans_channel_messages_received_total" => "Messages received from channels.",
+        "juglans_channel_receive_failures_total" => "Received messages that failed to dispatch.",
+        "juglans_channel_messages_sent_total" => "Messages delivered to channels.",
+        "juglans_channel_send_failures_total" => "Channel deliveries that failed.",
+        _ => "",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_labels(out: &mut String, labels: &Labels, extra: Option<(&str, &str)>) {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+        .collect();
+    if let Some((k, v)) = extra {
+        parts.push(format!("{}=\"{}\"", k, v));
+    }
+    if !parts.is_empty() {
+        let _ = write!(out, "{{{}}}", parts.join(","));
+    }
+}
+
+/// All metrics in the Prometheus text exposition format (version 0.0.4).
+pub fn render() -> String {
+    let mut out = String::new();
+    let reg = registry();
+
+    for (name, series) in &reg.counters {
+        let _ = writeln!(out, "# HELP {} {}", name, help(name));
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for (labels, value) in series {
+            out.push_str(name);
+            write_labels(&mut out, labels, None);
+            let _ = writeln!(out, " {}", value);
+        }
+    }
+
+    for (name, series) in &reg.histograms {
+        let _ = writeln!(out, "# HELP {} {}", name, help(name));
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (labels, h) in series {
+            let mut cumulative = 0;
+            for (bound, n) in h.buckets.iter().zip(&h.counts) {
+                cumulative += n;
+                let _ = write!(out, "{}_bucket", name);
+                write_labels(&mut out, labels, Some(("le", &bound.to_string())));
+                let _ = writeln!(out, " {}", cumulative);
+            }
+            let _ = write!(out, "{}_bucket", name);
+            write_labels(&mut out, labels, Some(("le", "+Inf")));
+            let _ = writeln!(out, " {}", h.count);
+            let _ = write!(out, "{}_sum", name);
+            write_labels(&mut out, labels, None);
+            let _ = writeln!(out, " {}", h.sum);
+            let _ = write!(out, "{}_count", name);
+            write_labels(&mut out, labels, None);
+            let _ = writeln!(out, " {}", h.count);
+        }
+    }
+    drop(reg);
+
+    out.push_str("# HELP juglans_active_runs Workflow runs in flight.\n");
+    out.push_str("# TYPE juglans_active_runs gauge\n");
+    for (kind, n) in RUN_KINDS.iter().zip(&ACTIVE_RUNS) {
+        let _ = writeln!(
+            out,
+            "juglans_active_runs{{kind=\"{}\"}} {}",
+            kind,
+            n.load(Ordering::SeqCst)
+        );
+    }
+    out.push_str("# HELP juglans_python_workers Python worker processes.\n");
+    out.push_str("# TYPE juglans_python_workers gauge\n");
+    let _ = writeln!(
+        out,
+        "juglans_python_workers {}",
+        PYTHON_WORKERS.load(Ordering::SeqCst)
+    );
+    out.push_str("# HELP juglans_python_workers_busy Python workers executing a call.\n");
+    out.push_str("# TYPE juglans_python_workers_busy gauge\n");
+    let _ = writeln!(
+        out,
+        "juglans_python_workers_busy {}",
+        PYTHON_BUSY.load(Ordering::SeqCst)
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_and_histograms() {
+        record_node("shop", "fetch", "fetch", true, Duration::from_millis(20));
+        record_node("shop", "fetch", "fetch", false, Duration::from_secs(60));
+        record_llm_tokens("openai", "gpt-4o", 12, 0);
+
+        let text = render();
+        assert!(text.contains("# TYPE juglans_node_executions_total counter"));
+        assert!(text.contains(
+            "juglans_node_executions_total{workflow=\"shop\",node=\"fetch\",tool=\"fetch\",status=\"error\"} 1"
+        ));
+        // 20ms lands in the 0.025 bucket and every bucket above it; 60s only in +Inf.
+        assert!(text.contains(
+            "juglans_node_duration_seconds_bucket{workflow=\"shop\",node=\"fetch\",tool=\"fetch\",le=\"0.01\"} 0"
+        ));
+        assert!(text.contains(
+            "juglans_node_duration_seconds_bucket{workflow=\"shop\",node=\"fetch\",tool=\"fetch\",le=\"30\"} 1"
+        ));
+        assert!(text.contains(
+            "juglans_node_duration_seconds_bucket{workflow=\"shop\",node=\"fetch\",tool=\"fetch\",le=\"+Inf\"} 2"
+        ));
+        assert!(text.contains(
+            "juglans_llm_tokens_total{provider=\"openai\",model=\"gpt-4o\",type=\"input\"} 12"
+        ));
+        assert!(!text.contains("type=\"output\""));
+    }
+
+    #[test]
+    fn active_run_guard_and_label_escaping() {
+        {
+            let _run = ActiveRun::start("job");
+            assert!(render().contains("juglans_active_runs{kind=\"job\"} 1"));
+        }
+        assert!(render().contains("juglans_active_runs{kind=\"job\"} 0"));
+
+        record_channel_sent("we\"ird\n", false);
+        assert!(
+            render().contains("juglans_channel_send_failures_total{channel=\"we\\\"ird\\n\"} 1")
+        );
+    }
+}
@@ -2,6 +2,8 @@
 pub mod prompt_loader;
 pub mod tool_registry;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod auth;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod config;
 #[cfg(not(target_arch = "wasm32"))]
@@ -11,6 +13,30 @@ pub mod github;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod history;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod identity;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod jobs;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod local_runtime;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod memory;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod openapi;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod outbox;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod runs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sandbox;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shutdown;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod static_files;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tool_policy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod validation;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod web_server;
@@ -0,0 +1,653 @@
+// src/services/openapi.rs
+//
+// OpenAPI 3 document for a workflow's decorator routes (`@get`, `@post`, ...).
+//
+// Everything is derived statically from the expanded graph — nothing runs:
+//   - paths and methods from `extract_routes_from_graph`; typed segments
+//     (`{id:int}`) become path parameters
+//   - `components.schemas` from struct field declarations (`name: str`)
+//   - query parameters from the `query=Struct` fields, else from
+//     `input.query.<name>` reads in the handler
+//   - the request body from `body=Struct`, else from a `new Struct(...)`
+//     built out of `input.body.*`, else from the `input.body.<field>` names
+//     the handler reads
+//   - responses from `response(status=..., body=...)` calls: a struct
+//     instance becomes a `$ref`, an object literal an inline schema. A
+//     handler without `response()` documents a plain 200. Routes with typed
+//     inputs also document the automatic 422.
+//
+// Served live at `/openapi.json` (Swagger UI at `/docs`) by both the inline
+// `serve()` server and `juglans serve`, and written by `juglans openapi`.
+
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::builtins::http::{extract_routes_from_graph, InlineRoute};
+use crate::core::expr_ast::Expr;
+use crate::core::graph::{ClassDef, ClassField, Node, NodeType, WorkflowGraph};
+use crate::core::types::JType;
+use crate::services::validation;
+
+pub const SPEC_PATH: &str = "/openapi.json";
+pub const DOCS_PATH: &str = "/docs";
+
+/// Build the OpenAPI document. `title` is used when the workflow declares
+/// no `name`.
+pub fn build_spec(workflow: &WorkflowGraph, title: &str) -> Value {
+    let mut routes = extract_routes_from_graph(workflow);
+    routes.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+
+    let mut paths: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+    for route in &routes {
+        let op = operation(workflow, route);
+        paths
+            .entry(spec_path(&route.path))
+            .or_default()
+            .insert(route.method.to_lowercase(), op);
+    }
+
+    let mut info = json!({
+        "title": if workflow.name.is_empty() { title } else { workflow.name.as_str() },
+        "version": if workflow.version.is_empty() { "0.1.0" } else { workflow.version.as_str() },
+    });
+    if !workflow.description.is_empty() {
+        info["description"] = json!(workflow.description);
+    }
+
+    let mut spec = json!({
+        "openapi": "3.0.3",
+        "info": info,
+        "paths": paths,
+    });
+    let schemas = component_schemas(workflow);
+    if !schemas.is_empty() {
+        spec["components"] = json!({ "schemas": schemas });
+    }
+    spec
+}
+
+/// Swagger UI page for the spec at `spec_url` (assets from the jsDelivr CDN).
+pub fn swagger_ui_html(spec_url: &str) -> String {
+    format!(
+        r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>API docs</title>
+  <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://cdn.jsdelivr.net/npm/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({{ url: "{}", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>
+"##,
+        spec_url
+    )
+}
+
+/// True when a decorator route already claims `path` (the workflow's own
+/// route wins over the generated docs endpoints).
+pub fn path_is_taken(routes: &[InlineRoute], path: &str) -> bool {
+    routes.iter().any(|r| r.path == path)
+}
+
+// ─── Operations ─────────────────────────────────────────────────────────────
+
+fn operation(workflow: &WorkflowGraph, route: &InlineRoute) -> Value {
+    let mut op = json!({ "operationId": route.handler });
+    let classes = &workflow.classes;
+    let handler = workflow.functions.get(&route.handler);
+
+    let mut nodes = Vec::new();
+    if let Some(handler) = handler {
+        collect_nodes(&handler.body, &mut nodes);
+    }
+    let exprs: Vec<&str> = nodes.iter().flat_map(|n| node_exprs(n)).collect();
+    let mut bindings = instance_bindings(&nodes);
+
+    let path_params: Vec<(&str, JType)> = route
+        .path
+        .split('/')
+        .filter_map(validation::path_param)
+        .collect();
+    let mut parameters: Vec<Value> = path_params
+        .iter()
+        .map(|(name, ty)| {
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": type_schema(ty, classes),
+            })
+        })
+        .collect();
+
+    // The body / query structs bind to the handler params left over after
+    // the path params, in that order (see `validation::bind_request`).
+    if let Some(handler) = handler {
+        let mut free = handler
+            .params
+            .iter()
+            .filter(|p| !path_params.iter().any(|(name, _)| name == p));
+        for class in [&route.body, &route.query].into_iter().flatten() {
+            if let Some(param) = free.next() {
+                bindings.insert(param.clone(), class.clone());
+            }
+        }
+    }
+
+    match route.query.as_ref().and_then(|c| classes.get(c)) {
+        Some(class) => {
+            for field in &class.fields {
+                let (schema, required) = field_schema(field, classes);
+                parameters.push(json!({
+                    "name": field.name,
+                    "in": "query",
+                    "required": required,
+                    "schema": schema,
+                }));
+            }
+        }
+        None => {
+            let query: BTreeSet<String> = exprs
+                .iter()
+                .flat_map(|e| field_reads(e, "input.query."))
+                .collect();
+            for name in query {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "query",
+                    "required": false,
+                    "schema": { "type": "string" },
+                }));
+            }
+        }
+    }
+    if !parameters.is_empty() {
+        op["parameters"] = Value::Array(parameters);
+    }
+
+    let body = match &route.body {
+        Some(class) if classes.contains_key(class) => Some(schema_ref(class)),
+        _ => request_body_schema(&nodes, &exprs, classes),
+    };
+    if let Some(schema) = body {
+        op["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } },
+        });
+    }
+
+    op["responses"] = responses(&nodes, &bindings, classes);
+    if !path_params.is_empty() || route.body.is_some() || route.query.is_some() {
+        op["responses"]["422"] = json!({
+            "description": status_description(422),
+            "content": { "application/json": { "schema": validation_error_schema() } },
+        });
+    }
+    op
+}
+
+/// `/users/{id:int}` → `/users/{id}`.
+fn spec_path(path: &str) -> String {
+    path.split('/')
+        .map(|seg| match validation::path_param(seg) {
+            Some((name, _)) => format!("{{{}}}", name),
+            None => seg.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The 422 body written by `validation::validation_error_body`.
+fn validation_error_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "error": { "type": "string" },
+            "details": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "loc": { "type": "string" },
+                        "message": { "type": "string" },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Every node of a handler body, including foreach/loop bodies.
+fn collect_nodes<'a>(graph: &'a WorkflowGraph, out: &mut Vec<&'a Node>) {
+    for idx in graph.graph.node_indices() {
+        let node = &graph.graph[idx];
+        out.push(node);
+        match &node.node_type {
+            NodeType::Foreach { body, .. } | NodeType::Loop { body, .. } => {
+                collect_nodes(body, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The raw expressions a node evaluates.
+fn node_exprs(node: &Node) -> Vec<&str> {
+    match &node.node_type {
+        NodeType::Task(action) | NodeType::AssignCall { action, .. } => {
+            action.params.values().map(String::as_str).collect()
+        }
+        NodeType::NewInstance { args, .. } | NodeType::MethodCall { args, .. } => {
+            args.values().map(String::as_str).collect()
+        }
+        NodeType::Assert(e) | NodeType::Yield(e) => vec![e.as_str()],
+        NodeType::Foreach { list, .. } => vec![list.as_str()],
+        NodeType::Loop { condition, .. } => vec![condition.as_str()],
+        NodeType::_ExternalCall { args, kwargs, .. } => args
+            .iter()
+            .chain(kwargs.values())
+            .map(String::as_str)
+            .collect(),
+        NodeType::Literal(_) | NodeType::ReturnErr(_) => Vec::new(),
+    }
+}
+
+/// Identifiers read right after `prefix` (`input.query.page` → `page`).
+fn field_reads(expr: &str, prefix: &str) -> Vec<String> {
+    expr.match_indices(prefix)
+        .filter_map(|(i, _)| {
+            let name: String = expr[i + prefix.len()..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+/// `new Struct(...)` instances in the handler: `(bound name, class, args)`.
+/// Named DAG nodes (`[u]: new User(...)`) bind their node id; assignments
+/// (`u = new User(...)`) bind the variable.
+fn instances<'a>(nodes: &[&'a Node]) -> Vec<(String, String, Vec<&'a str>)> {
+    let mut out = Vec::new();
+    for node in nodes {
+        match &node.node_type {
+            NodeType::NewInstance { class_name, args } => out.push((
+                node.id.clone(),
+                class_name.clone(),
+                args.values().map(String::as_str).collect(),
+            )),
+            NodeType::Task(action) if action.name == "set_context" => {
+                for (var, value) in &action.params {
+                    if let Some(class) = new_instance_class(value) {
+                        out.push((var.clone(), class, vec![value.as_str()]));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn new_instance_class(expr: &str) -> Option<String> {
+    let rest = expr.trim().strip_prefix("new ")?.trim_start();
+    let class: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!class.is_empty()).then_some(class)
+}
+
+fn instance_bindings(nodes: &[&Node]) -> HashMap<String, String> {
+    instances(nodes)
+        .into_iter()
+        .map(|(name, class, _)| (name, class))
+        .collect()
+}
+
+fn request_body_schema(
+    nodes: &[&Node],
+    exprs: &[&str],
+    classes: &HashMap<String, std::sync::Arc<ClassDef>>,
+) -> Option<Value> {
+    // A struct built from the body describes it best.
+    let from_struct = instances(nodes).into_iter().find(|(_, class, args)| {
+        classes.contains_key(class) && args.iter().any(|a| a.contains("input.body"))
+    });
+    if let Some((_, class, _)) = from_struct {
+        return Some(schema_ref(&class));
+    }
+
+    let fields: BTreeSet<String> = exprs
+        .iter()
+        .flat_map(|e| field_reads(e, "input.body."))
+        .collect();
+    if !fields.is_empty() {
+        let properties: Map<String, Value> = fields.into_iter().map(|f| (f, json!({}))).collect();
+        return Some(json!({ "type": "object", "properties": properties }));
+    }
+    exprs
+        .iter()
+        .any(|e| e.contains("input.body"))
+        .then(|| json!({}))
+}
+
+fn responses(
+    nodes: &[&Node],
+    bindings: &HashMap<String, String>,
+    classes: &HashMap<String, std::sync::Arc<ClassDef>>,
+) -> Value {
+    let mut out: BTreeMap<String, Value> = BTreeMap::new();
+    for node in nodes {
+        let action = match &node.node_type {
+            NodeType::Task(a) | NodeType::AssignCall { action: a, .. } if a.name == "response" => a,
+            _ => continue,
+        };
+        let status: u16 = action
+            .params
+            .get("status")
+            .and_then(|s| s.trim_matches('"').parse().ok())
+            .unwrap_or(200);
+        let mut resp = json!({ "description": status_description(status) });
+        if action.params.contains_key("file") {
+            resp["content"] = json!({
+                "application/octet-stream": { "schema": { "type": "string", "format": "binary" } }
+            });
+        } else if let Some(body) = action.params.get("body") {
+            resp["content"] = json!({
+                "application/json": { "schema": expr_schema(body, bindings, classes) }
+            });
+        }
+        // The first response() for a status documents it.
+        out.entry(status.to_string()).or_insert(resp);
+    }
+    if out.is_empty() {
+        out.insert(
+            "200".into(),
+            json!({
+                "description": "OK",
+                "content": { "application/json": { "schema": {} } },
+            }),
+        );
+    }
+    json!(out)
+}
+
+fn status_description(status: u16) -> &'static str {
+    axum::http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Response")
+}
+
+/// Schema of a `response(body=...)` expression.
+fn expr_schema(
+    raw: &str,
+    bindings: &HashMap<String, String>,
+    classes: &HashMap<String, std::sync::Arc<ClassDef>>,
+) -> Value {
+    match crate::core::expr_parser::parse_expr(raw) {
+        Ok(expr) => ast_schema(&expr, bindings, classes),
+        Err(_) => json!({}),
+    }
+}
+
+fn ast_schema(
+    expr: &Expr,
+    bindings: &HashMap<String, String>,
+    classes: &HashMap<String, std::sync::Arc<ClassDef>>,
+) -> Value {
+    match expr {
+        Expr::String(_) | Expr::FString(_) => json!({ "type": "string" }),
+        Expr::Number(n) if n.fract() == 0.0 => json!({ "type": "integer" }),
+        Expr::Number(_) => json!({ "type": "number" }),
+        Expr::Bool(_) => json!({ "type": "boolean" }),
+        Expr::None => json!({ "nullable": true }),
+        Expr::Array(items) => json!({
+            "type": "array",
+            "items": items
+                .first()
+                .map(|i| ast_schema(i, bindings, classes))
+                .unwrap_or_else(|| json!({})),
+        }),
+        Expr::Object(pairs) => {
+            let properties: Map<String, Value> = pairs
+                .iter()
+                .map(|(k, v)| (k.clone(), ast_schema(v, bindings, classes)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+        Expr::Identifier(name) | Expr::Variable(name) => match bindings.get(name) {
+            Some(class) if classes.contains_key(class) => schema_ref(class),
+            _ => json!({}),
+        },
+        _ => json!({}),
+    }
+}
+
+// ─── Components ─────────────────────────────────────────────────────────────
+
+fn schema_ref(class: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", class) })
+}
+
+/// One object schema per struct that declares fields.
+fn component_schemas(workflow: &WorkflowGraph) -> BTreeMap<String, Value> {
+    let mut out = BTreeMap::new();
+    for (name, class) in &workflow.classes {
+        if class.fields.is_empty() {
+            continue;
+        }
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for field in &class.fields {
+            let (schema, is_required) = field_schema(field, &workflow.classes);
+            if is_required {
+                required.push(json!(field.name));
+            }
+            properties.insert(field.name.clone(), schema);
+        }
+        let mut schema = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            schema["required"] = Value::Array(required);
+        }
+        out.insert(name.clone(), schema);
+    }
+    out
+}
+
+/// A struct field's schema, and whether a value must be supplied.
+fn field_schema(
+    field: &ClassField,
+    classes: &HashMap<String, std::sync::Arc<ClassDef>>,
+) -> (Value, bool) {
+    let ty = JType::from_hint(&field.type_hint);
+    let mut schema = type_schema(&ty, classes);
+    if let Some(default) = field
+        .default
+        .as_deref()
+        .and_then(|d| serde_json::from_str::<Value>(d).ok())
+    {
+        schema["default"] = default;
+    }
+    let required = field.default.is_none() && !matches!(ty, JType::Optional(_));
+    (schema, required)
+}
+
+fn type_schema(ty: &JType, classes: &HashMap<String, std::sync::Arc<ClassDef>>) -> Value {
+    match ty {
+        JType::Int => json!({ "type": "integer" }),
+        JType::Float => json!({ "type": "number" }),
+        JType::Str => json!({ "type": "string" }),
+        JType::Bool => json!({ "type": "boolean" }),
+        JType::List(item) => json!({ "type": "array", "items": type_schema(item, classes) }),
+        JType::Dict(_, value) => json!({
+            "type": "object",
+            "additionalProperties": type_schema(value, classes),
+        }),
+        JType::Optional(inner) => {
+            let mut s = type_schema(inner, classes);
+            if s.get("$ref").is_some() {
+                // Siblings of $ref are ignored in 3.0; wrap it instead.
+                s = json!({ "allOf": [s] });
+            }
+            s["nullable"] = json!(true);
+            s
+        }
+        JType::Class(name) if classes.contains_key(name) => schema_ref(name),
+        JType::Class(_) | JType::Any => json!({}),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::GraphParser;
+
+    const SRC: &str = r#"
+[CreateUser]: {
+  name: str
+  age: int = 0
+  active: bool
+}
+
+[User]: {
+  id: str
+  name: str
+}
+
+[start]: serve()
+
+@post("/api/users")
+[create_user()]: {
+  req = new CreateUser(name=input.body.name, age=input.body.age)
+  u = new User(id="u1", name=req.name)
+  response(status=201, body=u)
+}
+
+@get("/api/users")
+[list_users()]: response(status=200, body={"users": [], "page": input.query.page, "ok": true})
+
+@get("/api/lookup")
+[lookup()]: {
+  [found]: new User(id=input.query.id, name="x")
+  [missing]: response(status=404, body={"error": "not found"})
+  [ok]: response(status=200, body=found)
+  [found] -> [ok]
+}
+
+@post("/api/echo")
+[echo()]: response(status=200, body={"said": input.body.text})
+"#;
+
+    fn spec() -> Value {
+        let mut g = GraphParser::parse(SRC).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut g).unwrap();
+        build_spec(&g, "users")
+    }
+
+    #[test]
+    fn structs_become_component_schemas() {
+        let s = spec();
+        assert_eq!(s["openapi"], "3.0.3");
+        assert_eq!(s["info"]["title"], "users");
+        let create = &s["components"]["schemas"]["CreateUser"];
+        assert_eq!(create["properties"]["name"]["type"], "string");
+        assert_eq!(create["properties"]["age"]["type"], "integer");
+        assert_eq!(create["properties"]["age"]["default"], 0);
+        assert_eq!(create["properties"]["active"]["type"], "boolean");
+        assert_eq!(create["required"], json!(["name", "active"]));
+    }
+
+    #[test]
+    fn request_body_comes_from_the_struct_built_from_it() {
+        let s = spec();
+        let post = &s["paths"]["/api/users"]["post"];
+        assert_eq!(post["operationId"], "create_user");
+        assert_eq!(
+            post["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/CreateUser"
+        );
+        let created = &post["responses"]["201"];
+        assert_eq!(created["description"], "Created");
+        assert_eq!(
+            created["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/User"
+        );
+
+        // Without a struct, the body fields the handler reads are listed.
+        let echo = &s["paths"]["/api/echo"]["post"]["requestBody"]["content"]["application/json"]
+            ["schema"];
+        assert!(echo["properties"]["text"].is_object());
+    }
+
+    #[test]
+    fn query_reads_and_literal_bodies_are_documented() {
+        let s = spec();
+        let get = &s["paths"]["/api/users"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "page");
+        assert_eq!(get["parameters"][0]["in"], "query");
+        assert!(get.get("requestBody").is_none());
+        let body = &get["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(body["properties"]["users"]["type"], "array");
+        assert_eq!(body["properties"]["ok"]["type"], "boolean");
+
+        let lookup = &s["paths"]["/api/lookup"]["get"]["responses"];
+        assert_eq!(
+            lookup["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/User"
+        );
+        assert_eq!(lookup["404"]["description"], "Not Found");
+    }
+
+    #[test]
+    fn typed_routes_document_params_body_and_422() {
+        let src = r#"
+[CreateUser]: {
+  name: str
+}
+
+[Paging]: {
+  page: int = 1
+  q: str
+}
+
+[start]: serve()
+
+@get("/users/{id:int}")
+[get_user(id)]: response(status=200, body={"id": id})
+
+@post("/users", body=CreateUser)
+[create_user(user)]: response(status=201, body=user)
+
+@get("/users", query=Paging)
+[list_users()]: response(status=200, body={"users": []})
+"#;
+        let mut g = GraphParser::parse(src).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut g).unwrap();
+        let s = build_spec(&g, "users");
+
+        let get_user = &s["paths"]["/users/{id}"]["get"];
+        assert_eq!(get_user["parameters"][0]["in"], "path");
+        assert_eq!(get_user["parameters"][0]["schema"]["type"], "integer");
+        assert!(get_user["responses"]["422"].is_object());
+
+        let create = &s["paths"]["/users"]["post"];
+        let body = &create["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(body["$ref"], "#/components/schemas/CreateUser");
+        let created = &create["responses"]["201"]["content"]["application/json"]["schema"];
+        assert_eq!(created["$ref"], "#/components/schemas/CreateUser");
+
+        let params = &s["paths"]["/users"]["get"]["parameters"];
+        assert_eq!(params[0]["name"], "page");
+        assert_eq!(params[0]["required"], false);
+        assert_eq!(params[0]["schema"]["default"], 1);
+        assert_eq!(params[1]["name"], "q");
+        assert_eq!(params[1]["required"], true);
+    }
+}
@@ -0,0 +1,861 @@
+// src/services/outbox.rs
+//
+// Durable outbound queue. With `[outbox] enabled = true`, a channel reply or
+// `telegram.send_message` / `feishu.send_message` call that fails with a
+// retryable error (network, 429, 5xx) is written to SQLite instead of being
+// lost, and a background worker in `juglans serve` redelivers it:
+//
+//   [outbox]
+//   enabled = true
+//   path = ".juglans/outbox.db"
+//   max_attempts = 8
+//   base_delay_secs = 2        # doubles per attempt...
+//   max_delay_secs = 600       # ...up to this; a platform's retry_after wins
+//
+// Ordering is per conversation: while a conversation has queued messages,
+// new ones queue behind them, and the worker only ever attempts the oldest.
+// Messages that exhaust `max_attempts` move to the `dead_letters` table,
+// where `juglans outbox list|retry|purge` can inspect, requeue or drop them.
+//
+// Jobs name their sender: a channel id registered by `juglans serve`
+// (`telegram:12345`, `feishu:main`, ...) or a builtin (`telegram.send_message`).
+// Because channel ids are derived from config, queued replies survive a
+// restart and are delivered once the same channel is registered again.
+// Streamed replies (`start_stream`) are not queued.
+//
+// Long messages go out in several chunks. A sender that fails part-way
+// reports what it didn't deliver (`partial_failure`), and only that part is
+// queued, so a retry doesn't repeat chunks the user already has.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use dashmap::DashMap;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use super::config::OutboxConfig;
+use super::metrics;
+use crate::core::context::ChannelEgress;
+
+/// Jobs fetched per worker pass.
+const BATCH_SIZE: usize = 50;
+/// Longest the worker sleeps without being woken.
+const IDLE_POLL_SECS: u64 = 30;
+
+/// A send failure with enough detail to decide whether and when to retry.
+/// Adapters return it (wrapped in `anyhow::Error`) for HTTP-level failures;
+/// errors of any other type are treated as transient.
+#[derive(Debug)]
+pub struct DeliveryError {
+    pub status: Option<u16>,
+    /// Wait requested by the platform (Telegram `retry_after`, `Retry-After`).
+    pub retry_after: Option<Duration>,
+    pub message: String,
+}
+
+impl DeliveryError {
+    pub fn new(status: Option<u16>, retry_after: Option<Duration>, message: String) -> Self {
+        Self {
+            status,
+            retry_after,
+            message,
+        }
+    }
+
+    /// Client errors other than 408/429 won't succeed on retry (bad chat id,
+    /// bot kicked, malformed payload).
+    pub fn is_permanent(&self) -> bool {
+        matches!(self.status, Some(s) if (400..500).contains(&s) && s != 408 && s != 429)
+    }
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+/// Context on a send error after some chunks of the message went out:
+/// `remaining` is the undelivered rest.
+#[derive(Debug)]
+pub struct PartiallySent {
+    pub remaining: String,
+}
+
+impl fmt::Display for PartiallySent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "message partly delivered ({} chars left)",
+            self.remaining.chars().count()
+        )
+    }
+}
+
+/// Wrap a chunked sender's error: `sent` of `chunks` were delivered before
+/// it. A failure on the first chunk is returned unchanged.
+pub fn partial_failure(err: anyhow::Error, chunks: &[String], sent: usize) -> anyhow::Error {
+    if sent == 0 || sent >= chunks.len() {
+        return err;
+    }
+    err.context(PartiallySent {
+        remaining: chunks[sent..].join("\n"),
+    })
+}
+
+/// What still has to be delivered after `err`: the undelivered rest of a
+/// partly sent message, else all of `text`.
+fn unsent(err: &anyhow::Error, text: &str) -> String {
+    match err.downcast_ref::<PartiallySent>() {
+        Some(p) => p.remaining.clone(),
+        None => text.to_string(),
+    }
+}
+
+/// `(permanent, retry_after)` for any send error.
+fn classify(err: &anyhow::Error) -> (bool, Option<Duration>) {
+    match err.chain().find_map(|e| e.downcast_ref::<DeliveryError>()) {
+        Some(d) => (d.is_permanent(), d.retry_after),
+        None => (false, None),
+    }
+}
+
+/// Delay before attempt `attempts + 1`: exponential from `base_delay_secs`,
+/// capped at `max_delay_secs`, never shorter than the platform asked for.
+pub fn backoff(cfg: &OutboxConfig, attempts: u32, retry_after: Option<Duration>) -> Duration {
+    let exp = attempts.saturating_sub(1).min(30);
+    let secs = cfg
+        .base_delay_secs
+        .saturating_mul(1u64 << exp)
+        .min(cfg.max_delay_secs);
+    Duration::from_secs(secs).max(retry_after.unwrap_or_default())
+}
+
+#[derive(Debug, Clone)]
+pub struct OutboxJob {
+    pub id: i64,
+    /// Channel id or builtin name that delivers this job.
+    pub sender: String,
+    pub conversation: String,
+    pub text: String,
+    /// Sender-specific extras (e.g. Telegram `parse_mode`, `reply_markup`).
+    pub options: Value,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub sender: String,
+    pub conversation: String,
+    pub text: String,
+    pub options: Value,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub failed_at: i64,
+}
+
+/// Outcome of [`submit`].
+pub enum Delivery<T> {
+    Sent(T),
+    /// Queued for the worker under this outbox id.
+    Queued(i64),
+}
+
+// ─── Global ──────────────────────────────────────────────────────────────────
+
+static CONFIG: OnceLock<OutboxConfig> = OnceLock::new();
+static STORE: OnceLock<Option<Arc<OutboxStore>>> = OnceLock::new();
+static CHANNELS: OnceLock<DashMap<String, Arc<dyn ChannelEgress>>> = OnceLock::new();
+static WAKE: OnceLock<Notify> = OnceLock::new();
+static WORKER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the `[outbox]` section. Idempotent — only the first call takes
+/// effect. The database is opened lazily on first use.
+pub fn init_global(cfg: &OutboxConfig) {
+    let _ = CONFIG.set(cfg.clone());
+}
+
+/// The active config (defaults when init_global has not been called).
+pub fn global_config() -> OutboxConfig {
+    CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// The shared queue. None when the outbox is disabled, init_global has not
+/// been called, or the database can't be opened.
+pub fn global_store() -> Option<Arc<OutboxStore>> {
+    STORE
+        .get_or_init(|| {
+            let cfg = CONFIG.get()?;
+            if !cfg.enabled {
+                return None;
+            }
+            match OutboxStore::open(&store_path(cfg)) {
+                Ok(store) => Some(Arc::new(store)),
+                Err(e) => {
+                    warn!("[outbox] disabled: {:#}", e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Database location for `cfg` (also used by the `juglans outbox` CLI,
+/// which works whether or not the outbox is enabled).
+pub fn store_path(cfg: &OutboxConfig) -> std::path::PathBuf {
+    cfg.path
+        .clone()
+        .unwrap_or_else(|| ".juglans/outbox.db".into())
+        .into()
+}
+
+fn channels() -> &'static DashMap<String, Arc<dyn ChannelEgress>> {
+    CHANNELS.get_or_init(DashMap::new)
+}
+
+fn wake() {
+    WAKE.get_or_init(Notify::new).notify_one();
+}
+
+/// Make a channel's egress available to the worker under its stable id.
+/// `juglans serve` registers every configured channel at boot.
+pub fn register_channel(id: &str, egress: Arc<dyn ChannelEgress>) {
+    channels().insert(id.to_string(), egress);
+}
+
+/// Id of a registered channel, matched by identity (run origins carry the
+/// channel itself, not its id).
+pub(crate) fn channel_id_of(egress: &Arc<dyn ChannelEgress>) -> Option<String> {
+    let ptr = Arc::as_ptr(egress) as *const ();
+    channels()
+        .iter()
+        .find(|e| Arc::as_ptr(e.value()) as *const () == ptr)
+        .map(|e| e.key().clone())
+}
+
+/// Send through `send` unless `(sender, conversation)` already has queued
+/// messages; queue the message when the send fails with a retryable error.
+/// Permanent failures are returned unchanged. Without an outbox this is just
+/// `send.await`. Every attempt counts toward the channel's egress metrics.
+pub async fn submit<T>(
+    sender: &str,
+    conversation: &str,
+    text: &str,
+    options: Value,
+    send: impl Future<Output = Result<T>>,
+) -> Result<Delivery<T>> {
+    let store = match global_store() {
+        Some(s) => s,
+        None => {
+            let result = send.await;
+            metrics::record_channel_sent(sender, result.is_ok());
+            return result.map(Delivery::Sent);
+        }
+    };
+    let now = chrono::Utc::now().timestamp();
+    if store.has_pending(sender, conversation)? {
+        let id = store.enqueue(sender, conversation, text, &options, 0, now, None)?;
+        wake();
+        return Ok(Delivery::Queued(id));
+    }
+    let result = send.await;
+    metrics::record_channel_sent(sender, result.is_ok());
+    match result {
+        Ok(v) => Ok(Delivery::Sent(v)),
+        Err(e) => {
+            let (permanent, retry_after) = classify(&e);
+            if permanent {
+                return Err(e);
+            }
+            let delay = backoff(&global_config(), 1, retry_after);
+            let error = format!("{:#}", e);
+            let id = store.enqueue(
+                sender,
+                conversation,
+                &unsent(&e, text),
+                &options,
+                1,
+                now + delay.as_secs() as i64,
+                Some(&error),
+            )?;
+            warn!(
+                "[outbox] {} → {} failed ({}); queued as #{} (retry in {}s)",
+                sender,
+                conversation,
+                error,
+                id,
+                delay.as_secs()
+            );
+            wake();
+            Ok(Delivery::Queued(id))
+        }
+    }
+}
+
+/// Channel-reply entry point used by the egress driver: queue-backed for
+/// registered channels, a plain `send` otherwise.
+pub async fn send_reply(
+    channel: &Arc<dyn ChannelEgress>,
+    conversation: &str,
+    text: &str,
+) -> Result<()> {
+    let id = match channel_id_of(channel) {
+        Some(id) => id,
+        None => return channel.send(conversation, text).await,
+    };
+    submit(
+        &id,
+        conversation,
+        text,
+        Value::Null,
+        channel.send(conversation, text),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Start the delivery worker (once per process). No-op when the outbox is
+/// disabled.
+pub fn spawn_worker() {
+    let store = match global_store() {
+        Some(s) => s,
+        None => return,
+    };
+    if WORKER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let cfg = global_config();
+    tokio::spawn(async move {
+        match store.pending_count() {
+            Ok(0) | Err(_) => {}
+            Ok(n) => info!("[outbox] {} queued message(s) from a previous run", n),
+        }
+        loop {
+            if let Err(e) = run_due(&store, &cfg).await {
+                warn!("[outbox] worker pass failed: {:#}", e);
+            }
+            let now = chrono::Utc::now().timestamp();
+            let sleep = match store.next_due_at() {
+                Ok(Some(at)) => (at - now).clamp(0, IDLE_POLL_SECS as i64) as u64,
+                _ => IDLE_POLL_SECS,
+            };
+            tokio::select! {
+                _ = WAKE.get_or_init(Notify::new).notified() => {}
+                _ = tokio::time::sleep(Duration::from_secs(sleep.max(1))) => {}
+            }
+        }
+    });
+}
+
+/// Attempt every job that is due and first in its conversation.
+async fn run_due(store: &OutboxStore, cfg: &OutboxConfig) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    for job in store.due(now, BATCH_SIZE)? {
+        match deliver(&job).await {
+            Ok(()) => {
+                store.remove(job.id)?;
+                info!(
+                    "[outbox] delivered #{} to {} → {} (attempt {})",
+                    job.id,
+                    job.sender,
+                    job.conversation,
+                    job.attempts + 1
+                );
+            }
+            Err(e) => {
+                let attempts = job.attempts + 1;
+                let error = format!("{:#}", e);
+                let (permanent, retry_after) = classify(&e);
+                let rest = unsent(&e, &job.text);
+                if rest != job.text {
+                    store.set_text(job.id, &rest)?;
+                }
+                if permanent || attempts >= cfg.max_attempts {
+                    store.dead_letter(job.id, attempts, &error)?;
+                    warn!(
+                        "[outbox] #{} to {} → {} dead-lettered after {} attempt(s): {}",
+                        job.id, job.sender, job.conversation, attempts, error
+                    );
+                } else {
+                    let delay = backoff(cfg, attempts, retry_after);
+                    store.reschedule(job.id, attempts, now + delay.as_secs() as i64, &error)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn deliver(job: &OutboxJob) -> Result<()> {
+    let channel = channels().get(&job.sender).map(|c| c.value().clone());
+    let result = match channel {
+        Some(channel) => channel.send(&job.conversation, &job.text).await,
+        None => crate::builtins::platforms::redeliver(
+            &job.sender,
+            &job.conversation,
+            &job.text,
+            &job.options,
+        )
+        .await
+        .unwrap_or_else(|| {
+            Err(anyhow!(
+                "no channel or builtin named `{}` is registered (removed from config?)",
+                job.sender
+            ))
+        }),
+    };
+    metrics::record_channel_sent(&job.sender, result.is_ok());
+    result
+}
+
+// ─── Store ───────────────────────────────────────────────────────────────────
+
+pub struct OutboxStore {
+    conn: Mutex<Connection>,
+}
+
+const JOB_COLUMNS: &str =
+    "id, sender, conversation, text, options, attempts, next_attempt_at, last_error, created_at";
+const DEAD_COLUMNS: &str =
+    "id, sender, conversation, text, options, attempts, last_error, created_at, failed_at";
+
+fn options_from(raw: Option<String>) -> Value {
+    raw.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn job_from_row(row: &Row) -> rusqlite::Result<OutboxJob> {
+    Ok(OutboxJob {
+        id: row.get(0)?,
+        sender: row.get(1)?,
+        conversation: row.get(2)?,
+        text: row.get(3)?,
+        options: options_from(row.get(4)?),
+        attempts: row.get(5)?,
+        next_attempt_at: row.get(6)?,
+        last_error: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+fn dead_from_row(row: &Row) -> rusqlite::Result<DeadLetter> {
+    Ok(DeadLetter {
+        id: row.get(0)?,
+        sender: row.get(1)?,
+        conversation: row.get(2)?,
+        text: row.get(3)?,
+        options: options_from(row.get(4)?),
+        attempts: row.get(5)?,
+        last_error: row.get(6)?,
+        created_at: row.get(7)?,
+        failed_at: row.get(8)?,
+    })
+}
+
+impl OutboxStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS outbox (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                sender          TEXT NOT NULL,
+                conversation    TEXT NOT NULL,
+                text            TEXT NOT NULL,
+                options         TEXT,
+                attempts        INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL,
+                last_error      TEXT,
+                created_at      INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_outbox_conversation
+                ON outbox(sender, conversation, id);
+            CREATE TABLE IF NOT EXISTS dead_letters (
+                id           INTEGER PRIMARY KEY,
+                sender       TEXT NOT NULL,
+                conversation TEXT NOT NULL,
+                text         TEXT NOT NULL,
+                options      TEXT,
+                attempts     INTEGER NOT NULL,
+                last_error   TEXT,
+                created_at   INTEGER NOT NULL,
+                failed_at    INTEGER NOT NULL
+            );
+            ",
+        )
+        .context("Failed to initialize outbox schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Connection) -> Result<R>,
+    {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("outbox db lock poisoned"))?;
+        f(&mut conn)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn enqueue(
+        &self,
+        sender: &str,
+        conversation: &str,
+        text: &str,
+        options: &Value,
+        attempts: u32,
+        next_attempt_at: i64,
+        last_error: Option<&str>,
+    ) -> Result<i64> {
+        let options = (!options.is_null()).then(|| options.to_string());
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            c.execute(
+                "INSERT INTO outbox (sender, conversation, text, options, attempts,
+                                     next_attempt_at, last_error, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    sender,
+                    conversation,
+                    text,
+                    options,
+                    attempts,
+                    next_attempt_at,
+                    last_error,
+                    now
+                ],
+            )?;
+            Ok(c.last_insert_rowid())
+        })
+    }
+
+    pub fn has_pending(&self, sender: &str, conversation: &str) -> Result<bool> {
+        self.with_conn(|c| {
+            Ok(c.query_row(
+                "SELECT 1 FROM outbox WHERE sender = ?1 AND conversation = ?2 LIMIT 1",
+                params![sender, conversation],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+        })
+    }
+
+    /// Jobs due at `now` that are the oldest of their conversation.
+    pub fn due(&self, now: i64, limit: usize) -> Result<Vec<OutboxJob>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(&format!(
+                "SELECT {} FROM outbox o
+                 WHERE next_attempt_at <= ?1
+                   AND id = (SELECT MIN(id) FROM outbox h
+                             WHERE h.sender = o.sender AND h.conversation = o.conversation)
+                 ORDER BY id LIMIT ?2",
+                JOB_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![now, limit as i64], job_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    pub fn next_due_at(&self) -> Result<Option<i64>> {
+        self.with_conn(|c| {
+            Ok(c.query_row("SELECT MIN(next_attempt_at) FROM outbox", [], |r| r.get(0))?)
+        })
+    }
+
+    pub fn pending_count(&self) -> Result<usize> {
+        self.with_conn(|c| {
+            let n: i64 = c.query_row("SELECT COUNT(*) FROM outbox", [], |r| r.get(0))?;
+            Ok(n as usize)
+        })
+    }
+
+    pub fn remove(&self, id: i64) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute("DELETE FROM outbox WHERE id = ?1", params![id])?;
+            Ok(())
+        })
+    }
+
+    pub fn reschedule(
+        &self,
+        id: i64,
+        attempts: u32,
+        next_attempt_at: i64,
+        error: &str,
+    ) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute(
+                "UPDATE outbox SET attempts = ?2, next_attempt_at = ?3, last_error = ?4
+                 WHERE id = ?1",
+                params![id, attempts, next_attempt_at, error],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Replace a job's text (what is left after a partial delivery).
+    pub fn set_text(&self, id: i64, text: &str) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute(
+                "UPDATE outbox SET text = ?2 WHERE id = ?1",
+                params![id, text],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Move a job to `dead_letters`, keeping its id.
+    pub fn dead_letter(&self, id: i64, attempts: u32, error: &str) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            let tx = c.transaction()?;
+            tx.execute(
+                "INSERT OR REPLACE INTO dead_letters
+                    (id, sender, conversation, text, options, attempts, last_error,
+                     created_at, failed_at)
+                 SELECT id, sender, conversation, text, options, ?2, ?3, created_at, ?4
+                 FROM outbox WHERE id = ?1",
+                params![id, attempts, error, now],
+            )?;
+            tx.execute("DELETE FROM outbox WHERE id = ?1", params![id])?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    pub fn list_pending(&self, limit: usize) -> Result<Vec<OutboxJob>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(&format!(
+                "SELECT {} FROM outbox ORDER BY id LIMIT ?1",
+                JOB_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![limit as i64], job_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    pub fn list_dead(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(&format!(
+                "SELECT {} FROM dead_letters ORDER BY id LIMIT ?1",
+                DEAD_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![limit as i64], dead_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// Requeue dead letters (one id, or all with `None`) for immediate
+    /// delivery with a fresh attempt budget. Returns how many moved.
+    pub fn retry_dead(&self, id: Option<i64>) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            let tx = c.transaction()?;
+            let moved = tx.execute(
+                "INSERT INTO outbox (id, sender, conversation, text, options, attempts,
+                                     next_attempt_at, last_error, created_at)
+                 SELECT id, sender, conversation, text, options, 0, ?2, last_error, created_at
+                 FROM dead_letters WHERE ?1 IS NULL OR id = ?1",
+                params![id, now],
+            )?;
+            tx.execute(
+                "DELETE FROM dead_letters WHERE ?1 IS NULL OR id = ?1",
+                params![id],
+            )?;
+            tx.commit()?;
+            Ok(moved)
+        })
+    }
+
+    /// Delete dead letters, or queued messages with `pending`. `None` deletes
+    /// every row of that table. Returns how many were deleted.
+    pub fn purge(&self, id: Option<i64>, pending: bool) -> Result<usize> {
+        let table = if pending { "outbox" } else { "dead_letters" };
+        self.with_conn(|c| {
+            Ok(c.execute(
+                &format!("DELETE FROM {} WHERE ?1 IS NULL OR id = ?1", table),
+                params![id],
+            )?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cfg() -> OutboxConfig {
+        OutboxConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_caps_and_honors_retry_after() {
+        let cfg = cfg();
+        assert_eq!(backoff(&cfg, 1, None), Duration::from_secs(2));
+        assert_eq!(backoff(&cfg, 3, None), Duration::from_secs(8));
+        assert_eq!(backoff(&cfg, 40, None), Duration::from_secs(600));
+        assert_eq!(
+            backoff(&cfg, 1, Some(Duration::from_secs(30))),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn classifies_errors() {
+        let rate: anyhow::Error =
+            DeliveryError::new(Some(429), Some(Duration::from_secs(5)), "slow".into()).into();
+        assert_eq!(classify(&rate), (false, Some(Duration::from_secs(5))));
+        let gone: anyhow::Error = DeliveryError::new(Some(403), None, "kicked".into()).into();
+        assert!(classify(&gone).0);
+        let wrapped = anyhow::Error::from(DeliveryError::new(Some(502), None, "bad gw".into()))
+            .context("sendMessage");
+        assert_eq!(classify(&wrapped), (false, None));
+        assert_eq!(classify(&anyhow!("connection reset")), (false, None));
+    }
+
+    /// Delivers the first chunk of every message, then fails.
+    struct FirstChunkOnly(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl ChannelEgress for FirstChunkOnly {
+        async fn send(&self, _conversation: &str, text: &str) -> Result<()> {
+            let chunks: Vec<String> = text.split('\n').map(String::from).collect();
+            self.0.lock().unwrap().push(chunks[0].clone());
+            if chunks.len() == 1 {
+                return Ok(());
+            }
+            Err(partial_failure(
+                DeliveryError::new(Some(502), None, "bad gateway".into()).into(),
+                &chunks,
+                1,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn partial_delivery_requeues_only_the_rest() {
+        let first: anyhow::Error = partial_failure(anyhow!("reset"), &["a".into()], 0);
+        assert_eq!(unsent(&first, "a"), "a");
+
+        let sender = format!("test:{}", uuid::Uuid::new_v4());
+        let channel = Arc::new(FirstChunkOnly(Mutex::new(Vec::new())));
+        register_channel(&sender, channel.clone());
+        let store = OutboxStore::open_in_memory().unwrap();
+        let id = store
+            .enqueue(&sender, "c", "one\ntwo\nthree", &Value::Null, 0, 0, None)
+            .unwrap();
+
+        run_due(&store, &cfg()).await.unwrap();
+        let job = &store.list_pending(10).unwrap()[0];
+        assert_eq!((job.id, job.attempts), (id, 1));
+        assert_eq!(job.text, "two\nthree");
+        assert!(job.last_error.as_deref().unwrap().contains("bad gateway"));
+
+        store.reschedule(id, 1, 0, "").unwrap();
+        run_due(&store, &cfg()).await.unwrap();
+        store.reschedule(id, 2, 0, "").unwrap();
+        run_due(&store, &cfg()).await.unwrap();
+        assert_eq!(store.pending_count().unwrap(), 0);
+        assert_eq!(*channel.0.lock().unwrap(), vec!["one", "two", "three"]);
+    }
+
+    #[test]
+    fn due_returns_head_of_each_conversation() {
+        let store = OutboxStore::open_in_memory().unwrap();
+        let a1 = store
+            .enqueue("tg", "a", "one", &Value::Null, 1, 100, Some("x"))
+            .unwrap();
+        let a2 = store
+            .enqueue("tg", "a", "two", &Value::Null, 0, 0, None)
+            .unwrap();
+        let b1 = store
+            .enqueue("tg", "b", "hi", &json!({"parse_mode": "HTML"}), 0, 0, None)
+            .unwrap();
+
+        // a1 isn't due yet and a2 must not overtake it.
+        let due = store.due(50, 10).unwrap();
+        assert_eq!(due.iter().map(|j| j.id).collect::<Vec<_>>(), vec![b1]);
+        assert_eq!(due[0].options["parse_mode"], "HTML");
+        assert!(store.has_pending("tg", "a").unwrap());
+        assert_eq!(store.next_due_at().unwrap(), Some(0));
+
+        store.remove(b1).unwrap();
+        let due = store.due(100, 10).unwrap();
+        assert_eq!(due.iter().map(|j| j.id).collect::<Vec<_>>(), vec![a1]);
+        store.remove(a1).unwrap();
+        assert_eq!(store.due(100, 10).unwrap()[0].id, a2);
+    }
+
+    #[test]
+    fn dead_letters_can_be_retried_and_purged() {
+        let store = OutboxStore::open_in_memory().unwrap();
+        let id = store
+            .enqueue(
+                "feishu.send_message",
+                "oc_1",
+                "hi",
+                &Value::Null,
+                0,
+                0,
+                None,
+            )
+            .unwrap();
+        store.reschedule(id, 3, 10, "HTTP 500").unwrap();
+        store.dead_letter(id, 4, "HTTP 503").unwrap();
+        assert_eq!(store.pending_count().unwrap(), 0);
+        let dead = store.list_dead(10).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].id, dead[0].attempts), (id, 4));
+        assert_eq!(dead[0].last_error.as_deref(), Some("HTTP 503"));
+
+        assert_eq!(store.retry_dead(Some(id)).unwrap(), 1);
+        let pending = store.list_pending(10).unwrap();
+        assert_eq!((pending[0].id, pending[0].attempts), (id, 0));
+        assert!(store.list_dead(10).unwrap().is_empty());
+
+        store.dead_letter(id, 1, "again").unwrap();
+        assert_eq!(store.purge(None, false).unwrap(), 1);
+        assert_eq!(store.purge(None, true).unwrap(), 0);
+    }
+}
@@ -0,0 +1,688 @@
+// src/services/runs.rs
+//
+// Run store behind `/api/runs`. `juglans serve` records every run started
+// with `POST /api/runs` in SQLite: its input, status, final `output`, error,
+// node trace and the full `WorkflowEvent` log, so a long workflow doesn't need
+// an open HTTP connection and finished runs can be inspected later:
+//
+//   [runs]
+//   enabled = true             # default
+//   path = ".juglans/runs.db"
+//   retention_days = 30        # finished runs older than this are pruned at boot
+//
+// Events are numbered per run (`seq`, from 1). While a run is executing its
+// recorder also broadcasts each event, so `GET /api/runs/{id}/events` can
+// replay what is stored and then tail the live run without gaps. Runs still
+// executing when a draining server gives up on them are marked `failed`
+// before it exits; any left `running` by a crash are closed on the next
+// start. Under `[server] reuse_port` the next start skips that step: the
+// previous process may still be draining, and it closes out its own runs.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Context, Result};
+use dashmap::DashMap;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::config::RunsConfig;
+use crate::core::context::{ToolTraceEntry, TraceStatus, WorkflowEvent};
+
+/// Buffered live events per subscriber before it has to catch up from the store.
+const LIVE_CAPACITY: usize = 1024;
+/// Error recorded on runs interrupted by a shutdown or restart.
+const INTERRUPTED: &str = "interrupted: server stopped before the run finished";
+
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+pub const STATUS_FAILED: &str = "failed";
+
+/// One row of `runs`. `output` and `trace` are `null` until the run finishes.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub workflow: String,
+    pub status: String,
+    pub input: Value,
+    pub output: Value,
+    pub error: Option<String>,
+    pub trace: Value,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+    pub duration_ms: Option<u64>,
+}
+
+impl RunRecord {
+    /// The record without input, output and trace — what run listings show.
+    pub fn summary(&self) -> Value {
+        json!({
+            "id": self.id,
+            "workflow": self.workflow,
+            "status": self.status,
+            "error": self.error,
+            "created_at": self.created_at,
+            "finished_at": self.finished_at,
+            "duration_ms": self.duration_ms,
+        })
+    }
+}
+
+/// A stored event: `data` is the JSON payload, whose `type` names the event.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunEvent {
+    pub seq: i64,
+    pub data: Value,
+}
+
+/// Filters for [`RunStore::list`].
+#[derive(Debug, Default)]
+pub struct RunFilter<'a> {
+    pub workflow: Option<&'a str>,
+    pub status: Option<&'a str>,
+    pub limit: usize,
+}
+
+/// JSON payload for a workflow event, shaped like the `/api/chat` SSE data.
+/// `ToolCall` yields only `call_id` and `tools`; the caller keeps `result_tx`.
+pub fn event_payload(event: &WorkflowEvent) -> Value {
+    fn tagged(evt: impl Serialize, kind: &str) -> Value {
+        let mut data = serde_json::to_value(evt).unwrap_or_else(|_| json!({}));
+        data["type"] = json!(kind);
+        data
+    }
+    match event {
+        WorkflowEvent::Token(t) => json!({ "type": "content", "text": t }),
+        WorkflowEvent::Status(s) => json!({ "type": "meta", "status": s }),
+        WorkflowEvent::Meta(data) => match data {
+            Value::Object(obj) if obj.contains_key("type") => data.clone(),
+            Value::Object(_) => {
+                let mut data = data.clone();
+                data["type"] = json!("meta");
+                data
+            }
+            other => json!({ "type": "meta", "data": other }),
+        },
+        WorkflowEvent::Error(e) => json!({ "type": "error", "message": e }),
+        WorkflowEvent::Yield(data) => {
+            if data.get("type").and_then(|t| t.as_str()).is_some() {
+                data.clone()
+            } else {
+                json!({ "type": "yield", "data": data })
+            }
+        }
+        WorkflowEvent::ToolCall { call_id, tools, .. } => {
+            json!({ "type": "tool_call", "call_id": call_id, "tools": tools })
+        }
+        WorkflowEvent::ToolStart(evt) => tagged(evt, "tool_start"),
+        WorkflowEvent::ToolComplete(evt) => tagged(evt, "tool_complete"),
+        WorkflowEvent::NodeStart(evt) => tagged(evt, "node_start"),
+        WorkflowEvent::NodeComplete(evt) => tagged(evt, "node_complete"),
+    }
+}
+
+/// Node/tool trace of a finished run, as stored in `runs.trace`.
+pub fn trace_json(entries: &[ToolTraceEntry]) -> Value {
+    Value::Array(
+        entries
+            .iter()
+            .map(|e| {
+                let (status, error) = match &e.status {
+                    TraceStatus::Success => ("success", None),
+                    TraceStatus::Error(msg) => ("error", Some(msg.clone())),
+                };
+                json!({
+                    "node_id": e.node_id,
+                    "tool": e.tool,
+                    "params": e.params,
+                    "result": e.result,
+                    "status": status,
+                    "error": error,
+                    "duration_ms": e.duration.as_millis() as u64,
+                })
+            })
+            .collect(),
+    )
+}
+
+// ─── Global ──────────────────────────────────────────────────────────────────
+
+static CONFIG: OnceLock<RunsConfig> = OnceLock::new();
+static STORE: OnceLock<Option<Arc<RunStore>>> = OnceLock::new();
+static LIVE: OnceLock<DashMap<String, broadcast::Sender<RunEvent>>> = OnceLock::new();
+static KEEP_UNFINISHED: AtomicBool = AtomicBool::new(false);
+
+/// Install the `[runs]` section. Idempotent — only the first call takes
+/// effect. The database is opened lazily on first use.
+pub fn init_global(cfg: &RunsConfig) {
+    let _ = CONFIG.set(cfg.clone());
+}
+
+/// Leave `running` rows alone when the store is opened. For `reuse_port`,
+/// where they may belong to a previous process that is still draining them.
+/// Call before the first `global_store`.
+pub fn keep_unfinished_on_open() {
+    KEEP_UNFINISHED.store(true, Ordering::SeqCst);
+}
+
+/// The shared store. None when runs are disabled, init_global has not been
+/// called, or the database can't be opened. Opening it marks runs left over
+/// from a previous process as failed (unless `keep_unfinished_on_open`) and
+/// applies `retention_days`.
+pub fn global_store() -> Option<Arc<RunStore>> {
+    STORE
+        .get_or_init(|| {
+            let cfg = CONFIG.get()?;
+            if !cfg.enabled {
+                return None;
+            }
+            let store = match RunStore::open(&store_path(cfg)) {
+                Ok(store) => store,
+                Err(e) => {
+                    warn!("[runs] disabled: {:#}", e);
+                    return None;
+                }
+            };
+            let now = chrono::Utc::now().timestamp();
+            if !KEEP_UNFINISHED.load(Ordering::SeqCst) {
+                match store.interrupt_unfinished(now) {
+                    Ok(0) => {}
+                    Ok(n) => info!("[runs] marked {} unfinished run(s) as failed", n),
+                    Err(e) => warn!("[runs] could not close unfinished runs: {:#}", e),
+                }
+            }
+            if cfg.retention_days > 0 {
+                let cutoff = now - i64::from(cfg.retention_days) * 86_400;
+                if let Err(e) = store.prune(cutoff) {
+                    warn!("[runs] retention prune failed: {:#}", e);
+                }
+            }
+            Some(Arc::new(store))
+        })
+        .clone()
+}
+
+/// Database location for `cfg`.
+pub fn store_path(cfg: &RunsConfig) -> std::path::PathBuf {
+    cfg.path
+        .clone()
+        .unwrap_or_else(|| ".juglans/runs.db".into())
+        .into()
+}
+
+fn live() -> &'static DashMap<String, broadcast::Sender<RunEvent>> {
+    LIVE.get_or_init(DashMap::new)
+}
+
+/// Live feed of a run that is still executing in this process. Subscribe
+/// before reading stored events so nothing falls between replay and tail;
+/// the feed closes when the run finishes.
+pub fn subscribe(run_id: &str) -> Option<broadcast::Receiver<RunEvent>> {
+    live().get(run_id).map(|tx| tx.subscribe())
+}
+
+/// Fail the runs still executing in this process and close their live
+/// feeds, for a shutdown that can't wait for them. Returns how many.
+pub fn interrupt_live() -> usize {
+    let ids: Vec<String> = live().iter().map(|e| e.key().clone()).collect();
+    live().clear();
+    let Some(store) = global_store() else {
+        return 0;
+    };
+    let now = chrono::Utc::now().timestamp();
+    ids.iter()
+        .filter(|id| match store.interrupt(id, now) {
+            Ok(interrupted) => interrupted,
+            Err(e) => {
+                warn!("[runs] {}: could not be interrupted: {:#}", id, e);
+                false
+            }
+        })
+        .count()
+}
+
+/// Writes one run: creates the row, appends and broadcasts its events, and
+/// records the outcome.
+pub struct RunRecorder {
+    store: Arc<RunStore>,
+    run_id: String,
+    seq: i64,
+    tx: broadcast::Sender<RunEvent>,
+}
+
+impl RunRecorder {
+    pub fn start(
+        store: Arc<RunStore>,
+        run_id: &str,
+        workflow: &str,
+        input: &Value,
+    ) -> Result<Self> {
+        store.create(run_id, workflow, input, chrono::Utc::now().timestamp())?;
+        let (tx, _) = broadcast::channel(LIVE_CAPACITY);
+        live().insert(run_id.to_string(), tx.clone());
+        Ok(Self {
+            store,
+            run_id: run_id.to_string(),
+            seq: 0,
+            tx,
+        })
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Store then broadcast `data`. A failed write is logged, not fatal: the
+    /// run itself carries on.
+    pub fn record(&mut self, data: Value) {
+        self.seq += 1;
+        if let Err(e) = self.store.append_event(&self.run_id, self.seq, &data) {
+            warn!(
+                "[runs] {}: event #{} not stored: {:#}",
+                self.run_id, self.seq, e
+            );
+        }
+        let _ = self.tx.send(RunEvent {
+            seq: self.seq,
+            data,
+        });
+    }
+
+    /// Record the outcome and close the live feed.
+    pub fn finish(self, result: &Result<()>, output: Value, trace: Value, duration_ms: u64) {
+        let (status, error) = match result {
+            Ok(()) => (STATUS_SUCCEEDED, None),
+            Err(e) => (STATUS_FAILED, Some(e.to_string())),
+        };
+        let now = chrono::Utc::now().timestamp();
+        if let Err(e) = self.store.finish(
+            &self.run_id,
+            status,
+            &output,
+            error.as_deref(),
+            &trace,
+            now,
+            duration_ms,
+        ) {
+            warn!("[runs] {}: outcome not stored: {:#}", self.run_id, e);
+        }
+        live().remove(&self.run_id);
+    }
+}
+
+// ─── Store ───────────────────────────────────────────────────────────────────
+
+pub struct RunStore {
+    conn: Mutex<Connection>,
+}
+
+const RUN_COLUMNS: &str =
+    "id, workflow, status, input, output, error, trace, created_at, finished_at, duration_ms";
+
+fn json_from(raw: Option<String>) -> Value {
+    raw.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn run_from_row(row: &Row) -> rusqlite::Result<RunRecord> {
+    Ok(RunRecord {
+        id: row.get(0)?,
+        workflow: row.get(1)?,
+        status: row.get(2)?,
+        input: json_from(row.get(3)?),
+        output: json_from(row.get(4)?),
+        error: row.get(5)?,
+        trace: json_from(row.get(6)?),
+        created_at: row.get(7)?,
+        finished_at: row.get(8)?,
+        duration_ms: row.get::<_, Option<i64>>(9)?.map(|d| d as u64),
+    })
+}
+
+impl RunStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS runs (
+                id          TEXT PRIMARY KEY,
+                workflow    TEXT NOT NULL,
+                status      TEXT NOT NULL,
+                input       TEXT,
+                output      TEXT,
+                error       TEXT,
+                trace       TEXT,
+                created_at  INTEGER NOT NULL,
+                finished_at INTEGER,
+                duration_ms INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS idx_runs_workflow ON runs(workflow, created_at);
+            CREATE INDEX IF NOT EXISTS idx_runs_created ON runs(created_at);
+            CREATE TABLE IF NOT EXISTS run_events (
+                run_id     TEXT NOT NULL,
+                seq        INTEGER NOT NULL,
+                data       TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (run_id, seq)
+            );
+            ",
+        )
+        .context("Failed to initialize run store schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Connection) -> Result<R>,
+    {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("run store lock poisoned"))?;
+        f(&mut conn)
+    }
+
+    pub fn create(&self, id: &str, workflow: &str, input: &Value, now: i64) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute(
+                "INSERT INTO runs (id, workflow, status, input, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, workflow, STATUS_RUNNING, input.to_string(), now],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn append_event(&self, run_id: &str, seq: i64, data: &Value) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.with_conn(|c| {
+            c.execute(
+                "INSERT INTO run_events (run_id, seq, data, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![run_id, seq, data.to_string(), now],
+            )?;
+            Ok(())
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn finish(
+        &self,
+        id: &str,
+        status: &str,
+        output: &Value,
+        error: Option<&str>,
+        trace: &Value,
+        finished_at: i64,
+        duration_ms: u64,
+    ) -> Result<()> {
+        self.with_conn(|c| {
+            c.execute(
+                "UPDATE runs SET status = ?2, output = ?3, error = ?4, trace = ?5,
+                                 finished_at = ?6, duration_ms = ?7
+                 WHERE id = ?1",
+                params![
+                    id,
+                    status,
+                    output.to_string(),
+                    error,
+                    trace.to_string(),
+                    finished_at,
+                    duration_ms as i64
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<RunRecord>> {
+        self.with_conn(|c| {
+            Ok(c.query_row(
+                &format!("SELECT {} FROM runs WHERE id = ?1", RUN_COLUMNS),
+                params![id],
+                run_from_row,
+            )
+            .optional()?)
+        })
+    }
+
+    /// Newest first.
+    pub fn list(&self, filter: &RunFilter) -> Result<Vec<RunRecord>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(&format!(
+                "SELECT {} FROM runs
+                 WHERE (?1 IS NULL OR workflow = ?1) AND (?2 IS NULL OR status = ?2)
+                 ORDER BY created_at DESC, rowid DESC LIMIT ?3",
+                RUN_COLUMNS
+            ))?;
+            let rows = stmt.query_map(
+                params![filter.workflow, filter.status, filter.limit as i64],
+                run_from_row,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// Events of `run_id` with `seq > after`, in order.
+    pub fn events(&self, run_id: &str, after: i64) -> Result<Vec<RunEvent>> {
+        self.with_conn(|c| {
+            let mut stmt = c.prepare(
+                "SELECT seq, data FROM run_events WHERE run_id = ?1 AND seq > ?2 ORDER BY seq",
+            )?;
+            let rows = stmt.query_map(params![run_id, after], |row| {
+                Ok(RunEvent {
+                    seq: row.get(0)?,
+                    data: json_from(row.get(1)?),
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// Fail run `id` if it is still marked `running`.
+    pub fn interrupt(&self, id: &str, now: i64) -> Result<bool> {
+        self.with_conn(|c| {
+            let changed = c.execute(
+                "UPDATE runs SET status = ?2, error = ?3, finished_at = ?4
+                 WHERE id = ?1 AND status = ?5",
+                params![id, STATUS_FAILED, INTERRUPTED, now, STATUS_RUNNING],
+            )?;
+            Ok(changed > 0)
+        })
+    }
+
+    /// Fail every run still marked `running`. Only valid at startup, before
+    /// this process has started any.
+    pub fn interrupt_unfinished(&self, now: i64) -> Result<usize> {
+        self.with_conn(|c| {
+            Ok(c.execute(
+                "UPDATE runs SET status = ?1, error = ?2, finished_at = ?3 WHERE status = ?4",
+                params![STATUS_FAILED, INTERRUPTED, now, STATUS_RUNNING],
+            )?)
+        })
+    }
+
+    /// Delete finished runs created before `cutoff`, with their events.
+    pub fn prune(&self, cutoff: i64) -> Result<usize> {
+        self.with_conn(|c| {
+            let tx = c.transaction()?;
+            tx.execute(
+                "DELETE FROM run_events WHERE run_id IN
+                    (SELECT id FROM runs WHERE created_at < ?1 AND status != ?2)",
+                params![cutoff, STATUS_RUNNING],
+            )?;
+            let n = tx.execute(
+                "DELETE FROM runs WHERE created_at < ?1 AND status != ?2",
+                params![cutoff, STATUS_RUNNING],
+            )?;
+            tx.commit()?;
+            Ok(n)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::NodeCompleteEvent;
+
+    fn store() -> Arc<RunStore> {
+        Arc::new(RunStore::open_in_memory().unwrap())
+    }
+
+    #[test]
+    fn lifecycle_is_stored() {
+        let store = store();
+        let mut rec = RunRecorder::start(store.clone(), "r1", "triage", &json!({"q": 1})).unwrap();
+        assert!(subscribe("r1").is_some());
+        rec.record(json!({"type": "content", "text": "hi"}));
+        rec.record(json!({"type": "node_complete", "node_id": "a"}));
+
+        let run = store.get("r1").unwrap().unwrap();
+        assert_eq!(run.status, STATUS_RUNNING);
+        assert_eq!(run.input, json!({"q": 1}));
+        assert!(run.output.is_null());
+
+        rec.finish(&Ok(()), json!("done"), json!([]), 12);
+        assert!(subscribe("r1").is_none());
+
+        let run = store.get("r1").unwrap().unwrap();
+        assert_eq!(run.status, STATUS_SUCCEEDED);
+        assert_eq!(run.output, json!("done"));
+        assert_eq!(run.duration_ms, Some(12));
+        assert!(run.finished_at.is_some());
+
+        let events = store.events("r1", 0).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].seq, 1);
+        assert_eq!(events[1].data["node_id"], "a");
+        assert_eq!(store.events("r1", 1).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn live_feed_follows_recorder() {
+        let store = store();
+        let mut rec = RunRecorder::start(store, "r2", "wf", &Value::Null).unwrap();
+        let mut rx = subscribe("r2").unwrap();
+        rec.record(json!({"type": "content", "text": "a"}));
+        rec.finish(&Err(anyhow!("boom")), Value::Null, json!([]), 1);
+
+        let evt = rx.recv().await.unwrap();
+        assert_eq!(evt.seq, 1);
+        assert!(matches!(
+            rx.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        ));
+    }
+
+    #[test]
+    fn list_filters_and_orders() {
+        let store = store();
+        store.create("a", "alpha", &Value::Null, 100).unwrap();
+        store.create("b", "beta", &Value::Null, 200).unwrap();
+        store.create("c", "alpha", &Value::Null, 300).unwrap();
+        store
+            .finish(
+                "c",
+                STATUS_FAILED,
+                &Value::Null,
+                Some("x"),
+                &json!([]),
+                301,
+                5,
+            )
+            .unwrap();
+
+        let all = store
+            .list(&RunFilter {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        let ids: Vec<_> = all.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["c", "b", "a"]);
+
+        let alpha = store
+            .list(&RunFilter {
+                workflow: Some("alpha"),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(alpha.len(), 2);
+
+        let failed = store
+            .list(&RunFilter {
+                status: Some(STATUS_FAILED),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].summary()["error"], "x");
+    }
+
+    #[test]
+    fn restart_interrupts_and_prunes() {
+        let store = store();
+        store.create("old", "wf", &Value::Null, 10).unwrap();
+        store.append_event("old", 1, &json!({})).unwrap();
+        store.create("new", "wf", &Value::Null, 1_000).unwrap();
+
+        assert!(store.interrupt("new", 1_500).unwrap());
+        assert!(!store.interrupt("new", 1_600).unwrap());
+        assert_eq!(store.get("new").unwrap().unwrap().finished_at, Some(1_500));
+        assert_eq!(store.interrupt_unfinished(2_000).unwrap(), 1);
+        let old = store.get("old").unwrap().unwrap();
+        assert_eq!(old.status, STATUS_FAILED);
+        assert_eq!(old.error.as_deref(), Some(INTERRUPTED));
+
+        assert_eq!(store.prune(500).unwrap(), 1);
+        assert!(store.get("old").unwrap().is_none());
+        assert!(store.events("old", 0).unwrap().is_empty());
+        assert!(store.get("new").unwrap().is_some());
+    }
+
+    #[test]
+    fn payloads_are_typed() {
+        assert_eq!(
+            event_payload(&WorkflowEvent::Token("x".into())),
+            json!({"type": "content", "text": "x"})
+        );
+        assert_eq!(
+            event_payload(&WorkflowEvent::Yield(json!({"n": 1}))),
+            json!({"type": "yield", "data": {"n": 1}})
+        );
+        let done = event_payload(&WorkflowEvent::NodeComplete(NodeCompleteEvent {
+            node_id: "a".into(),
+            tool: "set".into(),
+            status: "success".into(),
+            result: None,
+            error: None,
+        }));
+        assert_eq!(done["type"], "node_complete");
+        assert_eq!(done["node_id"], "a");
+    }
+}
@@ -0,0 +1,439 @@
+// src/services/sandbox.rs
+//
+// Sandbox for the builtin devtools (read_file / write_file / edit_file /
+// glob / grep / bash).
+//
+//   [sandbox]
+//   enabled = true
+//   root = "./workspace"              # file tools + bash cwd confined here
+//   env_allowlist = ["PATH", "HOME"]  # everything else is stripped
+//   isolation = "auto"                # auto | bwrap | unshare | none
+//   network = false
+//   cpu_secs = 30
+//   memory_mb = 512
+//
+// File tools resolve every path against `root` (symlinks included) and
+// refuse anything that lands outside it. bash runs with a cleared
+// environment, `ulimit` CPU / address-space limits, and in fresh namespaces
+// without network access. `auto` takes bubblewrap, then unshare; if neither
+// can create namespaces here the sandbox refuses to start rather than run
+// bash unconfined. Only `isolation = "none"` with `network = true` opts out.
+//
+// Violations are `[permission_denied] ...` errors, which the executor's
+// `classify_error` surfaces as `$output.err.kind == "permission_denied"`.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tracing::{info, warn};
+
+use super::config::SandboxConfig;
+use super::tool_policy::normalize;
+
+/// How bash commands are isolated from the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    /// bubblewrap: new namespaces, read-only system dirs, only `root` writable.
+    Bwrap,
+    /// unshare(1): new user/pid (and net) namespaces, host filesystem visible.
+    Unshare,
+    /// rlimits + env stripping only.
+    None,
+}
+
+impl Isolation {
+    /// Whether this isolation works here: the binary is on PATH and can
+    /// create namespaces (user namespaces are often disabled in containers).
+    fn usable(self) -> bool {
+        let (bin, args): (&str, &[&str]) = match self {
+            Isolation::Bwrap => ("bwrap", &["--unshare-all", "--ro-bind", "/", "/", "true"]),
+            Isolation::Unshare => (
+                "unshare",
+                &[
+                    "--user",
+                    "--map-root-user",
+                    "--pid",
+                    "--fork",
+                    "--net",
+                    "true",
+                ],
+            ),
+            Isolation::None => return true,
+        };
+        find_on_path(bin).is_some()
+            && std::process::Command::new(bin)
+                .args(args)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success())
+    }
+}
+
+/// A resolved `[sandbox]` section: canonical root + isolation mode.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    root: PathBuf,
+    isolation: Isolation,
+    cfg: SandboxConfig,
+}
+
+/// `Ok(None)` when the sandbox is disabled (or was never initialized);
+/// `Err` when it is enabled but misconfigured, so devtools fail closed.
+static GLOBAL: OnceLock<std::result::Result<Option<Arc<Sandbox>>, String>> = OnceLock::new();
+
+/// Install the `[sandbox]` section as the process-wide sandbox.
+/// Idempotent — only the first call takes effect.
+pub fn init_global(cfg: &SandboxConfig) -> Result<()> {
+    if GLOBAL.get().is_some() {
+        return Ok(());
+    }
+    let value = if cfg.enabled {
+        let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Sandbox::new(cfg, &base)
+            .map(|sb| Some(Arc::new(sb)))
+            .map_err(|e| e.to_string())
+    } else {
+        Ok(None)
+    };
+    let result = value.as_ref().map(|_| ()).map_err(|e| anyhow!("{}", e));
+    let _ = GLOBAL.set(value);
+    result
+}
+
+/// The active sandbox, if any. Devtools call this on every invocation.
+pub fn current(tool: &str) -> Result<Option<Arc<Sandbox>>> {
+    match GLOBAL.get() {
+        None => Ok(None),
+        Some(Ok(sb)) => Ok(sb.clone()),
+        Some(Err(e)) => Err(permission_denied(
+            tool,
+            &format!("sandbox is enabled but misconfigured: {}", e),
+        )),
+    }
+}
+
+/// Build a typed `permission_denied` error.
+pub fn permission_denied(tool: &str, msg: &str) -> anyhow::Error {
+    anyhow!("[permission_denied] {}: {}", tool, msg)
+}
+
+impl Sandbox {
+    /// Resolve `cfg` relative to `base` (the project root). Creates the
+    /// sandbox root if it does not exist yet.
+    pub fn new(cfg: &SandboxConfig, base: &Path) -> Result<Self> {
+        let root = match cfg.root.as_deref() {
+            Some(r) if Path::new(r).is_absolute() => PathBuf::from(r),
+            Some(r) => base.join(r),
+            None => base.to_path_buf(),
+        };
+        std::fs::create_dir_all(&root)
+            .map_err(|e| anyhow!("cannot create sandbox root {}: {}", root.display(), e))?;
+        let root = root
+            .canonicalize()
+            .map_err(|e| anyhow!("cannot resolve sandbox root {}: {}", root.display(), e))?;
+
+        let isolation = match cfg.isolation.as_str() {
+            "auto" => [Isolation::Bwrap, Isolation::Unshare]
+                .into_iter()
+                .find(|i| i.usable())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "isolation = \"auto\" but neither bwrap nor unshare can create namespaces here; \
+                         install bubblewrap, or set isolation = \"none\" and network = true to run bash unconfined"
+                    )
+                })?,
+            "none" => Isolation::None,
+            "bwrap" | "unshare" => {
+                if find_on_path(&cfg.isolation).is_none() {
+                    return Err(anyhow!(
+                        "isolation = \"{}\" but `{}` is not on PATH",
+                        cfg.isolation,
+                        cfg.isolation
+                    ));
+                }
+                let isolation = if cfg.isolation == "bwrap" {
+                    Isolation::Bwrap
+                } else {
+                    Isolation::Unshare
+                };
+                if !isolation.usable() {
+                    return Err(anyhow!(
+                        "isolation = \"{}\" but `{}` cannot create namespaces here",
+                        cfg.isolation,
+                        cfg.isolation
+                    ));
+                }
+                isolation
+            }
+            other => {
+                return Err(anyhow!(
+                    "unknown isolation \"{}\" (expected auto | bwrap | unshare | none)",
+                    other
+                ))
+            }
+        };
+
+        match isolation {
+            Isolation::None if !cfg.network => {
+                return Err(anyhow!(
+                    "network = false cannot be enforced with isolation = \"none\"; \
+                     use auto, bwrap or unshare, or set network = true"
+                ));
+            }
+            Isolation::None => {
+                warn!("[sandbox] isolation = \"none\": bash can reach the network and read the host filesystem")
+            }
+            Isolation::Unshare => {
+                warn!("[sandbox] unshare isolation: bash can still read the host filesystem; install bubblewrap to confine it to root")
+            }
+            Isolation::Bwrap => {}
+        }
+        info!(
+            "[sandbox] root={} isolation={:?} network={}",
+            root.display(),
+            isolation,
+            cfg.network
+        );
+
+        Ok(Self {
+            root,
+            isolation,
+            cfg: cfg.clone(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolve a tool-supplied path inside the sandbox. Relative paths are
+    /// taken relative to the root; symlinks are followed before the
+    /// containment check, so a link pointing outside the root is refused.
+    pub fn resolve(&self, tool: &str, path: &str) -> Result<PathBuf> {
+        let p = Path::new(path);
+        let joined = if p.is_absolute() {
+            p.to_path_buf()
+        } else {
+            self.root.join(p)
+        };
+        let resolved = resolve_existing_prefix(&normalize(&joined))
+            .ok_or_else(|| permission_denied(tool, &format!("'{}' is a dangling symlink", path)))?;
+        if !resolved.starts_with(&self.root) {
+            return Err(permission_denied(
+                tool,
+                &format!("'{}' is outside the sandbox root", path),
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// True when `path` exists and its real location is inside the root.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.canonicalize()
+            .map(|p| p.starts_with(&self.root))
+            .unwrap_or(false)
+    }
+
+    /// Build the process that runs `cmd` under this sandbox.
+    pub fn command(&self, cmd: &str) -> tokio::process::Command {
+        let mut limits = String::new();
+        if self.cfg.cpu_secs > 0 {
+            limits.push_str(&format!("ulimit -t {} || exit 126; ", self.cfg.cpu_secs));
+        }
+        if self.cfg.memory_mb > 0 {
+            limits.push_str(&format!(
+                "ulimit -v {} || exit 126; ",
+                self.cfg.memory_mb * 1024
+            ));
+        }
+        // The user command travels as `$1`, never spliced into the script.
+        let script = format!("{}exec sh -c \"$1\"", limits);
+
+        let mut command = match self.isolation {
+            Isolation::Bwrap => {
+                let mut c = tokio::process::Command::new("bwrap");
+                c.args(self.bwrap_args()).arg("sh");
+                c
+            }
+            Isolation::Unshare => {
+                let mut c = tokio::process::Command::new("unshare");
+                c.args(["--user", "--map-root-user", "--pid", "--fork"]);
+                if !self.cfg.network {
+                    c.arg("--net");
+                }
+                c.arg("sh");
+                c
+            }
+            Isolation::None => tokio::process::Command::new("sh"),
+        };
+        command.arg("-c").arg(script).arg("sh").arg(cmd);
+        command.current_dir(&self.root);
+        command.env_clear();
+        for key in &self.cfg.env_allowlist {
+            if let Some(val) = std::env::var_os(key) {
+                command.env(key, val);
+            }
+        }
+        command.kill_on_drop(true);
+        command
+    }
+
+    fn bwrap_args(&self) -> Vec<String> {
+        let root = self.root.display().to_string();
+        let mut args: Vec<String> = vec!["--unshare-all".into()];
+        if self.cfg.network {
+            args.push("--share-net".into());
+        }
+        args.extend(["--die-with-parent".into(), "--new-session".into()]);
+        for dir in ["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"] {
+            args.extend(["--ro-bind-try".into(), dir.into(), dir.into()]);
+        }
+        args.extend([
+            "--proc".into(),
+            "/proc".into(),
+            "--dev".into(),
+            "/dev".into(),
+            "--tmpfs".into(),
+            "/tmp".into(),
+            "--bind".into(),
+            root.clone(),
+            root.clone(),
+            "--chdir".into(),
+            root,
+        ]);
+        args
+    }
+}
+
+/// Canonicalize the deepest existing ancestor of `path` and re-append the
+/// rest. Returns `None` if a component is a symlink whose target is missing
+/// (writing through it could create a file anywhere).
+fn resolve_existing_prefix(path: &Path) -> Option<PathBuf> {
+    let mut existing = path.to_path_buf();
+    let mut rest: Vec<std::ffi::OsString> = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(real) => {
+                let mut out = real;
+                for comp in rest.iter().rev() {
+                    out.push(comp);
+                }
+                return Some(out);
+            }
+            Err(_) => {
+                if existing.symlink_metadata().is_ok() {
+                    return None;
+                }
+                let name = existing.file_name()?.to_os_string();
+                rest.push(name);
+                if !existing.pop() {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+fn find_on_path(bin: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(bin))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(isolation: &str) -> (Sandbox, PathBuf) {
+        let base = std::env::temp_dir().join(format!("juglans-sandbox-{}", uuid::Uuid::new_v4()));
+        let cfg = SandboxConfig {
+            enabled: true,
+            root: Some("root".into()),
+            isolation: isolation.into(),
+            network: isolation == "none",
+            ..Default::default()
+        };
+        let sb = Sandbox::new(&cfg, &base).unwrap();
+        (sb, base)
+    }
+
+    #[test]
+    fn resolves_relative_paths_under_root() {
+        let (sb, base) = sandbox("none");
+        let p = sb.resolve("read_file", "src/main.rs").unwrap();
+        assert!(p.starts_with(sb.root()));
+        assert!(p.ends_with("src/main.rs"));
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn rejects_parent_and_absolute_escapes() {
+        let (sb, base) = sandbox("none");
+        let err = sb.resolve("read_file", "../secret").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("[permission_denied] read_file:"));
+        assert!(sb.resolve("write_file", "/etc/passwd").is_err());
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlink_escapes() {
+        let (sb, base) = sandbox("none");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, sb.root().join("link")).unwrap();
+        std::os::unix::fs::symlink(base.join("missing"), sb.root().join("dangling")).unwrap();
+
+        assert!(sb.resolve("write_file", "link/x.txt").is_err());
+        assert!(sb.resolve("write_file", "dangling").is_err());
+        assert!(!sb.contains(&sb.root().join("link")));
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn unknown_isolation_is_rejected() {
+        let base = std::env::temp_dir();
+        let cfg = SandboxConfig {
+            enabled: true,
+            isolation: "docker".into(),
+            ..Default::default()
+        };
+        assert!(Sandbox::new(&cfg, &base).is_err());
+    }
+
+    #[test]
+    fn unenforceable_network_block_is_rejected() {
+        let base = std::env::temp_dir();
+        let cfg = SandboxConfig {
+            enabled: true,
+            isolation: "none".into(),
+            network: false,
+            ..Default::default()
+        };
+        let err = Sandbox::new(&cfg, &base).unwrap_err();
+        assert!(err.to_string().contains("network = false"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_strips_environment_and_runs_in_root() {
+        let (sb, base) = sandbox("none");
+        std::env::set_var("JUGLANS_SANDBOX_TEST_SECRET", "leak");
+        let out = sb
+            .command("echo \"[$JUGLANS_SANDBOX_TEST_SECRET]\"; pwd")
+            .output()
+            .await
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.starts_with("[]"), "{}", stdout);
+        assert!(stdout.contains(&sb.root().display().to_string()));
+        let _ = std::fs::remove_dir_all(base);
+    }
+}
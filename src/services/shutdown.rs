@@ -0,0 +1,147 @@
+// src/services/shutdown.rs
+//
+// Coordinated shutdown for `juglans serve`. On SIGTERM or Ctrl-C the server
+// drains instead of dying mid-run:
+//
+//   [server]
+//   drain_timeout_secs = 30    # how long in-flight runs get to finish
+//   reuse_port = false         # SO_REUSEPORT, to overlap old and new process
+//
+//   1. `GET /ready` turns 503 and new requests get 503 with
+//      `Connection: close`. The port stays open so probes can see that,
+//      unless `reuse_port` is set: then a replacement process takes over
+//      new connections at once.
+//   2. Channel ingress loops stop and each channel persists its resume state
+//      (Telegram update offset, Discord gateway session), so the next
+//      process picks up where this one left off.
+//   3. In-flight runs — serve() requests, chat turns, `/api/runs`, jobs and
+//      channel messages up to their reply, as counted by
+//      `metrics::ActiveRun` — get `drain_timeout_secs` to finish.
+//   4. At the deadline, whatever is still running is closed out: recorded
+//      runs are marked failed and claimed jobs go back to the queue. Resume
+//      state is saved again, leaving unanswered Telegram updates to be
+//      fetched again. Then the process exits.
+//
+// A second signal cuts the wait short and goes straight to step 4.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use super::{jobs, metrics, runs};
+
+/// How often `wait_for_runs` re-checks the in-flight count.
+const IDLE_POLL: Duration = Duration::from_millis(100);
+
+/// Draining flag with a way to wait for it.
+struct Drain(watch::Sender<bool>);
+
+impl Drain {
+    fn new() -> Self {
+        Drain(watch::channel(false).0)
+    }
+
+    fn begin(&self) {
+        self.0.send_replace(true);
+    }
+
+    fn is_draining(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    async fn started(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+}
+
+static DRAIN: OnceLock<Drain> = OnceLock::new();
+
+fn drain() -> &'static Drain {
+    DRAIN.get_or_init(Drain::new)
+}
+
+/// Start draining. Idempotent.
+pub fn begin() {
+    drain().begin();
+}
+
+/// True once `begin` has been called.
+pub fn is_draining() -> bool {
+    drain().is_draining()
+}
+
+/// Resolves once draining has started (immediately if it already has).
+pub async fn drain_started() {
+    drain().started().await
+}
+
+/// Resolves on SIGTERM or SIGINT (Ctrl-C).
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = term.recv() => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Wait until no run is in flight, up to `timeout` or another signal.
+/// Returns the number still running when it gave up (0 when drained).
+pub async fn wait_for_runs(timeout: Duration) -> i64 {
+    let idle = async {
+        while metrics::active_runs() > 0 {
+            tokio::time::sleep(IDLE_POLL).await;
+        }
+    };
+    tokio::select! {
+        _ = idle => {}
+        _ = tokio::time::sleep(timeout) => {
+            warn!("🛑 Drain timeout ({}s) reached", timeout.as_secs());
+        }
+        _ = signal() => warn!("🛑 Second signal; not waiting any longer"),
+    }
+    metrics::active_runs()
+}
+
+/// Close out work that didn't finish in time: fail recorded runs and
+/// requeue claimed jobs.
+pub fn abandon_unfinished() {
+    let runs = runs::interrupt_live();
+    if runs > 0 {
+        warn!("[runs] marked {} unfinished run(s) as failed", runs);
+    }
+    let jobs = jobs::release_running();
+    if jobs > 0 {
+        info!("[jobs] released {} running job(s) back to the queue", jobs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drain_wakes_waiters_once_begun() {
+        let drain = Drain::new();
+        assert!(!drain.is_draining());
+        let pending = tokio::time::timeout(Duration::from_millis(20), drain.started()).await;
+        assert!(pending.is_err());
+
+        drain.begin();
+        drain.begin();
+        assert!(drain.is_draining());
+        tokio::time::timeout(Duration::from_millis(20), drain.started())
+            .await
+            .expect("already draining");
+    }
+}
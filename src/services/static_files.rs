@@ -0,0 +1,425 @@
+// src/services/static_files.rs
+//
+// `@static` — serve a directory of files next to a workflow's routes:
+//
+//   @static("/assets", dir="public")
+//   [start]: serve()
+//
+// `dir` is relative to the project root (default `public`). Options:
+// `index="index.html"` (served for directory paths, `index=false` turns it
+// off) and `max_age=<secs>` (`Cache-Control`, default `no-cache`). Several
+// `@static` decorators can be stacked on the serve() node.
+//
+// Both `juglans serve` and the inline `serve()` server answer GET/HEAD with
+// ETag / If-None-Match (304), Last-Modified / If-Modified-Since, and single
+// byte ranges (206, 416 when unsatisfiable). Decorator routes win over a
+// static mount with an overlapping prefix. Dotfiles and paths escaping `dir`
+// are never served.
+
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::core::graph::WorkflowGraph;
+use crate::services::web_server::error_response;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A directory mounted under a URL prefix by `@static`.
+#[derive(Clone, Debug)]
+pub struct StaticMount {
+    /// URL prefix without a trailing slash (`""` for the site root).
+    pub prefix: String,
+    pub dir: PathBuf,
+    pub index: Option<String>,
+    pub max_age: Option<u64>,
+}
+
+impl StaticMount {
+    /// The part of `path` below this mount, or `None` if it is outside.
+    fn relative<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let rest = path.strip_prefix(self.prefix.as_str())?;
+        if rest.is_empty() || rest.starts_with('/') {
+            Some(rest.trim_start_matches('/'))
+        } else {
+            None
+        }
+    }
+}
+
+/// Collect `@static` mounts from the graph, longest prefix first. Relative
+/// `dir`s are resolved against `root`.
+pub fn extract_static_mounts(workflow: &WorkflowGraph, root: &Path) -> Vec<StaticMount> {
+    let mut mounts: Vec<StaticMount> = workflow
+        .node_annotations
+        .iter()
+        .filter(|(_, key, _)| key == "static")
+        .map(|(_, _, ann)| StaticMount {
+            prefix: ann["path"]
+                .as_str()
+                .unwrap_or("/")
+                .trim_end_matches('/')
+                .to_string(),
+            dir: root.join(ann["dir"].as_str().unwrap_or("public")),
+            index: match &ann["index"] {
+                Value::String(s) if !s.is_empty() => Some(s.clone()),
+                Value::Bool(false) => None,
+                _ => Some("index.html".to_string()),
+            },
+            max_age: ann["max_age"].as_u64(),
+        })
+        .collect();
+    mounts.sort_by(|a, b| {
+        b.prefix
+            .len()
+            .cmp(&a.prefix.len())
+            .then(a.prefix.cmp(&b.prefix))
+    });
+    mounts
+}
+
+/// The mount that owns `path`, with the path relative to it.
+pub fn find<'a, 'p>(
+    mounts: &'a [StaticMount],
+    path: &'p str,
+) -> Option<(&'a StaticMount, &'p str)> {
+    mounts
+        .iter()
+        .find_map(|m| m.relative(path).map(|rest| (m, rest)))
+}
+
+/// Map a request path below the mount to a file inside `dir`. `None` for
+/// anything that could escape it (`..`, absolute segments, dotfiles).
+fn resolve_file(dir: &Path, rel: &str) -> Option<PathBuf> {
+    let mut path = dir.to_path_buf();
+    for segment in rel.split('/').filter(|s| !s.is_empty()) {
+        let segment = urlencoding::decode(segment).ok()?;
+        if segment.starts_with('.') || segment.contains(['/', '\\']) {
+            return None;
+        }
+        let mut components = Path::new(segment.as_ref()).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => path.push(segment.as_ref()),
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// Answer a GET/HEAD for `rel` (the path below the mount).
+pub async fn serve(
+    mount: &StaticMount,
+    rel: &str,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Response {
+    let Some(mut path) = resolve_file(&mount.dir, rel) else {
+        return error_response(StatusCode::NOT_FOUND, "Not found");
+    };
+    let mut meta = match tokio::fs::metadata(&path).await {
+        Ok(m) => m,
+        Err(_) => return error_response(StatusCode::NOT_FOUND, "Not found"),
+    };
+    if meta.is_dir() {
+        let Some(index) = &mount.index else {
+            return error_response(StatusCode::NOT_FOUND, "Not found");
+        };
+        path.push(index);
+        meta = match tokio::fs::metadata(&path).await {
+            Ok(m) if m.is_file() => m,
+            _ => return error_response(StatusCode::NOT_FOUND, "Not found"),
+        };
+    }
+    // Symlinks must not lead outside the mounted directory.
+    match (path.canonicalize(), mount.dir.canonicalize()) {
+        (Ok(file), Ok(dir)) if file.starts_with(&dir) => {}
+        _ => return error_response(StatusCode::NOT_FOUND, "Not found"),
+    }
+
+    let len = meta.len();
+    let modified = meta.modified().ok().map(DateTime::<Utc>::from);
+    let etag = format!(
+        "\"{:x}-{:x}\"",
+        len,
+        meta.modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    );
+    let last_modified = modified.map(|t| t.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+
+    let mut builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(
+            header::CACHE_CONTROL,
+            match mount.max_age {
+                Some(secs) => format!("public, max-age={}", secs),
+                None => "no-cache".to_string(),
+            },
+        );
+    if let Some(lm) = &last_modified {
+        builder = builder.header(header::LAST_MODIFIED, lm);
+    }
+
+    if not_modified(headers, &etag, modified) {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap_or_else(|_| {
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Response build error")
+            });
+    }
+
+    // A Range is honoured only if If-Range (when sent) still names this file.
+    let if_range_ok = header_str(headers, header::IF_RANGE).map_or(true, |v| v.trim() == etag);
+    let range = match header_str(headers, header::RANGE).filter(|_| if_range_ok) {
+        Some(spec) => match parse_range(spec, len) {
+            Ok(r) => r,
+            Err(()) => {
+                return builder
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                    .body(Body::empty())
+                    .unwrap_or_else(|_| {
+                        error_response(StatusCode::INTERNAL_SERVER_ERROR, "Response build error")
+                    });
+            }
+        },
+        None => None,
+    };
+
+    let (status, start, count) = match range {
+        Some((start, end)) => {
+            builder = builder.header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, len),
+            );
+            (StatusCode::PARTIAL_CONTENT, start, end - start + 1)
+        }
+        None => (StatusCode::OK, 0, len),
+    };
+    builder = builder
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type(&path))
+        .header(header::CONTENT_LENGTH, count);
+
+    let body = if *method == Method::HEAD || count == 0 {
+        Body::empty()
+    } else {
+        match file_body(&path, start, count).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("❌ [Static] Failed to read {}: {}", path.display(), e);
+                return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file");
+            }
+        }
+    };
+    builder.body(body).unwrap_or_else(|_| {
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "Response build error")
+    })
+}
+
+/// Stream `count` bytes of the file from `start`.
+async fn file_body(path: &Path, start: u64, count: u64) -> std::io::Result<Body> {
+    let mut file = tokio::fs::File::open(path).await?;
+    if start > 0 {
+        file.seek(std::io::SeekFrom::Start(start)).await?;
+    }
+    let stream = async_stream::try_stream! {
+        let mut remaining = count;
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        while remaining > 0 {
+            let want = remaining.min(CHUNK_SIZE as u64) as usize;
+            let n: usize = file.read(&mut buf[..want]).await?;
+            if n == 0 {
+                break;
+            }
+            remaining -= n as u64;
+            yield Bytes::copy_from_slice(&buf[..n]);
+        }
+    };
+    let stream: std::pin::Pin<Box<dyn futures::Stream<Item = std::io::Result<Bytes>> + Send>> =
+        Box::pin(stream);
+    Ok(Body::from_stream(stream))
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// If-None-Match wins over If-Modified-Since when both are sent.
+fn not_modified(headers: &HeaderMap, etag: &str, modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(inm) = header_str(headers, header::IF_NONE_MATCH) {
+        return inm
+            .split(',')
+            .map(|t| t.trim().trim_start_matches("W/"))
+            .any(|t| t == "*" || t == etag);
+    }
+    match (header_str(headers, header::IF_MODIFIED_SINCE), modified) {
+        (Some(since), Some(modified)) => DateTime::parse_from_rfc2822(since)
+            .map(|since| modified.timestamp() <= since.timestamp())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Parse a `Range` header against a file of `len` bytes. `Ok(None)` means
+/// "send the whole file" (unsupported units, multiple ranges); `Err` is a
+/// 416.
+fn parse_range(spec: &str, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(ranges) = spec.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if ranges.contains(',') {
+        return Ok(None);
+    }
+    let (start, end) = ranges.trim().split_once('-').ok_or(())?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let n: u64 = suffix.parse().map_err(|_| ())?;
+            if n == 0 || len == 0 {
+                return Err(());
+            }
+            (len.saturating_sub(n), len - 1)
+        }
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| ())?;
+            let end = match end {
+                "" => len.saturating_sub(1),
+                e => e.parse::<u64>().map_err(|_| ())?.min(len.saturating_sub(1)),
+            };
+            if start >= len || start > end {
+                return Err(());
+            }
+            (start, end)
+        }
+    };
+    Ok(Some((start, end)))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_parse_against_length() {
+        assert_eq!(parse_range("bytes=0-9", 100), Ok(Some((0, 9))));
+        assert_eq!(parse_range("bytes=90-", 100), Ok(Some((90, 99))));
+        assert_eq!(parse_range("bytes=-10", 100), Ok(Some((90, 99))));
+        assert_eq!(parse_range("bytes=50-500", 100), Ok(Some((50, 99))));
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), Ok(None));
+        assert_eq!(parse_range("items=0-1", 100), Ok(None));
+        assert_eq!(parse_range("bytes=100-", 100), Err(()));
+        assert_eq!(parse_range("bytes=9-3", 100), Err(()));
+        assert_eq!(parse_range("bytes=-0", 100), Err(()));
+    }
+
+    #[test]
+    fn request_paths_stay_inside_dir() {
+        let dir = Path::new("/srv/public");
+        assert_eq!(
+            resolve_file(dir, "css/site.css"),
+            Some(PathBuf::from("/srv/public/css/site.css"))
+        );
+        assert_eq!(
+            resolve_file(dir, "my%20file.txt"),
+            Some(PathBuf::from("/srv/public/my file.txt"))
+        );
+        assert_eq!(resolve_file(dir, ""), Some(PathBuf::from("/srv/public")));
+        assert_eq!(resolve_file(dir, "../secret"), None);
+        assert_eq!(resolve_file(dir, "a/%2e%2e/%2e%2e/etc"), None);
+        assert_eq!(resolve_file(dir, "..%2fsecret"), None);
+        assert_eq!(resolve_file(dir, ".env"), None);
+    }
+
+    #[test]
+    fn mounts_match_on_segment_boundaries() {
+        let mount = |prefix: &str| StaticMount {
+            prefix: prefix.to_string(),
+            dir: PathBuf::from("public"),
+            index: None,
+            max_age: None,
+        };
+        let mounts = vec![mount("/assets"), mount("")];
+        assert_eq!(
+            find(&mounts, "/assets/app.js").map(|(_, r)| r),
+            Some("app.js")
+        );
+        assert_eq!(find(&mounts, "/assets").map(|(_, r)| r), Some(""));
+        let (m, rest) = find(&mounts, "/assetsx/a").unwrap();
+        assert_eq!((m.prefix.as_str(), rest), ("", "assetsx/a"));
+        assert!(find(&mounts[..1], "/other").is_none());
+    }
+
+    #[test]
+    fn static_decorators_stack_on_serve_node() {
+        let src = r#"
+@static("/assets/", dir="web/public", max_age=3600)
+@static("/", index=false)
+[start]: serve()
+
+@get("/api")
+[api()]: response(status=200, body={})
+"#;
+        let mut g = crate::core::parser::GraphParser::parse(src).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut g).unwrap();
+        let mounts = extract_static_mounts(&g, Path::new("/app"));
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].prefix, "/assets");
+        assert_eq!(mounts[0].dir, PathBuf::from("/app/web/public"));
+        assert_eq!(mounts[0].index.as_deref(), Some("index.html"));
+        assert_eq!(mounts[0].max_age, Some(3600));
+        assert_eq!(mounts[1].prefix, "");
+        assert_eq!(mounts[1].dir, PathBuf::from("/app/public"));
+        assert_eq!(mounts[1].index, None);
+        assert_eq!(
+            crate::builtins::http::extract_routes_from_graph(&g).len(),
+            1
+        );
+    }
+}
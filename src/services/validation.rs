@@ -0,0 +1,519 @@
+// src/services/validation.rs
+//
+// Typed route parameters and request validation for decorator routes.
+//
+//   @get("/users/{id:int}")           path segment parsed as an int
+//   @post("/users", body=CreateUser)  JSON body checked against a struct
+//   @get("/users", query=Paging)      query string coerced into a struct
+//
+// Struct references are checked once, when the workflow is compiled
+// (`check_routes`), so a route naming a missing struct never serves. After
+// that everything is checked before the handler runs. A request that doesn't
+// fit gets a 422 listing every problem at once (`validation_error_body`). On
+// success `input.params` / `input.query` / `input.body` hold the typed
+// values, path params bind to handler params of the same name, and the body
+// / query structs bind, as instances, to the remaining handler params in
+// order: `[create_user(user)]` receives the `CreateUser`.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::builtins::http::InlineRoute;
+use crate::core::context::WorkflowContext;
+use crate::core::expr_eval::ExprEvaluator;
+use crate::core::graph::{ClassDef, WorkflowGraph};
+use crate::core::types::JType;
+
+/// One problem with a request: where (`path.id`, `body.tags.2`) and what.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FieldError {
+    pub loc: String,
+    pub message: String,
+}
+
+/// A request that passed validation, ready to be installed into a context.
+#[derive(Debug, Default)]
+pub struct BoundRequest {
+    /// Typed path parameters (`input.params`).
+    pub params: Map<String, Value>,
+    /// `input.query`: coerced struct fields, or the raw string map.
+    pub query: Value,
+    /// `input.body`: the validated struct (defaults filled in), or the raw body.
+    pub body: Value,
+    /// Struct instances for the handler: (param, class, field values).
+    instances: Vec<(String, String, Vec<Value>)>,
+}
+
+/// Find the route for `method path`. Literal routes win over templated ones;
+/// the second value holds the raw text of each `{name}` segment.
+pub fn find_route<'a>(
+    routes: &'a [InlineRoute],
+    method: &str,
+    path: &str,
+) -> Option<(&'a InlineRoute, Vec<(String, String)>)> {
+    let candidates = || routes.iter().filter(|r| r.method == method);
+    if let Some(route) = candidates().find(|r| r.path == path) {
+        return Some((route, Vec::new()));
+    }
+    candidates().find_map(|r| match_path(&r.path, path).map(|params| (r, params)))
+}
+
+/// Match `path` against a template like `/users/{id:int}/posts/{slug}`.
+fn match_path(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+    if !template.contains('{') {
+        return None;
+    }
+    let pattern: Vec<&str> = template.trim_matches('/').split('/').collect();
+    let actual: Vec<&str> = path.trim_matches('/').split('/').collect();
+    if pattern.len() != actual.len() {
+        return None;
+    }
+    let mut params = Vec::new();
+    for (pat, seg) in pattern.iter().zip(&actual) {
+        match path_param(pat) {
+            Some((name, _)) if !seg.is_empty() => {
+                let raw = urlencoding::decode(seg)
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|_| seg.to_string());
+                params.push((name.to_string(), raw));
+            }
+            Some(_) => return None,
+            None if pat != seg => return None,
+            None => {}
+        }
+    }
+    Some(params)
+}
+
+/// `{id:int}` → ("id", Int); `{slug}` → ("slug", Str). `None` for literals.
+pub fn path_param(segment: &str) -> Option<(&str, JType)> {
+    let inner = segment.strip_prefix('{')?.strip_suffix('}')?;
+    Some(match inner.split_once(':') {
+        Some((name, ty)) => (name.trim(), JType::parse(ty)),
+        None => (inner.trim(), JType::Str),
+    })
+}
+
+/// Check that every `body=` / `query=` struct a route names exists. Run when
+/// the workflow is compiled; a failure means the server must not start.
+pub fn check_routes(workflow: &WorkflowGraph, routes: &[InlineRoute]) -> anyhow::Result<()> {
+    for route in routes {
+        let refs = [("body", &route.body), ("query", &route.query)];
+        for (source, class_name) in refs {
+            let Some(class_name) = class_name else {
+                continue;
+            };
+            if !workflow.classes.contains_key(class_name) {
+                anyhow::bail!(
+                    "Route {} {} declares {}={}, but no struct [{}] exists",
+                    route.method,
+                    route.path,
+                    source,
+                    class_name,
+                    class_name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate a request against its route. Errors are always the caller's
+/// fault: the route's structs were checked by `check_routes`.
+pub fn bind_request(
+    workflow: &WorkflowGraph,
+    route: &InlineRoute,
+    path_values: Vec<(String, String)>,
+    query: &HashMap<String, String>,
+    body: Value,
+) -> Result<BoundRequest, Vec<FieldError>> {
+    let mut errors = Vec::new();
+    let mut bound = BoundRequest::default();
+
+    let types: HashMap<&str, JType> = route.path.split('/').filter_map(path_param).collect();
+    for (name, raw) in path_values {
+        let ty = types.get(name.as_str()).cloned().unwrap_or(JType::Str);
+        match coerce_str(&ty, &raw) {
+            Ok(v) => {
+                bound.params.insert(name, v);
+            }
+            Err(message) => errors.push(FieldError {
+                loc: format!("path.{}", name),
+                message,
+            }),
+        }
+    }
+
+    let handler_params: Vec<String> = workflow
+        .functions
+        .get(&route.handler)
+        .map(|f| f.params.clone())
+        .unwrap_or_default();
+    let mut free_params = handler_params
+        .into_iter()
+        .filter(|p| !bound.params.contains_key(p));
+
+    let mut structs = Vec::new();
+    if let Some(class) = &route.body {
+        let source = match &body {
+            Value::Object(map) => Some(map.clone()),
+            Value::Null => Some(Map::new()),
+            _ => None,
+        };
+        match source {
+            Some(map) => structs.push(("body", class, map)),
+            None => errors.push(FieldError {
+                loc: "body".into(),
+                message: "expected a JSON object".into(),
+            }),
+        }
+    }
+    bound.body = body;
+    if let Some(class) = &route.query {
+        let map = query.iter().map(|(k, v)| (k.clone(), json!(v))).collect();
+        structs.push(("query", class, map));
+    }
+    bound.query = json!(query);
+
+    for (source, class_name, map) in structs {
+        let Some(class) = workflow.classes.get(class_name) else {
+            continue;
+        };
+        let from_strings = source == "query";
+        if let Some(fields) = check_struct(class, &map, source, from_strings, workflow, &mut errors)
+        {
+            let object: Map<String, Value> = class
+                .fields
+                .iter()
+                .map(|f| f.name.clone())
+                .zip(fields.iter().cloned())
+                .collect();
+            if source == "body" {
+                bound.body = Value::Object(object);
+            } else {
+                bound.query = Value::Object(object);
+            }
+            if let Some(param) = free_params.next() {
+                bound.instances.push((param, class_name.clone(), fields));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(bound)
+    } else {
+        Err(errors)
+    }
+}
+
+impl BoundRequest {
+    /// Write the typed values into `input.*`, allocate the struct instances
+    /// and return the handler arguments.
+    pub fn install(
+        self,
+        ctx: &WorkflowContext,
+        workflow: &WorkflowGraph,
+    ) -> anyhow::Result<HashMap<String, Value>> {
+        ctx.set(
+            "input.params".to_string(),
+            Value::Object(self.params.clone()),
+        )?;
+        ctx.set("input.query".to_string(), self.query)?;
+        ctx.set("input.body".to_string(), self.body)?;
+        for (param, class_name, fields) in self.instances {
+            if let Some(class) = workflow.classes.get(&class_name) {
+                ctx.alloc_instance(param, class_name, Arc::clone(class), fields)?;
+            }
+        }
+        Ok(self.params.into_iter().collect())
+    }
+}
+
+/// 422 body: `{"error": "...", "details": [{"loc": "body.age", "message": "..."}]}`.
+pub fn validation_error_body(errors: &[FieldError]) -> Value {
+    json!({ "error": "Request validation failed", "details": errors })
+}
+
+// ─── Type checks ────────────────────────────────────────────────────────────
+
+/// Check `map` against the struct's fields. Returns the field values in
+/// declaration order, or `None` after pushing at least one error.
+fn check_struct(
+    class: &ClassDef,
+    map: &Map<String, Value>,
+    loc: &str,
+    from_strings: bool,
+    workflow: &WorkflowGraph,
+    errors: &mut Vec<FieldError>,
+) -> Option<Vec<Value>> {
+    let before = errors.len();
+    let mut values = Vec::with_capacity(class.fields.len());
+    for field in &class.fields {
+        let ty = JType::from_hint(&field.type_hint);
+        let field_loc = format!("{}.{}", loc, field.name);
+        let value = match map.get(&field.name) {
+            Some(Value::String(s)) if from_strings => {
+                coerce_str(&ty, s).map_err(|m| (field_loc.clone(), m))
+            }
+            Some(v) if !v.is_null() || matches!(ty, JType::Optional(_) | JType::Any) => {
+                check_value(&ty, v, &field_loc, workflow, errors)
+            }
+            _ => match &field.default {
+                Some(expr) => ExprEvaluator::new()
+                    .eval(expr, &|_: &str| None)
+                    .map_err(|e| (field_loc.clone(), format!("bad default: {}", e))),
+                None if matches!(ty, JType::Optional(_)) => Ok(Value::Null),
+                None => Err((field_loc.clone(), "field required".to_string())),
+            },
+        };
+        match value {
+            Ok(v) => values.push(v),
+            Err((loc, message)) => errors.push(FieldError { loc, message }),
+        }
+    }
+    (errors.len() == before).then_some(values)
+}
+
+/// Check a JSON value against a type. Nested structs are validated field by
+/// field and come back as plain objects.
+fn check_value(
+    ty: &JType,
+    value: &Value,
+    loc: &str,
+    workflow: &WorkflowGraph,
+    errors: &mut Vec<FieldError>,
+) -> Result<Value, (String, String)> {
+    let mismatch = || {
+        (
+            loc.to_string(),
+            format!("expected {}, got {}", ty, json_kind(value)),
+        )
+    };
+    match ty {
+        JType::Any => Ok(value.clone()),
+        JType::Optional(_) if value.is_null() => Ok(Value::Null),
+        JType::Optional(inner) => check_value(inner, value, loc, workflow, errors),
+        JType::Int if value.is_i64() || value.is_u64() => Ok(value.clone()),
+        JType::Float if value.is_number() => Ok(value.clone()),
+        JType::Str if value.is_string() => Ok(value.clone()),
+        JType::Bool if value.is_boolean() => Ok(value.clone()),
+        JType::List(item) => {
+            let items = value.as_array().ok_or_else(mismatch)?;
+            let mut out = Vec::with_capacity(items.len());
+            for (i, v) in items.iter().enumerate() {
+                match check_value(item, v, &format!("{}.{}", loc, i), workflow, errors) {
+                    Ok(v) => out.push(v),
+                    Err((loc, message)) => errors.push(FieldError { loc, message }),
+                }
+            }
+            Ok(Value::Array(out))
+        }
+        JType::Dict(_, item) => {
+            let map = value.as_object().ok_or_else(mismatch)?;
+            let mut out = Map::new();
+            for (k, v) in map {
+                match check_value(item, v, &format!("{}.{}", loc, k), workflow, errors) {
+                    Ok(v) => {
+                        out.insert(k.clone(), v);
+                    }
+                    Err((loc, message)) => errors.push(FieldError { loc, message }),
+                }
+            }
+            Ok(Value::Object(out))
+        }
+        JType::Class(name) => match workflow.classes.get(name) {
+            Some(class) => {
+                let map = value.as_object().ok_or_else(mismatch)?;
+                // Errors inside the struct are already recorded; report the
+                // object itself as fine so they aren't duplicated.
+                Ok(check_struct(class, map, loc, false, workflow, errors)
+                    .map(|fields| {
+                        Value::Object(
+                            class
+                                .fields
+                                .iter()
+                                .map(|f| f.name.clone())
+                                .zip(fields)
+                                .collect(),
+                        )
+                    })
+                    .unwrap_or(Value::Null))
+            }
+            None => Ok(value.clone()),
+        },
+        _ => Err(mismatch()),
+    }
+}
+
+/// Parse a path segment or query value into `ty`. Lists are comma-separated.
+fn coerce_str(ty: &JType, raw: &str) -> Result<Value, String> {
+    match ty {
+        JType::Int => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("expected int, got {:?}", raw)),
+        JType::Float => raw
+            .parse::<f64>()
+            .map(Value::from)
+            .map_err(|_| format!("expected float, got {:?}", raw)),
+        JType::Bool => match raw {
+            "true" | "1" => Ok(json!(true)),
+            "false" | "0" => Ok(json!(false)),
+            _ => Err(format!("expected bool, got {:?}", raw)),
+        },
+        JType::Optional(_) if raw.is_empty() => Ok(Value::Null),
+        JType::Optional(inner) => coerce_str(inner, raw),
+        JType::List(item) if !raw.is_empty() => raw
+            .split(',')
+            .map(|s| coerce_str(item, s.trim()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        JType::List(_) => Ok(json!([])),
+        _ => Ok(json!(raw)),
+    }
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "str",
+        Value::Array(_) => "list",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtins::http::extract_routes_from_graph;
+    use crate::core::parser::GraphParser;
+
+    const SRC: &str = r#"
+[Address]: {
+  city: str
+}
+
+[CreateUser]: {
+  name: str
+  age: int = 18
+  admin: bool = false
+  address: Address
+}
+
+[Paging]: {
+  page: int = 1
+  q: str = ""
+}
+
+[start]: serve()
+
+@get("/users/{id:int}")
+[get_user(id)]: response(status=200, body={"id": id})
+
+@get("/users/me")
+[me()]: response(status=200, body={"me": true})
+
+@post("/users", body=CreateUser)
+[create_user(user)]: response(status=201, body=user)
+
+@get("/users", query=Paging)
+[list_users(paging)]: response(status=200, body=paging)
+"#;
+
+    fn graph() -> WorkflowGraph {
+        let mut g = GraphParser::parse(SRC).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut g).unwrap();
+        g
+    }
+
+    fn bind(
+        g: &WorkflowGraph,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Value,
+    ) -> Result<BoundRequest, Vec<FieldError>> {
+        let routes = extract_routes_from_graph(g);
+        let (route, values) = find_route(&routes, method, path).expect("route");
+        let query = query
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        bind_request(g, route, values, &query, body)
+    }
+
+    #[test]
+    fn typed_path_segments_match_and_coerce() {
+        let g = graph();
+        let routes = extract_routes_from_graph(&g);
+        assert_eq!(
+            find_route(&routes, "GET", "/users/me").unwrap().0.handler,
+            "me"
+        );
+        assert!(find_route(&routes, "GET", "/users/1/posts").is_none());
+
+        let bound = bind(&g, "GET", "/users/42", &[], Value::Null).unwrap();
+        assert_eq!(bound.params["id"], json!(42));
+
+        let errors = bind(&g, "GET", "/users/abc", &[], Value::Null).unwrap_err();
+        assert_eq!(errors[0].loc, "path.id");
+        assert_eq!(errors[0].message, "expected int, got \"abc\"");
+    }
+
+    #[test]
+    fn body_struct_fills_defaults_and_binds_the_handler_param() {
+        let g = graph();
+        let body = json!({ "name": "ann", "address": { "city": "Oslo" }, "extra": 1 });
+        let bound = bind(&g, "POST", "/users", &[], body).unwrap();
+        assert_eq!(
+            bound.body,
+            json!({ "name": "ann", "age": 18, "admin": false, "address": { "city": "Oslo" } })
+        );
+        assert_eq!(bound.instances.len(), 1);
+        assert_eq!(bound.instances[0].0, "user");
+        assert_eq!(bound.instances[0].1, "CreateUser");
+    }
+
+    #[test]
+    fn body_errors_are_collected_together() {
+        let g = graph();
+        let body = json!({ "age": "old", "address": { "city": 7 } });
+        let errors = bind(&g, "POST", "/users", &[], body).unwrap_err();
+        let locs: Vec<&str> = errors.iter().map(|e| e.loc.as_str()).collect();
+        assert_eq!(locs, ["body.name", "body.age", "body.address.city"]);
+        assert_eq!(errors[0].message, "field required");
+        assert_eq!(errors[1].message, "expected int, got str");
+
+        let errors = bind(&g, "POST", "/users", &[], json!([1])).unwrap_err();
+        assert_eq!(errors[0].loc, "body");
+    }
+
+    #[test]
+    fn query_struct_coerces_strings() {
+        let g = graph();
+        let bound = bind(&g, "GET", "/users", &[("page", "3")], Value::Null).unwrap();
+        assert_eq!(bound.query, json!({ "page": 3, "q": "" }));
+
+        let errors = bind(&g, "GET", "/users", &[("page", "x")], Value::Null).unwrap_err();
+        assert_eq!(errors[0].loc, "query.page");
+    }
+
+    #[test]
+    fn missing_route_structs_are_caught_at_compile_time() {
+        let g = graph();
+        check_routes(&g, &extract_routes_from_graph(&g)).unwrap();
+
+        let src = SRC.replace("body=CreateUser", "body=NewUser");
+        let mut g = GraphParser::parse(&src).unwrap();
+        crate::core::macro_expand::expand_decorators(&mut g).unwrap();
+        let err = check_routes(&g, &extract_routes_from_graph(&g)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Route POST /users declares body=NewUser, but no struct [NewUser] exists"
+        );
+    }
+}
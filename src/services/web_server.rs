@@ -4,7 +4,10 @@
 use arc_swap::ArcSwap;
 use axum::{
     body::Body,
-    extract::{Extension, FromRequest, Multipart, Query},
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        Extension, FromRequest, Multipart, Path as RoutePath, Query,
+    },
     http::{HeaderMap, Method, Request, StatusCode, Uri},
     response::{
         sse::{Event, Sse},
@@ -30,16 +33,25 @@ use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::core::context::{ToolResultPayload, WorkflowContext, WorkflowEvent};
+use crate::core::context::{ToolResultPayload, UserInbox, WorkflowContext, WorkflowEvent};
 use crate::core::executor::WorkflowExecutor;
 use crate::core::graph::WorkflowGraph;
 use crate::core::parser::GraphParser;
 use crate::core::prompt_parser::PromptParser;
 use crate::core::resolver;
 use crate::core::validator::WorkflowValidator;
+use crate::services::auth;
 use crate::services::config::JuglansConfig;
+use crate::services::identity;
+use crate::services::jobs;
 use crate::services::local_runtime::LocalRuntime;
+use crate::services::metrics;
+use crate::services::openapi;
 use crate::services::prompt_loader::PromptRegistry;
+use crate::services::runs;
+use crate::services::shutdown;
+use crate::services::static_files;
+use crate::services::validation;
 
 // Feishu/Telegram passive ingress now lives behind the unified `Channel` trait
 // (see `adapters::feishu::FeishuEventChannel` and
@@ -73,25 +85,36 @@ fn spawn_workspace_watcher(project_root: PathBuf, state: Arc<WebState>) {
                 // Drain coalesced signals (multiple file events within a window).
                 while rebuild_rx.try_recv().is_ok() {}
 
-                let (info, cache) = match (&state.serve_workflow, &state.cache) {
-                    (Some(i), Some(c)) => (i.clone(), c.clone()),
-                    _ => continue,
-                };
+                // The main serve() workflow and every mount rebuild
+                // independently: one broken entry keeps only its own old cache.
+                let mut targets: Vec<(String, &ServeWorkflowInfo, &Arc<ArcSwap<CachedWorkflow>>)> =
+                    Vec::with_capacity(state.mounts.len() + 1);
+                if let (Some(i), Some(c)) = (&state.serve_workflow, &state.cache) {
+                    targets.push((i.slug.clone(), i, c));
+                }
+                targets.extend(
+                    state
+                        .mounts
+                        .iter()
+                        .map(|m| (format!("mount '{}'", m.name), &m.info, &m.cache)),
+                );
 
-                match build_cached_workflow(&info, &state.project_root).await {
-                    Ok(new) => {
-                        info!(
-                            "🔄 [Hot-reload] cache rebuilt: {} ({} route(s))",
-                            info.slug,
-                            new.routes.len()
-                        );
-                        cache.store(Arc::new(new));
-                    }
-                    Err(e) => {
-                        error!(
-                            "❌ [Hot-reload] rebuild failed (keeping previous cache): {:#}",
-                            e
-                        );
+                for (label, info, cache) in targets {
+                    match build_cached_workflow(info, &state.project_root).await {
+                        Ok(new) => {
+                            info!(
+                                "🔄 [Hot-reload] cache rebuilt: {} ({} route(s))",
+                                label,
+                                new.routes.len()
+                            );
+                            cache.store(Arc::new(new));
+                        }
+                        Err(e) => {
+                            error!(
+                                "❌ [Hot-reload] {} rebuild failed (keeping previous cache): {:#}",
+                                label, e
+                            );
+                        }
                     }
                 }
             }
@@ -119,7 +142,17 @@ fn spawn_workspace_watcher(project_root: PathBuf, state: Arc<WebState>) {
 
         info!("👀 Watching {:?} for file changes...", project_root);
 
-        for events in notify_rx.into_iter().flatten() {
+        // Poll rather than block so the thread ends once the server drains;
+        // a parked blocking task would keep the runtime from shutting down.
+        loop {
+            let events = match notify_rx.recv_timeout(Duration::from_millis(500)) {
+                Ok(Ok(events)) => events,
+                Ok(Err(_)) => continue,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) if !shutdown::is_draining() => {
+                    continue
+                }
+                Err(_) => break,
+            };
             let mut should_rebuild = false;
             for ev in events {
                 let path = &ev.path;
@@ -205,6 +238,8 @@ struct CachedWorkflow {
     pub executor: Arc<WorkflowExecutor>,
     /// Decorator-extracted routes (e.g., `@get("/api/users")`), pre-computed from `graph`.
     pub routes: Vec<crate::builtins::http::InlineRoute>,
+    /// `@static` directories, tried for GET/HEAD requests no route matches.
+    pub statics: Vec<static_files::StaticMount>,
 }
 
 struct WebState {
@@ -222,6 +257,186 @@ struct WebState {
     /// Cached compiled workflow + executor. `None` when no serve() workflow is discovered;
     /// when set, requests use this instead of rebuilding per-request. Atomic swap on hot-reload.
     pub cache: Option<Arc<ArcSwap<CachedWorkflow>>>,
+    /// `[server.mounts]` workflows, tried before the main serve() workflow.
+    pub mounts: Vec<Mount>,
+    /// `[server.auth]` is configured: callers are known only by verified
+    /// claims, never by a `user_id` they send.
+    pub auth_enabled: bool,
+}
+
+/// Which requests a `[server.mounts.<name>]` entry receives.
+#[derive(Debug, Clone, PartialEq)]
+struct MountRule {
+    /// Leading `/`, no trailing `/`.
+    prefix: Option<String>,
+    /// Lowercase; a leading `*.` matches any subdomain.
+    host: Option<String>,
+    strip_prefix: bool,
+}
+
+impl MountRule {
+    fn new(cfg: &crate::services::config::MountConfig) -> anyhow::Result<Self> {
+        let prefix = cfg
+            .path
+            .as_deref()
+            .map(|p| format!("/{}", p.trim_matches('/')))
+            .filter(|p| p != "/");
+        let host = cfg
+            .host
+            .as_deref()
+            .map(|h| h.trim().to_ascii_lowercase())
+            .filter(|h| !h.is_empty());
+        if prefix.is_none() && host.is_none() {
+            anyhow::bail!("needs a `path` prefix other than \"/\", a `host`, or both");
+        }
+        Ok(Self {
+            prefix,
+            host,
+            strip_prefix: cfg.strip_prefix,
+        })
+    }
+
+    /// How specific the match is, or None when the request isn't for this
+    /// mount. Host matches outrank path-only ones; longer prefixes win.
+    fn score(&self, host: Option<&str>, path: &str) -> Option<(bool, usize)> {
+        if let Some(want) = &self.host {
+            let host = host?;
+            let matched = match want.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                None => host == want,
+            };
+            if !matched {
+                return None;
+            }
+        }
+        let prefix_len = match &self.prefix {
+            Some(prefix) => {
+                let rest = path.strip_prefix(prefix.as_str())?;
+                if !(rest.is_empty() || rest.starts_with('/')) {
+                    return None;
+                }
+                prefix.len()
+            }
+            None => 0,
+        };
+        Some((self.host.is_some(), prefix_len))
+    }
+
+    /// The request path as the mounted workflow sees it.
+    fn local_path<'a>(&self, path: &'a str) -> &'a str {
+        match (&self.prefix, self.strip_prefix) {
+            (Some(prefix), true) => match &path[prefix.len().min(path.len())..] {
+                "" => "/",
+                rest => rest,
+            },
+            _ => path,
+        }
+    }
+
+    /// Public URL of a path inside the mount.
+    fn public_path(&self, local: &str) -> String {
+        match (&self.prefix, self.strip_prefix) {
+            (Some(prefix), true) => format!("{}{}", prefix, local),
+            _ => local.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for MountRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.host, &self.prefix) {
+            (Some(host), Some(prefix)) => write!(f, "{}{}", host, prefix),
+            (Some(host), None) => write!(f, "{}", host),
+            (None, Some(prefix)) => write!(f, "{}", prefix),
+            (None, None) => f.write_str("/"),
+        }
+    }
+}
+
+/// A `[server.mounts.<name>]` serve() workflow with its own compiled cache.
+struct Mount {
+    name: String,
+    /// `entry` as configured, for logs.
+    entry: String,
+    rule: MountRule,
+    info: ServeWorkflowInfo,
+    cache: Arc<ArcSwap<CachedWorkflow>>,
+}
+
+/// The most specific mount for a request.
+fn select_mount<'a>(mounts: &'a [Mount], host: Option<&str>, path: &str) -> Option<&'a Mount> {
+    mounts
+        .iter()
+        .filter_map(|m| m.rule.score(host, path).map(|score| (score, m)))
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, m)| m)
+}
+
+/// Request host without the port, lowercased.
+fn request_host(headers: &HeaderMap) -> Option<String> {
+    let host = headers.get(axum::http::header::HOST)?.to_str().ok()?;
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    Some(host.to_ascii_lowercase())
+}
+
+/// Compile every `[server.mounts]` entry. Like the main serve() workflow, a
+/// mount that fails to compile stops the server from starting.
+async fn build_mounts(
+    configs: &HashMap<String, crate::services::config::MountConfig>,
+    project_root: &Path,
+) -> anyhow::Result<Vec<Mount>> {
+    use anyhow::Context;
+
+    let mut names: Vec<&String> = configs.keys().collect();
+    names.sort();
+    let mut mounts: Vec<Mount> = Vec::with_capacity(names.len());
+    for name in names {
+        let cfg = &configs[name];
+        let rule = MountRule::new(cfg).with_context(|| format!("[server.mounts.{}]", name))?;
+        if let Some(other) = mounts
+            .iter()
+            .find(|m| m.rule.prefix == rule.prefix && m.rule.host == rule.host)
+        {
+            anyhow::bail!(
+                "[server.mounts.{}] and [server.mounts.{}] both mount {}",
+                other.name,
+                name,
+                rule
+            );
+        }
+        let path = project_root.join(&cfg.entry);
+        let info = serve_workflow_info(&path).ok_or_else(|| {
+            anyhow::anyhow!(
+                "[server.mounts.{}]: {:?} is missing, doesn't parse, or has no serve() node",
+                name,
+                path
+            )
+        })?;
+        let cached = build_cached_workflow(&info, project_root)
+            .await
+            .with_context(|| format!("Failed to compile mount '{}' ({:?})", name, path))?;
+        info!(
+            "✅ Compiled mount '{}': {} -> {} ({} route(s), {} static dir(s))",
+            name,
+            rule,
+            cfg.entry,
+            cached.routes.len(),
+            cached.statics.len()
+        );
+        mounts.push(Mount {
+            name: name.clone(),
+            entry: cfg.entry.clone(),
+            rule,
+            info,
+            cache: Arc::new(ArcSwap::from_pointee(cached)),
+        });
+    }
+    Ok(mounts)
 }
 
 /// Compile the serve() workflow into a `CachedWorkflow` ready to handle requests.
@@ -242,6 +457,7 @@ async fn build_cached_workflow(
         .with_context(|| format!("read workflow {:?}", serve_info.file_path))?;
 
     let mut graph = GraphParser::parse(&content).with_context(|| "parse workflow")?;
+    graph.default_slug_from(&serve_info.file_path);
 
     let wf_base_dir = serve_info.file_path.parent().unwrap_or(Path::new("."));
     let wf_canonical = serve_info
@@ -312,11 +528,21 @@ async fn build_cached_workflow(
         .set_executor(Arc::downgrade(&executor));
 
     let routes = crate::builtins::http::extract_routes_from_graph(&graph);
+    validation::check_routes(&graph, &routes)?;
+    let statics = static_files::extract_static_mounts(&graph, project_root);
+    for m in statics.iter().filter(|m| !m.dir.is_dir()) {
+        warn!(
+            "@static(\"/{}\") points at {:?}, which is not a directory",
+            m.prefix.trim_start_matches('/'),
+            m.dir
+        );
+    }
 
     Ok(CachedWorkflow {
         graph: Arc::new(graph),
         executor,
         routes,
+        statics,
     })
 }
 
@@ -442,10 +668,9 @@ fn format_uptime(secs: u64) -> String {
     }
 }
 
-/// Scan all .jg/.jgflow files under project_root, find workflow containing serve() node
-fn discover_serve_workflow(project_root: &Path) -> Option<ServeWorkflowInfo> {
-    use crate::core::graph::NodeType;
-
+/// Scan all .jg/.jgflow files under project_root, find workflow containing
+/// serve() node. Files in `exclude` (mount entries) are skipped.
+fn discover_serve_workflow(project_root: &Path, exclude: &[PathBuf]) -> Option<ServeWorkflowInfo> {
     let pattern_jg = project_root.join("**/*.jg").to_string_lossy().to_string();
     let pattern_jgflow = project_root
         .join("**/*.jgflow")
@@ -459,39 +684,51 @@ fn discover_serve_workflow(project_root: &Path) -> Option<ServeWorkflowInfo> {
         .flatten();
 
     for entry in all_paths {
-        if let Ok(content) = fs::read_to_string(&entry) {
-            if let Ok(graph) = GraphParser::parse(&content) {
-                for node_idx in graph.graph.node_indices() {
-                    let node = &graph.graph[node_idx];
-                    if let NodeType::Task(action) = &node.node_type {
-                        if action.name == "serve" {
-                            let slug = if graph.slug.is_empty() {
-                                entry
-                                    .file_stem()
-                                    .and_then(|s| s.to_str())
-                                    .unwrap_or("unknown")
-                                    .to_string()
-                            } else {
-                                graph.slug.clone()
-                            };
-                            info!(
-                                "🌐 Discovered serve() workflow: {} (node: [{}]) at {:?}",
-                                slug, node.id, entry
-                            );
-                            return Some(ServeWorkflowInfo {
-                                file_path: entry,
-                                slug,
-                                entry_node: node.id.clone(),
-                            });
-                        }
-                    }
-                }
-            }
+        if exclude.iter().any(|e| same_file(e, &entry)) {
+            continue;
+        }
+        if let Some(info) = serve_workflow_info(&entry) {
+            info!(
+                "🌐 Discovered serve() workflow: {} (node: [{}]) at {:?}",
+                info.slug, info.entry_node, entry
+            );
+            return Some(info);
         }
     }
     None
 }
 
+/// The serve() node of the workflow at `path`, if it parses and has one.
+fn serve_workflow_info(path: &Path) -> Option<ServeWorkflowInfo> {
+    use crate::core::graph::NodeType;
+
+    let content = fs::read_to_string(path).ok()?;
+    let graph = GraphParser::parse(&content).ok()?;
+    let node_idx = graph.graph.node_indices().find(|&idx| {
+        matches!(&graph.graph[idx].node_type, NodeType::Task(action) if action.name == "serve")
+    })?;
+    let slug = if graph.slug.is_empty() {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string()
+    } else {
+        graph.slug.clone()
+    };
+    Some(ServeWorkflowInfo {
+        file_path: path.to_path_buf(),
+        slug,
+        entry_node: graph.graph[node_idx].id.clone(),
+    })
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
 /// Convert HeaderMap to JSON object
 fn headers_to_json(headers: &HeaderMap) -> Value {
     let mut map = serde_json::Map::new();
@@ -515,24 +752,53 @@ async fn handle_serve_request(
     Extension(state): Extension<Arc<WebState>>,
     request: Request<Body>,
 ) -> Response {
-    let serve_info = match &state.serve_workflow {
-        Some(info) => info,
-        None => {
-            return error_response(StatusCode::NOT_FOUND, "No serve() workflow found");
-        }
+    // A matching [server.mounts] entry wins over the main serve() workflow.
+    let host = request_host(&headers);
+    let mount = select_mount(&state.mounts, host.as_deref(), uri.path());
+    let (serve_info, cache, path) = match mount {
+        Some(m) => (&m.info, &m.cache, m.rule.local_path(uri.path())),
+        None => match (&state.serve_workflow, &state.cache) {
+            (Some(info), Some(cache)) => (info, cache, uri.path()),
+            (None, _) => {
+                return error_response(StatusCode::NOT_FOUND, "No serve() workflow found");
+            }
+            (Some(_), None) => {
+                return error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Workflow cache not initialized",
+                );
+            }
+        },
     };
 
-    let cached = match &state.cache {
-        Some(c) => c.load_full(),
-        None => {
-            return error_response(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Workflow cache not initialized",
-            );
+    let cached = cache.load_full();
+    // Mounts get their own API docs under their prefix.
+    if let Some(m) = mount {
+        if method == Method::GET && !openapi::path_is_taken(&cached.routes, path) {
+            if path == openapi::SPEC_PATH {
+                let mut spec = openapi::build_spec(&cached.graph, &m.name);
+                let base = m.rule.public_path("");
+                if !base.is_empty() {
+                    spec["servers"] = json!([{ "url": base }]);
+                }
+                return Json(spec).into_response();
+            }
+            if path == openapi::DOCS_PATH {
+                return Html(openapi::swagger_ui_html(
+                    &m.rule.public_path(openapi::SPEC_PATH),
+                ))
+                .into_response();
+            }
         }
-    };
+    }
+    if let Some(response) =
+        serve_static(&cached.routes, &cached.statics, &method, path, &headers).await
+    {
+        return response;
+    }
     let executor = cached.executor.clone();
     let graph = cached.graph.clone();
+    let claims = request.extensions().get::<auth::AuthClaims>().cloned();
 
     // Drop the SSE mpsc allocation: serve() workflows don't bridge to a frontend, so
     // there's no listener for `WorkflowEvent`s. Workflows that use client-bridge tools
@@ -544,7 +810,10 @@ async fn handle_serve_request(
     let mut input = serde_json::Map::with_capacity(8);
 
     input.insert("method".into(), json!(method.as_str()));
-    input.insert("path".into(), json!(uri.path()));
+    input.insert("path".into(), json!(path));
+    if let Some(m) = mount {
+        input.insert("mount".into(), json!(m.name));
+    }
     input.insert("headers".into(), headers_to_json(&headers));
 
     let query_map: HashMap<String, String> = uri
@@ -562,8 +831,7 @@ async fn handle_serve_request(
         .unwrap_or_default();
     input.insert("query".into(), json!(query_map));
 
-    let path_parts: Vec<&str> = uri
-        .path()
+    let path_parts: Vec<&str> = path
         .trim_start_matches('/')
         .split('/')
         .filter(|s| !s.is_empty())
@@ -675,45 +943,62 @@ async fn handle_serve_request(
         input.insert("body".into(), body_value);
     }
 
+    // Decorator routes: match the request and validate typed params/bodies
+    // before anything runs, so a bad request never reaches the init graph.
+    let matched = if cached.routes.is_empty() {
+        None
+    } else {
+        let (route, path_values) =
+            match validation::find_route(&cached.routes, method.as_str(), path) {
+                Some(m) => m,
+                None => return error_response(StatusCode::NOT_FOUND, "Not found"),
+            };
+        if let Some(denied) = auth::check_route(route.auth.as_deref(), claims.as_ref()) {
+            return denied;
+        }
+        let body = input.remove("body").unwrap_or(Value::Null);
+        match validation::bind_request(&graph, route, path_values, &query_map, body) {
+            Ok(bound) => Some((route.clone(), bound)),
+            Err(errors) => return validation_error_response(&errors),
+        }
+    };
+
+    // Verified claims or null, written last so no request field can fake them.
+    input.insert("auth".into(), claims.map(|c| c.0).unwrap_or(Value::Null));
+
     // Single ctx.set — one RwLock write covers the whole input.* tree.
     ctx.set("input".to_string(), Value::Object(input)).ok();
+    let _run = metrics::ActiveRun::start("http");
 
     debug!(
         "🌐 [Serve] {} {} -> workflow '{}'",
-        method,
-        uri.path(),
-        serve_info.slug
+        method, path, serve_info.slug
     );
 
     // Decorator routes were extracted at cache-build time.
     ctx.set_root_workflow(graph.clone());
 
-    if !cached.routes.is_empty() {
-        // Decorator-based workflow: match request to route, run init + handler
-        let matched = cached
-            .routes
-            .iter()
-            .find(|r| r.method == method.as_str() && r.path == uri.path());
-        let route = match matched {
-            Some(r) => r.clone(),
-            None => {
-                return error_response(StatusCode::NOT_FOUND, "Not found");
-            }
-        };
-
+    if let Some((route, bound)) = matched {
+        // Decorator-based workflow: run init + the matched handler
         debug!(
             "🌐 [Serve] {} {} -> {}()",
             method.as_str(),
-            uri.path(),
+            path,
             route.handler
         );
 
+        let args = match bound.install(&ctx, &graph) {
+            Ok(args) => args,
+            Err(e) => {
+                return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+            }
+        };
+
         // Run init subgraph (db_connect → serve passthrough). _deco_* nodes are
         // excluded by the executor automatically.
         let _ = executor.clone().execute_graph(graph.clone(), &ctx).await;
 
         // Execute the matched handler function
-        let args = std::collections::HashMap::new();
         if let Err(e) = executor
             .execute_function(route.handler.clone(), args, graph, &ctx)
             .await
@@ -735,7 +1020,13 @@ async fn handle_serve_request(
         }
     }
 
-    // Read response
+    workflow_response(&ctx).await
+}
+
+/// Turn the `response.*` a workflow left in `ctx` into the HTTP response:
+/// `response.file` streams a file, otherwise `response.body` (or `output`)
+/// is the body, JSON unless a `content-type` header says otherwise.
+async fn workflow_response(ctx: &WorkflowContext) -> Response {
     let status_code = ctx.get_jvalue("response.status").i64().unwrap_or(200) as u16;
     let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
 
@@ -743,19 +1034,20 @@ async fn handle_serve_request(
     let resp_file = ctx.get_str("response.file");
 
     let mut builder = Response::builder().status(status);
-    let mut has_content_type = false;
+    let mut content_type: Option<String> = None;
 
     // Apply custom headers
     if let Some(Value::Object(map)) = resp_headers {
         for (k, v) in &map {
             if let Some(val) = v.as_str() {
-                if k.to_lowercase() == "content-type" {
-                    has_content_type = true;
+                if k.eq_ignore_ascii_case("content-type") {
+                    content_type = Some(val.to_ascii_lowercase());
                 }
                 builder = builder.header(k.as_str(), val);
             }
         }
     }
+    let has_content_type = content_type.is_some();
 
     if let Some(file_path) = resp_file {
         // Binary file response
@@ -797,11 +1089,16 @@ async fn handle_serve_request(
         } else {
             resp_body_jv.into_inner()
         };
+        // A string body with a non-JSON content type (`render()`, `text/plain`)
+        // is sent as-is rather than as a JSON string literal.
+        let bytes = match (&response_body, content_type.as_deref()) {
+            (Value::String(text), Some(ct)) if !ct.contains("json") => text.clone().into_bytes(),
+            _ => serde_json::to_vec(&response_body).unwrap_or_default(),
+        };
         if !has_content_type {
             builder = builder.header("content-type", "application/json");
         }
-        let json_bytes = serde_json::to_vec(&response_body).unwrap_or_default();
-        builder.body(Body::from(json_bytes)).unwrap_or_else(|_| {
+        builder.body(Body::from(bytes)).unwrap_or_else(|_| {
             error_response(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Failed to build response",
@@ -810,8 +1107,25 @@ async fn handle_serve_request(
     }
 }
 
+/// GET/HEAD for an `@static` directory, when no decorator route claims the path.
+async fn serve_static(
+    routes: &[InlineRoute],
+    statics: &[static_files::StaticMount],
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+) -> Option<Response> {
+    if !matches!(*method, Method::GET | Method::HEAD)
+        || validation::find_route(routes, method.as_str(), path).is_some()
+    {
+        return None;
+    }
+    let (mount, rel) = static_files::find(statics, path)?;
+    Some(static_files::serve(mount, rel, method, headers).await)
+}
+
 /// Helper: JSON error response
-fn error_response(status: StatusCode, message: &str) -> Response {
+pub(crate) fn error_response(status: StatusCode, message: &str) -> Response {
     let body = serde_json::to_vec(&json!({"error": message})).unwrap_or_default();
     Response::builder()
         .status(status)
@@ -825,11 +1139,84 @@ fn error_response(status: StatusCode, message: &str) -> Response {
         })
 }
 
+/// 422 with every validation problem (see `validation::validation_error_body`).
+fn validation_error_response(errors: &[validation::FieldError]) -> Response {
+    let body = serde_json::to_vec(&validation::validation_error_body(errors)).unwrap_or_default();
+    Response::builder()
+        .status(StatusCode::UNPROCESSABLE_ENTITY)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap_or_else(|_| error_response(StatusCode::UNPROCESSABLE_ENTITY, "Invalid request"))
+}
+
+/// `/openapi.json` for the serve() workflow. Built from the live cache, so
+/// hot-reloaded routes show up immediately.
+async fn openapi_spec(Extension(state): Extension<Arc<WebState>>) -> Response {
+    match (&state.cache, &state.serve_workflow) {
+        (Some(cache), Some(info)) => {
+            Json(openapi::build_spec(&cache.load().graph, &info.slug)).into_response()
+        }
+        _ => error_response(StatusCode::NOT_FOUND, "No serve() workflow found"),
+    }
+}
+
+/// Swagger UI over `/openapi.json`.
+async fn openapi_docs() -> Html<String> {
+    Html(openapi::swagger_ui_html(openapi::SPEC_PATH))
+}
+
 /// Health check (serverless platform liveness probe)
 async fn health_check() -> Json<Value> {
     Json(json!({"status": "ok"}))
 }
 
+/// Prometheus scrape endpoint (`[server] metrics`).
+async fn metrics_endpoint() -> Response {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        metrics::render(),
+    )
+        .into_response()
+}
+
+/// Readiness probe: 503 once the server has started draining, so load
+/// balancers stop routing to it while in-flight runs finish.
+async fn ready_check() -> Response {
+    if shutdown::is_draining() {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"status": "draining"})),
+        )
+            .into_response()
+    } else {
+        Json(json!({"status": "ready"})).into_response()
+    }
+}
+
+/// While draining, turn away new requests (probes and scrapes excepted) and
+/// close their connections so clients retry against another instance.
+async fn reject_while_draining(request: Request<Body>, next: axum::middleware::Next) -> Response {
+    if shutdown::is_draining() && !matches!(request.uri().path(), "/health" | "/ready" | "/metrics")
+    {
+        let mut response =
+            error_response(StatusCode::SERVICE_UNAVAILABLE, "server is shutting down");
+        let headers = response.headers_mut();
+        headers.insert(
+            axum::http::header::RETRY_AFTER,
+            axum::http::HeaderValue::from_static("5"),
+        );
+        headers.insert(
+            axum::http::header::CONNECTION,
+            axum::http::HeaderValue::from_static("close"),
+        );
+        return response;
+    }
+    next.run(request).await
+}
+
 /// Dashboard page
 async fn dashboard(Extension(state): Extension<Arc<WebState>>) -> Html<String> {
     let uptime = format_uptime(state.start_time.elapsed().as_secs());
@@ -986,6 +1373,46 @@ async fn dashboard(Extension(state): Extension<Arc<WebState>>) -> Html<String> {
         "0 found".to_string()
     };
 
+    // Recent runs from the run store (/api/runs)
+    let recent_runs = runs::global_store()
+        .and_then(|store| {
+            store
+                .list(&runs::RunFilter {
+                    limit: 10,
+                    ..Default::default()
+                })
+                .ok()
+        })
+        .unwrap_or_default();
+    let mut runs_html = String::new();
+    for run in &recent_runs {
+        let (class, icon) = match run.status.as_str() {
+            runs::STATUS_SUCCEEDED => ("wf-status-ok", "✓"),
+            runs::STATUS_FAILED => ("wf-status-err", "✗"),
+            _ => ("warning", "…"),
+        };
+        let started = DateTime::<Utc>::from_timestamp(run.created_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let duration = run
+            .duration_ms
+            .map(|ms| format!(" ({} ms)", ms))
+            .unwrap_or_default();
+        runs_html.push_str(&format!(
+            "    <span class=\"{}\">{}</span> {} - {} [{}] {}{}\n",
+            class, icon, run.id, run.workflow, run.status, started, duration
+        ));
+        if let Some(err) = &run.error {
+            runs_html.push_str(&format!(
+                "       <span class=\"error\">└─ {}</span>\n",
+                err.lines().next().unwrap_or_default()
+            ));
+        }
+    }
+    if runs_html.is_empty() {
+        runs_html = "    (none)\n".to_string();
+    }
+
     let html = format!(
         r#"<!DOCTYPE html>
 <html>
@@ -1028,12 +1455,25 @@ async fn dashboard(Extension(state): Extension<Arc<WebState>>) -> Html<String> {
 <pre>
 {}</pre>
 
+<h2>Recent Runs ({})</h2>
+<pre>
+{}</pre>
+
 <h2>API Endpoints</h2>
 <pre>
     GET  /               - This dashboard
     GET  /api/prompts    - List prompts
     GET  /api/workflows  - List workflows (with validation)
     POST /api/chat       - Chat endpoint (SSE streaming)
+    GET  /api/chat/ws    - Chat endpoint (WebSocket)
+    POST /api/runs       - Start a run (async, returns a run id)
+    GET  /api/runs       - Run history (?workflow=, ?status=, ?limit=)
+    GET  /api/runs/:id   - Run status, output and trace
+    GET  /api/runs/:id/events - Run events (SSE replay + live tail)
+    POST /api/jobs       - Enqueue a background job (run by `juglans worker`)
+    GET  /api/jobs       - Job queue status (?workflow=, ?status=, ?limit=)
+    GET  /api/jobs/:id   - Job status, attempts and output
+    POST /api/jobs/:id/retry - Requeue a dead job
 </pre>
 
 </body>
@@ -1053,11 +1493,41 @@ async fn dashboard(Extension(state): Extension<Arc<WebState>>) -> Html<String> {
         } else {
             cron_jobs_html
         },
+        recent_runs.len(),
+        runs_html,
     );
 
     Html(html)
 }
 
+/// Put `[server.auth]` in front of every route mounted on `app` so far.
+/// Without it, `@auth` routes can never be satisfied — say so at startup.
+fn with_auth(
+    app: Router,
+    config: Option<crate::services::config::AuthConfig>,
+    base_dir: &Path,
+    routes: &[InlineRoute],
+) -> anyhow::Result<Router> {
+    match config {
+        Some(config) => {
+            let authenticator = Arc::new(auth::Authenticator::new(config, base_dir)?);
+            Ok(app.layer(axum::middleware::from_fn_with_state(
+                authenticator,
+                auth::middleware,
+            )))
+        }
+        None => {
+            for r in routes.iter().filter(|r| r.auth.is_some()) {
+                warn!(
+                    "{} {} -> {}() is marked @auth but [server.auth] is not configured; it will answer 401",
+                    r.method, r.path, r.handler
+                );
+            }
+            Ok(app)
+        }
+    }
+}
+
 pub async fn start_web_server(
     host: String,
     port: u16,
@@ -1074,10 +1544,30 @@ pub async fn start_web_server(
         if let Err(e) = crate::services::history::init_global(&cfg.history) {
             tracing::warn!("[history] init_global failed: {}", e);
         }
+        crate::services::tool_policy::init_global(&cfg.tool_policy)?;
+        crate::services::memory::init_global(&cfg.memory);
+        crate::services::identity::init_global(&cfg.identity);
+        crate::services::outbox::init_global(&cfg.outbox);
+        runs::init_global(&cfg.runs);
+        if cfg.server.reuse_port {
+            runs::keep_unfinished_on_open();
+        }
+        jobs::init_global(&cfg.jobs);
+        if let Err(e) = crate::services::sandbox::init_global(&cfg.sandbox) {
+            tracing::warn!("[sandbox] init_global failed: {}", e);
+        }
     }
 
-    // Scan for serve() workflow
-    let serve_workflow = discover_serve_workflow(&project_root);
+    // Scan for serve() workflow, skipping files that [server.mounts] claim.
+    let mount_configs = config
+        .as_ref()
+        .map(|c| c.server.mounts.clone())
+        .unwrap_or_default();
+    let mount_entries: Vec<PathBuf> = mount_configs
+        .values()
+        .map(|m| project_root.join(&m.entry))
+        .collect();
+    let serve_workflow = discover_serve_workflow(&project_root, &mount_entries);
 
     // Build the workflow cache once at boot. Hard-fail if discovery succeeded but
     // compilation does not — the user has a broken workflow and silently starting
@@ -1086,9 +1576,10 @@ pub async fn start_web_server(
         match build_cached_workflow(info, &project_root).await {
             Ok(c) => {
                 info!(
-                    "✅ Compiled workflow cache: {} ({} route(s))",
+                    "✅ Compiled workflow cache: {} ({} route(s), {} static dir(s))",
                     info.slug,
-                    c.routes.len()
+                    c.routes.len(),
+                    c.statics.len()
                 );
                 Some(Arc::new(ArcSwap::from_pointee(c)))
             }
@@ -1103,6 +1594,7 @@ pub async fn start_web_server(
     } else {
         None
     };
+    let mounts = build_mounts(&mount_configs, &project_root).await?;
 
     let state = Arc::new(WebState {
         project_root: project_root.clone(),
@@ -1113,6 +1605,8 @@ pub async fn start_web_server(
         pending_tool_calls: Arc::new(Mutex::new(HashMap::new())),
         serve_workflow: serve_workflow.clone(),
         cache,
+        mounts,
+        auth_enabled: config.as_ref().is_some_and(|c| c.server.auth.is_some()),
     });
 
     let mut app = Router::new()
@@ -1121,7 +1615,62 @@ pub async fn start_web_server(
         .route("/api/workflows", get(list_local_workflows))
         .route("/api/chat", post(handle_chat))
         .route("/api/chat/tool-result", post(handle_tool_result))
-        .route("/health", get(health_check));
+        .route("/api/chat/ws", get(handle_chat_ws))
+        .route("/health", get(health_check))
+        .route("/ready", get(ready_check));
+
+    let metrics_enabled = config.as_ref().map_or(true, |c| c.server.metrics);
+    if metrics_enabled {
+        app = app.route("/metrics", get(metrics_endpoint));
+    }
+
+    // Run store: async runs with persisted status, trace and event log.
+    let runs_enabled = runs::global_store().is_some();
+    if runs_enabled {
+        app = app
+            .route("/api/runs", get(list_runs).post(start_run))
+            .route("/api/runs/:id", get(get_run))
+            .route("/api/runs/:id/events", get(run_events));
+    }
+
+    // Job queue: enqueue and inspect background jobs run by `juglans worker`.
+    let jobs_enabled = jobs::global_store().is_some();
+    if jobs_enabled {
+        app = app
+            .route("/api/jobs", get(list_jobs).post(enqueue_job))
+            .route("/api/jobs/:id", get(get_job))
+            .route("/api/jobs/:id/retry", post(retry_job));
+    }
+
+    // If serve() workflow is found, register catch-all fallback
+    let routes = state
+        .cache
+        .as_ref()
+        .map(|c| c.load().routes.clone())
+        .unwrap_or_default();
+    if serve_workflow.is_some() {
+        // Generated API docs, unless the workflow routes those paths itself.
+        if !openapi::path_is_taken(&routes, openapi::SPEC_PATH) {
+            app = app.route(openapi::SPEC_PATH, get(openapi_spec));
+        }
+        if !openapi::path_is_taken(&routes, openapi::DOCS_PATH) {
+            app = app.route(openapi::DOCS_PATH, get(openapi_docs));
+        }
+    }
+    if serve_workflow.is_some() || !state.mounts.is_empty() {
+        app = app.fallback(handle_serve_request);
+    }
+    let mut auth_routes = routes;
+    for m in &state.mounts {
+        auth_routes.extend(m.cache.load().routes.iter().cloned());
+    }
+
+    // [server.auth] wraps everything mounted so far (API, dashboard, serve()
+    // routes). Channel webhooks below stay outside: they verify their own
+    // platform signatures.
+    let auth_config = config.as_ref().and_then(|c| c.server.auth.clone());
+    let auth_methods = auth_config.as_ref().map(auth::method_names);
+    app = with_auth(app, auth_config, &project_root, &auth_routes)?;
 
     // Channel orchestration: each channel can be active (run() loop), passive
     // (install_routes mounts /webhook/<kind>/<id>), or both. Mount routes here
@@ -1132,14 +1681,15 @@ pub async fn start_web_server(
     for (ch, dispatcher) in &channels {
         *channel_summary.entry(ch.kind().to_string()).or_insert(0) += 1;
         app = ch.clone().install_routes(app, dispatcher.clone());
+        // Lets queued replies find their channel again after a restart.
+        crate::services::outbox::register_channel(ch.id(), ch.clone());
     }
-
-    // If serve() workflow is found, register catch-all fallback
-    if serve_workflow.is_some() {
-        app = app.fallback(handle_serve_request);
-    }
+    crate::services::outbox::spawn_worker();
+    let serve_workers = config.as_ref().map_or(0, |c| c.jobs.serve_workers);
+    jobs::spawn_workers(project_root.clone(), serve_workers);
 
     let app = app
+        .layer(axum::middleware::from_fn(reject_while_draining))
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
         .layer(Extension(state.clone()));
@@ -1159,6 +1709,17 @@ pub async fn start_web_server(
     info!("   - GET  /api/prompts");
     info!("   - GET  /api/workflows");
     info!("   - POST /api/chat");
+    info!("   - GET  /api/chat/ws (WebSocket)");
+    info!("   - GET  /health, /ready");
+    if metrics_enabled {
+        info!("   - GET  /metrics (Prometheus)");
+    }
+    if runs_enabled {
+        info!("   - GET|POST /api/runs, GET /api/runs/:id[/events]");
+    }
+    if jobs_enabled {
+        info!("   - GET|POST /api/jobs, GET /api/jobs/:id, POST /api/jobs/:id/retry");
+    }
     if !channel_summary.is_empty() {
         let summary: Vec<String> = channel_summary
             .iter()
@@ -1166,34 +1727,132 @@ pub async fn start_web_server(
             .collect();
         info!("📡 Channels: {}", summary.join(", "));
     }
+    if let Some(methods) = &auth_methods {
+        info!("🔒 Auth: {}", methods);
+    }
     if let Some(ref sw) = serve_workflow {
         info!("🌐 HTTP Backend: {} (entry: [{}])", sw.slug, sw.entry_node);
         info!("   - All other routes -> serve() workflow");
     }
+    for m in &state.mounts {
+        info!(
+            "🧭 Mount '{}': {} -> {} (entry: [{}])",
+            m.name, m.rule, m.entry, m.info.entry_node
+        );
+    }
     info!("--------------------------------------------------");
 
     // File watcher: log file changes and invalidate the workflow cache on relevant edits.
     spawn_workspace_watcher(project_root.clone(), state.clone());
 
     // Spawn each channel's active ingress loop (no-op for passive-only channels;
-    // their routes were already installed above).
+    // their routes were already installed above). The handles let a graceful
+    // shutdown stop ingress before draining.
+    let mut ingress = Vec::new();
     for (ch, dispatcher) in channels {
-        let ch_for_log = ch.clone();
-        tokio::spawn(async move {
-            let id = ch_for_log.id().to_string();
-            if let Err(e) = ch.run(dispatcher).await {
+        let active = ch.clone();
+        let task = tokio::spawn(async move {
+            let id = active.id().to_string();
+            if let Err(e) = active.run(dispatcher).await {
                 tracing::error!("[{}] exited: {:#}", id, e);
             }
         });
+        ingress.push((ch, task));
     }
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let reuse_port = config.as_ref().is_some_and(|c| c.server.reuse_port);
+    let drain_timeout =
+        std::time::Duration::from_secs(config.as_ref().map_or(30, |c| c.server.drain_timeout_secs));
+    let listener = bind_listener(addr, reuse_port).await?;
     info!("🚀 Server is ready and waiting for requests...");
-    axum::serve(listener, app).await?;
+    let (close_listener, listener_closed) = tokio::sync::oneshot::channel::<()>();
+    let mut server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = listener_closed.await;
+            })
+            .await
+    });
+    tokio::select! {
+        result = &mut server => return Ok(result??),
+        _ = shutdown::signal() => {}
+    }
 
+    // Graceful shutdown: see services/shutdown.rs for the sequence.
+    shutdown::begin();
+    info!(
+        "🛑 Shutting down: draining in-flight runs (up to {}s)",
+        drain_timeout.as_secs()
+    );
+    // Keep accepting so probes see `/ready` fail and new requests get a 503,
+    // unless a replacement process shares the port and should take them.
+    let mut close_listener = Some(close_listener);
+    let mut stop_accepting = || {
+        if let Some(tx) = close_listener.take() {
+            let _ = tx.send(());
+        }
+    };
+    if reuse_port {
+        stop_accepting();
+    }
+    let mut stopped = Vec::with_capacity(ingress.len());
+    for (ch, task) in ingress {
+        task.abort();
+        stopped.push(ch);
+    }
+    // Saved once now for a replacement that starts right away, and again
+    // after the wait so messages answered meanwhile aren't replayed.
+    let persist_resume_state = || async {
+        for ch in &stopped {
+            if let Err(e) = ch.persist_resume_state().await {
+                warn!("[{}] resume state not saved: {:#}", ch.id(), e);
+            }
+        }
+    };
+    persist_resume_state().await;
+    let deadline = tokio::time::Instant::now() + drain_timeout;
+    let unfinished = shutdown::wait_for_runs(drain_timeout).await;
+    persist_resume_state().await;
+    if unfinished == 0 {
+        // Let open connections flush their last responses.
+        stop_accepting();
+        let _ = tokio::time::timeout_at(deadline, &mut server).await;
+        info!("✅ Drained; exiting");
+    } else {
+        warn!("🛑 Exiting with {} run(s) unfinished", unfinished);
+        shutdown::abandon_unfinished();
+    }
     Ok(())
 }
 
+/// Bind the server socket. With `reuse_port` a replacement process can bind
+/// the same address while this one drains.
+async fn bind_listener(
+    addr: SocketAddr,
+    reuse_port: bool,
+) -> anyhow::Result<tokio::net::TcpListener> {
+    if !reuse_port {
+        return Ok(tokio::net::TcpListener::bind(addr).await?);
+    }
+    #[cfg(unix)]
+    {
+        let socket = if addr.is_ipv4() {
+            tokio::net::TcpSocket::new_v4()?
+        } else {
+            tokio::net::TcpSocket::new_v6()?
+        };
+        socket.set_reuseaddr(true)?;
+        socket.set_reuseport(true)?;
+        socket.bind(addr)?;
+        Ok(socket.listen(1024)?)
+    }
+    #[cfg(not(unix))]
+    {
+        warn!("[server] reuse_port is only supported on Unix; ignoring");
+        Ok(tokio::net::TcpListener::bind(addr).await?)
+    }
+}
+
 async fn list_local_prompts(
     Extension(state): Extension<Arc<WebState>>,
     Query(params): Query<PromptQuery>,
@@ -1326,26 +1985,230 @@ async fn list_local_workflows(
     Json(results)
 }
 
-async fn handle_chat(
-    _headers: HeaderMap,
-    Extension(state): Extension<Arc<WebState>>,
-    Json(req): Json<ChatRequest>,
-) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, Json<Value>> {
-    // Resolve @handle to workflow slug
-    let handle_slug = match &req.chat_id {
-        Some(ChatIdInput::Handle(h)) => Some(h.strip_prefix('@').unwrap_or(h).to_string()),
-        _ => None,
-    };
+/// Build a per-request executor: AI runtime from `config`, prompts and JSON
+/// tool definitions found under `project_root`.
+pub(crate) async fn build_request_executor(
+    project_root: &Path,
+    config: &JuglansConfig,
+) -> Arc<WorkflowExecutor> {
+    let runtime: Arc<LocalRuntime> = Arc::new(LocalRuntime::new_with_config(&config.ai));
 
-    // Extract workflow slug
-    // Priority: @handle > agent.slug > agent.id > default
-    let workflow_slug = if let Some(slug) = handle_slug {
-        slug
-    } else if let Some(ref agent_config) = req.agent {
-        agent_config
-            .slug
-            .clone()
-            .or(agent_config.id.map(|u| u.to_string()))
+    let mut prompt_registry = PromptRegistry::new();
+    let _ = prompt_registry.load_from_paths(&[
+        project_root.join("**/*.jgx").to_string_lossy().to_string(),
+        project_root
+            .join("**/*.jgprompt")
+            .to_string_lossy()
+            .to_string(),
+    ]);
+
+    let mut executor =
+        WorkflowExecutor::new_with_debug(Arc::new(prompt_registry), runtime, config.debug.clone())
+            .await;
+
+    // Load tool definitions (search for *.json tool files under project_root)
+    {
+        use crate::core::tool_loader::ToolLoader;
+        use crate::services::tool_registry::ToolRegistry;
+        let tool_pattern = project_root.join("**/*.json").to_string_lossy().to_string();
+        if let Ok(tools) = ToolLoader::load_from_glob(&tool_pattern, project_root) {
+            if !tools.is_empty() {
+                let mut registry = ToolRegistry::new();
+                registry.register_all(tools);
+                executor.set_tool_registry(Arc::new(registry));
+            }
+        }
+    }
+    let executor = Arc::new(executor);
+    // Inject executor reference into BuiltinRegistry so chat() can resolve tool slugs
+    executor
+        .get_registry()
+        .set_executor(Arc::downgrade(&executor));
+    executor
+}
+
+/// Resolve a workflow reference and load it ready to execute. `wf_ref` is a
+/// `.jg`/`.jgflow` path (relative to `project_root`) or a slug, searched as
+/// `**/{slug}.jg` then `**/{slug}.jgflow`. Runs the manifest merge, lib and
+/// flow imports, and pre-flight validation.
+pub(crate) fn load_workflow(project_root: &Path, wf_ref: &str) -> anyhow::Result<WorkflowGraph> {
+    // Determine if it's a file path or slug
+    let is_file_path = wf_ref.ends_with(".jg")
+        || wf_ref.ends_with(".jgflow")
+        || wf_ref.starts_with("./")
+        || wf_ref.starts_with("../")
+        || Path::new(wf_ref).is_absolute();
+
+    let wf_path = if is_file_path {
+        let full_wf_path = if Path::new(wf_ref).is_absolute() {
+            PathBuf::from(wf_ref)
+        } else {
+            project_root.join(wf_ref)
+        };
+        debug!("📂 Resolving workflow file: {:?}", full_wf_path);
+        full_wf_path
+    } else {
+        debug!(
+            "🔍 Resolving workflow by slug: '{}' in {:?}",
+            wf_ref, project_root
+        );
+        let pattern_jg = project_root
+            .join(format!("**/{}.jg", wf_ref))
+            .to_string_lossy()
+            .to_string();
+        let pattern_jgflow = project_root
+            .join(format!("**/{}.jgflow", wf_ref))
+            .to_string_lossy()
+            .to_string();
+        let found = glob::glob(&pattern_jg)
+            .ok()
+            .and_then(|mut paths| paths.find_map(|p| p.ok()))
+            .or_else(|| {
+                glob::glob(&pattern_jgflow)
+                    .ok()
+                    .and_then(|mut paths| paths.find_map(|p| p.ok()))
+            });
+        match found {
+            Some(path) => {
+                info!("📂 Found workflow '{}' at {:?}", wf_ref, path);
+                path
+            }
+            None => anyhow::bail!(
+                "Workflow '{}' not found in workspace {:?}",
+                wf_ref,
+                project_root
+            ),
+        }
+    };
+    let content = fs::read_to_string(&wf_path)
+        .map_err(|e| anyhow::anyhow!("Workflow File Error: {} (tried {:?})", e, wf_path))?;
+
+    // .jgflow manifest: follow source: field to load .jg file and merge metadata
+    // Track source file's directory for correct flow import resolution
+    let mut source_base_dir: Option<PathBuf> = None;
+    let manifest = if wf_path.extension().and_then(|e| e.to_str()) == Some("jgflow") {
+        GraphParser::parse_manifest(&content)
+            .ok()
+            .filter(|m| !m.source.is_empty())
+    } else {
+        None
+    };
+    let mut graph = match manifest {
+        Some(manifest) => {
+            let source_path = wf_path
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join(&manifest.source);
+            let source_content = fs::read_to_string(&source_path).map_err(|e| {
+                anyhow::anyhow!(
+                    "Workflow Source Read Error: {} (tried {:?})",
+                    e,
+                    source_path
+                )
+            })?;
+            let mut g = GraphParser::parse(&source_content)
+                .map_err(|e| anyhow::anyhow!("Workflow Source Parse Error: {}", e))?;
+            source_base_dir = Some(source_path.parent().unwrap_or(Path::new(".")).to_path_buf());
+            manifest.apply_to(&mut g);
+            g
+        }
+        None => GraphParser::parse(&content)
+            .map_err(|e| anyhow::anyhow!("Workflow Parse Error: {}", e))?,
+    };
+    graph.default_slug_from(&wf_path);
+
+    // Resolve lib imports + flow imports
+    // .jgflow + source: flow imports are relative to the source .jg file directory
+    let wf_base_dir = source_base_dir
+        .as_deref()
+        .unwrap_or_else(|| wf_path.parent().unwrap_or(Path::new(".")));
+    let wf_canonical = wf_path.canonicalize().unwrap_or(wf_path.clone());
+    let at_base: Option<PathBuf> = JuglansConfig::load()
+        .ok()
+        .and_then(|c| c.paths.base.map(|b| project_root.join(b)));
+    let mut import_stack = vec![wf_canonical.clone()];
+    resolver::resolve_lib_imports(
+        &mut graph,
+        wf_base_dir,
+        &mut import_stack,
+        at_base.as_deref(),
+    )
+    .map_err(|e| anyhow::anyhow!("Lib Import Error: {}", e))?;
+    let mut import_stack = vec![wf_canonical];
+    resolver::resolve_flow_imports(
+        &mut graph,
+        wf_base_dir,
+        &mut import_stack,
+        at_base.as_deref(),
+    )
+    .map_err(|e| anyhow::anyhow!("Flow Import Error: {}", e))?;
+
+    // Pre-flight validation
+    let validation = WorkflowValidator::validate(&graph);
+    if !validation.is_valid {
+        let err_msgs: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|e| format!("[{}] {}", e.code, e.message))
+            .collect();
+        anyhow::bail!("Validation failed: {}", err_msgs.join("; "));
+    }
+    Ok(graph)
+}
+
+/// A chat turn executing in the background: its event stream, the task
+/// (abort it to cancel the turn) and the inbox for messages sent mid-run.
+struct ChatTurn {
+    task: tokio::task::JoinHandle<()>,
+    events: mpsc::UnboundedReceiver<WorkflowEvent>,
+    inbox: UserInbox,
+}
+
+/// Populate a context from a chat request and start executing the resolved
+/// workflow (or a direct `chat()` for the default slug). Shared by the SSE
+/// and WebSocket transports.
+/// Resolve the caller of a web request with `identity::attach_web_caller`,
+/// using `input.username` (when the request set one) as the display name.
+fn attach_web_caller(
+    ctx: &WorkflowContext,
+    state: &WebState,
+    auth: Option<&auth::AuthClaims>,
+    claimed_user_id: Option<&str>,
+) {
+    let username = ctx
+        .resolve_path("input.username")
+        .ok()
+        .flatten()
+        .and_then(|v| v.as_str().map(String::from));
+    identity::attach_web_caller(
+        ctx,
+        auth,
+        state.auth_enabled,
+        claimed_user_id,
+        username.as_deref(),
+    );
+}
+
+async fn start_chat_turn(
+    state: &WebState,
+    req: ChatRequest,
+    auth: Option<auth::AuthClaims>,
+) -> Result<ChatTurn, String> {
+    // Resolve @handle to workflow slug
+    let handle_slug = match &req.chat_id {
+        Some(ChatIdInput::Handle(h)) => Some(h.strip_prefix('@').unwrap_or(h).to_string()),
+        _ => None,
+    };
+
+    // Extract workflow slug
+    // Priority: @handle > agent.slug > agent.id > default
+    let workflow_slug = if let Some(slug) = handle_slug {
+        slug
+    } else if let Some(ref agent_config) = req.agent {
+        agent_config
+            .slug
+            .clone()
+            .or(agent_config.id.map(|u| u.to_string()))
             .unwrap_or_else(|| "default".to_string())
     } else {
         "default".to_string()
@@ -1406,50 +2269,8 @@ async fn handle_chat(
     // Extract system_prompt override
     let system_prompt_override = req.agent.as_ref().and_then(|a| a.system_prompt.clone());
 
-    let config = JuglansConfig::load().map_err(|e| Json(json!({"error": e.to_string()})))?;
-
-    let runtime: Arc<LocalRuntime> = Arc::new(LocalRuntime::new_with_config(&config.ai));
-
-    let mut prompt_registry = PromptRegistry::new();
-    let _ = prompt_registry.load_from_paths(&[
-        state
-            .project_root
-            .join("**/*.jgx")
-            .to_string_lossy()
-            .to_string(),
-        state
-            .project_root
-            .join("**/*.jgprompt")
-            .to_string_lossy()
-            .to_string(),
-    ]);
-
-    let mut executor =
-        WorkflowExecutor::new_with_debug(Arc::new(prompt_registry), runtime, config.debug.clone())
-            .await;
-
-    // Load tool definitions (search for *.json tool files under project_root)
-    {
-        use crate::core::tool_loader::ToolLoader;
-        use crate::services::tool_registry::ToolRegistry;
-        let tool_pattern = state
-            .project_root
-            .join("**/*.json")
-            .to_string_lossy()
-            .to_string();
-        if let Ok(tools) = ToolLoader::load_from_glob(&tool_pattern, &state.project_root) {
-            if !tools.is_empty() {
-                let mut registry = ToolRegistry::new();
-                registry.register_all(tools);
-                executor.set_tool_registry(Arc::new(registry));
-            }
-        }
-    }
-    let executor = Arc::new(executor);
-    // Inject executor reference into BuiltinRegistry so chat() can resolve tool slugs
-    executor
-        .get_registry()
-        .set_executor(Arc::downgrade(&executor));
+    let config = JuglansConfig::load().map_err(|e| e.to_string())?;
+    let executor = build_request_executor(&state.project_root, &config).await;
 
     let (tx, rx) = mpsc::unbounded_channel::<WorkflowEvent>();
     let ctx = WorkflowContext::with_sender(tx.clone());
@@ -1479,9 +2300,7 @@ async fn handle_chat(
     // This allows workflow to route directly using fields like $input.event_type
     if let Ok(parsed) = serde_json::from_str::<Value>(&message_text) {
         if let Some(obj) = parsed.as_object() {
-            for (k, v) in obj {
-                ctx.set(format!("input.{}", k), v.clone()).ok();
-            }
+            identity::set_request_inputs(&ctx, obj);
             debug!(
                 "📦 [Web] Parsed message JSON into $input.* fields: {:?}",
                 obj.keys().collect::<Vec<_>>()
@@ -1500,19 +2319,17 @@ async fn handle_chat(
             .ok();
     }
 
-    // variables field overrides values parsed from message (higher priority)
-    if let Some(vars) = req.variables {
-        if let Some(obj) = vars.as_object() {
-            for (k, v) in obj {
-                ctx.set(format!("input.{}", k), v.clone()).ok();
-            }
-        }
+    // variables field overrides values parsed from message (higher priority).
+    // Who the caller is (user, platform, auth) is never taken from either.
+    let mut claimed_user_id = None;
+    if let Some(obj) = req.variables.as_ref().and_then(|v| v.as_object()) {
+        claimed_user_id = identity::claimed_user_id(obj);
+        identity::set_request_inputs(&ctx, obj);
     }
 
-    // Set defaults for API callers (e.g. clawdbot) if not already set
-    if ctx.resolve_path("input.platform").ok().flatten().is_none() {
-        ctx.set("input.platform".into(), json!("web")).ok();
-    }
+    // Resolve the caller as a `web` account: the verified subject under
+    // [server.auth], else the `variables.user_id` the client sent.
+    attach_web_caller(&ctx, state, auth.as_ref(), claimed_user_id.as_deref());
     if ctx
         .resolve_path("input.event_type")
         .ok()
@@ -1521,6 +2338,8 @@ async fn handle_chat(
     {
         ctx.set("input.event_type".into(), json!("message")).ok();
     }
+    // Set last so request variables can't spoof the verified identity.
+    auth::set_input_auth(&ctx, auth.as_ref());
 
     // Build chat parameters
     let tools_json = custom_tools.map(|t| serde_json::to_string(&t).unwrap_or_default());
@@ -1530,193 +2349,39 @@ async fn handle_chat(
     }
     let sys_prompt = system_prompt_override;
     let project_root = state.project_root.clone();
-
-    tokio::spawn(async move {
-        let wf_ref = &workflow_slug;
-        // Determine if it's a file path or slug
-        let is_file_path = wf_ref.ends_with(".jg")
-            || wf_ref.ends_with(".jgflow")
-            || wf_ref.starts_with("./")
-            || wf_ref.starts_with("../")
-            || Path::new(wf_ref).is_absolute();
-
-        let wf_result: Result<(String, PathBuf), anyhow::Error> = if is_file_path {
-            // File path format: parse using existing logic
-            let full_wf_path = if Path::new(wf_ref).is_absolute() {
-                PathBuf::from(wf_ref)
-            } else {
-                project_root.join(wf_ref)
-            };
-            debug!("📂 Resolving workflow file: {:?}", full_wf_path);
-            fs::read_to_string(&full_wf_path)
-                .map(|content| (content, full_wf_path.clone()))
-                .map_err(|e| {
-                    anyhow::anyhow!("Workflow File Error: {} (tried {:?})", e, full_wf_path)
-                })
-        } else if wf_ref == "default" {
-            // Default mode: direct chat (no workflow)
-            Err(anyhow::anyhow!("__direct_chat__"))
-        } else {
-            // Slug format: search **/{slug}.jg under project_root, fall back to **/{slug}.jgflow
-            debug!(
-                "🔍 Resolving workflow by slug: '{}' in {:?}",
-                wf_ref, project_root
-            );
-            let pattern_jg = project_root
-                .join(format!("**/{}.jg", wf_ref))
-                .to_string_lossy()
-                .to_string();
-            let pattern_jgflow = project_root
-                .join(format!("**/{}.jgflow", wf_ref))
-                .to_string_lossy()
-                .to_string();
-            let found = glob::glob(&pattern_jg)
-                .ok()
-                .and_then(|mut paths| paths.find_map(|p| p.ok()))
-                .or_else(|| {
-                    glob::glob(&pattern_jgflow)
-                        .ok()
-                        .and_then(|mut paths| paths.find_map(|p| p.ok()))
-                });
-            match found {
-                Some(path) => {
-                    info!("📂 Found workflow '{}' at {:?}", wf_ref, path);
-                    fs::read_to_string(&path)
-                        .map(|content| (content, path.clone()))
-                        .map_err(|e| {
-                            anyhow::anyhow!("Workflow File Error: {} (tried {:?})", e, path)
-                        })
-                }
-                None => Err(anyhow::anyhow!(
-                    "Workflow '{}' not found in workspace {:?}",
-                    wf_ref,
-                    project_root
-                )),
+    let inbox = ctx.user_inbox().clone();
+
+    let task = tokio::spawn(async move {
+        let _run = metrics::ActiveRun::start("chat");
+        let result = if workflow_slug == "default" {
+            // Direct chat mode (default workflow slug with no .jg file)
+            let mut params = std::collections::HashMap::new();
+            params.insert("message".to_string(), message_text.clone());
+
+            // Pass state parameter
+            if let Some(ref state_val) = req.state {
+                params.insert("state".to_string(), state_val.clone());
             }
-        };
-
-        let result = match wf_result {
-            Ok((content, wf_path)) => {
-                // .jgflow manifest: follow source: field to load .jg file and merge metadata
-                // Track source file's directory for correct flow import resolution
-                let mut source_base_dir: Option<PathBuf> = None;
-                let parse_result = if wf_path.extension().and_then(|e| e.to_str()) == Some("jgflow")
-                {
-                    match GraphParser::parse_manifest(&content) {
-                        Ok(manifest) if !manifest.source.is_empty() => {
-                            let source_path = wf_path
-                                .parent()
-                                .unwrap_or(Path::new("."))
-                                .join(&manifest.source);
-                            match fs::read_to_string(&source_path) {
-                                Ok(source_content) => match GraphParser::parse(&source_content) {
-                                    Ok(mut g) => {
-                                        source_base_dir = Some(
-                                            source_path
-                                                .parent()
-                                                .unwrap_or(Path::new("."))
-                                                .to_path_buf(),
-                                        );
-                                        manifest.apply_to(&mut g);
-                                        Ok(g)
-                                    }
-                                    Err(e) => {
-                                        Err(anyhow::anyhow!("Workflow Source Parse Error: {}", e))
-                                    }
-                                },
-                                Err(e) => Err(anyhow::anyhow!(
-                                    "Workflow Source Read Error: {} (tried {:?})",
-                                    e,
-                                    source_path
-                                )),
-                            }
-                        }
-                        _ => GraphParser::parse(&content)
-                            .map_err(|e| anyhow::anyhow!("Workflow Parse Error: {}", e)),
-                    }
-                } else {
-                    GraphParser::parse(&content)
-                        .map_err(|e| anyhow::anyhow!("Workflow Parse Error: {}", e))
-                };
 
-                match parse_result {
-                    Ok(mut graph) => {
-                        // Resolve lib imports + flow imports
-                        // .jgflow + source: flow imports are relative to the source .jg file directory
-                        let wf_base_dir = source_base_dir
-                            .as_deref()
-                            .unwrap_or_else(|| wf_path.parent().unwrap_or(Path::new(".")));
-                        let wf_canonical = wf_path.canonicalize().unwrap_or(wf_path.clone());
-                        let at_base: Option<PathBuf> = JuglansConfig::load()
-                            .ok()
-                            .and_then(|c| c.paths.base.map(|b| project_root.join(b)));
-                        let mut import_stack = vec![wf_canonical.clone()];
-                        match resolver::resolve_lib_imports(
-                            &mut graph,
-                            wf_base_dir,
-                            &mut import_stack,
-                            at_base.as_deref(),
-                        ) {
-                            Ok(()) => {
-                                import_stack = vec![wf_canonical];
-                                match resolver::resolve_flow_imports(
-                                    &mut graph,
-                                    wf_base_dir,
-                                    &mut import_stack,
-                                    at_base.as_deref(),
-                                ) {
-                                    Ok(()) => {
-                                        // Pre-flight validation
-                                        let validation = WorkflowValidator::validate(&graph);
-                                        if !validation.is_valid {
-                                            let err_msgs: Vec<String> = validation
-                                                .errors
-                                                .iter()
-                                                .map(|e| format!("[{}] {}", e.code, e.message))
-                                                .collect();
-                                            Err(anyhow::anyhow!(
-                                                "Validation failed: {}",
-                                                err_msgs.join("; ")
-                                            ))
-                                        } else {
-                                            executor.execute_graph(Arc::new(graph), &ctx).await
-                                        }
-                                    }
-                                    Err(e) => Err(anyhow::anyhow!("Flow Import Error: {}", e)),
-                                }
-                            }
-                            Err(e) => Err(anyhow::anyhow!("Lib Import Error: {}", e)),
-                        }
-                    }
-                    Err(e) => Err(e),
-                }
+            // Pass custom tools
+            if let Some(tools_str) = tools_json {
+                params.insert("tools".to_string(), tools_str);
             }
-            Err(e) if e.to_string() == "__direct_chat__" => {
-                // Direct chat mode (default workflow slug with no .jg file)
-                let mut params = std::collections::HashMap::new();
-                params.insert("message".to_string(), message_text.clone());
-
-                // Pass state parameter
-                if let Some(ref state_val) = req.state {
-                    params.insert("state".to_string(), state_val.clone());
-                }
 
-                // Pass custom tools
-                if let Some(tools_str) = tools_json {
-                    params.insert("tools".to_string(), tools_str);
-                }
-
-                // Pass system_prompt override
-                if let Some(sp) = sys_prompt {
-                    params.insert("system_prompt".to_string(), sp);
-                }
+            // Pass system_prompt override
+            if let Some(sp) = sys_prompt {
+                params.insert("system_prompt".to_string(), sp);
+            }
 
-                executor
-                    .execute_tool_internal("chat", &params, &ctx)
-                    .await
-                    .map(|_| ())
+            executor
+                .execute_tool_internal("chat", &params, &ctx)
+                .await
+                .map(|_| ())
+        } else {
+            match load_workflow(&project_root, &workflow_slug) {
+                Ok(graph) => executor.execute_graph(Arc::new(graph), &ctx).await,
+                Err(e) => Err(e),
             }
-            Err(e) => Err(e),
         };
 
         if let Err(e) = result {
@@ -1725,6 +2390,24 @@ async fn handle_chat(
         }
     });
 
+    Ok(ChatTurn {
+        task,
+        events: rx,
+        inbox,
+    })
+}
+
+async fn handle_chat(
+    _headers: HeaderMap,
+    Extension(state): Extension<Arc<WebState>>,
+    auth: Option<Extension<auth::AuthClaims>>,
+    Json(req): Json<ChatRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, Json<Value>> {
+    let turn = start_chat_turn(&state, req, auth.map(|Extension(claims)| claims))
+        .await
+        .map_err(|e| Json(json!({ "error": e })))?;
+    let rx = turn.events;
+
     // SSE event format (standard SSE event types)
     // Uses async_stream for yield-style done event after channel closes
     let pending_calls = state.pending_tool_calls.clone();
@@ -1852,6 +2535,575 @@ async fn handle_tool_result(
     }
 }
 
+// --- WebSocket Chat Transport (/api/chat/ws) ---
+
+/// Client frames on `/api/chat/ws`, tagged by `type`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WsClientFrame {
+    /// Start a turn. Same fields as a `POST /api/chat` body.
+    Chat(Box<ChatRequest>),
+    /// Answer a `tool_call` sent on this connection.
+    ToolResult(ToolResultRequest),
+    /// Mid-turn: queued for the running agent. Between turns: starts a new
+    /// turn with the settings of the last `chat` frame.
+    Message { content: String },
+    /// Stop the running turn.
+    Cancel,
+}
+
+type ToolResultSender = oneshot::Sender<(Vec<ToolResultPayload>, Option<Vec<Value>>)>;
+
+async fn handle_chat_ws(
+    ws: WebSocketUpgrade,
+    Extension(state): Extension<Arc<WebState>>,
+    auth: Option<Extension<auth::AuthClaims>>,
+) -> Response {
+    let claims = auth.map(|Extension(claims)| claims);
+    ws.on_upgrade(move |socket| chat_ws_session(socket, state, claims))
+}
+
+async fn ws_send(socket: &mut WebSocket, data: Value) -> bool {
+    socket.send(WsMessage::Text(data.to_string())).await.is_ok()
+}
+
+/// Next event of the running turn; pends forever between turns.
+async fn next_turn_event(turn: &mut Option<ChatTurn>) -> Option<WorkflowEvent> {
+    match turn {
+        Some(turn) => turn.events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The same request with `text` as its only user message.
+fn follow_up(mut req: ChatRequest, text: String) -> ChatRequest {
+    req.messages = Some(vec![MessagePart {
+        part_type: "text".to_string(),
+        role: Some("user".to_string()),
+        content: Some(text),
+        _data: None,
+        _tool_call_id: None,
+        parts: None,
+    }]);
+    req
+}
+
+/// One chat connection. Events of the running turn are pushed as JSON text
+/// frames (the `/api/chat` SSE payloads, plus `done`), and client tool calls
+/// are answered on the same socket — no shared state between server
+/// instances, so no sticky sessions behind a load balancer.
+async fn chat_ws_session(
+    mut socket: WebSocket,
+    state: Arc<WebState>,
+    auth: Option<auth::AuthClaims>,
+) {
+    let mut turn: Option<ChatTurn> = None;
+    let mut started = Instant::now();
+    let mut last_request: Option<ChatRequest> = None;
+    let mut pending_calls: HashMap<String, ToolResultSender> = HashMap::new();
+    // A turn to start once the current frame is handled.
+    let mut next_request: Option<ChatRequest> = None;
+
+    loop {
+        tokio::select! {
+            frame = socket.recv() => {
+                let text = match frame {
+                    Some(Ok(WsMessage::Text(text))) => text,
+                    Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let frame = match serde_json::from_str::<WsClientFrame>(&text) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        let error = format!("Invalid frame: {}", e);
+                        if !ws_send(&mut socket, json!({ "type": "error", "message": error })).await {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+                let reply = match frame {
+                    WsClientFrame::Chat(_) if turn.is_some() => Some(json!({
+                        "type": "error",
+                        "message": "A turn is already running; send `message` or `cancel`",
+                    })),
+                    WsClientFrame::Chat(req) => {
+                        next_request = Some(*req);
+                        None
+                    }
+                    WsClientFrame::ToolResult(result) => {
+                        match pending_calls.remove(&result.call_id) {
+                            Some(tx) => {
+                                let _ = tx.send((result.results, result.tools));
+                                None
+                            }
+                            None => Some(json!({
+                                "type": "error",
+                                "message": format!("No pending tool call `{}`", result.call_id),
+                            })),
+                        }
+                    }
+                    WsClientFrame::Message { content } => match (&turn, &last_request) {
+                        (Some(running), _) => {
+                            running.inbox.push(content);
+                            Some(json!({ "type": "message_queued" }))
+                        }
+                        (None, Some(req)) => {
+                            next_request = Some(follow_up(req.clone(), content));
+                            None
+                        }
+                        (None, None) => Some(json!({
+                            "type": "error",
+                            "message": "Send a `chat` frame before `message`",
+                        })),
+                    },
+                    WsClientFrame::Cancel => match turn.take() {
+                        Some(running) => {
+                            running.task.abort();
+                            pending_calls.clear();
+                            info!("🛑 [WS] Turn cancelled by client");
+                            Some(json!({
+                                "type": "done",
+                                "cancelled": true,
+                                "duration_ms": started.elapsed().as_millis() as u64,
+                            }))
+                        }
+                        None => None,
+                    },
+                };
+                if let Some(reply) = reply {
+                    if !ws_send(&mut socket, reply).await {
+                        break;
+                    }
+                }
+            }
+            event = next_turn_event(&mut turn) => {
+                let Some(event) = event else {
+                    // Turn finished. Messages that arrived after the agent's
+                    // last model call start the next turn.
+                    let leftover = turn.take().map(|t| t.inbox.take()).unwrap_or_default();
+                    pending_calls.clear();
+                    let done = json!({
+                        "type": "done",
+                        "duration_ms": started.elapsed().as_millis() as u64,
+                    });
+                    if !ws_send(&mut socket, done).await {
+                        break;
+                    }
+                    if let (false, Some(req)) = (leftover.is_empty(), &last_request) {
+                        next_request = Some(follow_up(req.clone(), leftover.join("\n")));
+                    }
+                    continue;
+                };
+                let data = runs::event_payload(&event);
+                if let WorkflowEvent::ToolCall { call_id, result_tx, .. } = event {
+                    pending_calls.insert(call_id, result_tx);
+                }
+                if !ws_send(&mut socket, data).await {
+                    break;
+                }
+            }
+        }
+
+        if let Some(req) = next_request.take() {
+            match start_chat_turn(&state, req.clone(), auth.clone()).await {
+                Ok(running) => {
+                    started = Instant::now();
+                    turn = Some(running);
+                    last_request = Some(req);
+                }
+                Err(e) => {
+                    if !ws_send(&mut socket, json!({ "type": "error", "message": e })).await {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // Client went away: don't leave the turn running.
+    if let Some(running) = turn {
+        running.task.abort();
+    }
+}
+
+// --- Run Management (/api/runs) ---
+
+#[derive(Deserialize)]
+struct StartRunRequest {
+    /// Workflow slug or `.jg`/`.jgflow` path, resolved like `/api/chat`.
+    workflow: String,
+    #[serde(default)]
+    input: Value,
+    /// Tool event verbosity recorded in the event log: "silent", "info", "verbose"
+    tool_event: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RunListQuery {
+    workflow: Option<String>,
+    status: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct RunEventsQuery {
+    /// Replay events after this `seq` (same as the `Last-Event-ID` header).
+    after: Option<i64>,
+}
+
+fn run_store_unavailable() -> Response {
+    error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Run store is unavailable (see [runs] in juglans.toml)",
+    )
+}
+
+/// Store a workflow event on the run; client tool calls are parked in
+/// `pending_tool_calls` so `/api/chat/tool-result` can answer them.
+fn record_run_event(recorder: &mut runs::RunRecorder, state: &WebState, event: WorkflowEvent) {
+    let data = runs::event_payload(&event);
+    if let WorkflowEvent::ToolCall {
+        call_id, result_tx, ..
+    } = event
+    {
+        if let Ok(mut map) = state.pending_tool_calls.lock() {
+            map.insert(call_id, result_tx);
+        }
+    }
+    recorder.record(data);
+}
+
+async fn start_run(
+    Extension(state): Extension<Arc<WebState>>,
+    auth: Option<Extension<auth::AuthClaims>>,
+    Json(req): Json<StartRunRequest>,
+) -> Response {
+    let Some(store) = runs::global_store() else {
+        return run_store_unavailable();
+    };
+    let graph = match load_workflow(&state.project_root, &req.workflow) {
+        Ok(graph) => graph,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    let config = match JuglansConfig::load() {
+        Ok(config) => config,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    let executor = build_request_executor(&state.project_root, &config).await;
+
+    let run_id = Uuid::new_v4().to_string();
+    let mut recorder = match runs::RunRecorder::start(store, &run_id, &req.workflow, &req.input) {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            error!("❌ [Runs] Could not record run: {:#}", e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Could not record run");
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<WorkflowEvent>();
+    let ctx = WorkflowContext::with_sender(tx);
+    ctx.set_stream_node_events(true);
+    ctx.set_tool_event_level(match req.tool_event.as_deref() {
+        Some("verbose") => 2,
+        Some("info") => 1,
+        _ => 0,
+    });
+    let claims = auth.as_ref().map(|Extension(claims)| claims);
+    let mut claimed_user_id = None;
+    if let Some(obj) = req.input.as_object() {
+        claimed_user_id = identity::claimed_user_id(obj);
+        identity::set_request_inputs(&ctx, obj);
+    }
+    ctx.set("input.run_id".into(), json!(run_id)).ok();
+    attach_web_caller(&ctx, &state, claims, claimed_user_id.as_deref());
+    // Set last so the input can't spoof the verified identity.
+    auth::set_input_auth(&ctx, claims);
+
+    info!("▶️  [Runs] {} started: {}", run_id, req.workflow);
+    let task_state = state.clone();
+    tokio::spawn(async move {
+        let _run = metrics::ActiveRun::start("run");
+        let start = Instant::now();
+        let execution = executor.execute_graph(Arc::new(graph), &ctx);
+        tokio::pin!(execution);
+        let result = loop {
+            tokio::select! {
+                result = &mut execution => break result,
+                Some(event) = rx.recv() => record_run_event(&mut recorder, &task_state, event),
+            }
+        };
+        while let Ok(event) = rx.try_recv() {
+            record_run_event(&mut recorder, &task_state, event);
+        }
+        if let Err(e) = &result {
+            error!("❌ [Runs] {} failed: {}", recorder.run_id(), e);
+            recorder.record(json!({ "type": "error", "message": e.to_string() }));
+        }
+        let output = ctx
+            .resolve_path("output")
+            .ok()
+            .flatten()
+            .unwrap_or(Value::Null);
+        let trace = runs::trace_json(&ctx.trace_entries());
+        recorder.finish(&result, output, trace, start.elapsed().as_millis() as u64);
+    });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "id": run_id,
+            "workflow": req.workflow,
+            "status": runs::STATUS_RUNNING,
+        })),
+    )
+        .into_response()
+}
+
+async fn list_runs(Query(query): Query<RunListQuery>) -> Response {
+    let Some(store) = runs::global_store() else {
+        return run_store_unavailable();
+    };
+    let filter = runs::RunFilter {
+        workflow: query.workflow.as_deref(),
+        status: query.status.as_deref(),
+        limit: query.limit.unwrap_or(50).clamp(1, 500),
+    };
+    match store.list(&filter) {
+        Ok(list) => {
+            let summaries: Vec<Value> = list.iter().map(|r| r.summary()).collect();
+            Json(json!({ "runs": summaries })).into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e)),
+    }
+}
+
+async fn get_run(RoutePath(id): RoutePath<String>) -> Response {
+    let Some(store) = runs::global_store() else {
+        return run_store_unavailable();
+    };
+    match store.get(&id) {
+        Ok(Some(run)) => Json(run).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Run not found"),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e)),
+    }
+}
+
+fn run_sse_event(event: runs::RunEvent) -> Result<Event, std::convert::Infallible> {
+    let name = event
+        .data
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("message")
+        .to_string();
+    Ok(Event::default()
+        .id(event.seq.to_string())
+        .event(name)
+        .data(event.data.to_string()))
+}
+
+/// Replay stored events, then tail the run live if it is still executing.
+/// Ends with a `done` event carrying the final status and output.
+async fn run_events(
+    RoutePath(id): RoutePath<String>,
+    headers: HeaderMap,
+    Query(query): Query<RunEventsQuery>,
+) -> Response {
+    let Some(store) = runs::global_store() else {
+        return run_store_unavailable();
+    };
+    match store.get(&id) {
+        Ok(Some(_)) => {}
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Run not found"),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e)),
+    }
+    let after = query
+        .after
+        .or_else(|| {
+            headers
+                .get("last-event-id")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+        })
+        .unwrap_or(0);
+
+    // Subscribe before reading the store so no event falls in between.
+    let live = runs::subscribe(&id);
+    let stream = async_stream::stream! {
+        let mut last = after;
+        for event in store.events(&id, last).unwrap_or_default() {
+            last = event.seq;
+            yield run_sse_event(event);
+        }
+        if let Some(mut rx) = live {
+            loop {
+                match rx.recv().await {
+                    Ok(event) if event.seq > last => {
+                        last = event.seq;
+                        yield run_sse_event(event);
+                    }
+                    Ok(_) => {}
+                    // Fell behind the broadcast buffer: catch up from the store.
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                        for event in store.events(&id, last).unwrap_or_default() {
+                            last = event.seq;
+                            yield run_sse_event(event);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            for event in store.events(&id, last).unwrap_or_default() {
+                yield run_sse_event(event);
+            }
+        }
+        if let Ok(Some(run)) = store.get(&id) {
+            yield Ok(Event::default().event("done").data(
+                json!({
+                    "type": "done",
+                    "status": run.status,
+                    "output": run.output,
+                    "error": run.error,
+                    "duration_ms": run.duration_ms,
+                })
+                .to_string(),
+            ));
+        }
+    };
+    Sse::new(stream)
+        .keep_alive(axum::response::sse::KeepAlive::default())
+        .into_response()
+}
+
+// --- Job Queue (/api/jobs) ---
+
+#[derive(Deserialize)]
+struct EnqueueJobRequest {
+    /// Workflow slug or `.jg`/`.jgflow` path, resolved by the worker.
+    workflow: String,
+    #[serde(default)]
+    input: Value,
+    /// Seconds, or a duration string such as "30s", "5m", "2h".
+    delay: Option<Value>,
+    #[serde(default)]
+    priority: i64,
+    unique_key: Option<String>,
+    max_attempts: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct JobListQuery {
+    workflow: Option<String>,
+    status: Option<String>,
+    limit: Option<usize>,
+}
+
+fn job_queue_unavailable() -> Response {
+    error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Job queue is unavailable (see [jobs] in juglans.toml)",
+    )
+}
+
+async fn enqueue_job(
+    Extension(state): Extension<Arc<WebState>>,
+    auth: Option<Extension<auth::AuthClaims>>,
+    Json(req): Json<EnqueueJobRequest>,
+) -> Response {
+    if jobs::global_store().is_none() {
+        return job_queue_unavailable();
+    }
+    // Reject unknown workflows now rather than dead-lettering them later.
+    if let Err(e) = load_workflow(&state.project_root, &req.workflow) {
+        return error_response(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+    if !(req.input.is_object() || req.input.is_null()) {
+        return error_response(StatusCode::BAD_REQUEST, "'input' must be an object");
+    }
+    let delay = match &req.delay {
+        None | Some(Value::Null) => std::time::Duration::ZERO,
+        Some(Value::Number(n)) => std::time::Duration::from_secs(n.as_u64().unwrap_or(0)),
+        Some(Value::String(s)) => match jobs::parse_delay(s) {
+            Ok(d) => d,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.to_string()),
+        },
+        Some(_) => return error_response(StatusCode::BAD_REQUEST, "invalid 'delay'"),
+    };
+    let queued = jobs::enqueue(jobs::NewJob {
+        workflow: req.workflow.clone(),
+        input: req.input,
+        priority: req.priority,
+        delay,
+        unique_key: req.unique_key.filter(|k| !k.is_empty()),
+        max_attempts: req.max_attempts,
+        auth: auth
+            .map(|Extension(claims)| claims.0)
+            .unwrap_or(Value::Null),
+    });
+    match queued {
+        Ok(queued) => {
+            info!("📥 [Jobs] #{} queued: {}", queued.id, req.workflow);
+            (
+                StatusCode::ACCEPTED,
+                Json(json!({
+                    "id": queued.id,
+                    "created": queued.created,
+                    "workflow": req.workflow,
+                    "status": jobs::STATUS_QUEUED,
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e)),
+    }
+}
+
+async fn list_jobs(Query(query): Query<JobListQuery>) -> Response {
+    let Some(store) = jobs::global_store() else {
+        return job_queue_unavailable();
+    };
+    let filter = jobs::JobFilter {
+        workflow: query.workflow.as_deref(),
+        status: query.status.as_deref(),
+        limit: query.limit.unwrap_or(50).clamp(1, 500),
+    };
+    match (store.list(&filter), store.counts()) {
+        (Ok(list), Ok(counts)) => {
+            let summaries: Vec<Value> = list.iter().map(|j| j.summary()).collect();
+            Json(json!({ "counts": counts, "jobs": summaries })).into_response()
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e))
+        }
+    }
+}
+
+async fn get_job(RoutePath(id): RoutePath<i64>) -> Response {
+    let Some(store) = jobs::global_store() else {
+        return job_queue_unavailable();
+    };
+    match store.get(id) {
+        Ok(Some(job)) => Json(job).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Job not found"),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &format!("{:#}", e)),
+    }
+}
+
+/// Requeue a dead job with a fresh attempt budget.
+async fn retry_job(RoutePath(id): RoutePath<i64>) -> Response {
+    let Some(store) = jobs::global_store() else {
+        return job_queue_unavailable();
+    };
+    match store.retry(id, Utc::now().timestamp()) {
+        Ok(true) => {
+            info!("🔁 [Jobs] #{} requeued", id);
+            Json(json!({ "id": id, "status": jobs::STATUS_QUEUED })).into_response()
+        }
+        Ok(false) => error_response(StatusCode::CONFLICT, "Only dead jobs can be retried"),
+        Err(e) => error_response(StatusCode::CONFLICT, &format!("{:#}", e)),
+    }
+}
+
 // ============================================================
 // Inline Server — started by serve() builtin in CLI mode
 // ============================================================
@@ -1860,6 +3112,7 @@ use crate::builtins::http::InlineRoute;
 
 struct InlineServerState {
     routes: Vec<InlineRoute>,
+    statics: Vec<static_files::StaticMount>,
     workflow: Arc<WorkflowGraph>,
     executor: Arc<WorkflowExecutor>,
 }
@@ -1871,22 +3124,43 @@ pub async fn start_inline_server(
     workflow: Arc<WorkflowGraph>,
     executor: Arc<WorkflowExecutor>,
     port: u16,
+    auth: Option<crate::services::config::AuthConfig>,
 ) -> anyhow::Result<()> {
+    validation::check_routes(&workflow, &routes)?;
+    let base_dir = std::env::current_dir().unwrap_or_default();
+    let statics = static_files::extract_static_mounts(&workflow, &base_dir);
     let route_summary: Vec<String> = routes
         .iter()
         .map(|r| format!("   - {} {} -> {}()", r.method, r.path, r.handler))
+        .chain(
+            statics
+                .iter()
+                .map(|m| format!("   - GET {}/* -> {}", m.prefix, m.dir.display())),
+        )
         .collect();
 
     let state = Arc::new(InlineServerState {
         routes,
+        statics,
         workflow,
         executor,
     });
 
-    let app = Router::new()
-        .fallback(handle_inline_request)
-        .layer(CorsLayer::permissive())
-        .layer(Extension(state));
+    let mut app = Router::new();
+    if !openapi::path_is_taken(&state.routes, openapi::SPEC_PATH) {
+        app = app.route(openapi::SPEC_PATH, get(inline_openapi_spec));
+    }
+    if !openapi::path_is_taken(&state.routes, openapi::DOCS_PATH) {
+        app = app.route(openapi::DOCS_PATH, get(openapi_docs));
+    }
+    let auth_methods = auth.as_ref().map(auth::method_names);
+    let app = with_auth(
+        app.fallback(handle_inline_request),
+        auth,
+        &base_dir,
+        &state.routes,
+    )?;
+    let app = app.layer(CorsLayer::permissive()).layer(Extension(state));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
@@ -1896,6 +3170,9 @@ pub async fn start_inline_server(
     for line in &route_summary {
         info!("{}", line);
     }
+    if let Some(methods) = &auth_methods {
+        info!("🔒 Auth: {}", methods);
+    }
     info!("--------------------------------------------------");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -1905,6 +3182,15 @@ pub async fn start_inline_server(
     Ok(())
 }
 
+async fn inline_openapi_spec(Extension(state): Extension<Arc<InlineServerState>>) -> Json<Value> {
+    let title = if state.workflow.slug.is_empty() {
+        "juglans"
+    } else {
+        state.workflow.slug.as_str()
+    };
+    Json(openapi::build_spec(&state.workflow, title))
+}
+
 async fn handle_inline_request(
     method: Method,
     uri: Uri,
@@ -1912,16 +3198,22 @@ async fn handle_inline_request(
     Extension(state): Extension<Arc<InlineServerState>>,
     request: Request<Body>,
 ) -> Response {
-    // Match route
-    let matched = state
-        .routes
-        .iter()
-        .find(|r| r.method == method.as_str() && r.path == uri.path());
+    if let Some(response) =
+        serve_static(&state.routes, &state.statics, &method, uri.path(), &headers).await
+    {
+        return response;
+    }
 
-    let route = match matched {
-        Some(r) => r,
-        None => return error_response(StatusCode::NOT_FOUND, "Not found"),
-    };
+    // Match route
+    let (route, path_values) =
+        match validation::find_route(&state.routes, method.as_str(), uri.path()) {
+            Some(m) => m,
+            None => return error_response(StatusCode::NOT_FOUND, "Not found"),
+        };
+    let claims = request.extensions().get::<auth::AuthClaims>().cloned();
+    if let Some(denied) = auth::check_route(route.auth.as_deref(), claims.as_ref()) {
+        return denied;
+    }
 
     let handler_fn = route.handler.clone();
 
@@ -1975,16 +3267,17 @@ async fn handle_inline_request(
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+    let bound =
+        match validation::bind_request(&state.workflow, route, path_values, &query_map, body_value)
+        {
+            Ok(bound) => bound,
+            Err(errors) => return validation_error_response(&errors),
+        };
+
     if wants_sse {
-        handle_inline_sse(
-            state, handler_fn, method, uri, headers, body_value, query_map,
-        )
-        .await
+        handle_inline_sse(state, handler_fn, method, uri, headers, bound, claims).await
     } else {
-        handle_inline_json(
-            state, handler_fn, method, uri, headers, body_value, query_map,
-        )
-        .await
+        handle_inline_json(state, handler_fn, method, uri, headers, bound, claims).await
     }
 }
 
@@ -1994,8 +3287,8 @@ async fn handle_inline_sse(
     method: Method,
     uri: Uri,
     headers: HeaderMap,
-    body: Value,
-    query: HashMap<String, String>,
+    request: validation::BoundRequest,
+    claims: Option<auth::AuthClaims>,
 ) -> Response {
     let (tx, rx) = mpsc::unbounded_channel::<WorkflowEvent>();
     let ctx = WorkflowContext::with_sender(tx);
@@ -2004,22 +3297,28 @@ async fn handle_inline_sse(
     ctx.set("input.method".to_string(), json!(method.as_str()))
         .ok();
     ctx.set("input.path".to_string(), json!(uri.path())).ok();
-    ctx.set("input.query".to_string(), json!(query)).ok();
     ctx.set("input.headers".to_string(), headers_to_json(&headers))
         .ok();
-    ctx.set("input.body".to_string(), body).ok();
     let route = format!("{} {}", method.as_str(), uri.path());
     ctx.set("input.route".to_string(), json!(route)).ok();
 
     let executor = state.executor.clone();
     let workflow = state.workflow.clone();
 
+    // input.params / input.query / input.body, plus struct instances
+    let args = match request.install(&ctx, &workflow) {
+        Ok(args) => args,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    // Set last so request data can't spoof the verified identity.
+    auth::set_input_auth(&ctx, claims.as_ref());
+
     // Pin root workflow so on_token/on_result handlers can find functions
     // defined in the main workflow (not just the handler's body sub-graph)
     ctx.set_root_workflow(workflow.clone());
 
     tokio::spawn(async move {
-        let args = HashMap::new();
+        let _run = metrics::ActiveRun::start("http");
         if let Err(e) = executor
             .execute_function(handler_fn, args, workflow, &ctx)
             .await
@@ -2094,8 +3393,8 @@ async fn handle_inline_json(
     method: Method,
     uri: Uri,
     headers: HeaderMap,
-    body: Value,
-    query: HashMap<String, String>,
+    request: validation::BoundRequest,
+    claims: Option<auth::AuthClaims>,
 ) -> Response {
     let (tx, _rx) = mpsc::unbounded_channel::<WorkflowEvent>();
     let ctx = WorkflowContext::with_sender(tx);
@@ -2104,20 +3403,26 @@ async fn handle_inline_json(
     ctx.set("input.method".to_string(), json!(method.as_str()))
         .ok();
     ctx.set("input.path".to_string(), json!(uri.path())).ok();
-    ctx.set("input.query".to_string(), json!(query)).ok();
     ctx.set("input.headers".to_string(), headers_to_json(&headers))
         .ok();
-    ctx.set("input.body".to_string(), body).ok();
     let route = format!("{} {}", method.as_str(), uri.path());
     ctx.set("input.route".to_string(), json!(route)).ok();
 
     let executor = state.executor.clone();
     let workflow = state.workflow.clone();
 
+    // input.params / input.query / input.body, plus struct instances
+    let args = match request.install(&ctx, &workflow) {
+        Ok(args) => args,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    // Set last so request data can't spoof the verified identity.
+    auth::set_input_auth(&ctx, claims.as_ref());
+
     // Pin root workflow so on_token/on_result handlers can find functions
     ctx.set_root_workflow(workflow.clone());
 
-    let args = HashMap::new();
+    let _run = metrics::ActiveRun::start("http");
     if let Err(e) = executor
         .execute_function(handler_fn, args, workflow, &ctx)
         .await
@@ -2129,28 +3434,117 @@ async fn handle_inline_json(
         );
     }
 
-    // Read response from context
-    let status_code = ctx
-        .resolve_path("response.status")
-        .ok()
-        .flatten()
-        .and_then(|v| v.as_u64())
-        .unwrap_or(200) as u16;
-    let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+    workflow_response(&ctx).await
+}
 
-    let resp_body = ctx
-        .resolve_path("response.body")
-        .ok()
-        .flatten()
-        .or_else(|| ctx.resolve_path("output").ok().flatten())
-        .unwrap_or(Value::Null);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ws_frames_parse_by_type() {
+        let frame: WsClientFrame = serde_json::from_value(json!({
+            "type": "chat",
+            "chat_id": "@support",
+            "messages": [{"role": "user", "content": "hi"}],
+        }))
+        .unwrap();
+        let WsClientFrame::Chat(req) = frame else {
+            panic!("expected chat frame");
+        };
+        assert!(matches!(req.chat_id, Some(ChatIdInput::Handle(ref h)) if h == "@support"));
+
+        let frame: WsClientFrame = serde_json::from_value(json!({
+            "type": "tool_result",
+            "call_id": "c1",
+            "results": [{"tool_call_id": "t1", "content": "ok"}],
+        }))
+        .unwrap();
+        assert!(matches!(frame, WsClientFrame::ToolResult(ref r) if r.call_id == "c1"));
+
+        let frame: WsClientFrame =
+            serde_json::from_value(json!({"type": "message", "content": "more"})).unwrap();
+        assert!(matches!(frame, WsClientFrame::Message { ref content } if content == "more"));
+        assert!(matches!(
+            serde_json::from_value(json!({"type": "cancel"})).unwrap(),
+            WsClientFrame::Cancel
+        ));
+        assert!(serde_json::from_value::<WsClientFrame>(json!({"type": "nope"})).is_err());
+    }
 
-    let body_bytes = serde_json::to_vec(&resp_body).unwrap_or_default();
-    Response::builder()
-        .status(status)
-        .header("content-type", "application/json")
-        .body(Body::from(body_bytes))
-        .unwrap_or_else(|_| {
-            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Response build error")
+    #[test]
+    fn follow_up_keeps_settings() {
+        let req: ChatRequest = serde_json::from_value(json!({
+            "chat_id": "@support",
+            "messages": [{"role": "user", "content": "first"}],
+            "variables": {"user_id": "u1"},
+        }))
+        .unwrap();
+        let next = follow_up(req, "second".into());
+        let messages = next.messages.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content.as_deref(), Some("second"));
+        assert_eq!(next.variables.unwrap()["user_id"], "u1");
+    }
+
+    fn rule(path: Option<&str>, host: Option<&str>) -> MountRule {
+        MountRule::new(&crate::services::config::MountConfig {
+            entry: "main.jg".into(),
+            path: path.map(String::from),
+            host: host.map(String::from),
+            strip_prefix: true,
         })
+        .unwrap()
+    }
+
+    #[test]
+    fn mount_rules_match_prefix_and_host() {
+        let billing = rule(Some("/billing/"), None);
+        assert_eq!(billing.prefix.as_deref(), Some("/billing"));
+        assert_eq!(billing.score(None, "/billing"), Some((false, 8)));
+        assert_eq!(billing.score(None, "/billing/invoices"), Some((false, 8)));
+        assert_eq!(billing.score(None, "/billingx"), None);
+        assert_eq!(billing.local_path("/billing"), "/");
+        assert_eq!(billing.local_path("/billing/invoices"), "/invoices");
+        assert_eq!(
+            billing.public_path("/openapi.json"),
+            "/billing/openapi.json"
+        );
+
+        let docs = rule(None, Some("Docs.Example.com"));
+        assert!(docs.score(Some("docs.example.com"), "/any").is_some());
+        assert!(docs.score(Some("example.com"), "/any").is_none());
+        assert!(docs.score(None, "/any").is_none());
+        assert_eq!(docs.local_path("/any"), "/any");
+
+        let tenants = rule(Some("/api"), Some("*.example.com"));
+        assert_eq!(
+            tenants.score(Some("acme.example.com"), "/api/x"),
+            Some((true, 4))
+        );
+        assert!(tenants.score(Some("example.com"), "/api/x").is_none());
+        assert!(tenants.score(Some("acme.example.com"), "/x").is_none());
+        // Host matches outrank longer path-only prefixes.
+        assert!(
+            tenants.score(Some("acme.example.com"), "/api/x")
+                > rule(Some("/api/x"), None).score(None, "/api/x")
+        );
+
+        let err = MountRule::new(&crate::services::config::MountConfig {
+            entry: "main.jg".into(),
+            path: Some("/".into()),
+            host: None,
+            strip_prefix: true,
+        });
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn request_host_drops_port() {
+        let mut headers = HeaderMap::new();
+        headers.insert("host", "API.example.com:8080".parse().unwrap());
+        assert_eq!(request_host(&headers).as_deref(), Some("api.example.com"));
+        headers.insert("host", "localhost".parse().unwrap());
+        assert_eq!(request_host(&headers).as_deref(), Some("localhost"));
+    }
 }
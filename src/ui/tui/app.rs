@@ -9,7 +9,7 @@ use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use unicode_width::UnicodeWidthChar;
 
-use crate::core::context::{WorkflowContext, WorkflowEvent};
+use crate::core::context::{ToolResultPayload, WorkflowContext, WorkflowEvent};
 use crate::core::executor::WorkflowExecutor;
 use crate::core::graph::WorkflowGraph;
 
@@ -100,6 +100,16 @@ pub struct App {
     tool_input_buf: String,
     needs_new_assistant_message: bool,
     pub pending_permission_response: Option<String>,
+    /// Agent-mode tool-policy approvals awaiting the permission dialog,
+    /// keyed by the dialog request id (the `ToolCall` call_id).
+    #[allow(clippy::type_complexity)]
+    pending_tool_approvals: HashMap<
+        String,
+        (
+            String,
+            tokio::sync::oneshot::Sender<(Vec<ToolResultPayload>, Option<Vec<serde_json::Value>>)>,
+        ),
+    >,
     pub pending_send_message: Option<PendingMessage>,
     pub attachments: Vec<Attachment>,
     pub attachment_selected: bool,
@@ -168,6 +178,7 @@ impl App {
             tool_input_buf: String::new(),
             needs_new_assistant_message: false,
             pending_permission_response: None,
+            pending_tool_approvals: HashMap::new(),
             pending_send_message: None,
             attachments: Vec::new(),
             attachment_selected: false,
@@ -232,11 +243,24 @@ impl App {
             if close {
                 if let Some(ref result) = result {
                     if result.starts_with("allow:") || result.starts_with("deny:") {
-                        // Permission response — schedule for async send
                         let allow = result.starts_with("allow:");
                         let req_id = if allow { &result[6..] } else { &result[5..] };
-                        let json = claude_code::build_permission_response(req_id, allow);
-                        self.pending_permission_response = Some(json);
+                        if let Some((tool_call_id, tx)) = self.pending_tool_approvals.remove(req_id)
+                        {
+                            // Agent-mode tool policy approval — answer the chat() call
+                            let content = if allow { "allow" } else { "deny" };
+                            let _ = tx.send((
+                                vec![ToolResultPayload {
+                                    tool_call_id,
+                                    content: content.to_string(),
+                                }],
+                                None,
+                            ));
+                        } else {
+                            // Permission response — schedule for async send
+                            let json = claude_code::build_permission_response(req_id, allow);
+                            self.pending_permission_response = Some(json);
+                        }
                     } else {
                         match self.active_dialog.as_ref() {
                             Some(Dialog::Quit) => {
@@ -843,6 +867,46 @@ impl App {
             WorkflowEvent::Status(_) => {
                 // Ignore non-sentinel status; TUI shows state via streaming flag
             }
+            WorkflowEvent::ToolCall {
+                call_id,
+                tools,
+                result_tx,
+            } => {
+                // Only tool-policy approvals are answerable from the TUI;
+                // other client tool calls are dropped (chat() reports it).
+                let approval = tools.first().filter(|t| {
+                    t.get("name").and_then(|n| n.as_str())
+                        == Some(crate::services::tool_policy::APPROVAL_TOOL_NAME)
+                });
+                if let Some(call) = approval {
+                    let tool_call_id = call
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    let args: serde_json::Value = call
+                        .get("arguments")
+                        .and_then(|a| a.as_str())
+                        .and_then(|a| serde_json::from_str(a).ok())
+                        .unwrap_or_default();
+                    let tool_name = args
+                        .get("tool")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("tool")
+                        .to_string();
+                    let input_display = args
+                        .get("arguments")
+                        .map(|a| serde_json::to_string_pretty(a).unwrap_or_default())
+                        .unwrap_or_default();
+                    self.pending_tool_approvals
+                        .insert(call_id.clone(), (tool_call_id, result_tx));
+                    self.active_dialog = Some(Dialog::_PermissionRequest {
+                        _request_id: call_id,
+                        _tool_name: tool_name,
+                        _input_display: input_display,
+                    });
+                }
+            }
             _ => {} // Meta, NodeStart, NodeComplete — ignored for now
         }
     }
@@ -199,6 +199,7 @@ const BUILTIN_TOOLS: &[(&str, &str)] = &[
     ("timer", "Delay execution"),
     ("serve", "HTTP server entry point"),
     ("response", "HTTP response builder"),
+    ("render", "Render .jgx template as HTML response"),
     ("assert", "Test assertion"),
     ("config", "Test configuration"),
     ("read_file", "Read file contents"),
@@ -209,7 +210,8 @@ const BUILTIN_TOOLS: &[(&str, &str)] = &[
     ("bash", "Execute shell command"),
     ("sh", "Execute shell command (alias)"),
     ("execute_workflow", "Run sub-workflow"),
-    ("memory_search", "Search memory store"),
+    ("memory_search", "Search long-term memories (query, user?)"),
+    ("memory.recall", "Recall long-term memories (query, user?)"),
     ("history", "Chat history"),
     ("vector_create_space", "Create vector space"),
     ("vector_upsert", "Upsert vectors"),
@@ -600,6 +602,7 @@ fn tool_description(name: &str) -> Option<&'static str> {
         "set" => Some("Set a context variable.\n\nParams: `key`, `value`"),
         "serve" => Some("Mark node as HTTP entry point.\n\nParams: `method`, `path`"),
         "response" => Some("Build HTTP response.\n\nParams: `status`, `body`, `headers`"),
+        "render" => Some("Render a .jgx template as an HTML response.\n\nParams: `template`, `status`, `headers`, template variables"),
         "assert" => Some("Test assertion.\n\nParams: `contains`, `eq`, `true`"),
         "bash" | "sh" => Some("Execute shell command.\n\nParams: `command`"),
         "read_file" => Some("Read file contents.\n\nParams: `path`"),
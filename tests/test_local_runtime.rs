@@ -26,6 +26,7 @@ async fn test_deepseek_chat() {
         tools: None,
         token_sender: None,
         tool_handler: None,
+        user_inbox: None,
     };
 
     let result = runtime.chat(req).await;